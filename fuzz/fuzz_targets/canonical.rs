@@ -0,0 +1,15 @@
+//! Decodes arbitrary data and checks that its encoding is stable.
+#![no_main]
+use ipld_core::ipld::Ipld;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(ipld) = serde_ipld_dagjson::ipld::from_slice(data) else {
+        return;
+    };
+    let encoded = serde_ipld_dagjson::ipld::to_vec(&ipld).expect("decoded data can be encoded");
+    let decoded: Ipld = serde_ipld_dagjson::from_slice(&encoded).expect("encoded data decodes");
+    assert_eq!(decoded, ipld);
+    let reencoded = serde_ipld_dagjson::to_vec(&decoded).expect("decoded data can be encoded");
+    assert_eq!(reencoded, encoded);
+});
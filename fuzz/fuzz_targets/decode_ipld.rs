@@ -0,0 +1,14 @@
+//! Decodes arbitrary data, with the generic as well as with the specialized code path.
+#![no_main]
+use ipld_core::ipld::Ipld;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let generic = serde_ipld_dagjson::from_slice::<Ipld>(data);
+    let specialized = serde_ipld_dagjson::ipld::from_slice(data);
+    // Both code paths must agree on what is valid.
+    assert_eq!(generic.is_ok(), specialized.is_ok());
+    if let (Ok(generic), Ok(specialized)) = (generic, specialized) {
+        assert_eq!(generic, specialized);
+    }
+});
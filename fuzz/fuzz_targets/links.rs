@@ -0,0 +1,22 @@
+//! Extracts the links of arbitrary data.
+#![no_main]
+use ipld_core::{codec::Links, ipld::Ipld};
+use libfuzzer_sys::fuzz_target;
+use serde_ipld_dagjson::codec::DagJsonCodec;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(links) = DagJsonCodec::links(data) else {
+        return;
+    };
+    let links = links.collect::<Vec<_>>();
+    // All links of the fully decoded data must have been extracted. Link extraction is more
+    // lenient, it e.g. doesn't reject duplicate map keys.
+    let Ok(ipld) = serde_ipld_dagjson::from_slice::<Ipld>(data) else {
+        return;
+    };
+    for node in ipld.iter() {
+        if let Ipld::Link(cid) = node {
+            assert!(links.contains(cid));
+        }
+    }
+});
@@ -0,0 +1,45 @@
+/* C ABI of serde_ipld_dagjson, available with the `ffi` feature. */
+#ifndef SERDE_IPLD_DAGJSON_H
+#define SERDE_IPLD_DAGJSON_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Status codes, their values are stable. */
+#define DAGJSON_OK 0
+#define DAGJSON_ERR_NULL_POINTER 1
+#define DAGJSON_ERR_DECODE 2
+#define DAGJSON_ERR_ENCODE 3
+#define DAGJSON_ERR_TRAILING_DATA 4
+#define DAGJSON_ERR_INTERNAL 5
+
+/*
+ * Outputs are NUL-terminated UTF-8 strings, `out_len` doesn't include the terminator. They must
+ * be released with `dagjson_free()`. On error `*out` is set to NULL.
+ */
+
+/* Encodes a JSON document in the DAG-JSON data model into a canonical DAG-JSON block. */
+int32_t dagjson_encode(const uint8_t *input, size_t input_len, uint8_t **out, size_t *out_len);
+
+/*
+ * Decodes a DAG-JSON block into plain JSON, links become their string form and bytes their
+ * base64 encoding.
+ */
+int32_t dagjson_decode_to_json(const uint8_t *input, size_t input_len, uint8_t **out,
+                               size_t *out_len);
+
+/* Returns the links of a DAG-JSON block, as CID strings separated by newlines. */
+int32_t dagjson_links(const uint8_t *input, size_t input_len, uint8_t **out, size_t *out_len);
+
+/* Releases an output, `len` is the length returned together with it. */
+void dagjson_free(uint8_t *ptr, size_t len);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* SERDE_IPLD_DAGJSON_H */
@@ -0,0 +1,30 @@
+//! Derive macros for [serde_ipld_dagjson](https://docs.rs/serde_ipld_dagjson).
+//!
+//! Use them through the `derive` feature of `serde_ipld_dagjson`, not directly.
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, parse_quote, DeriveInput};
+
+/// Implements `serde_ipld_dagjson::DagJsonBlock` for a type.
+///
+/// The type needs to implement `Serialize` and `Deserialize`.
+#[proc_macro_derive(DagJsonBlock)]
+pub fn derive_dagjson_block(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    let name = &input.ident;
+    // Generic types can only be a block if all their type parameters can be (de)serialized.
+    let mut generics = input.generics.clone();
+    for param in input.generics.type_params() {
+        let ident = &param.ident;
+        generics.make_where_clause().predicates.push(parse_quote! {
+            #ident: ::serde_ipld_dagjson::block::__private::Serialize
+                + ::serde_ipld_dagjson::block::__private::DeserializeOwned
+        });
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let expanded = quote! {
+        impl #impl_generics ::serde_ipld_dagjson::block::DagJsonBlock
+            for #name #ty_generics #where_clause {}
+    };
+    expanded.into()
+}
@@ -0,0 +1,94 @@
+//! Hooks for Advanced Data Layouts.
+//!
+//! An [Advanced Data Layout] (ADL) presents data, which is stored in a certain shape, as a
+//! different logical value. A sharded map, for example, is stored as a tree of nodes, but is used
+//! like a single map. ADLs are registered with [`DecodeOptions::adl`](crate::DecodeOptions::adl),
+//! they are then asked about every map that is decoded.
+//!
+//! [Advanced Data Layout]: https://ipld.io/docs/advanced-data-layouts/
+//!
+//! # Examples
+//!
+//! A layout that stores maps as a list of pairs:
+//!
+//! ```
+//! use std::collections::BTreeMap;
+//!
+//! use ipld_core::ipld::Ipld;
+//! use serde_ipld_dagjson::{adl::Adl, de, DecodeOptions};
+//!
+//! struct Pairs;
+//!
+//! impl Adl for Pairs {
+//!     fn matches(&self, first_key: &str) -> bool {
+//!         first_key == "pairs"
+//!     }
+//!
+//!     fn reify(&self, mut node: BTreeMap<String, Ipld>) -> Result<Ipld, String> {
+//!         let Some(Ipld::List(pairs)) = node.remove("pairs") else {
+//!             return Ok(Ipld::Map(node));
+//!         };
+//!         let mut map = BTreeMap::new();
+//!         for pair in pairs {
+//!             match pair {
+//!                 Ipld::List(mut pair) if pair.len() == 2 => {
+//!                     let value = pair.pop().unwrap();
+//!                     let Some(Ipld::String(key)) = pair.pop() else {
+//!                         return Err("keys must be strings".to_string());
+//!                     };
+//!                     map.insert(key, value);
+//!                 }
+//!                 _ => return Err("expected a pair".to_string()),
+//!             }
+//!         }
+//!         Ok(Ipld::Map(map))
+//!     }
+//! }
+//!
+//! let options = DecodeOptions::new().adl(Pairs);
+//! let input = br#"{"pairs": [["a", 1], ["b", 2]]}"#;
+//! let map: BTreeMap<String, u8> = de::from_slice_with_options(input, &options).unwrap();
+//! assert_eq!(map, BTreeMap::from([("a".to_string(), 1), ("b".to_string(), 2)]));
+//! ```
+use std::{collections::BTreeMap, fmt, sync::Arc};
+
+use ipld_core::ipld::Ipld;
+
+/// An Advanced Data Layout, which presents maps of a certain shape as a different logical value.
+pub trait Adl {
+    /// Returns whether maps starting with the given key might be nodes of this layout.
+    ///
+    /// Only those maps are buffered and passed on to [`Adl::reify`], all other maps are decoded
+    /// as usual, without any overhead.
+    fn matches(&self, first_key: &str) -> bool;
+
+    /// Returns the logical value of a node, the node itself is returned to keep it as it is.
+    ///
+    /// The error message is returned as decoding error.
+    fn reify(&self, node: BTreeMap<String, Ipld>) -> Result<Ipld, String>;
+}
+
+/// The registered layouts, by default there are none.
+#[derive(Clone, Default)]
+pub(crate) struct Adls(Vec<Arc<dyn Adl + Send + Sync>>);
+
+impl Adls {
+    pub(crate) fn push(&mut self, adl: Arc<dyn Adl + Send + Sync>) {
+        self.0.push(adl);
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns the first layout matching a map with the given first key.
+    pub(crate) fn find(&self, first_key: &str) -> Option<&Arc<dyn Adl + Send + Sync>> {
+        self.0.iter().find(|adl| adl.matches(first_key))
+    }
+}
+
+impl fmt::Debug for Adls {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Adls({})", self.0.len())
+    }
+}
@@ -0,0 +1,666 @@
+//! Encoding and decoding of types as DAG-JSON blocks.
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashSet},
+    mem,
+};
+
+use ipld_core::{
+    cid::{multihash::Multihash, Cid},
+    codec::Codec,
+    ipld::Ipld,
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    codec::DagJsonCodec,
+    de::DecodeOptions,
+    error::{CodecError, DecodeError, EncodeError},
+    index::{segments, to_path},
+    traversal::join_path,
+};
+
+#[doc(hidden)]
+pub mod __private {
+    pub use serde::{de::DeserializeOwned, Serialize};
+}
+
+/// Hashes the encoded data of a block.
+///
+/// It's implemented for all functions and closures returning a [`Multihash`].
+pub trait BlockHasher {
+    /// Returns the multihash of the given data.
+    fn digest(&self, data: &[u8]) -> Multihash<64>;
+}
+
+impl<F> BlockHasher for F
+where
+    F: Fn(&[u8]) -> Multihash<64>,
+{
+    fn digest(&self, data: &[u8]) -> Multihash<64> {
+        self(data)
+    }
+}
+
+/// A type that can be stored as DAG-JSON block.
+///
+/// All methods have default implementations, with the `derive` feature enabled it can be
+/// implemented with `#[derive(DagJsonBlock)]`.
+///
+/// # Examples
+///
+/// ```
+/// use ipld_core::cid::{multihash::Multihash, Cid};
+/// use serde::{Deserialize, Serialize};
+/// use serde_ipld_dagjson::block::DagJsonBlock;
+///
+/// #[derive(Debug, PartialEq, Deserialize, Serialize)]
+/// struct Post {
+///     text: String,
+///     parent: Option<Cid>,
+/// }
+///
+/// impl DagJsonBlock for Post {}
+///
+/// // The identity hash, for the sake of the example.
+/// let identity = |data: &[u8]| Multihash::wrap(0x00, data).unwrap();
+/// let post = Post { text: "hi".to_string(), parent: None };
+/// let (cid, bytes) = post.to_dagjson_block(&identity).unwrap();
+/// assert_eq!(cid.codec(), 0x0129);
+/// assert_eq!(Post::from_dagjson_block(&bytes).unwrap(), post);
+/// assert!(post.links().unwrap().is_empty());
+/// ```
+pub trait DagJsonBlock: Serialize + DeserializeOwned {
+    /// Encodes the value and returns it together with its CIDv1.
+    fn to_dagjson_block<H>(&self, hasher: &H) -> Result<(Cid, Vec<u8>), EncodeError>
+    where
+        H: BlockHasher + ?Sized,
+    {
+        let bytes = crate::to_vec(self)?;
+        Ok((block_cid(&bytes, hasher), bytes))
+    }
+
+    /// Decodes a value from the data of a block.
+    fn from_dagjson_block(bytes: &[u8]) -> Result<Self, DecodeError> {
+        crate::from_slice(bytes)
+    }
+
+    /// Returns all links of the value, in the order they are encoded.
+    fn links(&self) -> Result<Vec<Cid>, CodecError> {
+        self.links_with_options(&DecodeOptions::default())
+    }
+
+    /// Returns all links of the value, in the order they are encoded, with the given options.
+    ///
+    /// The options are applied like when decoding the value, e.g. the
+    /// [maximum number of links](crate::limits::Limits::max_links).
+    fn links_with_options(&self, options: &DecodeOptions) -> Result<Vec<Cid>, CodecError> {
+        let bytes = crate::to_vec(self)?;
+        let mut links = Vec::new();
+        DagJsonCodec::for_each_link_with_options(&bytes, options, |cid| links.push(cid))?;
+        Ok(links)
+    }
+}
+
+/// Returns the CIDv1 of DAG-JSON encoded data.
+fn block_cid<H>(bytes: &[u8], hasher: &H) -> Cid
+where
+    H: BlockHasher + ?Sized,
+{
+    let codec = <DagJsonCodec as Codec<Ipld>>::CODE;
+    Cid::new_v1(codec, hasher.digest(bytes))
+}
+
+/// An error when computing the CID of a block.
+#[cfg(feature = "hashes")]
+#[derive(Debug)]
+pub enum CidError {
+    /// There's no hash function for the given multihash code.
+    UnsupportedHash(u64),
+    /// The value couldn't be encoded.
+    Encode(EncodeError),
+    /// A hasher was registered for a multihash code, but returns multihashes with another one.
+    HashCodeMismatch {
+        /// The code the hasher was registered for.
+        registered: u64,
+        /// The code of the multihashes the hasher returns.
+        returned: u64,
+    },
+}
+
+#[cfg(feature = "hashes")]
+impl std::fmt::Display for CidError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnsupportedHash(code) => write!(f, "unsupported multihash code 0x{:x}", code),
+            Self::Encode(error) => write!(f, "encode error: {}", error),
+            Self::HashCodeMismatch {
+                registered,
+                returned,
+            } => write!(
+                f,
+                "hasher registered for multihash code 0x{:x} returns code 0x{:x}",
+                registered, returned
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "hashes")]
+impl std::error::Error for CidError {}
+
+/// Returns the CIDv1 of DAG-JSON encoded data, hashed with the hash function of the given
+/// multihash code, e.g. `0x12` for SHA2-256.
+///
+/// The supported hash functions are the ones of the [default registry](HasherRegistry::new).
+///
+/// # Examples
+///
+/// ```
+/// use serde_ipld_dagjson::block::cid_of;
+///
+/// let cid = cid_of(b"[1,2,3]", 0x12).unwrap();
+/// assert_eq!(cid.codec(), 0x0129);
+/// assert_eq!(cid.hash().code(), 0x12);
+/// ```
+#[cfg(feature = "hashes")]
+pub fn cid_of(bytes: &[u8], mh_code: u64) -> Result<Cid, CidError> {
+    HasherRegistry::new().cid_of(bytes, mh_code)
+}
+
+/// Encodes the value and returns the CIDv1 of the encoded data, see [`cid_of`].
+#[cfg(feature = "hashes")]
+pub fn recompute_cid<T>(value: &T, mh_code: u64) -> Result<Cid, CidError>
+where
+    T: Serialize + ?Sized,
+{
+    HasherRegistry::new().recompute_cid(value, mh_code)
+}
+
+/// The hash functions that are available by their multihash code.
+///
+/// By default it contains the SHA2 and BLAKE3 hash functions of `multihash-codetable`. Further
+/// hash functions can be [registered](Self::register), they take precedence over the default ones.
+///
+/// # Examples
+///
+/// ```
+/// use ipld_core::cid::multihash::Multihash;
+/// use serde_ipld_dagjson::block::{split, HasherRegistry, SplitPolicy};
+///
+/// /// A made up hash function, registered under a code of the private use range.
+/// fn xor(data: &[u8]) -> Multihash<64> {
+///     let digest = data.iter().fold(0u8, |acc, byte| acc ^ byte);
+///     Multihash::wrap(0x300001, &[digest]).unwrap()
+/// }
+///
+/// let registry = HasherRegistry::new().register(0x300001, xor).unwrap();
+/// assert_eq!(registry.cid_of(b"[1]", 0x300001).unwrap().hash().digest(), [b'[' ^ b'1' ^ b']']);
+/// assert_eq!(registry.cid_of(b"[1]", 0x1e).unwrap().hash().code(), 0x1e);
+///
+/// // The hashers can be used wherever a `BlockHasher` is expected.
+/// let blake3 = registry.hasher(0x1e).unwrap();
+/// let (_, blocks) = split(&ipld_core::ipld!([1]), &SplitPolicy::new(), &blake3).unwrap();
+/// assert!(blocks.is_empty());
+/// ```
+#[cfg(feature = "hashes")]
+#[derive(Clone)]
+pub struct HasherRegistry {
+    hashers: std::collections::HashMap<u64, RegisteredHasher>,
+    defaults: bool,
+}
+
+#[cfg(feature = "hashes")]
+impl HasherRegistry {
+    /// Creates a new registry with the default hash functions.
+    pub fn new() -> Self {
+        Self {
+            hashers: std::collections::HashMap::new(),
+            defaults: true,
+        }
+    }
+
+    /// Creates a new registry without any hash functions.
+    pub fn empty() -> Self {
+        Self {
+            defaults: false,
+            ..Self::new()
+        }
+    }
+
+    /// Registers a hash function for the given multihash code, which replaces a previously
+    /// registered or default one.
+    ///
+    /// The hasher must return multihashes with that code, otherwise the CIDs would claim the wrong
+    /// hash function. It's checked by hashing empty data, a mismatch results in a
+    /// [`CidError::HashCodeMismatch`].
+    pub fn register<H>(mut self, mh_code: u64, hasher: H) -> Result<Self, CidError>
+    where
+        H: BlockHasher + Send + Sync + 'static,
+    {
+        let returned = hasher.digest(&[]).code();
+        if returned != mh_code {
+            return Err(CidError::HashCodeMismatch {
+                registered: mh_code,
+                returned,
+            });
+        }
+        self.hashers
+            .insert(mh_code, RegisteredHasher(std::sync::Arc::new(hasher)));
+        Ok(self)
+    }
+
+    /// Returns the hash function for the given multihash code, if there is one.
+    pub fn hasher(&self, mh_code: u64) -> Option<RegisteredHasher> {
+        use multihash_codetable::{Code, MultihashDigest};
+
+        if let Some(hasher) = self.hashers.get(&mh_code) {
+            return Some(hasher.clone());
+        }
+        let code = Code::try_from(mh_code).ok().filter(|_| self.defaults)?;
+        Some(RegisteredHasher(std::sync::Arc::new(
+            move |data: &[u8]| code.digest(data),
+        )))
+    }
+
+    /// Returns the CIDv1 of DAG-JSON encoded data, hashed with the hash function of the given
+    /// multihash code.
+    pub fn cid_of(&self, bytes: &[u8], mh_code: u64) -> Result<Cid, CidError> {
+        let hasher = self
+            .hasher(mh_code)
+            .ok_or(CidError::UnsupportedHash(mh_code))?;
+        Ok(block_cid(bytes, &hasher))
+    }
+
+    /// Encodes the value and returns the CIDv1 of the encoded data, see
+    /// [`HasherRegistry::cid_of`].
+    pub fn recompute_cid<T>(&self, value: &T, mh_code: u64) -> Result<Cid, CidError>
+    where
+        T: Serialize + ?Sized,
+    {
+        let bytes = crate::to_vec(value).map_err(CidError::Encode)?;
+        self.cid_of(&bytes, mh_code)
+    }
+}
+
+#[cfg(feature = "hashes")]
+impl Default for HasherRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "hashes")]
+impl std::fmt::Debug for HasherRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut codes = self.hashers.keys().collect::<Vec<_>>();
+        codes.sort();
+        f.debug_struct("HasherRegistry")
+            .field("registered", &codes)
+            .field("defaults", &self.defaults)
+            .finish()
+    }
+}
+
+/// A hash function of a [`HasherRegistry`].
+#[cfg(feature = "hashes")]
+#[derive(Clone)]
+pub struct RegisteredHasher(std::sync::Arc<dyn BlockHasher + Send + Sync>);
+
+#[cfg(feature = "hashes")]
+impl BlockHasher for RegisteredHasher {
+    fn digest(&self, data: &[u8]) -> Multihash<64> {
+        self.0.digest(data)
+    }
+}
+
+#[cfg(feature = "hashes")]
+impl std::fmt::Debug for RegisteredHasher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RegisteredHasher").finish_non_exhaustive()
+    }
+}
+
+/// An encoded block together with its CID.
+pub type Block = (Cid, Vec<u8>);
+
+/// A value encoded as DAG-JSON and as DAG-CBOR block, see [`encode_dual`].
+#[cfg(feature = "dag-cbor")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DualBlock {
+    /// The DAG-JSON block.
+    pub dag_json: Block,
+    /// The DAG-CBOR block.
+    pub dag_cbor: Block,
+}
+
+/// An error when encoding a value in both codecs.
+#[cfg(feature = "dag-cbor")]
+#[derive(Debug)]
+pub enum DualEncodeError {
+    /// The value couldn't be encoded as DAG-JSON, or isn't valid in the IPLD data model.
+    DagJson(EncodeError),
+    /// The value couldn't be encoded as DAG-CBOR.
+    DagCbor(serde_ipld_dagcbor::EncodeError<std::collections::TryReserveError>),
+}
+
+#[cfg(feature = "dag-cbor")]
+impl std::fmt::Display for DualEncodeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::DagJson(error) => write!(f, "DAG-JSON encode error: {}", error),
+            Self::DagCbor(error) => write!(f, "DAG-CBOR encode error: {}", error),
+        }
+    }
+}
+
+#[cfg(feature = "dag-cbor")]
+impl std::error::Error for DualEncodeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::DagJson(error) => Some(error),
+            Self::DagCbor(error) => Some(error),
+        }
+    }
+}
+
+/// Encodes a value as DAG-JSON and as DAG-CBOR block, both are hashed with the same hasher.
+///
+/// The value is serialized only once, into the IPLD data model, which is then encoded with both
+/// codecs. Hence both blocks are in their canonical form, e.g. the fields of a struct are sorted,
+/// even though [`to_vec`](crate::to_vec) keeps their order.
+///
+/// # Examples
+///
+/// ```
+/// use ipld_core::cid::multihash::Multihash;
+/// use serde_ipld_dagjson::block::encode_dual;
+///
+/// // The identity hash, for the sake of the example.
+/// let identity = |data: &[u8]| Multihash::wrap(0x00, data).unwrap();
+/// let blocks = encode_dual(&vec!["a", "b"], &identity).unwrap();
+/// assert_eq!(blocks.dag_json.1, br#"["a","b"]"#);
+/// assert_eq!(blocks.dag_cbor.1, b"\x82\x61a\x61b");
+/// assert_eq!(blocks.dag_json.0.codec(), 0x0129);
+/// assert_eq!(blocks.dag_cbor.0.codec(), 0x71);
+/// ```
+#[cfg(feature = "dag-cbor")]
+pub fn encode_dual<T, H>(value: &T, hasher: &H) -> Result<DualBlock, DualEncodeError>
+where
+    T: Serialize + ?Sized,
+    H: BlockHasher + ?Sized,
+{
+    let ipld = ipld_core::serde::to_ipld(value)
+        .map_err(|error| DualEncodeError::DagJson(EncodeError::Message(error.to_string())))?;
+    let dag_json = crate::to_vec(&ipld).map_err(DualEncodeError::DagJson)?;
+    let dag_cbor = serde_ipld_dagcbor::to_vec(&ipld).map_err(DualEncodeError::DagCbor)?;
+    Ok(DualBlock {
+        dag_json: (block_cid(&dag_json, hasher), dag_json),
+        dag_cbor: (
+            Cid::new_v1(crate::multicodec::DAG_CBOR, hasher.digest(&dag_cbor)),
+            dag_cbor,
+        ),
+    })
+}
+
+/// Decides which subtrees [`split`] cuts out into blocks of their own.
+///
+/// By default nothing is cut out.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SplitPolicy {
+    min_size: Option<usize>,
+    paths: Vec<String>,
+}
+
+impl SplitPolicy {
+    /// Creates a new policy, which doesn't cut out anything.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cuts out all lists and maps, whose encoding is at least `size` bytes long.
+    ///
+    /// The size is measured after the nested subtrees were already replaced by links.
+    pub fn min_size(mut self, size: usize) -> Self {
+        self.min_size = Some(size);
+        self
+    }
+
+    /// Cuts out the value at the given path, the segments are separated by `/` and are
+    /// [escaped](crate::index::escape_segment).
+    pub fn path(mut self, path: impl Into<String>) -> Self {
+        self.paths.push(path.into());
+        self
+    }
+}
+
+/// Cuts subtrees out of a value into DAG-JSON blocks of their own and replaces them with links.
+///
+/// This is the inverse of [`traversal::expand`](crate::traversal::expand). Subtrees are processed
+/// bottom up, so a cut out subtree may itself link to further blocks. The blocks are returned in
+/// that order, each block comes after the blocks it links to. Equal subtrees result in the same
+/// block, which is returned only once. The value itself is never cut out.
+///
+/// # Examples
+///
+/// ```
+/// use ipld_core::{cid::multihash::Multihash, ipld, ipld::Ipld};
+/// use serde_ipld_dagjson::block::{split, SplitPolicy};
+///
+/// let identity = |data: &[u8]| Multihash::wrap(0x00, data).unwrap();
+/// let value = ipld!({"name": "post", "comments": ["first", "second"]});
+/// let policy = SplitPolicy::new().path("comments");
+/// let (root, blocks) = split(&value, &policy, &identity).unwrap();
+/// assert_eq!(blocks.len(), 1);
+/// assert_eq!(blocks[0].1, br#"["first","second"]"#);
+/// assert_eq!(root, ipld!({"name": "post", "comments": Ipld::Link(blocks[0].0)}));
+/// ```
+pub fn split<H>(
+    value: &Ipld,
+    policy: &SplitPolicy,
+    hasher: &H,
+) -> Result<(Ipld, Vec<Block>), EncodeError>
+where
+    H: BlockHasher + ?Sized,
+{
+    let mut splitter = Splitter {
+        policy,
+        hasher,
+        blocks: Vec::new(),
+        cids: HashSet::new(),
+    };
+    let root = splitter.children(value, "")?;
+    Ok((root, splitter.blocks))
+}
+
+/// The state of a [`split`].
+struct Splitter<'a, H: ?Sized> {
+    policy: &'a SplitPolicy,
+    hasher: &'a H,
+    blocks: Vec<Block>,
+    /// The CIDs of the blocks, so that each of them is only returned once.
+    cids: HashSet<Cid>,
+}
+
+impl<H> Splitter<'_, H>
+where
+    H: BlockHasher + ?Sized,
+{
+    /// Splits the children of a value, the value itself is kept.
+    fn children(&mut self, value: &Ipld, path: &str) -> Result<Ipld, EncodeError> {
+        let value = match value {
+            Ipld::List(list) => Ipld::List(
+                list.iter()
+                    .enumerate()
+                    .map(|(index, item)| self.value(item, &join_path(path, &index.to_string())))
+                    .collect::<Result<_, _>>()?,
+            ),
+            Ipld::Map(map) => Ipld::Map(
+                map.iter()
+                    .map(|(key, item)| Ok((key.clone(), self.value(item, &join_path(path, key))?)))
+                    .collect::<Result<_, EncodeError>>()?,
+            ),
+            other => other.clone(),
+        };
+        Ok(value)
+    }
+
+    /// Splits the children of a value and then possibly the value itself.
+    fn value(&mut self, value: &Ipld, path: &str) -> Result<Ipld, EncodeError> {
+        let policy = self.policy;
+        let value = self.children(value, path)?;
+        let by_path = policy.paths.iter().any(|cut| cut == path);
+        let nested = matches!(value, Ipld::List(_) | Ipld::Map(_));
+        if !(by_path || nested && policy.min_size.is_some()) {
+            return Ok(value);
+        }
+        let bytes = crate::ipld::to_vec(&value)?;
+        if !by_path && policy.min_size.is_some_and(|size| bytes.len() < size) {
+            return Ok(value);
+        }
+        let cid = block_cid(&bytes, self.hasher);
+        if self.cids.insert(cid) {
+            self.blocks.push((cid, bytes));
+        }
+        Ok(Ipld::Link(cid))
+    }
+}
+
+/// Replaces the values at the given paths with links to them, without returning their blocks.
+///
+/// The result is a smaller document, which still commits to the removed values: each link is the
+/// CID of a block `{"salt": <salt>, "value": <value>}`, which contains the removed value next to
+/// the given salt. This allows to publish a document, while revealing parts of it only to some
+/// parties, by handing them the block. The path segments are separated by `/` and are
+/// [escaped](crate::index::escape_segment). If a path is within another redacted one, it's covered
+/// by that. It's an error if there is no value at a path.
+///
+/// **The salt is what keeps the redacted values secret.** Without it, anyone could guess a value,
+/// e.g. a salary or a name, and check the guess against the CID, values with few possibilities
+/// are brute-forced in no time. Use at least 16 random bytes as salt, a new one for every
+/// document, and only disclose it together with the values. Equal values of the same document
+/// result in the same link, hence it reveals that they are equal.
+///
+/// # Examples
+///
+/// ```
+/// use ipld_core::{cid::multihash::Multihash, ipld, ipld::Ipld};
+/// use serde_ipld_dagjson::block::redact;
+///
+/// let identity = |data: &[u8]| Multihash::wrap(0x00, data).unwrap();
+/// // Use random bytes instead.
+/// let salt = b"0123456789abcdef";
+/// let value = ipld!({"name": "alice", "salary": 100});
+/// let redacted = redact(&value, ["salary"], salt, &identity).unwrap();
+/// let Ok(Some(Ipld::Link(cid))) = redacted.get("salary") else { panic!("not redacted") };
+/// assert_eq!(cid.hash().digest(), br#"{"salt":{"/":{"bytes":"MDEyMzQ1Njc4OWFiY2RlZg"}},"value":100}"#);
+/// ```
+pub fn redact<H, I, S>(value: &Ipld, paths: I, salt: &[u8], hasher: &H) -> Result<Ipld, EncodeError>
+where
+    H: BlockHasher + ?Sized,
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut paths = paths
+        .into_iter()
+        .map(|path| {
+            segments(path.as_ref())
+                .map(Cow::into_owned)
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    // Parents sort before their children, so that the children can be skipped.
+    paths.sort();
+    let mut redacted = value.clone();
+    let mut done: Vec<Vec<String>> = Vec::new();
+    for path in paths {
+        if done.iter().any(|parent| path.starts_with(parent)) {
+            continue;
+        }
+        let target = get_mut(&mut redacted, &path).ok_or_else(|| {
+            EncodeError::Message(format!("no value at path `{}`", to_path(&path)))
+        })?;
+        let salted = Ipld::Map(BTreeMap::from([
+            ("salt".to_string(), Ipld::Bytes(salt.to_vec())),
+            ("value".to_string(), mem::replace(target, Ipld::Null)),
+        ]));
+        let bytes = crate::ipld::to_vec(&salted)?;
+        *target = Ipld::Link(block_cid(&bytes, hasher));
+        done.push(path);
+    }
+    Ok(redacted)
+}
+
+/// Returns the value at the given path, without following links.
+fn get_mut<'a>(value: &'a mut Ipld, path: &[String]) -> Option<&'a mut Ipld> {
+    path.iter()
+        .try_fold(value, |current, segment| match current {
+            Ipld::Map(map) => map.get_mut(segment),
+            Ipld::List(list) => segment
+                .parse::<usize>()
+                .ok()
+                .and_then(move |index| list.get_mut(index)),
+            _ => None,
+        })
+}
+
+/// Encodes many values into blocks.
+///
+/// All values are encoded into the same buffer, so that each block is allocated with its exact
+/// size, without any intermediate allocations. The result contains one block per value, in the
+/// same order as the values.
+///
+/// # Examples
+///
+/// ```
+/// use ipld_core::cid::multihash::Multihash;
+/// use serde_ipld_dagjson::block::encode_blocks;
+///
+/// let identity = |data: &[u8]| Multihash::wrap(0x00, data).unwrap();
+/// let blocks = encode_blocks(&["a", "b"], &identity).unwrap();
+/// assert_eq!(blocks[1].1, br#""b""#);
+/// ```
+pub fn encode_blocks<'a, T, I, H>(values: I, hasher: &H) -> Result<Vec<Block>, EncodeError>
+where
+    T: Serialize + ?Sized + 'a,
+    I: IntoIterator<Item = &'a T>,
+    H: BlockHasher + ?Sized,
+{
+    let mut buffer = Vec::new();
+    values
+        .into_iter()
+        .map(|value| encode_block(value, hasher, &mut buffer))
+        .collect()
+}
+
+/// Encodes many values into blocks, in parallel.
+///
+/// The result contains one block per value, in the same order as the values.
+#[cfg(feature = "rayon")]
+pub fn par_encode_blocks<'a, T, I, H>(values: I, hasher: &H) -> Result<Vec<Block>, EncodeError>
+where
+    T: Serialize + Sync + ?Sized + 'a,
+    I: rayon::iter::IntoParallelIterator<Item = &'a T>,
+    I::Iter: rayon::iter::IndexedParallelIterator,
+    H: BlockHasher + Sync + ?Sized,
+{
+    use rayon::iter::ParallelIterator;
+
+    values
+        .into_par_iter()
+        .map_init(Vec::new, |buffer, value| {
+            encode_block(value, hasher, buffer)
+        })
+        .collect()
+}
+
+/// Encodes a single value into a block, using the given buffer.
+fn encode_block<T, H>(value: &T, hasher: &H, buffer: &mut Vec<u8>) -> Result<Block, EncodeError>
+where
+    T: Serialize + ?Sized,
+    H: BlockHasher + ?Sized,
+{
+    buffer.clear();
+    crate::to_writer(&mut *buffer, &value)?;
+    Ok((block_cid(buffer, hasher), buffer.to_vec()))
+}
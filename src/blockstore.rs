@@ -0,0 +1,150 @@
+//! Storage of blocks by their CID.
+//!
+//! The [`Blockstore`] trait is the storage interface of the DAG APIs of this crate, it's
+//! implemented on top of whatever database an application uses. [`MemoryBlockstore`] keeps the
+//! blocks in memory, e.g. for tests or small caches. It's also a
+//! [`BlockLoader`](crate::traversal::BlockLoader), hence it can be traversed directly.
+//!
+//! # Examples
+//!
+//! ```
+//! use ipld_core::cid::{multihash::Multihash, Cid};
+//! use serde_ipld_dagjson::blockstore::{Blockstore, MemoryBlockstore};
+//!
+//! let store = MemoryBlockstore::new();
+//! let data = br#"{"hello":"world"}"#.to_vec();
+//! let cid = Cid::new_v1(0x0129, Multihash::<64>::wrap(0x00, &data).unwrap());
+//! store.put(cid, data.clone()).unwrap();
+//! assert_eq!(store.get(&cid).unwrap(), Some(data));
+//! ```
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    error,
+    sync::{PoisonError, RwLock},
+};
+
+use cid::Cid;
+
+#[cfg(feature = "ipld")]
+use crate::traversal::BlockLoader;
+
+/// Stores the encoded data of blocks by their CID.
+///
+/// The store doesn't check that the CID matches the data, that's up to the caller.
+pub trait Blockstore {
+    /// The error of the underlying storage.
+    type Error: error::Error + Send + Sync + 'static;
+
+    /// Returns the data of the block with the given CID, or `None` if it isn't stored.
+    fn get(&self, cid: &Cid) -> Result<Option<Vec<u8>>, Self::Error>;
+
+    /// Stores the data of a block, a block that is already stored is replaced.
+    fn put(&self, cid: Cid, data: Vec<u8>) -> Result<(), Self::Error>;
+
+    /// Returns whether the block with the given CID is stored.
+    fn has(&self, cid: &Cid) -> Result<bool, Self::Error> {
+        Ok(self.get(cid)?.is_some())
+    }
+}
+
+impl<B> Blockstore for &B
+where
+    B: Blockstore + ?Sized,
+{
+    type Error = B::Error;
+
+    fn get(&self, cid: &Cid) -> Result<Option<Vec<u8>>, Self::Error> {
+        (**self).get(cid)
+    }
+
+    fn put(&self, cid: Cid, data: Vec<u8>) -> Result<(), Self::Error> {
+        (**self).put(cid, data)
+    }
+
+    fn has(&self, cid: &Cid) -> Result<bool, Self::Error> {
+        (**self).has(cid)
+    }
+}
+
+/// A [`Blockstore`] that keeps the blocks in memory.
+///
+/// It can be shared between threads, all methods take `&self`.
+#[derive(Debug, Default)]
+pub struct MemoryBlockstore {
+    blocks: RwLock<HashMap<Cid, Vec<u8>>>,
+}
+
+impl MemoryBlockstore {
+    /// Creates a new, empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of stored blocks.
+    pub fn len(&self) -> usize {
+        self.blocks
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+
+    /// Returns whether no blocks are stored.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the CIDs of all stored blocks, in no particular order.
+    pub fn cids(&self) -> Vec<Cid> {
+        self.blocks
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .keys()
+            .copied()
+            .collect()
+    }
+}
+
+impl Blockstore for MemoryBlockstore {
+    type Error = Infallible;
+
+    fn get(&self, cid: &Cid) -> Result<Option<Vec<u8>>, Self::Error> {
+        // A panic while holding the lock can't leave the map in an inconsistent state.
+        Ok(self
+            .blocks
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(cid)
+            .cloned())
+    }
+
+    fn put(&self, cid: Cid, data: Vec<u8>) -> Result<(), Self::Error> {
+        self.blocks
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(cid, data);
+        Ok(())
+    }
+
+    fn has(&self, cid: &Cid) -> Result<bool, Self::Error> {
+        Ok(self
+            .blocks
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .contains_key(cid))
+    }
+}
+
+#[cfg(feature = "ipld")]
+impl BlockLoader for MemoryBlockstore {
+    fn load(&self, cid: &Cid) -> Option<Vec<u8>> {
+        Blockstore::get(self, cid).unwrap_or_else(|error| match error {})
+    }
+}
+
+#[cfg(feature = "ipld")]
+impl BlockLoader for &MemoryBlockstore {
+    fn load(&self, cid: &Cid) -> Option<Vec<u8>> {
+        (**self).load(cid)
+    }
+}
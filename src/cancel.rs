@@ -0,0 +1,44 @@
+//! Cooperative cancellation.
+//!
+//! A [`CancellationToken`] is shared between a decode, or a traversal, and the code that may want
+//! to abort it, e.g. another thread or task. The token is checked periodically, so a slow or
+//! malicious stream can be aborted without killing the thread that decodes it.
+//!
+//! # Examples
+//!
+//! ```
+//! use serde_ipld_dagjson::{cancel::CancellationToken, de, DecodeError, DecodeOptions};
+//!
+//! let token = CancellationToken::new();
+//! let options = DecodeOptions::new().cancellation(token.clone());
+//! token.cancel();
+//! let result: Result<Vec<u8>, _> = de::from_slice_with_options(b"[1, 2, 3]", &options);
+//! assert!(matches!(result, Err(DecodeError::Cancelled)));
+//! ```
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// A token to cancel decodes or traversals from another thread.
+///
+/// Clones share the same state, cancelling one of them cancels all of them.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Creates a new token, which isn't cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels all operations using this token.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Returns whether the token was cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
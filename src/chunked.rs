@@ -0,0 +1,278 @@
+//! Large byte streams, which are split across linked blocks.
+//!
+//! Blocks are usually limited in size, so large binary payloads are split into chunks. Every chunk
+//! is stored as a block of its own, which contains just the bytes. The chunks are linked from
+//! blocks, which contain a list of links, those lists are again linked from lists until a single
+//! root block remains:
+//!
+//! ```json
+//! [{"/": "bafy...chunk1"}, {"/": "bafy...chunk2"}, {"/": "bafy...chunk3"}]
+//! ```
+//!
+//! A [`Chunker`] splits a stream into such blocks and a [`ChunkedReader`] reads it back as a
+//! single stream, loading the blocks on demand.
+//!
+//! # Examples
+//!
+//! ```
+//! use std::{
+//!     collections::HashMap,
+//!     hash::{DefaultHasher, Hash, Hasher},
+//!     io::Read,
+//! };
+//!
+//! use ipld_core::cid::multihash::Multihash;
+//! use serde_ipld_dagjson::chunked::{ChunkedReader, Chunker};
+//!
+//! // A toy hash, for the sake of the example.
+//! let hash = |data: &[u8]| {
+//!     let mut hasher = DefaultHasher::new();
+//!     data.hash(&mut hasher);
+//!     Multihash::wrap(0x300000, &hasher.finish().to_be_bytes()).unwrap()
+//! };
+//! let mut blocks = HashMap::new();
+//! let root = Chunker::new(hash)
+//!     .chunk_size(4)
+//!     .encode(&b"hello world"[..], |cid, data| {
+//!         blocks.insert(cid, data);
+//!     })
+//!     .unwrap();
+//! assert_eq!(blocks.len(), 4);
+//!
+//! let mut data = String::new();
+//! ChunkedReader::new(&blocks, root).read_to_string(&mut data).unwrap();
+//! assert_eq!(data, "hello world");
+//! ```
+use std::{
+    io::{self, Read},
+    mem, vec,
+};
+
+use ipld_core::{cid::Cid, ipld::Ipld};
+use serde::{ser, Serialize};
+
+use crate::{block::BlockHasher, multicodec::DAG_JSON, traversal::BlockLoader};
+
+/// The default number of bytes per chunk.
+const DEFAULT_CHUNK_SIZE: usize = 256 * 1024;
+/// The default maximum number of links per list.
+const DEFAULT_FANOUT: usize = 1024;
+/// The maximum number of nested lists the reader follows, it guards against cyclic data.
+const MAX_DEPTH: usize = 32;
+
+/// The bytes of a chunk, which are serialized as bytes instead of a list of integers.
+struct Chunk<'a>(&'a [u8]);
+
+impl Serialize for Chunk<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serializer.serialize_bytes(self.0)
+    }
+}
+
+/// Splits byte streams into chunks, which are stored as DAG-JSON blocks.
+#[derive(Clone, Debug)]
+pub struct Chunker<H> {
+    hasher: H,
+    chunk_size: usize,
+    fanout: usize,
+}
+
+impl<H> Chunker<H>
+where
+    H: BlockHasher,
+{
+    /// Creates a new chunker, which hashes the blocks with the given hasher.
+    ///
+    /// By default the chunks are 256 KiB and lists have at most 1024 links.
+    pub fn new(hasher: H) -> Self {
+        Self {
+            hasher,
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            fanout: DEFAULT_FANOUT,
+        }
+    }
+
+    /// Sets the number of bytes per chunk, only the last chunk may be smaller.
+    pub fn chunk_size(mut self, size: usize) -> Self {
+        self.chunk_size = size.max(1);
+        self
+    }
+
+    /// Sets the maximum number of links per list, it must be at least two.
+    pub fn fanout(mut self, fanout: usize) -> Self {
+        self.fanout = fanout.max(2);
+        self
+    }
+
+    /// Splits the stream into blocks and returns the CID of the root block.
+    ///
+    /// The blocks are passed to `store` as soon as they are complete, so the stream is never held
+    /// in memory as a whole. An empty stream results in a single empty chunk.
+    pub fn encode<R, F>(&self, mut reader: R, mut store: F) -> io::Result<Cid>
+    where
+        R: Read,
+        F: FnMut(Cid, Vec<u8>),
+    {
+        // The links of the lists, which aren't complete yet, by their height in the tree.
+        let mut levels: Vec<Vec<Cid>> = Vec::new();
+        let mut chunk = Vec::with_capacity(self.chunk_size);
+        loop {
+            chunk.clear();
+            (&mut reader)
+                .take(self.chunk_size as u64)
+                .read_to_end(&mut chunk)?;
+            if chunk.is_empty() && !levels.is_empty() {
+                break;
+            }
+            let cid = self.store(&Chunk(&chunk), &mut store)?;
+            self.push(&mut levels, cid, &mut store)?;
+            if chunk.len() < self.chunk_size {
+                break;
+            }
+        }
+
+        // Close the incomplete lists from the bottom up.
+        for level in 0..levels.len() {
+            let mut links = mem::take(&mut levels[level]);
+            let cid = match links.len() {
+                0 => continue,
+                1 => links.pop().expect("there is one link"),
+                _ => self.store(&links, &mut store)?,
+            };
+            match levels.get_mut(level + 1) {
+                Some(parent) => parent.push(cid),
+                None => return Ok(cid),
+            }
+        }
+        unreachable!("there is at least one chunk")
+    }
+
+    /// Adds a link at the lowest level, full lists are stored and linked one level up.
+    fn push<F>(&self, levels: &mut Vec<Vec<Cid>>, mut cid: Cid, store: &mut F) -> io::Result<()>
+    where
+        F: FnMut(Cid, Vec<u8>),
+    {
+        for level in 0.. {
+            if levels.len() == level {
+                levels.push(Vec::new());
+            }
+            levels[level].push(cid);
+            if levels[level].len() < self.fanout {
+                break;
+            }
+            let links = mem::take(&mut levels[level]);
+            cid = self.store(&links, store)?;
+        }
+        Ok(())
+    }
+
+    /// Encodes a block, passes it to `store` and returns its CID.
+    fn store<T, F>(&self, value: &T, store: &mut F) -> io::Result<Cid>
+    where
+        T: Serialize + ?Sized,
+        F: FnMut(Cid, Vec<u8>),
+    {
+        let data = crate::to_vec(value).map_err(io::Error::other)?;
+        let cid = Cid::new_v1(DAG_JSON, self.hasher.digest(&data));
+        store(cid, data);
+        Ok(cid)
+    }
+}
+
+/// Reads a byte stream, which is split across linked blocks, see the [module](self)
+/// documentation.
+///
+/// The blocks are loaded when they are needed. A missing block results in an error of kind
+/// [`io::ErrorKind::NotFound`], a block that is neither a chunk nor a list of links in an error of
+/// kind [`io::ErrorKind::InvalidData`].
+#[derive(Debug)]
+pub struct ChunkedReader<L> {
+    loader: L,
+    /// The links, which are still to be read, of every list from the root to the current chunk.
+    stack: Vec<vec::IntoIter<Cid>>,
+    chunk: Vec<u8>,
+    position: usize,
+}
+
+impl<L> ChunkedReader<L>
+where
+    L: BlockLoader,
+{
+    /// Creates a new reader of the stream with the given root block.
+    pub fn new(loader: L, root: Cid) -> Self {
+        Self {
+            loader,
+            stack: vec![vec![root].into_iter()],
+            chunk: Vec::new(),
+            position: 0,
+        }
+    }
+
+    /// Loads the next chunk, returns `false` at the end of the stream.
+    fn next_chunk(&mut self) -> io::Result<bool> {
+        loop {
+            let Some(links) = self.stack.last_mut() else {
+                return Ok(false);
+            };
+            let Some(cid) = links.next() else {
+                self.stack.pop();
+                continue;
+            };
+            let data = self.loader.load(&cid).ok_or_else(|| {
+                io::Error::new(io::ErrorKind::NotFound, format!("missing block {}", cid))
+            })?;
+            let invalid = || {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("block {} is neither bytes nor a list of links", cid),
+                )
+            };
+            match crate::from_slice(&data)
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?
+            {
+                Ipld::Bytes(bytes) => {
+                    self.chunk = bytes;
+                    self.position = 0;
+                    return Ok(true);
+                }
+                Ipld::List(list) => {
+                    if self.stack.len() > MAX_DEPTH {
+                        return Err(io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!("lists nested deeper than {} levels", MAX_DEPTH),
+                        ));
+                    }
+                    let links = list
+                        .into_iter()
+                        .map(|ipld| match ipld {
+                            Ipld::Link(cid) => Ok(cid),
+                            _ => Err(invalid()),
+                        })
+                        .collect::<io::Result<Vec<_>>>()?;
+                    self.stack.push(links.into_iter());
+                }
+                _ => return Err(invalid()),
+            }
+        }
+    }
+}
+
+impl<L> Read for ChunkedReader<L>
+where
+    L: BlockLoader,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.position == self.chunk.len() {
+            if !self.next_chunk()? {
+                return Ok(0);
+            }
+        }
+        let len = buf.len().min(self.chunk.len() - self.position);
+        buf[..len].copy_from_slice(&self.chunk[self.position..self.position + len]);
+        self.position += len;
+        Ok(len)
+    }
+}
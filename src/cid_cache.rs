@@ -0,0 +1,51 @@
+use std::collections::HashMap;
+
+use cid::Cid;
+
+/// A small least-recently-used cache of parsed CIDs, keyed by their string representation.
+#[derive(Debug)]
+pub(crate) struct CidCache {
+    capacity: usize,
+    /// Monotonically increasing counter, used to determine the least recently used entry.
+    tick: u64,
+    entries: HashMap<String, (Cid, u64)>,
+}
+
+impl CidCache {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            tick: 0,
+            entries: HashMap::with_capacity(capacity),
+        }
+    }
+
+    /// Returns the CID for the given string representation, if it's cached.
+    pub(crate) fn get(&mut self, encoded: &str) -> Option<Cid> {
+        self.tick += 1;
+        let tick = self.tick;
+        self.entries.get_mut(encoded).map(|(cid, last_used)| {
+            *last_used = tick;
+            *cid
+        })
+    }
+
+    /// Caches a CID, evicting the least recently used one if the cache is full.
+    pub(crate) fn insert(&mut self, encoded: String, cid: Cid) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.len() >= self.capacity && !self.entries.contains_key(&encoded) {
+            let least_recently_used = self
+                .entries
+                .iter()
+                .min_by_key(|(_, (_, last_used))| *last_used)
+                .map(|(key, _)| key.clone());
+            if let Some(key) = least_recently_used {
+                self.entries.remove(&key);
+            }
+        }
+        self.tick += 1;
+        self.entries.insert(encoded, (cid, self.tick));
+    }
+}
@@ -1,32 +1,190 @@
-use std::io::{BufRead, Write};
+use std::{
+    any::{Any, TypeId},
+    fmt,
+    io::{BufRead, Write},
+};
 
 use ipld_core::{
     cid::Cid,
     codec::{Codec, Links},
-    serde::ExtractLinks,
+    ipld::Ipld,
 };
 
-use serde::{de::Deserialize, ser::Serialize};
+use serde::{
+    de::{self, Deserialize, DeserializeSeed},
+    ser::Serialize,
+};
 
-use crate::{de::Deserializer, error::CodecError};
+use crate::{
+    de::{deserialize_seed_with_options, DecodeOptions, Deserializer},
+    error::{CodecError, DecodeError, EncodeError},
+    ser::Serializer,
+    shared::CidBytesVisitor,
+};
 
 /// DAG-JSON implementation of ipld-core's `Codec` trait.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct DagJsonCodec;
 
+impl DagJsonCodec {
+    /// Decodes [`Ipld`] from a reader.
+    ///
+    /// It's the code path specialized on [`Ipld`], which is faster. [`Codec::decode`] uses it
+    /// when decoding into [`Ipld`].
+    pub fn decode_ipld<R: BufRead>(reader: R) -> Result<Ipld, CodecError> {
+        Ok(crate::ipld::from_reader(reader)?)
+    }
+
+    /// Encodes [`Ipld`] into a writer.
+    ///
+    /// It's the code path specialized on [`Ipld`], which is faster. [`Codec::encode`] uses it
+    /// when encoding [`Ipld`].
+    pub fn encode_ipld<W: Write>(writer: W, data: &Ipld) -> Result<(), CodecError> {
+        Ok(crate::ipld::to_writer(writer, data)?)
+    }
+
+    /// Decodes many blocks into the same type.
+    ///
+    /// The result contains one entry per block, in the same order as the blocks.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_ipld_dagjson::codec::DagJsonCodec;
+    ///
+    /// let blocks = [&b"1"[..], &b"2"[..], &b"x"[..]];
+    /// let decoded = DagJsonCodec::decode_many::<u8, _>(blocks);
+    /// assert_eq!(decoded[1].as_ref().unwrap(), &2);
+    /// assert!(decoded[2].is_err());
+    /// ```
+    pub fn decode_many<'a, T, I>(blocks: I) -> Vec<Result<T, CodecError>>
+    where
+        T: Deserialize<'a>,
+        I: IntoIterator<Item = &'a [u8]>,
+    {
+        blocks
+            .into_iter()
+            .map(|block| Ok(crate::from_slice(block)?))
+            .collect()
+    }
+
+    /// Decodes many blocks into the same type, in parallel.
+    ///
+    /// The result contains one entry per block, in the same order as the blocks.
+    #[cfg(feature = "rayon")]
+    pub fn par_decode_many<'a, T, I>(blocks: I) -> Vec<Result<T, CodecError>>
+    where
+        T: Deserialize<'a> + Send,
+        I: rayon::iter::IntoParallelIterator<Item = &'a [u8]>,
+        I::Iter: rayon::iter::IndexedParallelIterator,
+    {
+        use rayon::iter::ParallelIterator;
+
+        blocks
+            .into_par_iter()
+            .map(|block| Ok(crate::from_slice(block)?))
+            .collect()
+    }
+
+    /// Calls `callback` for every link (CID) in the given encoded data.
+    ///
+    /// The links are passed on in the order they are encountered in the document. Contrary to
+    /// [`Links::links`], no intermediate collection of the CIDs is built up, which makes it a good
+    /// fit for counting or filtering links.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_ipld_dagjson::codec::DagJsonCodec;
+    ///
+    /// let data = br#"[{"/":"bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy"},1]"#;
+    /// let mut count = 0;
+    /// DagJsonCodec::for_each_link(data, |_cid| count += 1).unwrap();
+    /// assert_eq!(count, 1);
+    /// ```
+    pub fn for_each_link<F>(data: &[u8], mut callback: F) -> Result<(), CodecError>
+    where
+        F: FnMut(Cid),
+    {
+        let mut json_deserializer = serde_json::Deserializer::from_slice(data);
+        let deserializer = Deserializer::new(&mut json_deserializer);
+        LinkVisitor::new(&mut callback).deserialize(deserializer)?;
+        json_deserializer
+            .end()
+            .map_err(|_| DecodeError::TrailingData)?;
+        Ok(())
+    }
+
+    /// Calls `callback` for every link (CID) in the given encoded data, with the given options.
+    ///
+    /// The options are applied like when decoding, e.g. the
+    /// [maximum number of links](crate::limits::Limits::max_links) protects against documents
+    /// that would trigger an excessive number of fetches.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_ipld_dagjson::{codec::DagJsonCodec, limits::Limits, DecodeOptions};
+    ///
+    /// let link = r#"{"/":"bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy"}"#;
+    /// let data = format!("[{},{}]", link, link);
+    /// let options = DecodeOptions::new().limits(Limits::new().max_links(1));
+    /// let result = DagJsonCodec::for_each_link_with_options(data.as_bytes(), &options, |_| {});
+    /// assert!(result.is_err());
+    /// ```
+    pub fn for_each_link_with_options<F>(
+        data: &[u8],
+        options: &DecodeOptions,
+        mut callback: F,
+    ) -> Result<(), CodecError>
+    where
+        F: FnMut(Cid),
+    {
+        crate::de::check_size(data.len(), options)?;
+        let mut json_deserializer = serde_json::Deserializer::from_slice(data);
+        deserialize_seed_with_options(
+            LinkVisitor::new(&mut callback),
+            &mut json_deserializer,
+            options,
+        )?;
+        json_deserializer
+            .end()
+            .map_err(|_| DecodeError::TrailingData)?;
+        Ok(())
+    }
+}
+
+/// Decodes a value, with the code path specialized on [`Ipld`] if it's decoded into [`Ipld`].
+fn decode_specialized<T, R>(reader: R) -> Result<T, CodecError>
+where
+    T: for<'a> Deserialize<'a> + 'static,
+    R: BufRead,
+{
+    if TypeId::of::<T>() == TypeId::of::<Ipld>() {
+        let ipld: Box<dyn Any> = Box::new(DagJsonCodec::decode_ipld(reader)?);
+        return Ok(*ipld
+            .downcast::<T>()
+            .unwrap_or_else(|_| unreachable!("the type is `Ipld`")));
+    }
+    Ok(crate::from_reader(reader)?)
+}
+
 impl<T> Codec<T> for DagJsonCodec
 where
-    T: for<'a> Deserialize<'a> + Serialize,
+    T: for<'a> Deserialize<'a> + Serialize + 'static,
 {
     const CODE: u64 = 0x129;
     type Error = CodecError;
 
     fn decode<R: BufRead>(reader: R) -> Result<T, Self::Error> {
-        Ok(crate::from_reader(reader)?)
+        decode_specialized(reader)
     }
 
     fn encode<W: Write>(writer: W, data: &T) -> Result<(), Self::Error> {
-        Ok(crate::to_writer(writer, data)?)
+        match (data as &dyn Any).downcast_ref::<Ipld>() {
+            Some(ipld) => Self::encode_ipld(writer, ipld),
+            None => Ok(crate::to_writer(writer, data)?),
+        }
     }
 }
 
@@ -34,10 +192,171 @@ impl Links for DagJsonCodec {
     type LinksError = CodecError;
 
     fn links(data: &[u8]) -> Result<impl Iterator<Item = Cid>, Self::LinksError> {
-        let mut json_deserializer = serde_json::Deserializer::from_slice(data);
-        let deserializer = Deserializer::new(&mut json_deserializer);
-        Ok(ExtractLinks::deserialize(deserializer)?
-            .into_vec()
-            .into_iter())
+        let mut links = Vec::new();
+        Self::for_each_link(data, |cid| links.push(cid))?;
+        Ok(links.into_iter())
+    }
+}
+
+/// DAG-JSON implementation of ipld-core's `Codec` trait, which encodes pretty-printed documents.
+///
+/// Decoding is the same as with [`DagJsonCodec`]. Pretty-printed documents aren't canonical, their
+/// CIDs differ from the ones of the same data encoded with [`DagJsonCodec`]. It's meant for
+/// debugging and test fixtures, that are read by humans.
+///
+/// # Examples
+///
+/// ```
+/// use ipld_core::{codec::Codec, ipld};
+/// use serde_ipld_dagjson::codec::DagJsonPrettyCodec;
+///
+/// let encoded = DagJsonPrettyCodec::encode_to_vec(&ipld!({"a": [1]})).unwrap();
+/// assert_eq!(encoded, b"{\n  \"a\": [\n    1\n  ]\n}");
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct DagJsonPrettyCodec;
+
+impl<T> Codec<T> for DagJsonPrettyCodec
+where
+    T: for<'a> Deserialize<'a> + Serialize + 'static,
+{
+    const CODE: u64 = 0x129;
+    type Error = CodecError;
+
+    fn decode<R: BufRead>(reader: R) -> Result<T, Self::Error> {
+        decode_specialized(reader)
+    }
+
+    fn encode<W: Write>(writer: W, data: &T) -> Result<(), Self::Error> {
+        let mut json_serializer = serde_json::Serializer::pretty(writer);
+        data.serialize(Serializer::new(&mut json_serializer))
+            .map_err(EncodeError::from)?;
+        Ok(())
+    }
+}
+
+impl Links for DagJsonPrettyCodec {
+    type LinksError = CodecError;
+
+    fn links(data: &[u8]) -> Result<impl Iterator<Item = Cid>, Self::LinksError> {
+        DagJsonCodec::links(data)
+    }
+}
+
+/// Walks a whole document and passes every CID it encounters on to a callback.
+struct LinkVisitor<'a, F> {
+    callback: &'a mut F,
+}
+
+impl<'a, F> LinkVisitor<'a, F> {
+    fn new(callback: &'a mut F) -> Self {
+        Self { callback }
+    }
+}
+
+impl<'de, F> DeserializeSeed<'de> for LinkVisitor<'_, F>
+where
+    F: FnMut(Cid),
+{
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de, F> de::Visitor<'de> for LinkVisitor<'_, F>
+where
+    F: FnMut(Cid),
+{
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("anything at all")
+    }
+
+    fn visit_bool<E>(self, _value: bool) -> Result<Self::Value, E> {
+        Ok(())
+    }
+
+    fn visit_i64<E>(self, _value: i64) -> Result<Self::Value, E> {
+        Ok(())
+    }
+
+    fn visit_i128<E>(self, _value: i128) -> Result<Self::Value, E> {
+        Ok(())
+    }
+
+    fn visit_u64<E>(self, _value: u64) -> Result<Self::Value, E> {
+        Ok(())
+    }
+
+    fn visit_u128<E>(self, _value: u128) -> Result<Self::Value, E> {
+        Ok(())
+    }
+
+    fn visit_f64<E>(self, _value: f64) -> Result<Self::Value, E> {
+        Ok(())
+    }
+
+    fn visit_str<E>(self, _value: &str) -> Result<Self::Value, E> {
+        Ok(())
+    }
+
+    fn visit_bytes<E>(self, _value: &[u8]) -> Result<Self::Value, E> {
+        Ok(())
+    }
+
+    fn visit_byte_buf<E>(self, _value: Vec<u8>) -> Result<Self::Value, E> {
+        Ok(())
+    }
+
+    fn visit_none<E>(self) -> Result<Self::Value, E> {
+        Ok(())
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        self.deserialize(deserializer)
+    }
+
+    fn visit_unit<E>(self) -> Result<Self::Value, E> {
+        Ok(())
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        // DAG-JSON has no concept of newtype structs, hence visiting one means it's a CID.
+        let cid = deserializer.deserialize_bytes(CidBytesVisitor)?;
+        (self.callback)(cid);
+        Ok(())
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        while seq
+            .next_element_seed(LinkVisitor::new(self.callback))?
+            .is_some()
+        {}
+        Ok(())
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::MapAccess<'de>,
+    {
+        while map.next_key::<de::IgnoredAny>()?.is_some() {
+            map.next_value_seed(LinkVisitor::new(self.callback))?;
+        }
+        Ok(())
     }
 }
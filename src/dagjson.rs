@@ -0,0 +1,110 @@
+//! DAG-JSON embedded in strings.
+use std::{
+    ops::{Deref, DerefMut},
+    str::FromStr,
+};
+
+use serde::{de, ser, Deserialize, Serialize};
+
+use crate::error::{DecodeError, EncodeError};
+
+/// A value that is represented by its DAG-JSON encoding as a string.
+///
+/// It implements [`FromStr`] and has a fallible [`to_string`](Self::to_string), so that DAG-JSON
+/// can be used for command line arguments, environment variables and alike. When serialized, the value is
+/// embedded as a string containing its DAG-JSON encoding.
+///
+/// # Examples
+///
+/// ```
+/// use ipld_core::ipld::Ipld;
+/// use serde_ipld_dagjson::DagJson;
+///
+/// let arg: DagJson<Ipld> = r#"{"/": {"bytes": "dm14"}}"#.parse().unwrap();
+/// assert_eq!(*arg, Ipld::Bytes(b"vmx".to_vec()));
+/// assert_eq!(arg.to_string().unwrap(), r#"{"/":{"bytes":"dm14"}}"#);
+///
+/// let embedded = serde_ipld_dagjson::to_vec(&DagJson(vec![1, 2])).unwrap();
+/// assert_eq!(embedded, br#""[1,2]""#);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DagJson<T>(pub T);
+
+impl<T> DagJson<T> {
+    /// Returns the wrapped value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> DagJson<T>
+where
+    T: Serialize,
+{
+    /// Returns the DAG-JSON encoding of the value.
+    ///
+    /// There is no [`Display`](std::fmt::Display) implementation, as not every value can be
+    /// encoded, e.g. a NaN float.
+    pub fn to_string(&self) -> Result<String, EncodeError> {
+        let encoded = crate::to_vec(&self.0)?;
+        String::from_utf8(encoded).map_err(|error| EncodeError::Message(error.to_string()))
+    }
+}
+
+impl<T> Deref for DagJson<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for DagJson<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<T> From<T> for DagJson<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+
+impl<T> FromStr for DagJson<T>
+where
+    T: de::DeserializeOwned,
+{
+    type Err = DecodeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        crate::from_slice(s.as_bytes()).map(Self)
+    }
+}
+
+impl<T> Serialize for DagJson<T>
+where
+    T: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        let encoded = crate::to_vec(&self.0).map_err(ser::Error::custom)?;
+        let encoded = String::from_utf8(encoded).map_err(ser::Error::custom)?;
+        serializer.serialize_str(&encoded)
+    }
+}
+
+impl<'de, T> Deserialize<'de> for DagJson<T>
+where
+    T: de::DeserializeOwned,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let encoded = String::deserialize(deserializer)?;
+        encoded.parse().map_err(de::Error::custom)
+    }
+}
@@ -1,20 +1,59 @@
 //! Deserialization.
-use std::{fmt, io};
-
-use ipld_core::cid::serde::CID_SERDE_PRIVATE_IDENTIFIER;
+#[cfg(feature = "ipld")]
+use std::collections::BTreeMap;
+use std::{
+    borrow::Cow,
+    cell::{Cell, RefCell},
+    collections::{HashMap, HashSet},
+    fmt,
+    marker::PhantomData,
+    mem,
+    rc::Rc,
+    sync::{Arc, RwLock},
+    time::Instant,
+};
+#[cfg(feature = "io")]
+use std::{fs, io, path::Path};
+
+use cid::{serde::CID_SERDE_PRIVATE_IDENTIFIER, Cid, Version};
+#[cfg(feature = "ipld")]
+use ipld_core::ipld::Ipld;
+#[cfg(feature = "ipld")]
+use serde::de::value::MapAccessDeserializer;
 use serde::{
     de::{
         self,
-        value::{BytesDeserializer, StringDeserializer},
+        value::{BorrowedStrDeserializer, BytesDeserializer, StringDeserializer},
     },
     Deserialize,
 };
 
+#[cfg(feature = "ipld")]
+use crate::adl::{Adl, Adls};
+#[cfg(feature = "extensions")]
+use crate::ext::{ExtMap, Extension, Extensions, EXT_SERDE_PRIVATE_IDENTIFIER};
+#[cfg(not(feature = "float"))]
+use crate::shared::FLOATS_UNSUPPORTED;
+#[cfg(feature = "io")]
+use crate::streaming::BYTES_SINK_SERDE_PRIVATE_IDENTIFIER;
 use crate::{
+    cancel::CancellationToken,
+    cid_cache::CidCache,
     error::DecodeError,
+    index::Kind,
+    lazy::{LAZY_BYTES_SERDE_PRIVATE_IDENTIFIER, LAZY_IPLD_SERDE_PRIVATE_IDENTIFIER},
+    limits::{Limit, Limits, LinkPolicy, RejectedLink, FUEL_BYTES_PER_UNIT},
+    observe::{DecodeEvent, DecodeObserver, Observer},
     shared::{ReservedKeyMap, ReservedKeyValue, ReservedKeyValueParsed},
+    strict,
 };
 
+#[cfg(feature = "io")]
+mod iter;
+
+#[cfg(feature = "io")]
+pub use self::iter::{map_iter, seq_iter, MapIter, SeqIter};
+
 /// Decodes a value from DAG-JSON data in a slice.
 ///
 /// # Examples
@@ -40,6 +79,26 @@ where
     Ok(value)
 }
 
+/// Decodes a value from an already parsed [`serde_json::Value`], the reserved `{"/": ...}` maps
+/// are decoded into links and bytes.
+///
+/// # Examples
+///
+/// ```
+/// use ipld_core::cid::Cid;
+/// use serde_json::json;
+///
+/// let value = json!({"/": "bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy"});
+/// let cid: Cid = serde_ipld_dagjson::de::from_json_value(value).unwrap();
+/// assert_eq!(cid.codec(), 0x55);
+/// ```
+pub fn from_json_value<T>(value: serde_json::Value) -> Result<T, DecodeError>
+where
+    T: de::DeserializeOwned,
+{
+    Ok(T::deserialize(Deserializer::new(value))?)
+}
+
 /// Decodes a value from DAG-JSON data in a reader.
 ///
 /// # Examples
@@ -52,6 +111,7 @@ where
 /// let value: String = de::from_reader(&input[..]).unwrap();
 /// assert_eq!(value, "foobar");
 /// ```
+#[cfg(feature = "io")]
 pub fn from_reader<T, R>(reader: R) -> Result<T, DecodeError>
 where
     T: de::DeserializeOwned,
@@ -66,10 +126,1160 @@ where
     Ok(value)
 }
 
+/// Decodes a value from a DAG-JSON file.
+///
+/// The file is read through a buffer.
+///
+/// # Examples
+///
+/// ```no_run
+/// use ipld_core::ipld::Ipld;
+///
+/// let state: Ipld = serde_ipld_dagjson::de::from_file("state.json").unwrap();
+/// ```
+#[cfg(feature = "io")]
+pub fn from_file<T, P>(path: P) -> Result<T, DecodeError>
+where
+    T: de::DeserializeOwned,
+    P: AsRef<Path>,
+{
+    from_reader(io::BufReader::new(fs::File::open(path)?))
+}
+
+/// Decodes a value from DAG-JSON data in [`Bytes`](bytes::Bytes).
+///
+/// The data is decoded in place, without copying it first.
+#[cfg(feature = "bytes")]
+pub fn from_bytes<T>(bytes: bytes::Bytes) -> Result<T, DecodeError>
+where
+    T: de::DeserializeOwned,
+{
+    from_slice(&bytes)
+}
+
+/// Decodes a value from DAG-JSON data in a slice, with the given options.
+///
+/// # Examples
+///
+/// Fail if decoding would use too much memory
+///
+/// ```
+/// # use serde_ipld_dagjson::de::{self, DecodeOptions};
+/// let options = DecodeOptions::new().memory_budget(64);
+/// let input = br#"["a long string that exceeds the budget", "and another one"]"#;
+/// let value: Result<Vec<String>, _> = de::from_slice_with_options(input, &options);
+/// assert!(value.is_err());
+/// ```
+pub fn from_slice_with_options<'a, T>(
+    buf: &'a [u8],
+    options: &DecodeOptions,
+) -> Result<T, DecodeError>
+where
+    T: de::Deserialize<'a>,
+{
+    from_slice_seed_with_options(PhantomData, buf, options)
+}
+
+/// Decodes a value with a seed from DAG-JSON data in a slice, with the given options.
+pub(crate) fn from_slice_seed_with_options<'a, S>(
+    seed: S,
+    buf: &'a [u8],
+    options: &DecodeOptions,
+) -> Result<S::Value, DecodeError>
+where
+    S: de::DeserializeSeed<'a>,
+{
+    check_input(buf, options)?;
+    let mut json_deserializer = serde_json::Deserializer::from_slice(buf);
+    let value = deserialize_seed_with_options(seed, &mut json_deserializer, options)?;
+    json_deserializer
+        .end()
+        .map_err(|_| DecodeError::TrailingData)?;
+    options
+        .observer
+        .event(DecodeEvent::Finished { bytes: buf.len() });
+    Ok(value)
+}
+
+/// Decodes a value from DAG-JSON data in a reader, with the given options.
+#[cfg(feature = "io")]
+pub fn from_reader_with_options<T, R>(reader: R, options: &DecodeOptions) -> Result<T, DecodeError>
+where
+    T: de::DeserializeOwned,
+    R: io::Read,
+{
+    decode_reader_with_state(reader, DecodeState::new(options))
+}
+
+/// Decodes a value from DAG-JSON data in a reader, while reporting the progress.
+///
+/// The callback is called every [`PROGRESS_INTERVAL`] bytes and once the end of the input is
+/// reached. If the length of the input is known, e.g. from the file metadata, it's passed on in
+/// order to calculate the percentage.
+///
+/// # Examples
+///
+/// ```
+/// use serde_ipld_dagjson::{de, DecodeOptions};
+///
+/// let input = br#"["foo", "bar"]"#;
+/// let mut reports = Vec::new();
+/// let value: Vec<String> = de::from_reader_with_progress(
+///     &input[..],
+///     Some(input.len()),
+///     &DecodeOptions::new(),
+///     |progress| reports.push(progress.percent()),
+/// )
+/// .unwrap();
+/// assert_eq!(value, ["foo", "bar"]);
+/// assert_eq!(reports, [Some(100)]);
+/// ```
+#[cfg(feature = "io")]
+pub fn from_reader_with_progress<T, R, F>(
+    reader: R,
+    len: Option<usize>,
+    options: &DecodeOptions,
+    progress: F,
+) -> Result<T, DecodeError>
+where
+    T: de::DeserializeOwned,
+    R: io::Read,
+    F: FnMut(Progress),
+{
+    let reader = ProgressReader {
+        reader,
+        progress,
+        consumed: 0,
+        total: len,
+        next_report: PROGRESS_INTERVAL,
+        finished: false,
+    };
+    decode_reader_with_state(reader, DecodeState::new(options))
+}
+
+/// The number of bytes between two progress reports of [`from_reader_with_progress`].
+pub const PROGRESS_INTERVAL: usize = 64 * 1024;
+
+/// The progress of a decode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Progress {
+    /// The number of bytes consumed so far.
+    pub consumed: usize,
+    /// The total length of the input, if it's known.
+    pub total: Option<usize>,
+}
+
+impl Progress {
+    /// Returns the approximate percentage of the input, that was consumed, if the length is
+    /// known.
+    pub fn percent(&self) -> Option<u8> {
+        let total = self.total?;
+        if total == 0 {
+            return Some(100);
+        }
+        let percent = self.consumed.saturating_mul(100) / total;
+        Some(percent.min(100) as u8)
+    }
+}
+
+/// A reader that reports its progress.
+#[cfg(feature = "io")]
+struct ProgressReader<R, F> {
+    reader: R,
+    progress: F,
+    consumed: usize,
+    total: Option<usize>,
+    next_report: usize,
+    finished: bool,
+}
+
+#[cfg(feature = "io")]
+impl<R, F> io::Read for ProgressReader<R, F>
+where
+    R: io::Read,
+    F: FnMut(Progress),
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.reader.read(buf)?;
+        self.consumed += read;
+        let at_end = read == 0 && !buf.is_empty() && !self.finished;
+        if self.consumed >= self.next_report || at_end {
+            self.finished |= at_end;
+            self.next_report = self.consumed + PROGRESS_INTERVAL;
+            (self.progress)(Progress {
+                consumed: self.consumed,
+                total: self.total,
+            });
+        }
+        Ok(read)
+    }
+}
+
+/// Decodes a value from DAG-JSON data in a reader, aborting with a [`DecodeError::TimedOut`]
+/// once the deadline has passed.
+///
+/// Unlike a timeout around a blocking task, the decode itself stops, no work is leaked. See
+/// [`DecodeOptions::deadline`] for when the deadline is checked.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::{Duration, Instant};
+///
+/// use serde_ipld_dagjson::{de, DecodeOptions};
+///
+/// let deadline = Instant::now() + Duration::from_secs(1);
+/// let value: Vec<u8> =
+///     de::from_reader_with_deadline(&b"[1, 2, 3]"[..], deadline, &DecodeOptions::new()).unwrap();
+/// assert_eq!(value, [1, 2, 3]);
+/// ```
+#[cfg(feature = "io")]
+pub fn from_reader_with_deadline<T, R>(
+    reader: R,
+    deadline: Instant,
+    options: &DecodeOptions,
+) -> Result<T, DecodeError>
+where
+    T: de::DeserializeOwned,
+    R: io::Read,
+{
+    from_reader_with_options(reader, &options.clone().deadline(deadline))
+}
+
+/// The conditions, which abort a decode early.
+#[derive(Clone, Debug, Default)]
+struct Interrupt {
+    cancellation: Option<CancellationToken>,
+    deadline: Option<Instant>,
+}
+
+/// The number of checks between two reads of the clock, as reading it is comparatively expensive.
+const DEADLINE_INTERVAL: usize = 256;
+
+impl Interrupt {
+    /// Returns an error if the decode needs to be aborted.
+    ///
+    /// `checks` counts the calls, the deadline is only compared against the clock on the first
+    /// one and every [`DEADLINE_INTERVAL`] after that.
+    // `usize::is_multiple_of` needs a newer compiler than the crate supports.
+    #[allow(clippy::manual_is_multiple_of)]
+    fn check(&self, checks: &Cell<usize>) -> Result<(), DecodeError> {
+        if self
+            .cancellation
+            .as_ref()
+            .is_some_and(CancellationToken::is_cancelled)
+        {
+            return Err(DecodeError::Cancelled);
+        }
+        if let Some(deadline) = self.deadline {
+            let count = checks.get();
+            checks.set(count.wrapping_add(1));
+            if count % DEADLINE_INTERVAL == 0 && Instant::now() >= deadline {
+                return Err(DecodeError::TimedOut);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns an error if the input is larger than the size limit.
+pub(crate) fn check_size(size: usize, options: &DecodeOptions) -> Result<(), DecodeError> {
+    match options.limits.max_size {
+        Some(max) if size > max => Err(DecodeError::LimitExceeded(Limit::Size(max))),
+        _ => Ok(()),
+    }
+}
+
+/// Returns an error if the input is larger than the size limit or, if required, not canonical or
+/// with numbers that aren't strict.
+fn check_input(buf: &[u8], options: &DecodeOptions) -> Result<(), DecodeError> {
+    check_size(buf.len(), options)?;
+    if options.checks_syntax() {
+        strict::check_syntax(buf, options)?;
+    }
+    Ok(())
+}
+
+#[cfg(feature = "io")]
+fn decode_reader_with_state<T, R>(reader: R, state: DecodeState) -> Result<T, DecodeError>
+where
+    T: de::DeserializeOwned,
+    R: io::Read,
+{
+    let observer = state.options.observer.clone();
+    let reader = SizeLimited::new(reader, &state.options);
+    let failed = Rc::clone(&reader.failed);
+    let consumed = Rc::clone(&reader.consumed);
+    // The syntax can only be checked on the complete input.
+    let value = if state.options.checks_syntax() {
+        let mut buf = Vec::new();
+        let mut reader = reader;
+        io::Read::read_to_end(&mut reader, &mut buf)
+            .map_err(|error| failed.take().unwrap_or_else(|| error.into()))?;
+        strict::check_syntax(&buf, &state.options)?;
+        decode_with_state(serde_json::de::SliceRead::new(&buf), state)?
+    } else {
+        // The parser turns the I/O error of the reader into a generic one, hence the original
+        // error is recorded separately.
+        decode_with_state(serde_json::de::IoRead::new(reader), state)
+            .map_err(|error| failed.take().unwrap_or(error))?
+    };
+    observer.event(DecodeEvent::Finished {
+        bytes: consumed.get(),
+    });
+    Ok(value)
+}
+
+/// A reader that fails once more data than the size limit is read, or once the decode is
+/// interrupted.
+#[cfg(feature = "io")]
+struct SizeLimited<R> {
+    reader: R,
+    max_size: Option<usize>,
+    interrupt: Interrupt,
+    /// The number of reads the interrupt was checked for.
+    interrupt_checks: Cell<usize>,
+    /// The error the reader failed with.
+    failed: Rc<RefCell<Option<DecodeError>>>,
+    /// The number of bytes read so far.
+    consumed: Rc<Cell<usize>>,
+}
+
+#[cfg(feature = "io")]
+impl<R: io::Read> io::Read for SizeLimited<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if let Err(error) = self.interrupt.check(&self.interrupt_checks) {
+            return Err(self.fail(error));
+        }
+        let consumed = self.consumed.get();
+        let Some(max) = self.max_size else {
+            let read = self.reader.read(buf)?;
+            self.consumed.set(consumed + read);
+            return Ok(read);
+        };
+        let remaining = max - consumed;
+        // Allow reading one byte more than the limit, in order to detect exceeding it.
+        let len = buf.len().min(remaining.saturating_add(1));
+        let read = self.reader.read(&mut buf[..len])?;
+        if read > remaining {
+            return Err(self.fail(DecodeError::LimitExceeded(Limit::Size(max))));
+        }
+        self.consumed.set(consumed + read);
+        Ok(read)
+    }
+}
+
+#[cfg(feature = "io")]
+impl<R> SizeLimited<R> {
+    fn new(reader: R, options: &DecodeOptions) -> Self {
+        Self {
+            reader,
+            max_size: options.limits.max_size,
+            interrupt: options.interrupt.clone(),
+            interrupt_checks: Cell::new(0),
+            failed: Rc::new(RefCell::new(None)),
+            consumed: Rc::new(Cell::new(0)),
+        }
+    }
+
+    /// Records the error, the returned I/O error only carries its message.
+    fn fail(&self, error: DecodeError) -> io::Error {
+        let io_error = io::Error::other(error.to_string());
+        *self.failed.borrow_mut() = Some(error);
+        io_error
+    }
+}
+
+fn decode_with_state<'de, T, R>(read: R, state: DecodeState) -> Result<T, DecodeError>
+where
+    T: de::Deserialize<'de>,
+    R: serde_json::de::Read<'de>,
+{
+    decode_with_shared_state(read, &Rc::new(state))
+}
+
+fn decode_with_shared_state<'de, T, R>(read: R, state: &Rc<DecodeState>) -> Result<T, DecodeError>
+where
+    T: de::Deserialize<'de>,
+    R: serde_json::de::Read<'de>,
+{
+    let mut json_deserializer = serde_json::Deserializer::new(read);
+    let value = deserialize_seed_with_shared_state(PhantomData, &mut json_deserializer, state)?;
+    json_deserializer
+        .end()
+        .map_err(|_| DecodeError::TrailingData)?;
+    Ok(value)
+}
+
+/// Deserializes a value with the given options, on top of any underlying deserializer.
+pub(crate) fn deserialize_with_options<'de, T, D>(
+    de: D,
+    options: &DecodeOptions,
+) -> Result<T, DecodeError>
+where
+    T: de::Deserialize<'de>,
+    D: de::Deserializer<'de>,
+    D::Error: Into<DecodeError>,
+{
+    deserialize_seed_with_options(PhantomData, de, options)
+}
+
+/// Deserializes a value with a seed and the given options, on top of any underlying deserializer.
+pub(crate) fn deserialize_seed_with_options<'de, S, D>(
+    seed: S,
+    de: D,
+    options: &DecodeOptions,
+) -> Result<S::Value, DecodeError>
+where
+    S: de::DeserializeSeed<'de>,
+    D: de::Deserializer<'de>,
+    D::Error: Into<DecodeError>,
+{
+    deserialize_seed_with_state(seed, de, DecodeState::new(options))
+}
+
+fn deserialize_seed_with_state<'de, S, D>(
+    seed: S,
+    de: D,
+    state: DecodeState,
+) -> Result<S::Value, DecodeError>
+where
+    S: de::DeserializeSeed<'de>,
+    D: de::Deserializer<'de>,
+    D::Error: Into<DecodeError>,
+{
+    deserialize_seed_with_shared_state(seed, de, &Rc::new(state))
+}
+
+fn deserialize_seed_with_shared_state<'de, S, D>(
+    seed: S,
+    de: D,
+    state: &Rc<DecodeState>,
+) -> Result<S::Value, DecodeError>
+where
+    S: de::DeserializeSeed<'de>,
+    D: de::Deserializer<'de>,
+    D::Error: Into<DecodeError>,
+{
+    let deserializer = Deserializer::with_state(de, Some(Rc::clone(state)));
+    // Errors detected by this crate are recorded in the state, as the error type of the underlying
+    // deserializer can only carry a message.
+    seed.deserialize(deserializer)
+        .map_err(|error| state.take_error().unwrap_or_else(|| error.into()))
+}
+
+/// Options for decoding DAG-JSON.
+///
+/// By default no limits are applied.
+#[derive(Clone, Debug, Default)]
+pub struct DecodeOptions {
+    memory_budget: Option<usize>,
+    cid_cache_capacity: usize,
+    limits: Limits,
+    link_policy: LinkPolicy,
+    #[cfg(feature = "ipld")]
+    inline_identity: bool,
+    cid_v0: CidV0,
+    strict_multibase: bool,
+    canonical: bool,
+    strict_numbers: bool,
+    strict_kinds: bool,
+    #[cfg(feature = "ipld")]
+    adls: Adls,
+    observer: Observer,
+    interrupt: Interrupt,
+    #[cfg(feature = "extensions")]
+    extensions: Extensions,
+}
+
+/// How CIDv0 links are handled when decoding.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CidV0 {
+    /// Keep them as they are.
+    #[default]
+    Keep,
+    /// Upgrade them to the equivalent CIDv1, which is encoded in base32.
+    Upgrade,
+    /// Reject them with a [`DecodeError::LinkRejected`].
+    Reject,
+}
+
+impl DecodeOptions {
+    /// Creates new options with the default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limits the memory (in bytes) that may be used by the decoded data.
+    ///
+    /// The accounting is an estimate: the length of all strings and bytes, plus a fixed overhead
+    /// for every decoded node. Exceeding the budget results in a
+    /// [`DecodeError::MemoryBudgetExceeded`].
+    pub fn memory_budget(mut self, budget: usize) -> Self {
+        self.memory_budget = Some(budget);
+        self
+    }
+
+    /// Caches up to `capacity` parsed CIDs during a single decode.
+    ///
+    /// Documents often contain the same link many times, with the cache the string representation
+    /// of such a link is only decoded once. Least recently used CIDs are evicted first. A capacity
+    /// of zero (the default) disables the cache.
+    pub fn cid_cache(mut self, capacity: usize) -> Self {
+        self.cid_cache_capacity = capacity;
+        self
+    }
+
+    /// Applies the given limits, e.g. [`Limits::strict_network`] for untrusted input.
+    ///
+    /// Exceeding any of them results in a [`DecodeError::LimitExceeded`].
+    pub fn limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Restricts which links are acceptable.
+    ///
+    /// Links that are rejected by the policy result in a [`DecodeError::LinkRejected`].
+    pub fn link_policy(mut self, policy: LinkPolicy) -> Self {
+        self.link_policy = policy;
+        self
+    }
+
+    /// Decodes links with the identity multihash as the data they contain.
+    ///
+    /// Identity CIDs embed small values directly instead of hashing them. If the CID has the
+    /// DAG-JSON codec, the embedded DAG-JSON is decoded in place of the link. With the raw codec
+    /// it's decoded as bytes. Other codecs, and values explicitly decoded as a CID, stay links.
+    #[cfg(feature = "ipld")]
+    pub fn inline_identity(mut self, inline: bool) -> Self {
+        self.inline_identity = inline;
+        self
+    }
+
+    /// Sets how CIDv0 links are handled.
+    ///
+    /// Upgrading them makes stored graphs uniform, regardless of which version the producer
+    /// used. Decoding into [`Ipld`] and encoding it again then results in the canonical form.
+    pub fn cid_v0(mut self, cid_v0: CidV0) -> Self {
+        self.cid_v0 = cid_v0;
+        self
+    }
+
+    /// Only accepts links in their conventional string form.
+    ///
+    /// By default links may be encoded in any multibase. With this option, CIDv1 must be encoded
+    /// in lower case base32 and CIDv0 in base58btc (without a multibase prefix). Other links
+    /// result in a [`DecodeError::LinkRejected`].
+    pub fn strict_multibase(mut self, strict: bool) -> Self {
+        self.strict_multibase = strict;
+        self
+    }
+
+    /// Only accepts input in the canonical form, whatever type it's decoded into.
+    ///
+    /// The input is validated like in [canonical](crate::strict::StrictDeserializer::canonical)
+    /// mode of the strict parser: no whitespace, map keys sorted by their bytes and numbers in
+    /// their shortest form. The spelling of numbers is defined by this crate, so the verdict
+    /// doesn't depend on the `serde_json` version. Non-canonical input results in a [`DecodeError::Syntax`], even if it
+    /// could be decoded. When decoding from a reader, the input is read completely before it's
+    /// decoded.
+    pub fn canonical(mut self, canonical: bool) -> Self {
+        self.canonical = canonical;
+        self
+    }
+
+    /// Returns whether only canonical input is accepted.
+    pub(crate) fn is_canonical(&self) -> bool {
+        self.canonical
+    }
+
+    /// Only accepts numbers, which are spelled the way the encoder writes them.
+    ///
+    /// The input is validated like with [strict
+    /// numbers](crate::strict::StrictDeserializer::strict_numbers) of the strict parser: no
+    /// negative zero and floats in their shortest form, e.g. `1e100`, but not `1E2`, `1e+100` or
+    /// `1.50`. The spelling is defined by this crate, it doesn't depend on the `serde_json`
+    /// version.
+    /// Unlike [`DecodeOptions::canonical`], whitespace and the order of map keys are up to the
+    /// producer. Other numbers result in a [`DecodeError::Syntax`]. The input is checked by the
+    /// strict parser, so everything else it rejects, like duplicate map keys, is rejected too.
+    /// When decoding from a reader, the input is read completely before it's decoded.
+    pub fn strict_numbers(mut self, strict: bool) -> Self {
+        self.strict_numbers = strict;
+        self
+    }
+
+    /// Returns whether only strict numbers are accepted.
+    pub(crate) fn has_strict_numbers(&self) -> bool {
+        self.strict_numbers
+    }
+
+    /// Requires numbers to be of the kind of the type they are decoded into.
+    ///
+    /// By default an integer like `3` may be decoded into an `f64`. With this option, fields of
+    /// integer types only accept integers and fields of float types only accept floats like
+    /// `3.0`. Other numbers result in a [`DecodeError::KindMismatch`], which contains the path of
+    /// the number. Values that are decoded without a specific type, e.g. into
+    /// [`Ipld`](ipld_core::ipld::Ipld), aren't affected.
+    pub fn strict_kinds(mut self, strict: bool) -> Self {
+        self.strict_kinds = strict;
+        self
+    }
+
+    /// Returns whether the input needs to be checked by the strict parser.
+    fn checks_syntax(&self) -> bool {
+        self.canonical || self.strict_numbers
+    }
+
+    /// Registers an [Advanced Data Layout](crate::adl), which may replace maps with a logical
+    /// value.
+    ///
+    /// Layouts are asked in the order they were registered.
+    #[cfg(feature = "ipld")]
+    pub fn adl<A>(mut self, adl: A) -> Self
+    where
+        A: Adl + Send + Sync + 'static,
+    {
+        self.adls.push(Arc::new(adl));
+        self
+    }
+
+    /// Registers an [observer](crate::observe), which receives an event for every decoded node
+    /// and link.
+    ///
+    /// A previously registered observer is replaced.
+    pub fn observer<O>(mut self, observer: O) -> Self
+    where
+        O: DecodeObserver + Send + Sync + 'static,
+    {
+        self.observer = Observer::new(Arc::new(observer));
+        self
+    }
+
+    /// Aborts the decode with a [`DecodeError::Cancelled`], once the given token is cancelled.
+    ///
+    /// The token is checked for every decoded node and, when decoding from a reader, for every
+    /// read.
+    pub fn cancellation(mut self, token: CancellationToken) -> Self {
+        self.interrupt.cancellation = Some(token);
+        self
+    }
+
+    /// Registers an [extension](crate::ext), which isn't part of DAG-JSON.
+    ///
+    /// Only registered extensions can be decoded into [`Ext`](crate::ext::Ext).
+    #[cfg(feature = "extensions")]
+    pub fn extension<T: Extension>(mut self) -> Self {
+        self.extensions.push(T::TAG);
+        self
+    }
+
+    /// Aborts the decode with a [`DecodeError::TimedOut`], once the deadline has passed.
+    ///
+    /// The deadline is checked before the first decoded node and then every few hundred decoded
+    /// nodes and, when decoding from a reader, every few hundred reads, in order to keep the cost
+    /// of reading the clock low. A reader that blocks forever can't be interrupted though.
+    pub fn deadline(mut self, deadline: Instant) -> Self {
+        self.interrupt.deadline = Some(deadline);
+        self
+    }
+}
+
+/// The estimated memory overhead of a single decoded node, the size of its largest kind, a link.
+const NODE_OVERHEAD: usize = mem::size_of::<Cid>();
+
+/// State that is shared across all layers of a single decode.
+#[derive(Debug)]
+struct DecodeState {
+    options: DecodeOptions,
+    memory_used: Cell<usize>,
+    depth: Cell<usize>,
+    links: Cell<usize>,
+    fuel: Cell<usize>,
+    /// The number of nodes the interrupt was checked for.
+    interrupt_checks: Cell<usize>,
+    cid_cache: Option<RefCell<CidCache>>,
+    /// The struct shapes of a [`DecodePlan`], if the decode is using one.
+    struct_shapes: Option<Arc<RwLock<StructShapes>>>,
+    /// The path segments of the value that is currently decoded, if the path is tracked.
+    path: RefCell<Vec<String>>,
+    /// The last decoded map key, it's the path segment of the following value.
+    key: RefCell<Option<String>>,
+    /// Whether a map key is currently decoded.
+    reading_key: Cell<bool>,
+    error: RefCell<Option<DecodeError>>,
+}
+
+impl DecodeState {
+    fn new(options: &DecodeOptions) -> Self {
+        let cid_cache = (options.cid_cache_capacity > 0)
+            .then(|| RefCell::new(CidCache::new(options.cid_cache_capacity)));
+        Self {
+            options: options.clone(),
+            memory_used: Cell::new(0),
+            depth: Cell::new(0),
+            links: Cell::new(0),
+            fuel: Cell::new(0),
+            interrupt_checks: Cell::new(0),
+            cid_cache,
+            struct_shapes: None,
+            path: RefCell::new(Vec::new()),
+            key: RefCell::new(None),
+            reading_key: Cell::new(false),
+            error: RefCell::new(None),
+        }
+    }
+
+    /// Prepares the state for the next decode, the allocated buffers and the CID cache are kept.
+    fn reset(&mut self) {
+        self.memory_used.set(0);
+        self.depth.set(0);
+        self.links.set(0);
+        self.fuel.set(0);
+        self.interrupt_checks.set(0);
+        self.path.get_mut().clear();
+        *self.key.get_mut() = None;
+        self.reading_key.set(false);
+        *self.error.get_mut() = None;
+    }
+
+    /// Returns the field table of a struct, if the decode is using a [`DecodePlan`].
+    fn field_table(&self, fields: &'static [&'static str]) -> Option<Arc<FieldTable>> {
+        // Layouts are selected by the first key, they need it as it is.
+        #[cfg(feature = "ipld")]
+        if !self.options.adls.is_empty() {
+            return None;
+        }
+        let struct_shapes = self.struct_shapes.as_ref()?;
+        // The table only depends on the fields, hence they identify the shape, not the name, which
+        // isn't unique.
+        let shape = (fields.as_ptr() as usize, fields.len());
+        if let Some(table) = struct_shapes.read().ok()?.get(&shape) {
+            return Some(Arc::clone(table));
+        }
+        let table = Arc::new(fields.iter().copied().collect::<FieldTable>());
+        struct_shapes
+            .write()
+            .ok()?
+            .insert(shape, Arc::clone(&table));
+        Some(table)
+    }
+
+    /// Records an error and returns a generic one that carries the same message.
+    fn fail<E>(&self, error: DecodeError) -> E
+    where
+        E: de::Error,
+    {
+        let message = error.to_string();
+        *self.error.borrow_mut() = Some(error.traced());
+        E::custom(message)
+    }
+
+    fn take_error(&self) -> Option<DecodeError> {
+        self.error.borrow_mut().take()
+    }
+
+    /// Returns whether the path of the decoded value is tracked.
+    fn tracks_path(&self) -> bool {
+        self.options.strict_kinds
+    }
+
+    /// Records a decoded string as map key, if a key is currently decoded.
+    fn key(&self, value: &str) {
+        if self.reading_key.replace(false) {
+            *self.key.borrow_mut() = Some(value.to_string());
+        }
+    }
+
+    /// Fails if a number of the given kind is decoded into a type of another kind.
+    fn check_kind<E>(&self, expected: Kind, found: Kind) -> Result<(), E>
+    where
+        E: de::Error,
+    {
+        if expected == found {
+            return Ok(());
+        }
+        Err(self.fail(DecodeError::KindMismatch {
+            path: self.path.borrow().join("/"),
+            expected,
+            found,
+        }))
+    }
+
+    /// Accounts for a decoded node, `size` is the size of its content, e.g. the length of a string.
+    fn node<E>(&self, kind: Kind, size: usize) -> Result<(), E>
+    where
+        E: de::Error,
+    {
+        self.options.observer.event(DecodeEvent::Node(kind));
+        if let Err(error) = self.options.interrupt.check(&self.interrupt_checks) {
+            return Err(self.fail(error));
+        }
+        if let Some(max) = self.options.limits.max_fuel {
+            let fuel = self
+                .fuel
+                .get()
+                .saturating_add(1)
+                .saturating_add(size / FUEL_BYTES_PER_UNIT);
+            self.fuel.set(fuel);
+            if fuel > max {
+                return Err(self.fail(DecodeError::LimitExceeded(Limit::Fuel(max))));
+            }
+        }
+        let memory_used = self
+            .memory_used
+            .get()
+            .saturating_add(NODE_OVERHEAD)
+            .saturating_add(size);
+        self.memory_used.set(memory_used);
+        match self.options.memory_budget {
+            Some(budget) if memory_used > budget => {
+                Err(self.fail(DecodeError::MemoryBudgetExceeded(budget)))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Enters a list or map, fails if it exceeds the depth limit.
+    fn enter<E>(&self) -> Result<(), E>
+    where
+        E: de::Error,
+    {
+        let depth = self.depth.get() + 1;
+        self.depth.set(depth);
+        match self.options.limits.max_depth {
+            Some(max) if depth > max => {
+                Err(self.fail(DecodeError::LimitExceeded(Limit::Depth(max))))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn exit(&self) {
+        self.depth.set(self.depth.get() - 1);
+    }
+
+    /// Parses the value of a map with the reserved key `/`, the result is checked against the
+    /// limits.
+    fn parse_reserved<E>(&self, value: &ReservedKeyValue) -> Result<ReservedKeyValueParsed, E>
+    where
+        E: de::Error,
+    {
+        let mut parsed = self.parse_reserved_cached(value)?;
+        if let (ReservedKeyValue::Cid(encoded), ReservedKeyValueParsed::Cid(cid)) = (value, &parsed)
+        {
+            // The string form of a CID uses the conventional multibase.
+            if self.options.strict_multibase && cid.to_string() != *encoded {
+                return Err(self.fail(DecodeError::LinkRejected(RejectedLink::Multibase)));
+            }
+        }
+        if let ReservedKeyValueParsed::Cid(cid) = &mut parsed {
+            if cid.version() == Version::V0 {
+                match self.options.cid_v0 {
+                    CidV0::Keep => {}
+                    CidV0::Upgrade => *cid = cid.into_v1().map_err(de::Error::custom)?,
+                    CidV0::Reject => {
+                        return Err(self.fail(DecodeError::LinkRejected(RejectedLink::Version0)))
+                    }
+                }
+            }
+        }
+        let limits = &self.options.limits;
+        match &parsed {
+            ReservedKeyValueParsed::Cid(cid) => {
+                if let Err(rejected) = self.options.link_policy.check(cid) {
+                    return Err(self.fail(DecodeError::LinkRejected(rejected)));
+                }
+                self.options.observer.event(DecodeEvent::Link(cid));
+                let links = self.links.get() + 1;
+                self.links.set(links);
+                match limits.max_links {
+                    Some(max) if links > max => {
+                        return Err(self.fail(DecodeError::LimitExceeded(Limit::Links(max))))
+                    }
+                    _ => {}
+                }
+            }
+            ReservedKeyValueParsed::Bytes(bytes) => match limits.max_bytes_len {
+                Some(max) if bytes.len() > max => {
+                    return Err(self.fail(DecodeError::LimitExceeded(Limit::BytesLen(max))))
+                }
+                _ => {}
+            },
+        }
+        Ok(parsed)
+    }
+
+    /// Returns the value an identity CID contains, if it should be inlined.
+    ///
+    /// Embedded DAG-JSON is decoded with this state, so that it counts towards the limits of the
+    /// whole decode.
+    #[cfg(feature = "ipld")]
+    fn inline_identity<E>(self: &Rc<Self>, cid: &Cid) -> Result<Option<Ipld>, E>
+    where
+        E: de::Error,
+    {
+        if !self.options.inline_identity || cid.hash().code() != crate::limits::IDENTITY_CODE {
+            return Ok(None);
+        }
+        match cid.codec() {
+            // Not decoded with `from_slice_with_options`, as the embedded data isn't a separate
+            // decode for the observer.
+            crate::multicodec::DAG_JSON => {
+                let digest = cid.hash().digest();
+                check_input(digest, &self.options)
+                    .and_then(|_| {
+                        decode_with_shared_state(serde_json::de::SliceRead::new(digest), self)
+                    })
+                    .map(Some)
+                    .map_err(|error| self.fail(error))
+            }
+            crate::multicodec::RAW => Ok(Some(Ipld::Bytes(cid.hash().digest().to_vec()))),
+            _ => Ok(None),
+        }
+    }
+
+    /// Parses the value of a map with the reserved key `/`, using the CID cache if enabled.
+    fn parse_reserved_cached<E>(
+        &self,
+        value: &ReservedKeyValue,
+    ) -> Result<ReservedKeyValueParsed, E>
+    where
+        E: de::Error,
+    {
+        match (value, &self.cid_cache) {
+            (ReservedKeyValue::Cid(encoded), Some(cid_cache)) => {
+                let cached = cid_cache.borrow_mut().get(encoded);
+                let cid = match cached {
+                    Some(cid) => cid,
+                    None => match value.parse()? {
+                        ReservedKeyValueParsed::Cid(cid) => {
+                            cid_cache.borrow_mut().insert(encoded.clone(), cid);
+                            cid
+                        }
+                        parsed => return Ok(parsed),
+                    },
+                };
+                Ok(ReservedKeyValueParsed::Cid(cid))
+            }
+            _ => value.parse(),
+        }
+    }
+}
+
+/// Parses the value of a map with the reserved key `/`, taking the decode options into account.
+fn parse_reserved<E>(
+    value: &ReservedKeyValue,
+    state: Option<&Rc<DecodeState>>,
+) -> Result<ReservedKeyValueParsed, E>
+where
+    E: de::Error,
+{
+    match state {
+        Some(state) => state.parse_reserved(value),
+        None => value.parse(),
+    }
+}
+
+/// The field names of a struct, to match the keys of a map against.
+type FieldTable = HashSet<&'static str>;
+
+/// The field tables of the structs, identified by the address and length of their fields.
+type StructShapes = HashMap<(usize, usize), Arc<FieldTable>>;
+
+/// A plan for decoding many documents into the same type.
+///
+/// The plan records the shapes of the structs it encounters and reuses them for all subsequent
+/// documents. The first key of a map needs to be checked for being the reserved `/` key, the key
+/// is then passed on to the struct. Keys that match one of the fields are looked up in the
+/// recorded field table and passed on as static string, instead of allocating a copy of them for
+/// every struct of every document. The decoded values are the same as without a plan.
+///
+/// A plan can be shared between threads, e.g. to decode blocks in parallel.
+///
+/// # Examples
+///
+/// ```
+/// # use serde::Deserialize;
+/// # use serde_ipld_dagjson::de::DecodePlan;
+/// #[derive(Deserialize)]
+/// struct Entry {
+///     name: String,
+///     size: u64,
+/// }
+///
+/// let plan = DecodePlan::<Entry>::new();
+/// for input in [&br#"{"name":"a","size":1}"#[..], &br#"{"name":"b","size":2}"#[..]] {
+///     let entry = plan.decode_slice(input).unwrap();
+///     assert_eq!(entry.name.len(), 1);
+/// }
+/// ```
+#[derive(Debug)]
+pub struct DecodePlan<T> {
+    options: DecodeOptions,
+    struct_shapes: Arc<RwLock<StructShapes>>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T> DecodePlan<T>
+where
+    T: de::DeserializeOwned,
+{
+    /// Creates a new decode plan.
+    pub fn new() -> Self {
+        Self::with_options(DecodeOptions::default())
+    }
+
+    /// Creates a new decode plan, which applies the given options on every decode.
+    pub fn with_options(options: DecodeOptions) -> Self {
+        Self {
+            options,
+            struct_shapes: Arc::new(RwLock::new(HashMap::new())),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Decodes a value from DAG-JSON data in a slice.
+    pub fn decode_slice(&self, buf: &[u8]) -> Result<T, DecodeError> {
+        check_input(buf, &self.options)?;
+        let value = decode_with_state(serde_json::de::SliceRead::new(buf), self.state())?;
+        self.options
+            .observer
+            .event(DecodeEvent::Finished { bytes: buf.len() });
+        Ok(value)
+    }
+
+    /// Decodes a value from DAG-JSON data in a reader.
+    #[cfg(feature = "io")]
+    pub fn decode_reader<R>(&self, reader: R) -> Result<T, DecodeError>
+    where
+        R: io::Read,
+    {
+        decode_reader_with_state(reader, self.state())
+    }
+
+    fn state(&self) -> DecodeState {
+        let mut state = DecodeState::new(&self.options);
+        state.struct_shapes = Some(Arc::clone(&self.struct_shapes));
+        state
+    }
+}
+
+impl<T> Default for DecodePlan<T>
+where
+    T: de::DeserializeOwned,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A reusable DAG-JSON decoder.
+///
+/// The decoder keeps its buffers between documents, so that tight loops don't allocate them for
+/// every document: decoding from a reader first reads the whole input into an internal buffer,
+/// and the state of a decode, e.g. the tracked path and the [CID cache](DecodeOptions::cid_cache),
+/// is reused as well. Only the scratch buffer of `serde_json` for strings with escapes is
+/// allocated anew, as `serde_json` doesn't expose it.
+///
+/// # Examples
+///
+/// ```
+/// # use serde_ipld_dagjson::de::Decoder;
+/// let mut decoder = Decoder::new();
+/// for input in [&br#""foo""#[..], &br#""bar""#[..]] {
+///     let value: String = decoder.decode_slice(input).unwrap();
+///     assert_eq!(value.len(), 3);
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct Decoder {
+    buffer: Vec<u8>,
+    options: DecodeOptions,
+    /// The state of the last decode, it's created on the first one.
+    state: Option<DecodeState>,
+}
+
+impl Decoder {
+    /// Creates a new decoder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new decoder, which applies the given options on every decode.
+    pub fn with_options(options: DecodeOptions) -> Self {
+        Self {
+            buffer: Vec::new(),
+            options,
+            state: None,
+        }
+    }
+
+    /// Decodes a value from DAG-JSON data in a slice.
+    pub fn decode_slice<'a, T>(&mut self, buf: &'a [u8]) -> Result<T, DecodeError>
+    where
+        T: de::Deserialize<'a>,
+    {
+        decode_reusing(buf, &self.options, &mut self.state)
+    }
+
+    /// Decodes a value from DAG-JSON data in a reader, reusing the internal buffer.
+    #[cfg(feature = "io")]
+    ///
+    /// The input is read in chunks, reading fails once the size limit is exceeded or the decode is
+    /// cancelled or past its [deadline](DecodeOptions::deadline).
+    pub fn decode_reader<T, R>(&mut self, reader: R) -> Result<T, DecodeError>
+    where
+        T: de::DeserializeOwned,
+        R: io::Read,
+    {
+        self.buffer.clear();
+        let mut reader = SizeLimited::new(reader, &self.options);
+        io::Read::read_to_end(&mut reader, &mut self.buffer)
+            .map_err(|error| reader.failed.take().unwrap_or_else(|| error.into()))?;
+        decode_reusing(&self.buffer, &self.options, &mut self.state)
+    }
+
+    /// Resets the decoder, releasing the memory of its buffers.
+    pub fn reset(&mut self) {
+        self.buffer = Vec::new();
+        self.state = None;
+    }
+}
+
+/// Decodes a value from a slice, with the state of the previous decode, if there is one.
+fn decode_reusing<'a, T>(
+    buf: &'a [u8],
+    options: &DecodeOptions,
+    reused: &mut Option<DecodeState>,
+) -> Result<T, DecodeError>
+where
+    T: de::Deserialize<'a>,
+{
+    check_input(buf, options)?;
+    let state = Rc::new(match reused.take() {
+        Some(mut state) => {
+            state.reset();
+            state
+        }
+        None => DecodeState::new(options),
+    });
+    let result = decode_with_shared_state(serde_json::de::SliceRead::new(buf), &state);
+    // All layers of the decode are dropped by now, so that the state can be taken back.
+    *reused = Rc::try_unwrap(state).ok();
+    let value = result?;
+    options
+        .observer
+        .event(DecodeEvent::Finished { bytes: buf.len() });
+    Ok(value)
+}
+
 /// A Serde `Deserializer` of DAG-JSON data.
 #[derive(Debug)]
 pub struct Deserializer<D> {
     de: D,
+    state: Option<Rc<DecodeState>>,
 }
 
 impl<'de, D> Deserializer<D>
@@ -77,7 +1287,11 @@ where
     D: de::Deserializer<'de>,
 {
     pub fn new(de: D) -> Self {
-        Self { de }
+        Self { de, state: None }
+    }
+
+    fn with_state(de: D, state: Option<Rc<DecodeState>>) -> Self {
+        Self { de, state }
     }
 
     /// Deserialize a CID.
@@ -86,21 +1300,47 @@ where
         V: de::Visitor<'de>,
     {
         let reserved = ReservedKeyMap::deserialize(self.de)?;
-        match reserved._slash.parse()? {
+        match parse_reserved(&reserved._slash, self.state.as_ref())? {
             ReservedKeyValueParsed::Cid(cid) => {
-                visitor.visit_newtype_struct(BytesDeserializer::new(&cid.to_bytes()))
+                let cid_bytes = cid.to_bytes();
+                if let Some(state) = &self.state {
+                    state.node(Kind::Link, cid_bytes.len())?;
+                }
+                visitor.visit_newtype_struct(BytesDeserializer::new(&cid_bytes))
             }
             _ => Err(de::Error::custom("Expected a CID")),
         }
     }
 
+    /// Deserialize an extension wrapper map, if the extension is registered.
+    #[cfg(feature = "extensions")]
+    fn deserialize_ext<V>(self, visitor: V) -> Result<V::Value, D::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        let ext = ExtMap::deserialize(self.de)?._slash;
+        let registered = self
+            .state
+            .as_ref()
+            .is_some_and(|state| state.options.extensions.contains(&ext.ext));
+        if !registered {
+            return Err(de::Error::custom(format!(
+                "extension `{}` isn't registered",
+                ext.ext
+            )));
+        }
+        visitor.visit_seq(de::value::SeqDeserializer::new(
+            [ext.ext, ext.value].into_iter(),
+        ))
+    }
+
     /// Deserialize bytes.
     fn deserialize_reserved_bytes<V>(self, visitor: V) -> Result<V::Value, D::Error>
     where
         V: de::Visitor<'de>,
     {
         let reserved = ReservedKeyMap::deserialize(self.de)?;
-        match reserved._slash.parse()? {
+        match parse_reserved(&reserved._slash, self.state.as_ref())? {
             ReservedKeyValueParsed::Bytes(bytes) => visitor.visit_byte_buf(bytes),
             _ => Err(de::Error::custom("Expected bytes")),
         }
@@ -117,147 +1357,168 @@ where
     where
         V: de::Visitor<'de>,
     {
-        self.de.deserialize_any(Visitor::new(visitor))
+        self.de
+            .deserialize_any(Visitor::with_state(visitor, self.state))
     }
 
     fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value, D::Error>
     where
         V: de::Visitor<'de>,
     {
-        self.de.deserialize_bool(Visitor::new(visitor))
+        self.de
+            .deserialize_bool(Visitor::with_state(visitor, self.state))
     }
 
     fn deserialize_u8<V>(self, visitor: V) -> Result<V::Value, D::Error>
     where
         V: de::Visitor<'de>,
     {
-        self.de.deserialize_u8(Visitor::new(visitor))
+        self.de
+            .deserialize_u8(Visitor::number(visitor, self.state, Kind::Integer))
     }
 
     fn deserialize_u16<V>(self, visitor: V) -> Result<V::Value, D::Error>
     where
         V: de::Visitor<'de>,
     {
-        self.de.deserialize_u16(Visitor::new(visitor))
+        self.de
+            .deserialize_u16(Visitor::number(visitor, self.state, Kind::Integer))
     }
 
     fn deserialize_u32<V>(self, visitor: V) -> Result<V::Value, D::Error>
     where
         V: de::Visitor<'de>,
     {
-        self.de.deserialize_u32(Visitor::new(visitor))
+        self.de
+            .deserialize_u32(Visitor::number(visitor, self.state, Kind::Integer))
     }
 
     fn deserialize_u64<V>(self, visitor: V) -> Result<V::Value, D::Error>
     where
         V: de::Visitor<'de>,
     {
-        self.de.deserialize_u64(Visitor::new(visitor))
+        self.de
+            .deserialize_u64(Visitor::number(visitor, self.state, Kind::Integer))
     }
 
     fn deserialize_u128<V>(self, visitor: V) -> Result<V::Value, D::Error>
     where
         V: de::Visitor<'de>,
     {
-        self.de.deserialize_u128(Visitor::new(visitor))
+        self.de
+            .deserialize_u128(Visitor::number(visitor, self.state, Kind::Integer))
     }
 
     fn deserialize_i8<V>(self, visitor: V) -> Result<V::Value, D::Error>
     where
         V: de::Visitor<'de>,
     {
-        self.de.deserialize_i8(Visitor::new(visitor))
+        self.de
+            .deserialize_i8(Visitor::number(visitor, self.state, Kind::Integer))
     }
 
     fn deserialize_i16<V>(self, visitor: V) -> Result<V::Value, D::Error>
     where
         V: de::Visitor<'de>,
     {
-        self.de.deserialize_i16(Visitor::new(visitor))
+        self.de
+            .deserialize_i16(Visitor::number(visitor, self.state, Kind::Integer))
     }
 
     fn deserialize_i32<V>(self, visitor: V) -> Result<V::Value, D::Error>
     where
         V: de::Visitor<'de>,
     {
-        self.de.deserialize_i32(Visitor::new(visitor))
+        self.de
+            .deserialize_i32(Visitor::number(visitor, self.state, Kind::Integer))
     }
 
     fn deserialize_i64<V>(self, visitor: V) -> Result<V::Value, D::Error>
     where
         V: de::Visitor<'de>,
     {
-        self.de.deserialize_i64(Visitor::new(visitor))
+        self.de
+            .deserialize_i64(Visitor::number(visitor, self.state, Kind::Integer))
     }
 
     fn deserialize_i128<V>(self, visitor: V) -> Result<V::Value, D::Error>
     where
         V: de::Visitor<'de>,
     {
-        self.de.deserialize_i128(Visitor::new(visitor))
+        self.de
+            .deserialize_i128(Visitor::number(visitor, self.state, Kind::Integer))
     }
 
     fn deserialize_f32<V>(self, visitor: V) -> Result<V::Value, D::Error>
     where
         V: de::Visitor<'de>,
     {
-        self.de.deserialize_f32(Visitor::new(visitor))
+        self.de
+            .deserialize_f32(Visitor::number(visitor, self.state, Kind::Float))
     }
 
     fn deserialize_f64<V>(self, visitor: V) -> Result<V::Value, D::Error>
     where
         V: de::Visitor<'de>,
     {
-        self.de.deserialize_f64(Visitor::new(visitor))
+        self.de
+            .deserialize_f64(Visitor::number(visitor, self.state, Kind::Float))
     }
 
     fn deserialize_char<V>(self, visitor: V) -> Result<V::Value, D::Error>
     where
         V: de::Visitor<'de>,
     {
-        self.de.deserialize_char(Visitor::new(visitor))
+        self.de
+            .deserialize_char(Visitor::with_state(visitor, self.state))
     }
 
     fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, D::Error>
     where
         V: de::Visitor<'de>,
     {
-        self.de.deserialize_str(Visitor::new(visitor))
+        self.de
+            .deserialize_str(Visitor::with_state(visitor, self.state))
     }
 
     fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, D::Error>
     where
         V: de::Visitor<'de>,
     {
-        self.de.deserialize_string(Visitor::new(visitor))
+        self.de
+            .deserialize_string(Visitor::with_state(visitor, self.state))
     }
 
     fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, D::Error>
     where
         V: de::Visitor<'de>,
     {
-        self.deserialize_reserved_bytes(Visitor::new(visitor))
+        let state = self.state.clone();
+        self.deserialize_reserved_bytes(Visitor::with_state(visitor, state))
     }
 
     fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value, D::Error>
     where
         V: de::Visitor<'de>,
     {
-        self.deserialize_reserved_bytes(Visitor::new(visitor))
+        let state = self.state.clone();
+        self.deserialize_reserved_bytes(Visitor::with_state(visitor, state))
     }
 
     fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, D::Error>
     where
         V: de::Visitor<'de>,
     {
-        self.de.deserialize_option(Visitor::new(visitor))
+        self.de
+            .deserialize_option(Visitor::with_state(visitor, self.state))
     }
 
     fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, D::Error>
     where
         V: de::Visitor<'de>,
     {
-        self.de.deserialize_unit(Visitor::new(visitor))
+        self.de
+            .deserialize_unit(Visitor::with_state(visitor, self.state))
     }
 
     fn deserialize_unit_struct<V>(
@@ -268,7 +1529,8 @@ where
     where
         V: de::Visitor<'de>,
     {
-        self.de.deserialize_unit_struct(name, Visitor::new(visitor))
+        self.de
+            .deserialize_unit_struct(name, Visitor::with_state(visitor, self.state))
     }
 
     fn deserialize_newtype_struct<V>(
@@ -279,11 +1541,35 @@ where
     where
         V: de::Visitor<'de>,
     {
+        #[cfg(feature = "extensions")]
+        if name == EXT_SERDE_PRIVATE_IDENTIFIER {
+            return self.deserialize_ext(visitor);
+        }
+        #[cfg(feature = "io")]
+        if name == BYTES_SINK_SERDE_PRIVATE_IDENTIFIER {
+            // The sink decodes the reserved map itself, so that the bytes aren't materialized.
+            if let Some(state) = &self.state {
+                state.node(Kind::Bytes, 0)?;
+            }
+            return self.de.deserialize_map(visitor);
+        }
+        if name == LAZY_BYTES_SERDE_PRIVATE_IDENTIFIER {
+            // Lazy bytes keep the base64 string, so that it's only decoded when it's accessed.
+            if let Some(state) = &self.state {
+                state.node(Kind::Bytes, 0)?;
+            }
+            return self.de.deserialize_map(visitor);
+        }
         if name == CID_SERDE_PRIVATE_IDENTIFIER {
-            self.deserialize_reserved_cid(Visitor::new(visitor))
+            let state = self.state.clone();
+            self.deserialize_reserved_cid(Visitor::with_state(visitor, state))
+        } else if name == LAZY_IPLD_SERDE_PRIVATE_IDENTIFIER {
+            // Lazy values interpret the reserved `/` maps themselves.
+            self.de
+                .deserialize_any(Visitor::without_reserved(visitor, self.state))
         } else {
             self.de
-                .deserialize_newtype_struct(name, Visitor::new(visitor))
+                .deserialize_newtype_struct(name, Visitor::with_state(visitor, self.state))
         }
     }
 
@@ -291,14 +1577,16 @@ where
     where
         V: de::Visitor<'de>,
     {
-        self.de.deserialize_seq(Visitor::new(visitor))
+        self.de
+            .deserialize_seq(Visitor::with_state(visitor, self.state))
     }
 
     fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, D::Error>
     where
         V: de::Visitor<'de>,
     {
-        self.de.deserialize_tuple(len, Visitor::new(visitor))
+        self.de
+            .deserialize_tuple(len, Visitor::with_state(visitor, self.state))
     }
 
     fn deserialize_tuple_struct<V>(
@@ -311,14 +1599,15 @@ where
         V: de::Visitor<'de>,
     {
         self.de
-            .deserialize_tuple_struct(name, len, Visitor::new(visitor))
+            .deserialize_tuple_struct(name, len, Visitor::with_state(visitor, self.state))
     }
 
     fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, D::Error>
     where
         V: de::Visitor<'de>,
     {
-        self.de.deserialize_map(Visitor::new(visitor))
+        self.de
+            .deserialize_map(Visitor::with_state(visitor, self.state))
     }
 
     fn deserialize_struct<V>(
@@ -330,8 +1619,15 @@ where
     where
         V: de::Visitor<'de>,
     {
-        self.de
-            .deserialize_struct(name, fields, Visitor::new(visitor))
+        let fields_table = self
+            .state
+            .as_ref()
+            .and_then(|state| state.field_table(fields));
+        let visitor = Visitor {
+            fields: fields_table,
+            ..Visitor::with_state(visitor, self.state)
+        };
+        self.de.deserialize_struct(name, fields, visitor)
     }
 
     fn deserialize_enum<V>(
@@ -344,21 +1640,23 @@ where
         V: de::Visitor<'de>,
     {
         self.de
-            .deserialize_enum(name, variants, Visitor::new(visitor))
+            .deserialize_enum(name, variants, Visitor::with_state(visitor, self.state))
     }
 
     fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value, D::Error>
     where
         V: de::Visitor<'de>,
     {
-        self.de.deserialize_identifier(Visitor::new(visitor))
+        self.de
+            .deserialize_identifier(Visitor::with_state(visitor, self.state))
     }
 
     fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, D::Error>
     where
         V: de::Visitor<'de>,
     {
-        self.de.deserialize_ignored_any(Visitor::new(visitor))
+        self.de
+            .deserialize_ignored_any(Visitor::with_state(visitor, self.state))
     }
 
     fn is_human_readable(&self) -> bool {
@@ -366,13 +1664,130 @@ where
     }
 }
 
-struct Visitor<V> {
+/// A visitor that applies the DAG-JSON semantics to the values it visits, before they are passed
+/// on to the wrapped visitor.
+///
+/// Maps with the reserved `/` key are visited as links or bytes and nested lists and maps are
+/// decoded with the same semantics. This is the building block of the [`Deserializer`], it can be
+/// used directly when implementing a custom [`Deserializer`](de::Deserializer) on top of another
+/// format layer, e.g. one that decrypts an envelope first. Values are decoded without
+/// [options](DecodeOptions).
+///
+/// # Examples
+///
+/// ```
+/// use std::fmt;
+///
+/// use serde::de::{self, Deserializer as _};
+/// use serde_ipld_dagjson::de::Visitor;
+///
+/// /// Only accepts bytes.
+/// struct BytesVisitor;
+///
+/// impl de::Visitor<'_> for BytesVisitor {
+///     type Value = Vec<u8>;
+///
+///     fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+///         formatter.write_str("bytes")
+///     }
+///
+///     fn visit_byte_buf<E: de::Error>(self, bytes: Vec<u8>) -> Result<Self::Value, E> {
+///         Ok(bytes)
+///     }
+/// }
+///
+/// let mut json = serde_json::Deserializer::from_slice(br#"{"/": {"bytes": "AQI"}}"#);
+/// let bytes = json.deserialize_any(Visitor::new(BytesVisitor)).unwrap();
+/// assert_eq!(bytes, [1, 2]);
+/// ```
+pub struct Visitor<V> {
     visitor: V,
+    state: Option<Rc<DecodeState>>,
+    /// Whether the first key of a map needs to be checked for being the reserved `/` key.
+    peek_reserved: bool,
+    /// The fields of the struct that is decoded, if they are known.
+    fields: Option<Arc<FieldTable>>,
+    /// The kind of number that is expected, if it's checked.
+    expected: Option<Kind>,
 }
 
 impl<V> Visitor<V> {
-    fn new(visitor: V) -> Self {
-        Self { visitor }
+    /// Wraps the given visitor.
+    pub fn new(visitor: V) -> Self {
+        Self::with_state(visitor, None)
+    }
+
+    fn with_state(visitor: V, state: Option<Rc<DecodeState>>) -> Self {
+        Self {
+            visitor,
+            state,
+            peek_reserved: true,
+            fields: None,
+            expected: None,
+        }
+    }
+
+    /// A visitor for a number of the given kind.
+    fn number(visitor: V, state: Option<Rc<DecodeState>>, kind: Kind) -> Self {
+        let expected = state
+            .as_ref()
+            .filter(|state| state.options.strict_kinds)
+            .map(|_| kind);
+        Self {
+            expected,
+            ..Self::with_state(visitor, state)
+        }
+    }
+
+    /// A visitor for maps that are known to never be a link or bytes.
+    fn without_reserved(visitor: V, state: Option<Rc<DecodeState>>) -> Self {
+        Self {
+            visitor,
+            state,
+            peek_reserved: false,
+            fields: None,
+            expected: None,
+        }
+    }
+
+    /// Accounts for a decoded node, `size` is the size of its content, e.g. the length of a string.
+    fn node<E>(&self, kind: Kind, size: usize) -> Result<(), E>
+    where
+        E: de::Error,
+    {
+        match &self.state {
+            Some(state) => {
+                if let Some(expected) = self.expected {
+                    state.check_kind(expected, kind)?;
+                }
+                state.node(kind, size)
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Records a decoded string as map key, if a key is currently decoded.
+    fn key(&self, value: &str) {
+        if let Some(state) = &self.state {
+            state.key(value);
+        }
+    }
+
+    /// Visits a list or map, while keeping track of the nesting depth.
+    fn nested<T, E, F>(self, visit: F) -> Result<T, E>
+    where
+        E: de::Error,
+        F: FnOnce(V, Option<Rc<DecodeState>>) -> Result<T, E>,
+    {
+        match self.state {
+            Some(state) => {
+                state.enter()?;
+                let result = visit(self.visitor, Some(Rc::clone(&state)));
+                state.exit();
+                result
+            }
+            None => visit(self.visitor, None),
+        }
     }
 }
 
@@ -390,6 +1805,7 @@ where
     where
         E: de::Error,
     {
+        self.node(Kind::Bool, 0)?;
         self.visitor.visit_bool(value)
     }
 
@@ -397,6 +1813,7 @@ where
     where
         E: de::Error,
     {
+        self.node(Kind::Integer, 0)?;
         self.visitor.visit_i8(value)
     }
 
@@ -404,6 +1821,7 @@ where
     where
         E: de::Error,
     {
+        self.node(Kind::Integer, 0)?;
         self.visitor.visit_i16(value)
     }
 
@@ -411,6 +1829,7 @@ where
     where
         E: de::Error,
     {
+        self.node(Kind::Integer, 0)?;
         self.visitor.visit_i32(value)
     }
 
@@ -418,6 +1837,7 @@ where
     where
         E: de::Error,
     {
+        self.node(Kind::Integer, 0)?;
         self.visitor.visit_i64(value)
     }
 
@@ -425,6 +1845,7 @@ where
     where
         E: de::Error,
     {
+        self.node(Kind::Integer, 0)?;
         self.visitor.visit_i128(value)
     }
 
@@ -432,6 +1853,7 @@ where
     where
         E: de::Error,
     {
+        self.node(Kind::Integer, 0)?;
         self.visitor.visit_u8(value)
     }
 
@@ -439,6 +1861,7 @@ where
     where
         E: de::Error,
     {
+        self.node(Kind::Integer, 0)?;
         self.visitor.visit_u16(value)
     }
 
@@ -446,6 +1869,7 @@ where
     where
         E: de::Error,
     {
+        self.node(Kind::Integer, 0)?;
         self.visitor.visit_u32(value)
     }
 
@@ -453,6 +1877,7 @@ where
     where
         E: de::Error,
     {
+        self.node(Kind::Integer, 0)?;
         self.visitor.visit_u64(value)
     }
 
@@ -460,27 +1885,49 @@ where
     where
         E: de::Error,
     {
+        self.node(Kind::Integer, 0)?;
         self.visitor.visit_u128(value)
     }
 
+    #[cfg(feature = "float")]
     fn visit_f32<E>(self, value: f32) -> Result<Self::Value, E>
     where
         E: de::Error,
     {
+        self.node(Kind::Float, 0)?;
         self.visitor.visit_f32(value)
     }
 
+    #[cfg(not(feature = "float"))]
+    fn visit_f32<E>(self, _value: f32) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Err(de::Error::custom(FLOATS_UNSUPPORTED))
+    }
+
+    #[cfg(feature = "float")]
     fn visit_f64<E>(self, value: f64) -> Result<Self::Value, E>
     where
         E: de::Error,
     {
+        self.node(Kind::Float, 0)?;
         self.visitor.visit_f64(value)
     }
 
+    #[cfg(not(feature = "float"))]
+    fn visit_f64<E>(self, _value: f64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Err(de::Error::custom(FLOATS_UNSUPPORTED))
+    }
+
     fn visit_char<E>(self, value: char) -> Result<Self::Value, E>
     where
         E: de::Error,
     {
+        self.node(Kind::String, 0)?;
         self.visitor.visit_char(value)
     }
 
@@ -488,6 +1935,8 @@ where
     where
         E: de::Error,
     {
+        self.node(Kind::String, value.len())?;
+        self.key(value);
         self.visitor.visit_str(value)
     }
 
@@ -495,6 +1944,8 @@ where
     where
         E: de::Error,
     {
+        self.node(Kind::String, value.len())?;
+        self.key(value);
         self.visitor.visit_borrowed_str(value)
     }
 
@@ -502,6 +1953,8 @@ where
     where
         E: de::Error,
     {
+        self.node(Kind::String, value.len())?;
+        self.key(&value);
         self.visitor.visit_string(value)
     }
 
@@ -509,6 +1962,7 @@ where
     where
         E: de::Error,
     {
+        self.node(Kind::Bytes, value.len())?;
         self.visitor.visit_bytes(value)
     }
 
@@ -516,6 +1970,7 @@ where
     where
         E: de::Error,
     {
+        self.node(Kind::Bytes, value.len())?;
         self.visitor.visit_borrowed_bytes(value)
     }
 
@@ -523,12 +1978,14 @@ where
     where
         E: de::Error,
     {
+        self.node(Kind::Bytes, value.len())?;
         self.visitor.visit_byte_buf(value)
     }
     fn visit_none<E>(self) -> Result<Self::Value, E>
     where
         E: de::Error,
     {
+        self.node(Kind::Null, 0)?;
         self.visitor.visit_none()
     }
 
@@ -536,13 +1993,15 @@ where
     where
         D: de::Deserializer<'de>,
     {
-        self.visitor.visit_some(Deserializer::new(deserializer))
+        self.visitor
+            .visit_some(Deserializer::with_state(deserializer, self.state))
     }
 
     fn visit_unit<E>(self) -> Result<Self::Value, E>
     where
         E: de::Error,
     {
+        self.node(Kind::Null, 0)?;
         self.visitor.visit_unit()
     }
 
@@ -557,27 +2016,73 @@ where
     where
         A: de::SeqAccess<'de>,
     {
-        self.visitor.visit_seq(SeqAccess::new(visitor))
+        self.node(Kind::List, 0)?;
+        self.nested(|inner, state| inner.visit_seq(SeqAccess::with_state(visitor, state)))
     }
 
     fn visit_map<A>(self, mut visitor: A) -> Result<Self::Value, A::Error>
     where
         A: de::MapAccess<'de>,
     {
+        if !self.peek_reserved {
+            self.node(Kind::Map, 0)?;
+            return self.nested(|inner, state| {
+                inner.visit_map(MapAccess::with_state(visitor, None, state))
+            });
+        }
+
         // Get the first key, if it's the reserved `"/"` one, deserialize in a a special way.
-        let maybe_key = visitor.next_key::<String>()?;
+        let maybe_key = match &self.fields {
+            Some(fields) => visitor.next_key_seed(FirstKey(fields))?,
+            None => visitor.next_key::<String>()?.map(Cow::Owned),
+        };
 
         match maybe_key {
             Some(ref key) if key == "/" => {
                 let value: ReservedKeyValue = visitor.next_value()?;
-                match value.parse()? {
-                    ReservedKeyValueParsed::Cid(cid) => self
-                        .visitor
-                        .visit_newtype_struct(BytesDeserializer::new(&cid.to_bytes())),
-                    ReservedKeyValueParsed::Bytes(bytes) => self.visitor.visit_byte_buf(bytes),
+                match parse_reserved(&value, self.state.as_ref())? {
+                    ReservedKeyValueParsed::Cid(cid) => {
+                        #[cfg(feature = "ipld")]
+                        if let Some(state) = &self.state {
+                            if let Some(value) = state.inline_identity(&cid)? {
+                                return de::Deserializer::deserialize_any(value, self.visitor)
+                                    .map_err(de::Error::custom);
+                            }
+                        }
+                        let cid_bytes = cid.to_bytes();
+                        self.node(Kind::Link, cid_bytes.len())?;
+                        self.visitor
+                            .visit_newtype_struct(BytesDeserializer::new(&cid_bytes))
+                    }
+                    ReservedKeyValueParsed::Bytes(bytes) => {
+                        self.node(Kind::Bytes, bytes.len())?;
+                        self.visitor.visit_byte_buf(bytes)
+                    }
                 }
             }
-            _ => self.visitor.visit_map(MapAccess::new(visitor, maybe_key)),
+            _ => {
+                self.node(Kind::Map, 0)?;
+                // The first key was already consumed, hence account for it here.
+                if let Some(key) = &maybe_key {
+                    self.node(Kind::String, key.len())?;
+                }
+                #[cfg(feature = "ipld")]
+                if let (Some(state), Some(key)) = (&self.state, &maybe_key) {
+                    if let Some(adl) = state.options.adls.find(key) {
+                        let adl = Arc::clone(adl);
+                        return self.nested(|inner, state| {
+                            let access = MapAccess::with_state(visitor, maybe_key, state);
+                            let node = BTreeMap::deserialize(MapAccessDeserializer::new(access))?;
+                            let value = adl.reify(node).map_err(de::Error::custom)?;
+                            de::Deserializer::deserialize_any(value, inner)
+                                .map_err(de::Error::custom)
+                        });
+                    }
+                }
+                self.nested(|inner, state| {
+                    inner.visit_map(MapAccess::with_state(visitor, maybe_key, state))
+                })
+            }
         }
     }
 
@@ -585,17 +2090,51 @@ where
     where
         A: de::EnumAccess<'de>,
     {
-        self.visitor.visit_enum(EnumAccess::new(visitor))
+        self.visitor
+            .visit_enum(EnumAccess::new(visitor, self.state))
+    }
+}
+
+/// Decodes the first key of a map into a struct, keys that match one of its fields aren't copied.
+struct FirstKey<'a>(&'a FieldTable);
+
+impl<'de> de::DeserializeSeed<'de> for FirstKey<'_> {
+    type Value = Cow<'static, str>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_str(self)
+    }
+}
+
+impl de::Visitor<'_> for FirstKey<'_> {
+    type Value = Cow<'static, str>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a string")
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        match self.0.get(value) {
+            Some(field) => Ok(Cow::Borrowed(field)),
+            None => Ok(Cow::Owned(value.to_string())),
+        }
     }
 }
 
 struct DeserializeSeed<S> {
     seed: S,
+    state: Option<Rc<DecodeState>>,
 }
 
 impl<S> DeserializeSeed<S> {
-    fn new(seed: S) -> Self {
-        Self { seed }
+    fn new(seed: S, state: Option<Rc<DecodeState>>) -> Self {
+        Self { seed, state }
     }
 }
 
@@ -609,17 +2148,19 @@ where
     where
         D: de::Deserializer<'de>,
     {
-        self.seed.deserialize(Deserializer::new(deserializer))
+        self.seed
+            .deserialize(Deserializer::with_state(deserializer, self.state))
     }
 }
 
 struct VariantAccess<D> {
     access: D,
+    state: Option<Rc<DecodeState>>,
 }
 
 impl<D> VariantAccess<D> {
-    fn new(access: D) -> Self {
-        Self { access }
+    fn new(access: D, state: Option<Rc<DecodeState>>) -> Self {
+        Self { access, state }
     }
 }
 
@@ -637,14 +2178,16 @@ where
     where
         T: de::DeserializeSeed<'de>,
     {
-        self.access.newtype_variant_seed(DeserializeSeed::new(seed))
+        self.access
+            .newtype_variant_seed(DeserializeSeed::new(seed, self.state))
     }
 
     fn tuple_variant<V>(self, len: usize, visitor: V) -> Result<V::Value, D::Error>
     where
         V: de::Visitor<'de>,
     {
-        self.access.tuple_variant(len, Visitor::new(visitor))
+        self.access
+            .tuple_variant(len, Visitor::with_state(visitor, self.state))
     }
 
     fn struct_variant<V>(
@@ -655,17 +2198,49 @@ where
     where
         V: de::Visitor<'de>,
     {
-        self.access.struct_variant(fields, Visitor::new(visitor))
+        self.access
+            .struct_variant(fields, Visitor::with_state(visitor, self.state))
+    }
+}
+
+/// Calls `f` with the given segment appended to the path, if the path is tracked.
+fn within<F, R>(state: Option<&Rc<DecodeState>>, segment: impl FnOnce() -> String, f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    match state {
+        Some(state) if state.tracks_path() => {
+            state.path.borrow_mut().push(segment());
+            let result = f();
+            state.path.borrow_mut().pop();
+            result
+        }
+        _ => f(),
     }
 }
 
-struct SeqAccess<D> {
+/// A sequence access that decodes its elements with the DAG-JSON semantics.
+///
+/// It wraps the access of another format layer, see [`Visitor`].
+pub struct SeqAccess<D> {
     access: D,
+    state: Option<Rc<DecodeState>>,
+    /// The index of the next element.
+    index: usize,
 }
 
 impl<D> SeqAccess<D> {
-    fn new(access: D) -> Self {
-        Self { access }
+    /// Wraps the given access.
+    pub fn new(access: D) -> Self {
+        Self::with_state(access, None)
+    }
+
+    fn with_state(access: D, state: Option<Rc<DecodeState>>) -> Self {
+        Self {
+            access,
+            state,
+            index: 0,
+        }
     }
 }
 
@@ -679,7 +2254,16 @@ where
     where
         T: de::DeserializeSeed<'de>,
     {
-        self.access.next_element_seed(DeserializeSeed::new(seed))
+        let index = self.index;
+        self.index += 1;
+        within(
+            self.state.as_ref(),
+            || index.to_string(),
+            || {
+                self.access
+                    .next_element_seed(DeserializeSeed::new(seed, self.state.clone()))
+            },
+        )
     }
 
     fn size_hint(&self) -> Option<usize> {
@@ -687,19 +2271,36 @@ where
     }
 }
 
-/// Parsed a map, may take an already parsed key.
+/// A map access that decodes its keys and values with the DAG-JSON semantics.
 ///
-/// Passing in a key makes it possible to peek into the map. In case you want to proceed parsing it
-/// as a normal map, you can just pass thar key in. If you want to use the usual map parsing, pass
-/// in `None` as key.
-struct MapAccess<D> {
+/// It wraps the access of another format layer, see [`Visitor`]. Whether the map itself is a link
+/// or bytes is up to the caller, e.g. by peeking at its first key.
+//
+// Internally it may take an already parsed key. Passing in a key makes it possible to peek into
+// the map. In case you want to proceed parsing it as a normal map, you can just pass that key in.
+// If you want to use the usual map parsing, pass in `None` as key.
+pub struct MapAccess<D> {
     access: D,
-    parsed_key: Option<String>,
+    parsed_key: Option<Cow<'static, str>>,
+    state: Option<Rc<DecodeState>>,
 }
 
 impl<D> MapAccess<D> {
-    fn new(access: D, parsed_key: Option<String>) -> Self {
-        Self { access, parsed_key }
+    /// Wraps the given access.
+    pub fn new(access: D) -> Self {
+        Self::with_state(access, None, None)
+    }
+
+    fn with_state(
+        access: D,
+        parsed_key: Option<Cow<'static, str>>,
+        state: Option<Rc<DecodeState>>,
+    ) -> Self {
+        Self {
+            access,
+            parsed_key,
+            state,
+        }
     }
 }
 
@@ -716,10 +2317,27 @@ where
         // With `take()` we make sure that only the very first key is a special case, all following
         // keys are just normal JSON.
         if let Some(parsed_key) = self.parsed_key.take() {
-            seed.deserialize(StringDeserializer::new(parsed_key))
-                .map(Some)
+            if let Some(state) = self.state.as_ref().filter(|state| state.tracks_path()) {
+                *state.key.borrow_mut() = Some(parsed_key.to_string());
+            }
+            match parsed_key {
+                Cow::Borrowed(key) => seed.deserialize(BorrowedStrDeserializer::new(key)),
+                Cow::Owned(key) => seed.deserialize(StringDeserializer::new(key)),
+            }
+            .map(Some)
         } else {
-            self.access.next_key_seed(DeserializeSeed::new(seed))
+            let Some(state) = self.state.as_ref().filter(|state| state.tracks_path()) else {
+                return self
+                    .access
+                    .next_key_seed(DeserializeSeed::new(seed, self.state.clone()));
+            };
+            state.reading_key.set(true);
+            let result = self
+                .access
+                .next_key_seed(DeserializeSeed::new(seed, Some(Rc::clone(state))));
+            // Keys that aren't visited as a string, e.g. integers, aren't recorded.
+            state.reading_key.set(false);
+            result
         }
     }
 
@@ -727,7 +2345,16 @@ where
     where
         V: de::DeserializeSeed<'de>,
     {
-        self.access.next_value_seed(DeserializeSeed::new(seed))
+        let key = || {
+            self.state
+                .as_ref()
+                .and_then(|state| state.key.borrow_mut().take())
+                .unwrap_or_default()
+        };
+        within(self.state.as_ref(), key, || {
+            self.access
+                .next_value_seed(DeserializeSeed::new(seed, self.state.clone()))
+        })
     }
 
     fn size_hint(&self) -> Option<usize> {
@@ -737,11 +2364,12 @@ where
 
 struct EnumAccess<D> {
     access: D,
+    state: Option<Rc<DecodeState>>,
 }
 
 impl<D> EnumAccess<D> {
-    fn new(access: D) -> Self {
-        EnumAccess { access }
+    fn new(access: D, state: Option<Rc<DecodeState>>) -> Self {
+        EnumAccess { access, state }
     }
 }
 
@@ -757,7 +2385,7 @@ where
         V: de::DeserializeSeed<'de>,
     {
         self.access
-            .variant_seed(DeserializeSeed::new(seed))
-            .map(|(value, access)| (value, VariantAccess::new(access)))
+            .variant_seed(DeserializeSeed::new(seed, self.state.clone()))
+            .map(|(value, access)| (value, VariantAccess::new(access, self.state)))
     }
 }
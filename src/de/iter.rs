@@ -0,0 +1,443 @@
+//! Iterating over the contents of large top-level lists and maps, without decoding them as a
+//! whole.
+use std::{fmt, io, marker::PhantomData};
+
+use serde::de;
+
+use crate::{error::DecodeError, strict::Tokenizer};
+
+/// Returns an iterator over the elements of a top-level list, which are decoded one at a time.
+///
+/// Only a single element is held in memory at once, hence lists of gigabytes can be processed with
+/// constant memory. An element that can't be decoded into `T` results in an error for that
+/// element, the iteration continues with the next one. Invalid input, e.g. a missing comma,
+/// results in a [`DecodeError::Syntax`] and ends the iteration.
+///
+/// # Examples
+///
+/// ```
+/// use serde_ipld_dagjson::de::seq_iter;
+///
+/// let input = br#"[1, 2, "three", 4]"#;
+/// let elements = seq_iter::<u64, _>(&input[..]).collect::<Vec<_>>();
+/// assert_eq!(elements[1].as_ref().unwrap(), &2);
+/// assert!(elements[2].is_err());
+/// assert_eq!(elements[3].as_ref().unwrap(), &4);
+/// ```
+pub fn seq_iter<T, R>(reader: R) -> SeqIter<T, R>
+where
+    T: de::DeserializeOwned,
+    R: io::Read,
+{
+    SeqIter {
+        scanner: Scanner::new(reader),
+        position: Position::Start,
+        _marker: PhantomData,
+    }
+}
+
+/// An iterator over the elements of a top-level list, see [`seq_iter`].
+pub struct SeqIter<T, R> {
+    scanner: Scanner<R>,
+    position: Position,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T, R> fmt::Debug for SeqIter<T, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SeqIter")
+            .field("offset", &self.scanner.offset)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Where the iteration is within the list or map.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Position {
+    /// Before the opening bracket.
+    Start,
+    /// After the opening bracket.
+    Open,
+    /// After an element or entry.
+    Element,
+    /// After the closing bracket, or after an error.
+    Done,
+}
+
+impl<T, R> SeqIter<T, R>
+where
+    T: de::DeserializeOwned,
+    R: io::Read,
+{
+    /// Reads up to the next element, returns `false` at the end of the list.
+    fn advance(&mut self) -> Result<bool, DecodeError> {
+        if self.position == Position::Start {
+            self.scanner.expect(b'[', "expected a list")?;
+            self.position = Position::Open;
+        }
+        match (self.position, self.scanner.peek_token()?) {
+            (Position::Open | Position::Element, Some(b']')) => {
+                self.scanner.consume(1);
+                self.scanner.end()?;
+                Ok(false)
+            }
+            (Position::Element, Some(b',')) => {
+                self.scanner.consume(1);
+                Ok(true)
+            }
+            (Position::Element, _) => Err(self.scanner.error("expected `,` or `]`")),
+            _ => Ok(true),
+        }
+    }
+}
+
+impl<T, R> Iterator for SeqIter<T, R>
+where
+    T: de::DeserializeOwned,
+    R: io::Read,
+{
+    type Item = Result<T, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.position == Position::Done {
+            return None;
+        }
+        let result = self.advance().and_then(|more| {
+            if more {
+                self.scanner.read_value().map(Some)
+            } else {
+                Ok(None)
+            }
+        });
+        match result {
+            Ok(Some(value)) => {
+                self.position = Position::Element;
+                Some(crate::de::from_slice(value))
+            }
+            Ok(None) => {
+                self.position = Position::Done;
+                None
+            }
+            Err(error) => {
+                self.position = Position::Done;
+                Some(Err(error))
+            }
+        }
+    }
+}
+
+/// Returns an iterator over the entries of a top-level map, whose values are decoded one at a time.
+///
+/// Only a single entry is held in memory at once, hence large key-value snapshots can be processed
+/// without materializing the whole map. The entries are returned in the order of the input. Like
+/// with [`seq_iter`], a value that can't be decoded into `V` results in an error for that entry,
+/// while invalid input ends the iteration. Links and bytes aren't maps, a top-level map with the
+/// reserved key `/` results in an error.
+///
+/// # Examples
+///
+/// ```
+/// use serde_ipld_dagjson::de::map_iter;
+///
+/// let input = br#"{"alice": 3, "bob": 5}"#;
+/// let mut total = 0;
+/// for entry in map_iter::<u64, _>(&input[..]) {
+///     let (_name, count) = entry.unwrap();
+///     total += count;
+/// }
+/// assert_eq!(total, 8);
+/// ```
+pub fn map_iter<V, R>(reader: R) -> MapIter<V, R>
+where
+    V: de::DeserializeOwned,
+    R: io::Read,
+{
+    MapIter {
+        scanner: Scanner::new(reader),
+        position: Position::Start,
+        _marker: PhantomData,
+    }
+}
+
+/// An iterator over the entries of a top-level map, see [`map_iter`].
+pub struct MapIter<V, R> {
+    scanner: Scanner<R>,
+    position: Position,
+    _marker: PhantomData<fn() -> V>,
+}
+
+impl<V, R> fmt::Debug for MapIter<V, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MapIter")
+            .field("offset", &self.scanner.offset)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<V, R> MapIter<V, R>
+where
+    V: de::DeserializeOwned,
+    R: io::Read,
+{
+    /// Reads the key of the next entry, returns `None` at the end of the map.
+    fn next_key(&mut self) -> Result<Option<String>, DecodeError> {
+        if self.position == Position::Start {
+            self.scanner.expect(b'{', "expected a map")?;
+            self.position = Position::Open;
+        }
+        match (self.position, self.scanner.peek_token()?) {
+            (Position::Open | Position::Element, Some(b'}')) => {
+                self.scanner.consume(1);
+                self.scanner.end()?;
+                return Ok(None);
+            }
+            (Position::Element, Some(b',')) => self.scanner.consume(1),
+            (Position::Element, _) => return Err(self.scanner.error("expected `,` or `}`")),
+            _ => {}
+        }
+        let next = self.scanner.peek_token()?;
+        let offset = self.scanner.offset;
+        let key = match next {
+            Some(b'"') => self.scanner.read_string()?,
+            _ => return Err(self.scanner.error("expected a string key")),
+        };
+        if self.position == Position::Open && key == "/" {
+            return Err(self
+                .scanner
+                .error_at(offset, "expected a map, found a link or bytes"));
+        }
+        self.scanner.expect(b':', "expected `:`")?;
+        Ok(Some(key))
+    }
+}
+
+impl<V, R> Iterator for MapIter<V, R>
+where
+    V: de::DeserializeOwned,
+    R: io::Read,
+{
+    type Item = Result<(String, V), DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.position == Position::Done {
+            return None;
+        }
+        let result = self.next_key().and_then(|key| match key {
+            Some(key) => Ok(Some((key, self.scanner.read_value()?))),
+            None => Ok(None),
+        });
+        match result {
+            Ok(Some((key, value))) => {
+                self.position = Position::Element;
+                Some(crate::de::from_slice(value).map(|value| (key, value)))
+            }
+            Ok(None) => {
+                self.position = Position::Done;
+                None
+            }
+            Err(error) => {
+                self.position = Position::Done;
+                Some(Err(error))
+            }
+        }
+    }
+}
+
+/// The number of bytes read from the input at once, unless a larger value needs more.
+const CHUNK_SIZE: usize = 8 * 1024;
+
+/// How far the tokenizer looks beyond the position of an error it reports, at most.
+///
+/// An error this close to the end of the buffered input, e.g. a surrogate pair that is cut off,
+/// might be caused by the input that wasn't read yet.
+const LOOKAHEAD: usize = 12;
+
+/// Splits the input into the raw bytes of single values, without decoding them.
+///
+/// The input is read in chunks and tokenized with the [tokenizer of the strict
+/// parser](crate::strict), a value that spans several chunks is scanned again once more input was
+/// read.
+struct Scanner<R> {
+    reader: R,
+    /// The input that was read, the unconsumed part starts at `start`.
+    buf: Vec<u8>,
+    start: usize,
+    /// Whether the reader reached the end of the input.
+    eof: bool,
+    /// The offset of the next byte.
+    offset: usize,
+}
+
+impl<R> Scanner<R>
+where
+    R: io::Read,
+{
+    fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buf: Vec::new(),
+            start: 0,
+            eof: false,
+            offset: 0,
+        }
+    }
+
+    fn error(&self, message: &str) -> DecodeError {
+        self.error_at(self.offset, message)
+    }
+
+    fn error_at(&self, offset: usize, message: &str) -> DecodeError {
+        DecodeError::Syntax {
+            offset,
+            message: message.to_string(),
+        }
+        .traced()
+    }
+
+    /// Reads more input, the unconsumed input is kept.
+    fn fill(&mut self) -> Result<(), DecodeError> {
+        self.buf.drain(..self.start);
+        self.start = 0;
+        let len = self.buf.len();
+        self.buf.resize(len + CHUNK_SIZE.max(len), 0);
+        let read = loop {
+            match self.reader.read(&mut self.buf[len..]) {
+                Ok(read) => break read,
+                Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+                Err(error) => {
+                    self.buf.truncate(len);
+                    return Err(error.into());
+                }
+            }
+        };
+        self.buf.truncate(len + read);
+        self.eof = read == 0;
+        Ok(())
+    }
+
+    /// Tokenizes the unconsumed input, returns the result and the number of bytes it spans.
+    ///
+    /// Nothing is consumed. If the result might change with more input, more is read and the
+    /// input is tokenized again.
+    fn scan<T, F>(&mut self, mut tokenize: F) -> Result<(T, usize), DecodeError>
+    where
+        F: FnMut(&mut Tokenizer<'_>) -> Result<T, DecodeError>,
+    {
+        loop {
+            let mut tokens = Tokenizer::new(&self.buf[self.start..]);
+            let result = tokenize(&mut tokens);
+            let end = match &result {
+                Err(DecodeError::Syntax { offset, .. }) => offset.saturating_add(LOOKAHEAD),
+                _ => tokens.offset,
+            };
+            if end >= tokens.input.len() && !self.eof {
+                self.fill()?;
+                continue;
+            }
+            return match result {
+                Ok(value) => Ok((value, tokens.offset)),
+                Err(DecodeError::Syntax { offset, message }) => {
+                    Err(self.error_at(self.offset + offset, &message))
+                }
+                Err(error) => Err(error),
+            };
+        }
+    }
+
+    fn consume(&mut self, len: usize) {
+        self.start += len;
+        self.offset += len;
+    }
+
+    /// Skips whitespace and returns the next byte without consuming it.
+    fn peek_token(&mut self) -> Result<Option<u8>, DecodeError> {
+        let (byte, len) = self.scan(|tokens| {
+            tokens.skip_whitespace(true)?;
+            Ok(tokens.peek())
+        })?;
+        self.consume(len);
+        Ok(byte)
+    }
+
+    /// Consumes the given byte, after skipping whitespace.
+    fn expect(&mut self, expected: u8, message: &str) -> Result<(), DecodeError> {
+        match self.peek_token()? {
+            Some(byte) if byte == expected => {
+                self.consume(1);
+                Ok(())
+            }
+            _ => Err(self.error(message)),
+        }
+    }
+
+    /// Fails if there is anything but whitespace left.
+    fn end(&mut self) -> Result<(), DecodeError> {
+        match self.peek_token()? {
+            Some(_) => Err(DecodeError::TrailingData),
+            None => Ok(()),
+        }
+    }
+
+    /// Reads a string, after skipping whitespace.
+    fn read_string(&mut self) -> Result<String, DecodeError> {
+        self.peek_token()?;
+        let (string, len) = self.scan(|tokens| {
+            tokens.offset += 1;
+            tokens
+                .parse_string()
+                .map(|string| string.as_str().to_string())
+        })?;
+        self.consume(len);
+        Ok(string)
+    }
+
+    /// Reads the raw bytes of the next value, after skipping whitespace.
+    ///
+    /// The value is only split off the input, it's validated when it's decoded.
+    fn read_value(&mut self) -> Result<&[u8], DecodeError> {
+        self.peek_token()?;
+        let ((), len) = self.scan(skip_value)?;
+        let start = self.start;
+        self.consume(len);
+        Ok(&self.buf[start..start + len])
+    }
+}
+
+/// Skips a single value, the tokens are checked, but not whether they are in a valid order.
+fn skip_value(tokens: &mut Tokenizer<'_>) -> Result<(), DecodeError> {
+    // The closing brackets of the lists and maps the tokenizer is in.
+    let mut closing = Vec::new();
+    loop {
+        tokens.skip_whitespace(true)?;
+        match tokens.peek() {
+            Some(byte @ (b'[' | b'{')) => {
+                closing.push(if byte == b'[' { b']' } else { b'}' });
+                tokens.offset += 1;
+                continue;
+            }
+            Some(byte @ (b']' | b'}')) if closing.last() == Some(&byte) => {
+                closing.pop();
+                tokens.offset += 1;
+            }
+            Some(b',' | b':') if !closing.is_empty() => {
+                tokens.offset += 1;
+                continue;
+            }
+            Some(b'"') => {
+                tokens.offset += 1;
+                tokens.parse_string()?;
+            }
+            Some(b't') => tokens.expect_literal(b"true")?,
+            Some(b'f') => tokens.expect_literal(b"false")?,
+            Some(b'n') => tokens.expect_literal(b"null")?,
+            Some(b'-' | b'0'..=b'9') => {
+                tokens.scan_number()?;
+            }
+            Some(_) => return Err(tokens.error("expected value")),
+            None => return Err(tokens.error("unexpected end of input")),
+        }
+        if closing.is_empty() {
+            return Ok(());
+        }
+    }
+}
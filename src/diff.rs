@@ -0,0 +1,179 @@
+//! Structural comparison of DAG-JSON documents.
+//!
+//! [`diff`] compares two decoded values and returns the [`Change`]s between them, each with the
+//! path of the changed value. [`render_diff`] renders the changes between two documents in a
+//! format similar to a unified diff, e.g. for reviewing changes of fixtures or debugging
+//! replication mismatches. Links and bytes are shown as such, instead of their `{"/": ...}` maps.
+//!
+//! Lists are compared element by element, an element that is inserted at the front shows up as
+//! changes of all following elements.
+//!
+//! # Examples
+//!
+//! ```
+//! use serde_ipld_dagjson::diff::render_diff;
+//!
+//! let old = br#"{"name": "hello.txt", "size": 5, "tags": ["a"]}"#;
+//! let new = br#"{"name": "hello.md", "tags": ["a", "b"]}"#;
+//! assert_eq!(
+//!     render_diff(old, new).unwrap(),
+//!     r#"--- old
+//! +++ new
+//! @@ name @@
+//! -"hello.txt"
+//! +"hello.md"
+//! @@ size @@
+//! -5
+//! @@ tags/1 @@
+//! +"b"
+//! "#
+//! );
+//! ```
+use std::fmt::Write;
+
+use ipld_core::ipld::Ipld;
+
+use crate::{
+    error::DecodeError,
+    explain::{codec_name, plural},
+};
+
+/// The maximum number of bytes that are shown.
+const MAX_BYTES_LEN: usize = 32;
+
+/// A change between two values.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Change {
+    /// The value at the path only exists in the new value.
+    Added { path: String, value: Ipld },
+    /// The value at the path only exists in the old value.
+    Removed { path: String, value: Ipld },
+    /// The value at the path differs.
+    Changed { path: String, old: Ipld, new: Ipld },
+}
+
+impl Change {
+    /// Returns the path of the changed value, the segments are separated by `/`.
+    pub fn path(&self) -> &str {
+        match self {
+            Self::Added { path, .. } | Self::Removed { path, .. } | Self::Changed { path, .. } => {
+                path
+            }
+        }
+    }
+}
+
+/// Returns the changes between two values, ordered by their paths.
+pub fn diff(old: &Ipld, new: &Ipld) -> Vec<Change> {
+    let mut changes = Vec::new();
+    compare(old, new, &mut Vec::new(), &mut changes);
+    changes
+}
+
+fn compare(old: &Ipld, new: &Ipld, path: &mut Vec<String>, changes: &mut Vec<Change>) {
+    match (old, new) {
+        (Ipld::Map(old), Ipld::Map(new)) => {
+            let mut keys = old.keys().chain(new.keys()).collect::<Vec<_>>();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                path.push(key.clone());
+                match (old.get(key), new.get(key)) {
+                    (Some(old), Some(new)) => compare(old, new, path, changes),
+                    (Some(old), None) => changes.push(Change::Removed {
+                        path: path.join("/"),
+                        value: old.clone(),
+                    }),
+                    (None, Some(new)) => changes.push(Change::Added {
+                        path: path.join("/"),
+                        value: new.clone(),
+                    }),
+                    (None, None) => unreachable!("the key is in one of the maps"),
+                }
+                path.pop();
+            }
+        }
+        (Ipld::List(old), Ipld::List(new)) => {
+            for index in 0..old.len().max(new.len()) {
+                path.push(index.to_string());
+                match (old.get(index), new.get(index)) {
+                    (Some(old), Some(new)) => compare(old, new, path, changes),
+                    (Some(old), None) => changes.push(Change::Removed {
+                        path: path.join("/"),
+                        value: old.clone(),
+                    }),
+                    (None, Some(new)) => changes.push(Change::Added {
+                        path: path.join("/"),
+                        value: new.clone(),
+                    }),
+                    (None, None) => unreachable!("the index is in one of the lists"),
+                }
+                path.pop();
+            }
+        }
+        _ if old != new => changes.push(Change::Changed {
+            path: path.join("/"),
+            old: old.clone(),
+            new: new.clone(),
+        }),
+        _ => {}
+    }
+}
+
+/// Renders the changes between two DAG-JSON documents, it's empty if they are equal.
+///
+/// Every change starts with a line containing its path, followed by the removed value prefixed
+/// with `-` and the added one prefixed with `+`.
+pub fn render_diff(old: &[u8], new: &[u8]) -> Result<String, DecodeError> {
+    let old: Ipld = crate::from_slice(old)?;
+    let new: Ipld = crate::from_slice(new)?;
+    let changes = diff(&old, &new);
+    let mut output = String::new();
+    if changes.is_empty() {
+        return Ok(output);
+    }
+    writeln!(output, "--- old\n+++ new").unwrap();
+    for change in &changes {
+        let path = match change.path() {
+            "" => "(root)",
+            path => path,
+        };
+        writeln!(output, "@@ {} @@", path).unwrap();
+        match change {
+            Change::Added { value, .. } => writeln!(output, "+{}", render(value)).unwrap(),
+            Change::Removed { value, .. } => writeln!(output, "-{}", render(value)).unwrap(),
+            Change::Changed { old, new, .. } => {
+                writeln!(output, "-{}\n+{}", render(old), render(new)).unwrap();
+                if let (Ipld::Bytes(old), Ipld::Bytes(new)) = (old, new) {
+                    let offset = old
+                        .iter()
+                        .zip(new)
+                        .position(|(old, new)| old != new)
+                        .unwrap_or_else(|| old.len().min(new.len()));
+                    writeln!(output, " first difference at byte {}", offset).unwrap();
+                }
+            }
+        }
+    }
+    Ok(output)
+}
+
+/// Renders a value on a single line, links and bytes in a readable form, everything else as
+/// DAG-JSON.
+fn render(ipld: &Ipld) -> String {
+    match ipld {
+        Ipld::Link(cid) => format!("link {} ({})", cid, codec_name(cid.codec())),
+        Ipld::Bytes(bytes) => {
+            let mut output = format!("bytes ({}) ", plural(bytes.len(), "byte"));
+            for byte in bytes.iter().take(MAX_BYTES_LEN) {
+                write!(output, "{:02x}", byte).unwrap();
+            }
+            if bytes.len() > MAX_BYTES_LEN {
+                output.push_str("...");
+            }
+            output
+        }
+        // Decoded values can always be encoded.
+        _ => String::from_utf8(crate::to_vec(ipld).unwrap_or_default()).unwrap_or_default(),
+    }
+}
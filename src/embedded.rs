@@ -0,0 +1,74 @@
+//! Decoding and encoding over [`embedded_io`] readers and writers.
+//!
+//! This way, firmware that exchanges DAG-JSON over e.g. a serial port, can use its
+//! [`embedded_io::Read`] and [`embedded_io::Write`] implementations directly.
+use std::io;
+
+use serde::{de, ser};
+
+use crate::error::{DecodeError, EncodeError};
+
+/// Decodes a value from DAG-JSON data in an [`embedded_io::Read`]er.
+///
+/// Errors of the reader are returned as [`DecodeError::Io`].
+pub fn from_reader<T, R>(reader: R) -> Result<T, DecodeError>
+where
+    T: de::DeserializeOwned,
+    R: embedded_io::Read,
+{
+    crate::de::from_reader(Adapter(reader))
+}
+
+/// Serializes a value to an [`embedded_io::Write`]r.
+pub fn to_writer<W, T>(writer: W, value: &T) -> Result<(), EncodeError>
+where
+    W: embedded_io::Write,
+    T: ser::Serialize,
+{
+    let mut adapter = Adapter(writer);
+    crate::ser::to_writer(&mut adapter, value)?;
+    io::Write::flush(&mut adapter).map_err(|error| EncodeError::Message(error.to_string()))
+}
+
+/// Makes an [`embedded_io`] reader or writer usable as [`std::io`] one.
+struct Adapter<T>(T);
+
+fn to_io_error<E: embedded_io::Error>(error: E) -> io::Error {
+    let kind = match error.kind() {
+        embedded_io::ErrorKind::NotFound => io::ErrorKind::NotFound,
+        embedded_io::ErrorKind::PermissionDenied => io::ErrorKind::PermissionDenied,
+        embedded_io::ErrorKind::ConnectionRefused => io::ErrorKind::ConnectionRefused,
+        embedded_io::ErrorKind::ConnectionReset => io::ErrorKind::ConnectionReset,
+        embedded_io::ErrorKind::ConnectionAborted => io::ErrorKind::ConnectionAborted,
+        embedded_io::ErrorKind::NotConnected => io::ErrorKind::NotConnected,
+        embedded_io::ErrorKind::AddrInUse => io::ErrorKind::AddrInUse,
+        embedded_io::ErrorKind::AddrNotAvailable => io::ErrorKind::AddrNotAvailable,
+        embedded_io::ErrorKind::BrokenPipe => io::ErrorKind::BrokenPipe,
+        embedded_io::ErrorKind::AlreadyExists => io::ErrorKind::AlreadyExists,
+        embedded_io::ErrorKind::InvalidInput => io::ErrorKind::InvalidInput,
+        embedded_io::ErrorKind::InvalidData => io::ErrorKind::InvalidData,
+        embedded_io::ErrorKind::TimedOut => io::ErrorKind::TimedOut,
+        embedded_io::ErrorKind::Interrupted => io::ErrorKind::Interrupted,
+        embedded_io::ErrorKind::Unsupported => io::ErrorKind::Unsupported,
+        embedded_io::ErrorKind::OutOfMemory => io::ErrorKind::OutOfMemory,
+        embedded_io::ErrorKind::WriteZero => io::ErrorKind::WriteZero,
+        _ => io::ErrorKind::Other,
+    };
+    io::Error::new(kind, format!("{:?}", error))
+}
+
+impl<R: embedded_io::Read> io::Read for Adapter<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf).map_err(to_io_error)
+    }
+}
+
+impl<W: embedded_io::Write> io::Write for Adapter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf).map_err(to_io_error)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush().map_err(to_io_error)
+    }
+}
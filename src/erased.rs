@@ -0,0 +1,90 @@
+//! Type-erased encoding and decoding.
+//!
+//! The regular entry points are generic over the serialized type and the underlying
+//! `serde_json` (de)serializer, which leads to a lot of generated code when many different types
+//! are used. These entry points are based on [`erased_serde`], hence the DAG-JSON specific layers
+//! are only compiled once, at the cost of some dynamic dispatch.
+use std::io;
+
+use serde::de;
+
+use crate::{
+    de::Deserializer,
+    error::{DecodeError, EncodeError},
+    ser::Serializer,
+};
+
+/// Serializes a type-erased value to a vector.
+///
+/// # Examples
+///
+/// ```
+/// let encoded = serde_ipld_dagjson::erased::to_vec(&vec![1, 2, 3]).unwrap();
+/// assert_eq!(encoded, b"[1,2,3]");
+/// ```
+pub fn to_vec(value: &dyn erased_serde::Serialize) -> Result<Vec<u8>, EncodeError> {
+    let mut writer = Vec::new();
+    serialize_erased(&mut writer, value)?;
+    Ok(writer)
+}
+
+/// Serializes a type-erased value to a writer.
+#[cfg(feature = "io")]
+pub fn to_writer(
+    writer: &mut dyn io::Write,
+    value: &dyn erased_serde::Serialize,
+) -> Result<(), EncodeError> {
+    serialize_erased(writer, value)
+}
+
+fn serialize_erased(
+    writer: &mut dyn io::Write,
+    value: &dyn erased_serde::Serialize,
+) -> Result<(), EncodeError> {
+    let mut json_serializer = serde_json::Serializer::new(writer);
+    let serializer = Serializer::new(&mut json_serializer);
+    Ok(erased_serde::serialize(value, serializer)?)
+}
+
+/// Decodes a value from DAG-JSON data in a slice, through a type-erased deserializer.
+///
+/// # Examples
+///
+/// ```
+/// let value: Vec<u8> = serde_ipld_dagjson::erased::from_slice(b"[1,2,3]").unwrap();
+/// assert_eq!(value, vec![1, 2, 3]);
+/// ```
+pub fn from_slice<'a, T>(buf: &'a [u8]) -> Result<T, DecodeError>
+where
+    T: de::Deserialize<'a>,
+{
+    let mut json_deserializer = serde_json::Deserializer::from_slice(buf);
+    let value = deserialize_erased(Deserializer::new(&mut json_deserializer))?;
+    json_deserializer
+        .end()
+        .map_err(|_| DecodeError::TrailingData)?;
+    Ok(value)
+}
+
+/// Decodes a value from DAG-JSON data in a reader, through a type-erased deserializer.
+#[cfg(feature = "io")]
+pub fn from_reader<T>(reader: &mut dyn io::Read) -> Result<T, DecodeError>
+where
+    T: de::DeserializeOwned,
+{
+    let mut json_deserializer = serde_json::Deserializer::from_reader(reader);
+    let value = deserialize_erased(Deserializer::new(&mut json_deserializer))?;
+    json_deserializer
+        .end()
+        .map_err(|_| DecodeError::TrailingData)?;
+    Ok(value)
+}
+
+fn deserialize_erased<'de, T, D>(deserializer: D) -> Result<T, DecodeError>
+where
+    T: de::Deserialize<'de>,
+    D: de::Deserializer<'de>,
+{
+    let mut erased = <dyn erased_serde::Deserializer>::erase(deserializer);
+    erased_serde::deserialize(&mut erased).map_err(|error| DecodeError::Message(error.to_string()))
+}
@@ -1,38 +1,127 @@
-use std::fmt;
+//! Errors of encoding and decoding.
+//!
+//! With the `backtrace` feature, a [`Backtrace`](std::backtrace::Backtrace) is captured whenever
+//! an error is created and can be retrieved with `DecodeError::last_backtrace()` and
+//! `EncodeError::last_backtrace()`. Like with `std`, backtraces are only captured if the
+//! `RUST_BACKTRACE` or `RUST_LIB_BACKTRACE` environment variable enables them.
+#[cfg(feature = "backtrace")]
+use std::{backtrace::Backtrace, cell::RefCell, sync::Arc, thread::LocalKey};
+use std::{fmt, io};
 
 use serde::{de, ser};
 
+use crate::{
+    index::Kind,
+    limits::{Limit, RejectedLink},
+};
+
+#[cfg(feature = "backtrace")]
+thread_local! {
+    static DECODE_BACKTRACE: RefCell<Option<Arc<Backtrace>>> = const { RefCell::new(None) };
+    static ENCODE_BACKTRACE: RefCell<Option<Arc<Backtrace>>> = const { RefCell::new(None) };
+}
+
+/// Captures a backtrace into the given slot of the current thread.
+#[cfg(feature = "backtrace")]
+fn capture(slot: &'static LocalKey<RefCell<Option<Arc<Backtrace>>>>) {
+    let backtrace = Arc::new(Backtrace::capture());
+    slot.with(|slot| *slot.borrow_mut() = Some(backtrace));
+}
+
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum EncodeError {
     Message(String),
+    Io(io::Error),
+    /// A link at the given path was rejected by the
+    /// [`LinkPolicy`](crate::limits::LinkPolicy) of the
+    /// [`EncodeOptions`](crate::ser::EncodeOptions).
+    LinkRejected {
+        path: String,
+        reason: RejectedLink,
+    },
 }
 
 impl fmt::Display for EncodeError {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Self::Message(message) => write!(f, "{}", message),
+            Self::Io(error) => write!(f, "io error: {}", error),
+            Self::LinkRejected { path, reason } => write!(f, "{} at path `{}`", reason, path),
         }
     }
 }
 
 impl std::error::Error for EncodeError {}
 
+impl EncodeError {
+    /// Returns the backtrace of the encode error that was created last on the current thread.
+    ///
+    /// The errors themselves don't carry a backtrace, so that their variants stay the same with
+    /// and without the `backtrace` feature. Retrieve it right after an encode failed, before the
+    /// thread creates another error.
+    #[cfg(feature = "backtrace")]
+    pub fn last_backtrace() -> Option<Arc<Backtrace>> {
+        ENCODE_BACKTRACE.with(|slot| slot.borrow().clone())
+    }
+
+    /// Records a backtrace for the error, if the `backtrace` feature is enabled.
+    pub(crate) fn traced(self) -> Self {
+        #[cfg(feature = "backtrace")]
+        capture(&ENCODE_BACKTRACE);
+        self
+    }
+}
+
 impl ser::Error for EncodeError {
     fn custom<T: fmt::Display>(message: T) -> Self {
-        Self::Message(message.to_string())
+        Self::Message(message.to_string()).traced()
     }
 }
 
 impl From<serde_json::Error> for EncodeError {
     fn from(error: serde_json::Error) -> Self {
-        Self::Message(error.to_string())
+        Self::Message(error.to_string()).traced()
+    }
+}
+
+impl From<io::Error> for EncodeError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error).traced()
     }
 }
 
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum DecodeError {
     Message(String),
     TrailingData,
+    Io(io::Error),
+    /// The decoded data would exceed the configured memory budget (in bytes).
+    MemoryBudgetExceeded(usize),
+    /// Invalid input at the given byte offset, reported by the
+    /// [strict parser](crate::strict::StrictDeserializer) or when iterating over a
+    /// [list](crate::de::seq_iter) or [map](crate::de::map_iter).
+    Syntax {
+        offset: usize,
+        message: String,
+    },
+    /// The input exceeds one of the configured [`Limits`](crate::limits::Limits).
+    LimitExceeded(Limit),
+    /// A link was rejected by the configured [`LinkPolicy`](crate::limits::LinkPolicy).
+    LinkRejected(RejectedLink),
+    /// The decode was cancelled through its
+    /// [`CancellationToken`](crate::cancel::CancellationToken).
+    Cancelled,
+    /// The decode didn't finish before its [deadline](crate::DecodeOptions::deadline).
+    TimedOut,
+    /// A number at the given path isn't of the kind of the type it's decoded into, see
+    /// [`DecodeOptions::strict_kinds`](crate::DecodeOptions::strict_kinds).
+    KindMismatch {
+        path: String,
+        expected: Kind,
+        found: Kind,
+    },
 }
 
 impl fmt::Display for DecodeError {
@@ -40,26 +129,68 @@ impl fmt::Display for DecodeError {
         match self {
             Self::Message(message) => write!(f, "{}", message),
             Self::TrailingData => write!(f, "trailing data"),
+            Self::Io(error) => write!(f, "io error: {}", error),
+            Self::MemoryBudgetExceeded(budget) => {
+                write!(f, "memory budget of {} bytes exceeded", budget)
+            }
+            Self::Syntax { offset, message } => write!(f, "{} at byte {}", message, offset),
+            Self::LimitExceeded(limit) => write!(f, "{} exceeded", limit),
+            Self::LinkRejected(rejected) => write!(f, "{}", rejected),
+            Self::Cancelled => write!(f, "decode cancelled"),
+            Self::TimedOut => write!(f, "decode timed out"),
+            Self::KindMismatch {
+                path,
+                expected,
+                found,
+            } => write!(
+                f,
+                "expected {}, found {} at path `{}`",
+                expected, found, path
+            ),
         }
     }
 }
 
 impl std::error::Error for DecodeError {}
 
+impl DecodeError {
+    /// Returns the backtrace of the decode error that was created last on the current thread.
+    ///
+    /// See [`EncodeError::last_backtrace`] for why it's not part of the error.
+    #[cfg(feature = "backtrace")]
+    pub fn last_backtrace() -> Option<Arc<Backtrace>> {
+        DECODE_BACKTRACE.with(|slot| slot.borrow().clone())
+    }
+
+    /// Records a backtrace for the error, if the `backtrace` feature is enabled.
+    pub(crate) fn traced(self) -> Self {
+        #[cfg(feature = "backtrace")]
+        capture(&DECODE_BACKTRACE);
+        self
+    }
+}
+
 impl de::Error for DecodeError {
     fn custom<T: fmt::Display>(message: T) -> Self {
-        Self::Message(message.to_string())
+        Self::Message(message.to_string()).traced()
     }
 }
 
 impl From<serde_json::Error> for DecodeError {
     fn from(error: serde_json::Error) -> Self {
-        Self::Message(error.to_string())
+        Self::Message(error.to_string()).traced()
+    }
+}
+
+impl From<io::Error> for DecodeError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error).traced()
     }
 }
 
 /// Encode and Decode error combined.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum CodecError {
     /// A decoding error.
     Decode(DecodeError),
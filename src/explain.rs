@@ -0,0 +1,120 @@
+//! Human readable rendering of DAG-JSON documents.
+//!
+//! [`explain`] renders a document as an indented tree, similar to `ipld explore`. Every node shows
+//! its kind and size, long strings and bytes are truncated and links show the codec of the block
+//! they point to. It's meant for humans, e.g. in command line tools or admin interfaces, the output
+//! format may change between versions.
+//!
+//! # Examples
+//!
+//! ```
+//! use serde_ipld_dagjson::explain::explain;
+//!
+//! let input = br#"{
+//!     "name": "hello.txt",
+//!     "data": {"/": {"bytes": "aGVsbG8"}},
+//!     "parents": [{"/": "bafyreih4ia2w5w6x3fprtdlqhvs6o5etzixqvgorfdxpwfcqxpvnfwwzia"}]
+//! }"#;
+//! assert_eq!(
+//!     explain(input).unwrap(),
+//!     "\
+//! map (3 entries)
+//!   data: bytes (5 bytes) 68656c6c6f
+//!   name: string (9 bytes) \"hello.txt\"
+//!   parents: list (1 element)
+//!     0: link bafyreih4ia2w5w6x3fprtdlqhvs6o5etzixqvgorfdxpwfcqxpvnfwwzia (dag-cbor)
+//! "
+//! );
+//! ```
+use std::fmt::{self, Write};
+
+use ipld_core::ipld::Ipld;
+
+use crate::{error::DecodeError, multicodec};
+
+/// The maximum number of characters of a string that is shown.
+const MAX_STRING_LEN: usize = 32;
+/// The maximum number of bytes that are shown.
+const MAX_BYTES_LEN: usize = 16;
+
+/// Renders a DAG-JSON document as an indented tree.
+pub fn explain(buf: &[u8]) -> Result<String, DecodeError> {
+    let ipld: Ipld = crate::from_slice(buf)?;
+    let mut output = String::new();
+    render(&mut output, &ipld, 0).unwrap();
+    Ok(output)
+}
+
+/// Renders a node and its children, the label of the node is already written.
+fn render(output: &mut String, ipld: &Ipld, depth: usize) -> fmt::Result {
+    match ipld {
+        Ipld::Null => writeln!(output, "null"),
+        Ipld::Bool(bool) => writeln!(output, "bool {}", bool),
+        Ipld::Integer(integer) => writeln!(output, "integer {}", integer),
+        Ipld::Float(float) => writeln!(output, "float {:?}", float),
+        Ipld::String(string) => {
+            let size = plural(string.len(), "byte");
+            match string.char_indices().nth(MAX_STRING_LEN) {
+                Some((end, _)) => writeln!(output, "string ({}) {:?}...", size, &string[..end]),
+                None => writeln!(output, "string ({}) {:?}", size, string),
+            }
+        }
+        Ipld::Bytes(bytes) => {
+            write!(output, "bytes ({}) ", plural(bytes.len(), "byte"))?;
+            for byte in bytes.iter().take(MAX_BYTES_LEN) {
+                write!(output, "{:02x}", byte)?;
+            }
+            if bytes.len() > MAX_BYTES_LEN {
+                write!(output, "...")?;
+            }
+            writeln!(output)
+        }
+        Ipld::Link(cid) => writeln!(output, "link {} ({})", cid, codec_name(cid.codec())),
+        Ipld::List(list) => {
+            writeln!(output, "list ({})", plural(list.len(), "element"))?;
+            for (index, element) in list.iter().enumerate() {
+                write!(
+                    output,
+                    "{:indent$}{}: ",
+                    "",
+                    index,
+                    indent = (depth + 1) * 2
+                )?;
+                render(output, element, depth + 1)?;
+            }
+            Ok(())
+        }
+        Ipld::Map(map) => {
+            writeln!(output, "map ({})", plural(map.len(), "entry"))?;
+            for (key, value) in map {
+                write!(output, "{:indent$}{}: ", "", key, indent = (depth + 1) * 2)?;
+                render(output, value, depth + 1)?;
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Returns the count with the singular or plural of the noun.
+pub(crate) fn plural(count: usize, noun: &str) -> String {
+    match (count, noun.strip_suffix('y')) {
+        (1, _) => format!("1 {}", noun),
+        (_, Some(stem)) => format!("{} {}ies", count, stem),
+        (_, None) => format!("{} {}s", count, noun),
+    }
+}
+
+/// Returns the name of a multicodec, or its code if it's not a well-known one.
+pub(crate) fn codec_name(code: u64) -> String {
+    let name = match code {
+        0x51 => "cbor",
+        multicodec::RAW => "raw",
+        0x70 => "dag-pb",
+        multicodec::DAG_CBOR => "dag-cbor",
+        0x72 => "libp2p-key",
+        multicodec::DAG_JSON => "dag-json",
+        0x0200 => "json",
+        _ => return format!("0x{:x}", code),
+    };
+    name.to_string()
+}
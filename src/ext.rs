@@ -0,0 +1,187 @@
+//! Application-defined extensions, this is **not** part of DAG-JSON.
+//!
+//! Some applications need to pass a small set of values through DAG-JSON, which have no
+//! representation in the IPLD data model, e.g. big decimals. An [`Extension`] maps such a Rust
+//! type to a wrapper map, which uses the reserved `/` key like links and bytes do:
+//!
+//! ```json
+//! {"/": {"ext": "<tag>", "value": "<value>"}}
+//! ```
+//!
+//! Other DAG-JSON implementations reject such data. Decoding it with this crate also fails,
+//! unless the extension was registered with
+//! [`DecodeOptions::extension`](crate::DecodeOptions::extension) and the value is decoded into
+//! [`Ext`]. This keeps extensions from leaking into places, that don't expect them.
+//!
+//! # Examples
+//!
+//! ```
+//! use serde_ipld_dagjson::{
+//!     de,
+//!     ext::{Ext, Extension},
+//!     DecodeOptions,
+//! };
+//!
+//! /// A decimal with a fixed number of fractional digits.
+//! #[derive(Debug, PartialEq)]
+//! struct Cents(i128);
+//!
+//! impl Extension for Cents {
+//!     const TAG: &'static str = "cents";
+//!
+//!     fn encode(&self) -> String {
+//!         self.0.to_string()
+//!     }
+//!
+//!     fn decode(value: &str) -> Result<Self, String> {
+//!         value.parse().map(Cents).map_err(|error| format!("{}", error))
+//!     }
+//! }
+//!
+//! let encoded = serde_ipld_dagjson::to_vec(&Ext(Cents(1999))).unwrap();
+//! assert_eq!(encoded, br#"{"/":{"ext":"cents","value":"1999"}}"#);
+//!
+//! let options = DecodeOptions::new().extension::<Cents>();
+//! let decoded: Ext<Cents> = de::from_slice_with_options(&encoded, &options).unwrap();
+//! assert_eq!(decoded.0, Cents(1999));
+//! ```
+use std::{fmt, marker::PhantomData};
+
+use serde::{de, ser, Deserialize, Serialize};
+
+/// The newtype name [`Ext`] uses to signal that it expects an extension wrapper map.
+pub(crate) const EXT_SERDE_PRIVATE_IDENTIFIER: &str = "$__private__serde_ipld_dagjson_ext";
+
+/// A type, which is represented as an extension wrapper map.
+pub trait Extension: Sized {
+    /// The tag identifying the extension, it must be unique within an application.
+    const TAG: &'static str;
+
+    /// Returns the string representation of the value.
+    fn encode(&self) -> String;
+
+    /// Parses a value from its string representation, the error message is returned as decoding
+    /// error.
+    fn decode(value: &str) -> Result<Self, String>;
+}
+
+/// Encodes and decodes a value as extension wrapper map.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Ext<T>(pub T);
+
+/// The wrapper map of an extension.
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct ExtMap {
+    #[serde(rename = "/")]
+    pub(crate) _slash: ExtValue,
+}
+
+/// The tag and value of an extension.
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct ExtValue {
+    pub(crate) ext: String,
+    pub(crate) value: String,
+}
+
+/// The registered extension tags, by default there are none.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Extensions(Vec<&'static str>);
+
+impl Extensions {
+    pub(crate) fn push(&mut self, tag: &'static str) {
+        if !self.contains(tag) {
+            self.0.push(tag);
+        }
+    }
+
+    pub(crate) fn contains(&self, tag: &str) -> bool {
+        self.0.contains(&tag)
+    }
+}
+
+impl<T> ser::Serialize for Ext<T>
+where
+    T: Extension,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        ExtMap {
+            _slash: ExtValue {
+                ext: T::TAG.to_string(),
+                value: self.0.encode(),
+            },
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de, T> de::Deserialize<'de> for Ext<T>
+where
+    T: Extension,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer
+            .deserialize_newtype_struct(EXT_SERDE_PRIVATE_IDENTIFIER, ExtVisitor(PhantomData))
+    }
+}
+
+struct ExtVisitor<T>(PhantomData<T>);
+
+impl<T> ExtVisitor<T>
+where
+    T: Extension,
+{
+    fn decode<E>(value: ExtValue) -> Result<Ext<T>, E>
+    where
+        E: de::Error,
+    {
+        if value.ext != T::TAG {
+            return Err(de::Error::custom(format!(
+                "expected extension `{}`, found `{}`",
+                T::TAG,
+                value.ext
+            )));
+        }
+        T::decode(&value.value).map(Ext).map_err(de::Error::custom)
+    }
+}
+
+impl<'de, T> de::Visitor<'de> for ExtVisitor<T>
+where
+    T: Extension,
+{
+    type Value = Ext<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "the extension `{}`", T::TAG)
+    }
+
+    /// Called by the DAG-JSON deserializer with the tag and the value, after it checked that the
+    /// extension is registered.
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        let ext = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let value = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        Self::decode(ExtValue { ext, value })
+    }
+
+    /// Called by other deserializers.
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let ext = ExtMap::deserialize(deserializer)?;
+        Self::decode(ext._slash)
+    }
+}
@@ -0,0 +1,160 @@
+//! A minimal C ABI for encoding and decoding DAG-JSON.
+//!
+//! The declarations for C are in `include/serde_ipld_dagjson.h`. All functions return one of the
+//! `DAGJSON_*` status codes, those values are stable.
+//!
+//! Outputs are always UTF-8 strings, which are allocated by this library. They are NUL-terminated
+//! for convenience, the returned length doesn't include the terminator. They must be released
+//! with [`dagjson_free`].
+use std::{
+    panic::{self, AssertUnwindSafe},
+    ptr, slice,
+};
+
+use crate::{codec::DagJsonCodec, error::CodecError, DecodeError};
+
+/// The call succeeded.
+pub const DAGJSON_OK: i32 = 0;
+/// A required pointer argument was null.
+pub const DAGJSON_ERR_NULL_POINTER: i32 = 1;
+/// The input is not valid DAG-JSON.
+pub const DAGJSON_ERR_DECODE: i32 = 2;
+/// The data cannot be encoded as DAG-JSON.
+pub const DAGJSON_ERR_ENCODE: i32 = 3;
+/// The input contains data after the DAG-JSON document.
+pub const DAGJSON_ERR_TRAILING_DATA: i32 = 4;
+/// An unexpected internal error.
+pub const DAGJSON_ERR_INTERNAL: i32 = 5;
+
+/// Encodes a JSON document in the DAG-JSON data model into a canonical DAG-JSON block.
+///
+/// Links and bytes are given in their DAG-JSON form, `{"/": "<cid>"}` and
+/// `{"/": {"bytes": "<base64>"}}`. The result has its map keys sorted and contains no whitespace.
+///
+/// # Safety
+///
+/// `input` must point to `input_len` readable bytes, `out` and `out_len` must be valid for
+/// writes.
+#[no_mangle]
+pub unsafe extern "C" fn dagjson_encode(
+    input: *const u8,
+    input_len: usize,
+    out: *mut *mut u8,
+    out_len: *mut usize,
+) -> i32 {
+    call(input, input_len, out, out_len, |input| {
+        let ipld = crate::ipld::from_slice(input).map_err(decode_status)?;
+        crate::ipld::to_vec(&ipld).map_err(|_| DAGJSON_ERR_ENCODE)
+    })
+}
+
+/// Decodes a DAG-JSON block into plain JSON, for consumers that don't understand DAG-JSON.
+///
+/// The block is fully validated. Links and bytes are replaced by strings, links by their string
+/// form and bytes by their base64 encoding, see [`to_plain_json`](crate::plain::to_plain_json).
+///
+/// # Safety
+///
+/// `input` must point to `input_len` readable bytes, `out` and `out_len` must be valid for
+/// writes.
+#[no_mangle]
+pub unsafe extern "C" fn dagjson_decode_to_json(
+    input: *const u8,
+    input_len: usize,
+    out: *mut *mut u8,
+    out_len: *mut usize,
+) -> i32 {
+    call(input, input_len, out, out_len, |input| {
+        let json = crate::plain::to_plain_json(input).map_err(decode_status)?;
+        serde_json::to_vec(&json).map_err(|_| DAGJSON_ERR_ENCODE)
+    })
+}
+
+/// Returns the links of a DAG-JSON block, as CID strings separated by newlines.
+///
+/// The links are in the order they appear in the block.
+///
+/// # Safety
+///
+/// `input` must point to `input_len` readable bytes, `out` and `out_len` must be valid for
+/// writes.
+#[no_mangle]
+pub unsafe extern "C" fn dagjson_links(
+    input: *const u8,
+    input_len: usize,
+    out: *mut *mut u8,
+    out_len: *mut usize,
+) -> i32 {
+    call(input, input_len, out, out_len, |input| {
+        let mut links = Vec::new();
+        DagJsonCodec::for_each_link(input, |cid| links.push(cid.to_string())).map_err(|error| {
+            match error {
+                CodecError::Decode(error) => decode_status(error),
+                _ => DAGJSON_ERR_DECODE,
+            }
+        })?;
+        Ok(links.join("\n").into_bytes())
+    })
+}
+
+/// Releases an output of this library.
+///
+/// `len` is the length that was returned together with the output. Passing a null pointer does
+/// nothing.
+///
+/// # Safety
+///
+/// `ptr` must be null or an output of this library that wasn't freed yet.
+#[no_mangle]
+pub unsafe extern "C" fn dagjson_free(ptr: *mut u8, len: usize) {
+    if !ptr.is_null() {
+        // The allocation includes the NUL terminator.
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(ptr, len + 1)));
+    }
+}
+
+fn decode_status(error: DecodeError) -> i32 {
+    match error {
+        DecodeError::TrailingData => DAGJSON_ERR_TRAILING_DATA,
+        _ => DAGJSON_ERR_DECODE,
+    }
+}
+
+/// Checks the arguments, runs the function and returns its output.
+unsafe fn call<F>(
+    input: *const u8,
+    input_len: usize,
+    out: *mut *mut u8,
+    out_len: *mut usize,
+    function: F,
+) -> i32
+where
+    F: FnOnce(&[u8]) -> Result<Vec<u8>, i32>,
+{
+    if (input.is_null() && input_len > 0) || out.is_null() || out_len.is_null() {
+        return DAGJSON_ERR_NULL_POINTER;
+    }
+    let input = if input_len == 0 {
+        &[]
+    } else {
+        slice::from_raw_parts(input, input_len)
+    };
+    // Unwinding across the C ABI is undefined behaviour. Nothing is observable after a panic, so
+    // asserting unwind safety is fine.
+    let result = panic::catch_unwind(AssertUnwindSafe(|| function(input)))
+        .unwrap_or(Err(DAGJSON_ERR_INTERNAL));
+    match result {
+        Ok(mut output) => {
+            let len = output.len();
+            output.push(0);
+            *out = Box::into_raw(output.into_boxed_slice()).cast::<u8>();
+            *out_len = len;
+            DAGJSON_OK
+        }
+        Err(status) => {
+            *out = ptr::null_mut();
+            *out_len = 0;
+            status
+        }
+    }
+}
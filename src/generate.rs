@@ -0,0 +1,205 @@
+//! Deterministic generation of random DAG-JSON documents.
+//!
+//! The same seed and configuration always produce the same document, on every platform. This
+//! makes the generated documents suitable for benchmarks, fuzz corpora and load tests.
+//!
+//! # Examples
+//!
+//! ```
+//! use serde_ipld_dagjson::generate::{self, GenConfig};
+//!
+//! let config = GenConfig::new().max_depth(3).link_density(0.5);
+//! let document = generate::document(42, &config);
+//! assert_eq!(document, generate::document(42, &config));
+//! let _ipld: ipld_core::ipld::Ipld = serde_ipld_dagjson::from_slice(&document).unwrap();
+//! ```
+use std::collections::BTreeMap;
+
+use ipld_core::{
+    cid::{multihash::Multihash, Cid},
+    codec::Codec,
+    ipld::Ipld,
+};
+
+use crate::codec::DagJsonCodec;
+
+/// The multihash code of SHA2-256.
+const SHA2_256: u64 = 0x12;
+
+/// The characters map keys and strings are made of.
+const ALPHABET: &[u8] = b"abcdefghijklmnopqrstuvwxyz0123456789";
+
+/// Configuration of the generated documents.
+#[derive(Clone, Debug)]
+pub struct GenConfig {
+    max_depth: usize,
+    max_len: usize,
+    link_density: f64,
+    max_bytes_len: usize,
+    max_string_len: usize,
+}
+
+impl Default for GenConfig {
+    fn default() -> Self {
+        Self {
+            max_depth: 4,
+            max_len: 8,
+            link_density: 0.1,
+            max_bytes_len: 32,
+            max_string_len: 16,
+        }
+    }
+}
+
+impl GenConfig {
+    /// Creates a new configuration with the default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximum nesting of lists and maps, the default is 4.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Sets the maximum number of elements of lists and entries of maps, the default is 8.
+    pub fn max_len(mut self, len: usize) -> Self {
+        self.max_len = len;
+        self
+    }
+
+    /// Sets the probability (from 0.0 to 1.0) of a scalar value being a link, the default is 0.1.
+    pub fn link_density(mut self, density: f64) -> Self {
+        self.link_density = density.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Sets the maximum length of bytes values, the default is 32.
+    pub fn max_bytes_len(mut self, len: usize) -> Self {
+        self.max_bytes_len = len;
+        self
+    }
+
+    /// Sets the maximum length of strings and map keys, the default is 16.
+    pub fn max_string_len(mut self, len: usize) -> Self {
+        self.max_string_len = len;
+        self
+    }
+}
+
+/// Generates a DAG-JSON encoded document.
+///
+/// Unless the maximum depth is zero, the document is a map.
+pub fn document(seed: u64, config: &GenConfig) -> Vec<u8> {
+    crate::ipld::to_vec(&ipld(seed, config)).expect("generated data can always be encoded")
+}
+
+/// Generates the data of a document, see [`document`].
+pub fn ipld(seed: u64, config: &GenConfig) -> Ipld {
+    let mut generator = Generator {
+        rng: SplitMix64(seed),
+        config,
+    };
+    if config.max_depth == 0 {
+        generator.scalar()
+    } else {
+        generator.map(1)
+    }
+}
+
+struct Generator<'a> {
+    rng: SplitMix64,
+    config: &'a GenConfig,
+}
+
+impl Generator<'_> {
+    fn value(&mut self, depth: usize) -> Ipld {
+        if depth < self.config.max_depth && self.rng.chance(0.3) {
+            if self.rng.chance(0.5) {
+                self.list(depth + 1)
+            } else {
+                self.map(depth + 1)
+            }
+        } else {
+            self.scalar()
+        }
+    }
+
+    fn list(&mut self, depth: usize) -> Ipld {
+        let len = self.rng.below(self.config.max_len + 1);
+        Ipld::List((0..len).map(|_| self.value(depth)).collect())
+    }
+
+    fn map(&mut self, depth: usize) -> Ipld {
+        let len = self.rng.below(self.config.max_len + 1);
+        let mut map = BTreeMap::new();
+        for _ in 0..len {
+            // Keys are never empty, hence never the reserved `/` key.
+            let key = self.string(1);
+            let value = self.value(depth);
+            map.insert(key, value);
+        }
+        Ipld::Map(map)
+    }
+
+    fn scalar(&mut self) -> Ipld {
+        if self.rng.chance(self.config.link_density) {
+            return Ipld::Link(self.cid());
+        }
+        match self.rng.below(6) {
+            0 => Ipld::Null,
+            1 => Ipld::Bool(self.rng.chance(0.5)),
+            2 => Ipld::Integer((self.rng.next() as i64).into()),
+            // Integers divided by a power of ten are always finite.
+            3 => Ipld::Float((self.rng.next() as i64) as f64 / 1000.0),
+            4 => Ipld::String(self.string(0)),
+            _ => {
+                let len = self.rng.below(self.config.max_bytes_len + 1);
+                Ipld::Bytes((0..len).map(|_| self.rng.next() as u8).collect())
+            }
+        }
+    }
+
+    fn string(&mut self, min_len: usize) -> String {
+        let max_len = self.config.max_string_len.max(min_len);
+        let len = min_len + self.rng.below(max_len - min_len + 1);
+        (0..len)
+            .map(|_| ALPHABET[self.rng.below(ALPHABET.len())] as char)
+            .collect()
+    }
+
+    /// Returns a CIDv1 with a random SHA2-256 digest, it's not the hash of any actual data.
+    fn cid(&mut self) -> Cid {
+        let mut digest = [0; 32];
+        for chunk in digest.chunks_mut(8) {
+            chunk.copy_from_slice(&self.rng.next().to_le_bytes());
+        }
+        let hash = Multihash::wrap(SHA2_256, &digest).expect("digest fits into a multihash");
+        Cid::new_v1(<DagJsonCodec as Codec<Ipld>>::CODE, hash)
+    }
+}
+
+/// The SplitMix64 pseudo random number generator, it's small, fast and portable.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number from 0 up to, but not including, `bound`.
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+
+    /// Returns `true` with the given probability.
+    fn chance(&mut self, probability: f64) -> bool {
+        // The upper 53 bits make up a uniformly distributed float from 0.0 to 1.0.
+        ((self.next() >> 11) as f64 / (1u64 << 53) as f64) < probability
+    }
+}
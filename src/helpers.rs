@@ -0,0 +1,353 @@
+//! Helpers for fields with `#[serde(with = ...)]`.
+//!
+//! A [`Cid`] only gets its DAG-JSON link representation when it's serialized with this crate. These
+//! helpers make specific fields use the DAG-JSON link semantics, no matter which serde format is
+//! used. Links are encoded as `{"/": "<cid>"}`, which is also what this crate produces.
+//!
+//! # Examples
+//!
+//! ```
+//! use ipld_core::cid::Cid;
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Deserialize, Serialize)]
+//! struct Block {
+//!     #[serde(with = "serde_ipld_dagjson::helpers::opt_cid")]
+//!     parent: Option<Cid>,
+//! }
+//!
+//! let block = Block {
+//!     parent: Some("bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy".parse().unwrap()),
+//! };
+//! let json = serde_json::to_string(&block).unwrap();
+//! assert_eq!(
+//!     json,
+//!     r#"{"parent":{"/":"bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy"}}"#
+//! );
+//! ```
+use std::{
+    fmt,
+    time::{Duration, SystemTime},
+};
+
+use cid::Cid;
+use serde::{de, ser, Deserialize, Serialize};
+
+use crate::shared::{CidBytesVisitor, ReservedKeyMap, ReservedKeyValue};
+
+pub mod listpairs;
+pub mod stringjoin;
+pub mod tuple_repr;
+
+/// Serializes and deserializes a [`Cid`] as its plain string representation.
+pub mod cid_as_string {
+    use super::*;
+
+    pub fn serialize<S>(cid: &Cid, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serializer.collect_str(cid)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Cid, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_str(CidStringVisitor)
+    }
+}
+
+/// Serializes and deserializes an optional [`Cid`] as link.
+pub mod opt_cid {
+    use super::*;
+
+    pub fn serialize<S>(cid: &Option<Cid>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        match cid {
+            Some(cid) => serializer.serialize_some(&SerializeLink(cid)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<Cid>, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let link = Option::<DeserializeLink>::deserialize(deserializer)?;
+        Ok(link.map(|link| link.0))
+    }
+}
+
+/// Serializes and deserializes a vector of [`Cid`]s as list of links.
+pub mod vec_cid {
+    use super::*;
+
+    pub fn serialize<S>(cids: &[Cid], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serializer.collect_seq(cids.iter().map(SerializeLink))
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<Cid>, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let links = Vec::<DeserializeLink>::deserialize(deserializer)?;
+        Ok(links.into_iter().map(|link| link.0).collect())
+    }
+}
+
+/// Serializes and deserializes a [`BTreeMap`](std::collections::BTreeMap) with [`Cid`] values as
+/// map of links.
+pub mod btreemap_values_cid {
+    use std::collections::BTreeMap;
+
+    use super::*;
+
+    pub fn serialize<K, S>(map: &BTreeMap<K, Cid>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        K: Serialize,
+        S: ser::Serializer,
+    {
+        serializer.collect_map(map.iter().map(|(key, cid)| (key, SerializeLink(cid))))
+    }
+
+    pub fn deserialize<'de, K, D>(deserializer: D) -> Result<BTreeMap<K, Cid>, D::Error>
+    where
+        K: Deserialize<'de> + Ord,
+        D: de::Deserializer<'de>,
+    {
+        let links = BTreeMap::<K, DeserializeLink>::deserialize(deserializer)?;
+        Ok(links.into_iter().map(|(key, link)| (key, link.0)).collect())
+    }
+}
+
+/// Serializes and deserializes maps keyed by [`Cid`]s, like
+/// [`HashMap<Cid, T>`](std::collections::HashMap) or
+/// [`BTreeMap<Cid, T>`](std::collections::BTreeMap).
+///
+/// The keys are encoded as CID strings, as DAG-JSON only supports string keys. The entries are
+/// sorted by those strings, so that the encoding is canonical regardless of the map's order.
+pub mod cid_key_map {
+    use std::marker::PhantomData;
+
+    use super::*;
+
+    pub fn serialize<'a, M, T, S>(map: &'a M, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        &'a M: IntoIterator<Item = (&'a Cid, &'a T)>,
+        T: Serialize + 'a,
+        S: ser::Serializer,
+    {
+        let mut entries: Vec<_> = map
+            .into_iter()
+            .map(|(cid, value)| (cid.to_string(), value))
+            .collect();
+        // The string order differs from the order of CIDs, e.g. by their bytes.
+        entries.sort_unstable_by(|(a, _), (b, _)| a.as_bytes().cmp(b.as_bytes()));
+        serializer.collect_map(entries)
+    }
+
+    pub fn deserialize<'de, M, T, D>(deserializer: D) -> Result<M, D::Error>
+    where
+        M: FromIterator<(Cid, T)>,
+        T: Deserialize<'de>,
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_map(CidKeyMapVisitor(PhantomData))
+    }
+
+    struct CidKeyMapVisitor<M, T>(PhantomData<fn() -> (M, T)>);
+
+    impl<'de, M, T> de::Visitor<'de> for CidKeyMapVisitor<M, T>
+    where
+        M: FromIterator<(Cid, T)>,
+        T: Deserialize<'de>,
+    {
+        type Value = M;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("a map keyed by CID strings")
+        }
+
+        fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+        where
+            A: de::MapAccess<'de>,
+        {
+            let mut entries = Vec::with_capacity(map.size_hint().unwrap_or(0));
+            while let Some((key, value)) = map.next_entry::<DeserializeCidString, T>()? {
+                entries.push((key.0, value));
+            }
+            Ok(entries.into_iter().collect())
+        }
+    }
+}
+
+/// Serializes and deserializes a [`SystemTime`] as integer of seconds since the Unix epoch.
+///
+/// Fractions of a second are truncated towards the past. Times before the epoch are negative.
+pub mod timestamp_seconds {
+    use super::*;
+
+    pub fn serialize<S>(time: &SystemTime, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serializer.serialize_i64(to_unix(time, 1_000_000_000)?)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<SystemTime, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        from_unix(i64::deserialize(deserializer)?, 1_000_000_000)
+    }
+}
+
+/// Serializes and deserializes a [`SystemTime`] as integer of milliseconds since the Unix epoch.
+///
+/// Fractions of a millisecond are truncated towards the past. Times before the epoch are negative.
+pub mod timestamp_millis {
+    use super::*;
+
+    pub fn serialize<S>(time: &SystemTime, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serializer.serialize_i64(to_unix(time, 1_000_000)?)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<SystemTime, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        from_unix(i64::deserialize(deserializer)?, 1_000_000)
+    }
+}
+
+/// Converts a time into units since the Unix epoch, the unit is given in nanoseconds.
+fn to_unix<E>(time: &SystemTime, unit_nanos: i128) -> Result<i64, E>
+where
+    E: ser::Error,
+{
+    let nanos = match time.duration_since(SystemTime::UNIX_EPOCH) {
+        Ok(after) => after.as_nanos() as i128,
+        Err(before) => -(before.duration().as_nanos() as i128),
+    };
+    i64::try_from(nanos.div_euclid(unit_nanos))
+        .map_err(|_| ser::Error::custom("Timestamp out of range"))
+}
+
+/// Converts units since the Unix epoch into a time, the unit is given in nanoseconds.
+fn from_unix<E>(units: i64, unit_nanos: i128) -> Result<SystemTime, E>
+where
+    E: de::Error,
+{
+    let nanos = i128::from(units) * unit_nanos;
+    let abs_nanos = nanos.unsigned_abs();
+    let duration = Duration::new(
+        (abs_nanos / 1_000_000_000) as u64,
+        (abs_nanos % 1_000_000_000) as u32,
+    );
+    let time = if nanos >= 0 {
+        SystemTime::UNIX_EPOCH.checked_add(duration)
+    } else {
+        SystemTime::UNIX_EPOCH.checked_sub(duration)
+    };
+    time.ok_or_else(|| de::Error::custom("Timestamp out of range"))
+}
+
+/// Serializes a [`Cid`] as DAG-JSON link, independent of the serializer.
+struct SerializeLink<'a>(&'a Cid);
+
+impl Serialize for SerializeLink<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        ReservedKeyMap {
+            _slash: ReservedKeyValue::Cid(self.0.to_string()),
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Deserializes a [`Cid`] from a DAG-JSON link, independent of the deserializer.
+struct DeserializeLink(Cid);
+
+impl<'de> Deserialize<'de> for DeserializeLink {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer
+            .deserialize_any(LinkVisitor)
+            .map(DeserializeLink)
+    }
+}
+
+struct LinkVisitor;
+
+impl<'de> de::Visitor<'de> for LinkVisitor {
+    type Value = Cid;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a link")
+    }
+
+    /// Links decoded by this crate are passed on as newtype struct containing the CID bytes.
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_bytes(CidBytesVisitor)
+    }
+
+    /// Other deserializers pass on the reserved `/` map as is.
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::MapAccess<'de>,
+    {
+        match map.next_key::<String>()? {
+            Some(key) if key == "/" => {}
+            _ => return Err(de::Error::custom("Expected a link")),
+        }
+        let cid = map.next_value::<DeserializeCidString>()?.0;
+        if map.next_key::<de::IgnoredAny>()?.is_some() {
+            return Err(de::Error::custom("Expected a link"));
+        }
+        Ok(cid)
+    }
+}
+
+struct DeserializeCidString(Cid);
+
+impl<'de> Deserialize<'de> for DeserializeCidString {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        cid_as_string::deserialize(deserializer).map(DeserializeCidString)
+    }
+}
+
+struct CidStringVisitor;
+
+impl de::Visitor<'_> for CidStringVisitor {
+    type Value = Cid;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a CID string")
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Cid::try_from(value).map_err(|_| de::Error::custom(format!("Invalid CID `{}`", value)))
+    }
+}
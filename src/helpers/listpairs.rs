@@ -0,0 +1,105 @@
+//! Serializes and deserializes a map in the IPLD schema listpairs representation.
+//!
+//! The map is encoded as list of `[key, value]` pairs, in iteration order. It works with any map
+//! type, like [`BTreeMap`](std::collections::BTreeMap) or [`HashMap`](std::collections::HashMap).
+//!
+//! # Examples
+//!
+//! ```
+//! use std::collections::BTreeMap;
+//!
+//! use serde::{Deserialize, Serialize};
+//! use serde_ipld_dagjson::helpers::listpairs;
+//!
+//! #[derive(Debug, PartialEq, Deserialize, Serialize)]
+//! struct Headers {
+//!     #[serde(with = "listpairs")]
+//!     values: BTreeMap<String, u32>,
+//! }
+//!
+//! let headers = Headers { values: BTreeMap::from([("a".into(), 1), ("b".into(), 2)]) };
+//! let encoded = serde_ipld_dagjson::to_vec(&headers).unwrap();
+//! assert_eq!(encoded, br#"{"values":[["a",1],["b",2]]}"#);
+//! assert_eq!(serde_ipld_dagjson::from_slice::<Headers>(&encoded).unwrap(), headers);
+//! ```
+use std::{fmt, marker::PhantomData};
+
+use serde::{de, ser, Deserialize, Serialize};
+
+pub fn serialize<'a, M, K, V, S>(map: &'a M, serializer: S) -> Result<S::Ok, S::Error>
+where
+    &'a M: IntoIterator<Item = (&'a K, &'a V)>,
+    K: Serialize + 'a,
+    V: Serialize + 'a,
+    S: ser::Serializer,
+{
+    serializer.collect_seq(map)
+}
+
+pub fn deserialize<'de, M, K, V, D>(deserializer: D) -> Result<M, D::Error>
+where
+    M: FromIterator<(K, V)>,
+    K: Deserialize<'de>,
+    V: Deserialize<'de>,
+    D: de::Deserializer<'de>,
+{
+    deserializer.deserialize_seq(ListPairsVisitor(PhantomData))
+}
+
+/// A map that is serialized and deserialized in the listpairs representation.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ListPairs<M>(pub M);
+
+impl<M, K, V> Serialize for ListPairs<M>
+where
+    for<'a> &'a M: IntoIterator<Item = (&'a K, &'a V)>,
+    K: Serialize,
+    V: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serialize(&self.0, serializer)
+    }
+}
+
+impl<'de, M, K, V> Deserialize<'de> for ListPairs<M>
+where
+    M: FromIterator<(K, V)> + IntoIterator<Item = (K, V)>,
+    K: Deserialize<'de>,
+    V: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserialize(deserializer).map(ListPairs)
+    }
+}
+
+struct ListPairsVisitor<M, K, V>(PhantomData<(M, K, V)>);
+
+impl<'de, M, K, V> de::Visitor<'de> for ListPairsVisitor<M, K, V>
+where
+    M: FromIterator<(K, V)>,
+    K: Deserialize<'de>,
+    V: Deserialize<'de>,
+{
+    type Value = M;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a list of key-value pairs")
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        let mut entries = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(entry) = seq.next_element::<(K, V)>()? {
+            entries.push(entry);
+        }
+        Ok(entries.into_iter().collect())
+    }
+}
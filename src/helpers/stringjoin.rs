@@ -0,0 +1,108 @@
+//! Serializes and deserializes a struct in the IPLD schema stringjoin representation.
+//!
+//! The fields are joined into a single string, in the order they are declared, separated by the
+//! `DELIMITER`. All fields must be strings that don't contain the delimiter.
+//!
+//! # Examples
+//!
+//! ```
+//! use serde::{Deserialize, Serialize};
+//! use serde_ipld_dagjson::helpers::stringjoin::{self, StringJoin};
+//!
+//! #[derive(Debug, PartialEq, Deserialize, Serialize)]
+//! struct Version {
+//!     name: String,
+//!     tag: String,
+//! }
+//!
+//! #[derive(Debug, PartialEq, Deserialize, Serialize)]
+//! struct Package {
+//!     #[serde(serialize_with = "stringjoin::serialize::<':', _, _>")]
+//!     #[serde(deserialize_with = "stringjoin::deserialize::<':', _, _>")]
+//!     version: Version,
+//! }
+//!
+//! let package = Package { version: Version { name: "serde".into(), tag: "1.0".into() } };
+//! let encoded = serde_ipld_dagjson::to_vec(&package).unwrap();
+//! assert_eq!(encoded, br#"{"version":"serde:1.0"}"#);
+//! assert_eq!(serde_ipld_dagjson::from_slice::<Package>(&encoded).unwrap(), package);
+//!
+//! let version = StringJoin::<_, '/'>(Version { name: "a".into(), tag: "b".into() });
+//! assert_eq!(serde_ipld_dagjson::to_vec(&version).unwrap(), br#""a/b""#);
+//! ```
+use serde::{
+    de::{self, value::SeqDeserializer, DeserializeOwned},
+    ser, Deserialize, Serialize,
+};
+use serde_json::Value;
+
+use super::tuple_repr;
+
+pub fn serialize<const DELIMITER: char, T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Serialize + ?Sized,
+    S: ser::Serializer,
+{
+    let fields = match tuple_repr::serialize(value, serde_json::value::Serializer) {
+        Ok(Value::Array(fields)) => fields,
+        Ok(_) => unreachable!("the tuple representation is always a list"),
+        Err(error) => return Err(ser::Error::custom(error)),
+    };
+    let mut joined = String::new();
+    for (index, field) in fields.into_iter().enumerate() {
+        let Value::String(field) = field else {
+            return Err(ser::Error::custom(
+                "The stringjoin representation only supports string fields",
+            ));
+        };
+        if field.contains(DELIMITER) {
+            return Err(ser::Error::custom(format!(
+                "Field `{}` contains the delimiter `{}`",
+                field, DELIMITER
+            )));
+        }
+        if index > 0 {
+            joined.push(DELIMITER);
+        }
+        joined.push_str(&field);
+    }
+    serializer.serialize_str(&joined)
+}
+
+pub fn deserialize<'de, const DELIMITER: char, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: DeserializeOwned,
+    D: de::Deserializer<'de>,
+{
+    let joined = String::deserialize(deserializer)?;
+    let fields = joined.split(DELIMITER).map(str::to_string);
+    tuple_repr::deserialize(SeqDeserializer::<_, D::Error>::new(fields))
+}
+
+/// A struct that is serialized and deserialized in the stringjoin representation.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct StringJoin<T, const DELIMITER: char>(pub T);
+
+impl<T, const DELIMITER: char> Serialize for StringJoin<T, DELIMITER>
+where
+    T: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serialize::<DELIMITER, _, _>(&self.0, serializer)
+    }
+}
+
+impl<'de, T, const DELIMITER: char> Deserialize<'de> for StringJoin<T, DELIMITER>
+where
+    T: DeserializeOwned,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserialize::<DELIMITER, _, _>(deserializer).map(StringJoin)
+    }
+}
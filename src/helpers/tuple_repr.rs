@@ -0,0 +1,258 @@
+//! Serializes and deserializes a struct in the IPLD schema tuple representation.
+//!
+//! The fields are encoded as a list, in the order they are declared, without their names. Only
+//! the outermost struct is affected, nested structs keep their own representation. Skipped fields
+//! aren't supported, as they would shift the positions of the following fields.
+//!
+//! # Examples
+//!
+//! ```
+//! use serde::{Deserialize, Serialize};
+//! use serde_ipld_dagjson::helpers::tuple_repr::{self, TupleRepr};
+//!
+//! #[derive(Debug, PartialEq, Deserialize, Serialize)]
+//! struct Point {
+//!     x: i32,
+//!     y: i32,
+//! }
+//!
+//! #[derive(Debug, PartialEq, Deserialize, Serialize)]
+//! struct Line {
+//!     #[serde(with = "tuple_repr")]
+//!     from: Point,
+//!     #[serde(with = "tuple_repr")]
+//!     to: Point,
+//! }
+//!
+//! let line = Line { from: Point { x: 0, y: 0 }, to: Point { x: 1, y: 2 } };
+//! let encoded = serde_ipld_dagjson::to_vec(&line).unwrap();
+//! assert_eq!(encoded, br#"{"from":[0,0],"to":[1,2]}"#);
+//! assert_eq!(serde_ipld_dagjson::from_slice::<Line>(&encoded).unwrap(), line);
+//!
+//! let encoded = serde_ipld_dagjson::to_vec(&TupleRepr(Point { x: 3, y: 4 })).unwrap();
+//! assert_eq!(encoded, b"[3,4]");
+//! ```
+use std::fmt::Display;
+
+use serde::{de, ser, Deserialize, Serialize};
+
+pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Serialize + ?Sized,
+    S: ser::Serializer,
+{
+    value.serialize(TupleSerializer(serializer))
+}
+
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: Deserialize<'de>,
+    D: de::Deserializer<'de>,
+{
+    T::deserialize(TupleDeserializer(deserializer))
+}
+
+/// A struct that is serialized and deserialized in the tuple representation.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TupleRepr<T>(pub T);
+
+impl<T> Serialize for TupleRepr<T>
+where
+    T: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serialize(&self.0, serializer)
+    }
+}
+
+impl<'de, T> Deserialize<'de> for TupleRepr<T>
+where
+    T: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserialize(deserializer).map(TupleRepr)
+    }
+}
+
+/// Serializes structs as tuples and rejects everything else.
+struct TupleSerializer<S>(S);
+
+fn not_a_struct<T, E>() -> Result<T, E>
+where
+    E: ser::Error,
+{
+    Err(ser::Error::custom(
+        "The tuple representation only supports structs",
+    ))
+}
+
+/// Implements serializer methods that fail, as the value isn't a struct.
+macro_rules! reject {
+    ($($method:ident($($arg:ty),*) -> $ret:ty;)*) => {
+        $(
+            fn $method(self, $(_: $arg),*) -> Result<$ret, Self::Error> {
+                not_a_struct()
+            }
+        )*
+    };
+}
+
+impl<S> ser::Serializer for TupleSerializer<S>
+where
+    S: ser::Serializer,
+{
+    type Ok = S::Ok;
+    type Error = S::Error;
+
+    type SerializeSeq = ser::Impossible<S::Ok, S::Error>;
+    type SerializeTuple = ser::Impossible<S::Ok, S::Error>;
+    type SerializeTupleStruct = ser::Impossible<S::Ok, S::Error>;
+    type SerializeTupleVariant = ser::Impossible<S::Ok, S::Error>;
+    type SerializeMap = ser::Impossible<S::Ok, S::Error>;
+    type SerializeStruct = TupleStructSerializer<S::SerializeTuple>;
+    type SerializeStructVariant = ser::Impossible<S::Ok, S::Error>;
+
+    reject! {
+        serialize_bool(bool) -> Self::Ok;
+        serialize_i8(i8) -> Self::Ok;
+        serialize_i16(i16) -> Self::Ok;
+        serialize_i32(i32) -> Self::Ok;
+        serialize_i64(i64) -> Self::Ok;
+        serialize_u8(u8) -> Self::Ok;
+        serialize_u16(u16) -> Self::Ok;
+        serialize_u32(u32) -> Self::Ok;
+        serialize_u64(u64) -> Self::Ok;
+        serialize_f32(f32) -> Self::Ok;
+        serialize_f64(f64) -> Self::Ok;
+        serialize_char(char) -> Self::Ok;
+        serialize_str(&str) -> Self::Ok;
+        serialize_bytes(&[u8]) -> Self::Ok;
+        serialize_none() -> Self::Ok;
+        serialize_unit() -> Self::Ok;
+        serialize_unit_struct(&'static str) -> Self::Ok;
+        serialize_unit_variant(&'static str, u32, &'static str) -> Self::Ok;
+        serialize_seq(Option<usize>) -> Self::SerializeSeq;
+        serialize_tuple(usize) -> Self::SerializeTuple;
+        serialize_tuple_struct(&'static str, usize) -> Self::SerializeTupleStruct;
+        serialize_tuple_variant(&'static str, u32, &'static str, usize)
+            -> Self::SerializeTupleVariant;
+        serialize_map(Option<usize>) -> Self::SerializeMap;
+        serialize_struct_variant(&'static str, u32, &'static str, usize)
+            -> Self::SerializeStructVariant;
+    }
+
+    fn serialize_some<T>(self, _value: &T) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        not_a_struct()
+    }
+
+    fn serialize_newtype_struct<T>(
+        self,
+        _name: &'static str,
+        _value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        not_a_struct()
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        not_a_struct()
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Ok(TupleStructSerializer(self.0.serialize_tuple(len)?))
+    }
+
+    fn collect_str<T>(self, _value: &T) -> Result<Self::Ok, Self::Error>
+    where
+        T: ?Sized + Display,
+    {
+        not_a_struct()
+    }
+}
+
+/// Serializes the fields of a struct as elements of a tuple.
+struct TupleStructSerializer<S>(S);
+
+impl<S> ser::SerializeStruct for TupleStructSerializer<S>
+where
+    S: ser::SerializeTuple,
+{
+    type Ok = S::Ok;
+    type Error = S::Error;
+
+    fn serialize_field<T>(&mut self, _key: &'static str, value: &T) -> Result<(), Self::Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.0.serialize_element(value)
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<(), Self::Error> {
+        Err(ser::Error::custom(format!(
+            "The tuple representation doesn't support skipping field `{}`",
+            key
+        )))
+    }
+
+    fn end(self) -> Result<Self::Ok, Self::Error> {
+        self.0.end()
+    }
+}
+
+/// Deserializes structs from tuples, everything else is passed on as is.
+struct TupleDeserializer<D>(D);
+
+impl<'de, D> de::Deserializer<'de> for TupleDeserializer<D>
+where
+    D: de::Deserializer<'de>,
+{
+    type Error = D::Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.0.deserialize_any(visitor)
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.0.deserialize_tuple(fields.len(), visitor)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf option
+        unit unit_struct newtype_struct seq tuple tuple_struct map enum identifier ignored_any
+    }
+}
@@ -0,0 +1,429 @@
+//! Random access into DAG-JSON documents.
+use std::{borrow::Cow, collections::HashMap, fmt, fmt::Write, ops::Range};
+
+use serde::de;
+
+use crate::{de::from_slice, error::DecodeError, strict::Tokenizer};
+
+/// The maximum nesting of lists and maps, the same limit `serde_json` uses.
+const RECURSION_LIMIT: usize = 128;
+
+/// The kind of a node within a DAG-JSON document.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    Null,
+    Bool,
+    Integer,
+    Float,
+    String,
+    Bytes,
+    List,
+    Map,
+    Link,
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Null => "null",
+            Self::Bool => "bool",
+            Self::Integer => "integer",
+            Self::Float => "float",
+            Self::String => "string",
+            Self::Bytes => "bytes",
+            Self::List => "list",
+            Self::Map => "map",
+            Self::Link => "link",
+        };
+        f.pad(name)
+    }
+}
+
+/// The maximum number of characters of a leaf value shown in a [dump](IndexedDocument::dump).
+const DUMP_VALUE_LEN: usize = 40;
+
+/// A node within a DAG-JSON document.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Node {
+    /// The path of the node, the segments are separated by `/` and are
+    /// [escaped](escape_segment). The root has an empty path.
+    pub path: String,
+    /// The kind of the node.
+    pub kind: Kind,
+    /// The byte span of the node within the encoded document.
+    pub span: Range<usize>,
+}
+
+/// A DAG-JSON document, indexed for random access.
+///
+/// The document is scanned once, recording the path, kind and byte span of every node. Afterwards
+/// values can be decoded by their path, which only decodes the relevant part of the document.
+/// Links and bytes are leaves, their reserved `/` maps are not indexed. Paths are made of map
+/// keys and list indices, separated by `/`, keys that contain a `/` or `~` and the empty key are
+/// [escaped](escape_segment).
+///
+/// # Examples
+///
+/// ```
+/// use serde_ipld_dagjson::index::IndexedDocument;
+///
+/// let input = br#"{"users": [{"name": "alice"}, {"name": "bob"}]}"#;
+/// let document = IndexedDocument::new(input).unwrap();
+/// let name: String = document.get("users/1/name").unwrap().unwrap();
+/// assert_eq!(name, "bob");
+/// ```
+#[derive(Debug)]
+pub struct IndexedDocument<'a> {
+    buf: &'a [u8],
+    nodes: Vec<Node>,
+    paths: HashMap<String, usize>,
+}
+
+impl<'a> IndexedDocument<'a> {
+    /// Indexes DAG-JSON data in a slice.
+    pub fn new(buf: &'a [u8]) -> Result<Self, DecodeError> {
+        let nodes = scan(buf)?;
+        let mut paths = HashMap::with_capacity(nodes.len());
+        for (index, node) in nodes.iter().enumerate() {
+            paths.insert(node.path.clone(), index);
+        }
+        Ok(Self { buf, nodes, paths })
+    }
+
+    /// Returns all nodes of the document, in the order they appear in it.
+    pub fn nodes(&self) -> &[Node] {
+        &self.nodes
+    }
+
+    /// Returns the node at the given path.
+    pub fn node(&self, path: &str) -> Option<&Node> {
+        self.paths.get(path).map(|index| &self.nodes[*index])
+    }
+
+    /// Returns the raw encoded bytes of the value at the given path.
+    pub fn raw(&self, path: &str) -> Option<&'a [u8]> {
+        self.node(path).map(|node| &self.buf[node.span.clone()])
+    }
+
+    /// Returns an annotated dump of the document, for debugging.
+    ///
+    /// Every node is on its own line with its byte span, kind and path, indented by its depth.
+    /// Leaves are followed by their encoded form, which is truncated if it's long. Comparing the
+    /// dumps of two documents shows where their encodings, and hence their CIDs, diverge.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_ipld_dagjson::index::IndexedDocument;
+    ///
+    /// let input = br#"{"a": [1, {"/": {"bytes": "AQI"}}]}"#;
+    /// let document = IndexedDocument::new(input).unwrap();
+    /// assert_eq!(
+    ///     document.dump(),
+    ///     "\
+    /// 0..35  map
+    /// 6..34    list    a
+    /// 7..8       integer a/0 = 1
+    /// 10..33     bytes   a/1 = {\"/\": {\"bytes\": \"AQI\"}}
+    /// "
+    /// );
+    /// ```
+    pub fn dump(&self) -> String {
+        let width = self.buf.len().to_string().len() * 2 + 2;
+        let mut dump = String::new();
+        // The ends of the spans of the enclosing nodes.
+        let mut ancestors: Vec<usize> = Vec::new();
+        for node in &self.nodes {
+            while ancestors.last().is_some_and(|end| *end <= node.span.start) {
+                ancestors.pop();
+            }
+            let span = format!("{}..{}", node.span.start, node.span.end);
+            let indent = ancestors.len() * 2;
+            let mut line = format!(
+                "{:width$} {:indent$}{:7} {}",
+                span, "", node.kind, node.path
+            );
+            if matches!(node.kind, Kind::List | Kind::Map) {
+                ancestors.push(node.span.end);
+            } else {
+                let raw = String::from_utf8_lossy(&self.buf[node.span.clone()]);
+                let _ = match raw.char_indices().nth(DUMP_VALUE_LEN) {
+                    Some((end, _)) => write!(line, " = {}...", &raw[..end]),
+                    None => write!(line, " = {}", raw),
+                };
+            }
+            dump.push_str(line.trim_end());
+            dump.push('\n');
+        }
+        dump
+    }
+
+    /// Decodes the value at the given path.
+    ///
+    /// Returns `Ok(None)` if there is no value at that path.
+    pub fn get<T>(&self, path: &str) -> Result<Option<T>, DecodeError>
+    where
+        T: de::Deserialize<'a>,
+    {
+        self.raw(path).map(from_slice).transpose()
+    }
+}
+
+/// Escapes a map key for use as segment of a path.
+///
+/// Segments are separated by `/`, hence a `/` within a key is escaped as `~1` and a `~` as `~0`,
+/// like in a JSON Pointer. The empty key is escaped as `~2`, as the root has an empty path.
+///
+/// # Examples
+///
+/// ```
+/// use serde_ipld_dagjson::index::{escape_segment, IndexedDocument};
+///
+/// let input = br#"{"a/b": 1, "a": {"b": 2}}"#;
+/// let document = IndexedDocument::new(input).unwrap();
+/// let path = escape_segment("a/b");
+/// assert_eq!(path, "a~1b");
+/// assert_eq!(document.get::<u8>(&path).unwrap(), Some(1));
+/// assert_eq!(document.get::<u8>("a/b").unwrap(), Some(2));
+///
+/// let input = br#"{"": 1}"#;
+/// let document = IndexedDocument::new(input).unwrap();
+/// assert_eq!(escape_segment(""), "~2");
+/// assert_eq!(document.get::<u8>("~2").unwrap(), Some(1));
+/// ```
+pub fn escape_segment(segment: &str) -> Cow<'_, str> {
+    if segment.is_empty() {
+        Cow::Borrowed("~2")
+    } else if segment.contains(['~', '/']) {
+        Cow::Owned(segment.replace('~', "~0").replace('/', "~1"))
+    } else {
+        Cow::Borrowed(segment)
+    }
+}
+
+/// Reverts [`escape_segment`], `~1` becomes `/`, `~0` becomes `~` and `~2` the empty key.
+pub fn unescape_segment(segment: &str) -> Cow<'_, str> {
+    if segment == "~2" {
+        Cow::Borrowed("")
+    } else if segment.contains('~') {
+        Cow::Owned(segment.replace("~1", "/").replace("~0", "~"))
+    } else {
+        Cow::Borrowed(segment)
+    }
+}
+
+/// Returns the unescaped segments of a path, empty segments are skipped.
+#[cfg(feature = "codec")]
+pub(crate) fn segments(path: &str) -> impl Iterator<Item = Cow<'_, str>> {
+    path.split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(unescape_segment)
+}
+
+/// Joins segments into a path, they are [escaped](escape_segment).
+#[cfg(feature = "ipld")]
+pub(crate) fn to_path<S>(segments: &[S]) -> String
+where
+    S: AsRef<str>,
+{
+    let mut path = String::new();
+    for segment in segments {
+        push_segment(&mut path, segment.as_ref());
+    }
+    path
+}
+
+/// Appends an escaped segment to a path, the root has an empty path.
+pub(crate) fn push_segment(path: &mut String, segment: &str) {
+    if !path.is_empty() {
+        path.push('/');
+    }
+    path.push_str(&escape_segment(segment));
+}
+
+/// Scans a whole document, returning all its nodes.
+pub(crate) fn scan(buf: &[u8]) -> Result<Vec<Node>, DecodeError> {
+    let mut scanner = Scanner {
+        tokens: Tokenizer::new(buf),
+        depth: 0,
+        nodes: Vec::new(),
+    };
+    scanner.value(&mut String::new())?;
+    scanner.tokens.skip_whitespace(true)?;
+    if scanner.tokens.offset != buf.len() {
+        return Err(DecodeError::TrailingData);
+    }
+    Ok(scanner.nodes)
+}
+
+/// The result of scanning a single value.
+struct Scanned {
+    kind: Kind,
+    /// For maps with exactly one entry, its key and the kind of its value.
+    only_entry: Option<(String, Kind)>,
+}
+
+impl Scanned {
+    fn new(kind: Kind) -> Self {
+        Self {
+            kind,
+            only_entry: None,
+        }
+    }
+}
+
+/// Records the nodes of a document, the tokens are the same as the ones of the strict parser.
+struct Scanner<'a> {
+    tokens: Tokenizer<'a>,
+    depth: usize,
+    nodes: Vec<Node>,
+}
+
+impl Scanner<'_> {
+    /// Skips whitespace and returns the next byte, without consuming it.
+    fn peek_token(&mut self) -> Result<Option<u8>, DecodeError> {
+        self.tokens.skip_whitespace(true)?;
+        Ok(self.tokens.peek())
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), DecodeError> {
+        if self.peek_token()? == Some(byte) {
+            self.tokens.offset += 1;
+            Ok(())
+        } else {
+            Err(self.tokens.error(format!("expected `{}`", byte as char)))
+        }
+    }
+
+    fn value(&mut self, path: &mut String) -> Result<Scanned, DecodeError> {
+        self.tokens.skip_whitespace(true)?;
+        let start = self.tokens.offset;
+        let node_index = self.nodes.len();
+        self.nodes.push(Node {
+            path: path.clone(),
+            kind: Kind::Null,
+            span: start..start,
+        });
+        let scanned = match self.tokens.peek() {
+            Some(b'{') => self.map(path)?,
+            Some(b'[') => self.list(path)?,
+            Some(b'"') => {
+                self.tokens.offset += 1;
+                self.tokens.parse_string()?;
+                Scanned::new(Kind::String)
+            }
+            Some(b't') => self.literal(b"true", Kind::Bool)?,
+            Some(b'f') => self.literal(b"false", Kind::Bool)?,
+            Some(b'n') => self.literal(b"null", Kind::Null)?,
+            Some(b'-' | b'0'..=b'9') => match self.tokens.scan_number()? {
+                (_, true) => Scanned::new(Kind::Float),
+                (_, false) => Scanned::new(Kind::Integer),
+            },
+            Some(_) => return Err(self.tokens.error("expected value")),
+            None => return Err(self.tokens.error("unexpected end of input")),
+        };
+        // Links and bytes are leaves, hence drop the nodes of the reserved map.
+        if matches!(scanned.kind, Kind::Link | Kind::Bytes) {
+            self.nodes.truncate(node_index + 1);
+        }
+        let node = &mut self.nodes[node_index];
+        node.kind = scanned.kind;
+        node.span = start..self.tokens.offset;
+        Ok(scanned)
+    }
+
+    fn enter(&mut self) -> Result<(), DecodeError> {
+        self.depth += 1;
+        if self.depth > RECURSION_LIMIT {
+            return Err(self.tokens.error("recursion limit exceeded"));
+        }
+        self.tokens.offset += 1;
+        Ok(())
+    }
+
+    fn map(&mut self, path: &mut String) -> Result<Scanned, DecodeError> {
+        self.enter()?;
+        let mut entries = Vec::new();
+        if self.peek_token()? == Some(b'}') {
+            self.tokens.offset += 1;
+        } else {
+            loop {
+                if self.peek_token()? != Some(b'"') {
+                    return Err(self.tokens.error("expected string key"));
+                }
+                let key = self.string()?;
+                self.expect(b':')?;
+
+                let path_len = path.len();
+                push_segment(path, &key);
+                let value = self.value(path)?;
+                path.truncate(path_len);
+                entries.push((key, value));
+
+                match self.peek_token()? {
+                    Some(b',') => self.tokens.offset += 1,
+                    Some(b'}') => {
+                        self.tokens.offset += 1;
+                        break;
+                    }
+                    _ => return Err(self.tokens.error("expected `,` or `}`")),
+                }
+            }
+        }
+        self.depth -= 1;
+
+        if entries.len() != 1 {
+            return Ok(Scanned::new(Kind::Map));
+        }
+        let (key, value) = entries.pop().expect("map has exactly one entry");
+        let kind = match (key.as_str(), value.kind, &value.only_entry) {
+            ("/", Kind::String, _) => Kind::Link,
+            ("/", Kind::Map, Some((bytes_key, Kind::String))) if bytes_key == "bytes" => {
+                Kind::Bytes
+            }
+            _ => Kind::Map,
+        };
+        Ok(Scanned {
+            kind,
+            only_entry: Some((key, value.kind)),
+        })
+    }
+
+    fn list(&mut self, path: &mut String) -> Result<Scanned, DecodeError> {
+        self.enter()?;
+        if self.peek_token()? == Some(b']') {
+            self.tokens.offset += 1;
+        } else {
+            let mut index = 0;
+            loop {
+                let path_len = path.len();
+                push_segment(path, &index.to_string());
+                self.value(path)?;
+                path.truncate(path_len);
+                index += 1;
+
+                match self.peek_token()? {
+                    Some(b',') => self.tokens.offset += 1,
+                    Some(b']') => {
+                        self.tokens.offset += 1;
+                        break;
+                    }
+                    _ => return Err(self.tokens.error("expected `,` or `]`")),
+                }
+            }
+        }
+        self.depth -= 1;
+        Ok(Scanned::new(Kind::List))
+    }
+
+    /// Scans a string and returns it unescaped.
+    fn string(&mut self) -> Result<String, DecodeError> {
+        self.tokens.offset += 1;
+        Ok(self.tokens.parse_string()?.as_str().to_string())
+    }
+
+    fn literal(&mut self, literal: &[u8], kind: Kind) -> Result<Scanned, DecodeError> {
+        self.tokens.expect_literal(literal)?;
+        Ok(Scanned::new(kind))
+    }
+}
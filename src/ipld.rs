@@ -0,0 +1,547 @@
+//! Specialized encoding and decoding of [`Ipld`].
+//!
+//! The generic [`Serializer`](crate::Serializer) and [`Deserializer`](crate::Deserializer) wrap
+//! every single value in order to support arbitrary types. For [`Ipld`] the DAG-JSON specific
+//! handling of links and bytes can be done directly on top of `serde_json`, which is considerably
+//! faster.
+#[cfg(feature = "io")]
+use std::io;
+use std::{
+    collections::{BTreeMap, HashSet},
+    sync::Arc,
+};
+
+use ipld_core::{cid::Cid, ipld::Ipld};
+use serde::{de, ser, Serialize};
+
+#[cfg(feature = "float")]
+use crate::shared::serialize_float;
+#[cfg(not(feature = "float"))]
+use crate::shared::FLOATS_UNSUPPORTED;
+use crate::{
+    de::{from_slice_seed_with_options, DecodeOptions},
+    error::{DecodeError, EncodeError},
+    shared::{encode_base64, KindVisitor, Kinds, ReservedKeyMap, ReservedKeyValue},
+};
+
+/// Decodes [`Ipld`] from DAG-JSON data in a slice.
+///
+/// # Examples
+///
+/// ```
+/// use ipld_core::ipld::Ipld;
+///
+/// let ipld = serde_ipld_dagjson::ipld::from_slice(br#"{"/": {"bytes": "dm14"}}"#).unwrap();
+/// assert_eq!(ipld, Ipld::Bytes(b"vmx".to_vec()));
+/// ```
+pub fn from_slice(buf: &[u8]) -> Result<Ipld, DecodeError> {
+    let mut json_deserializer = serde_json::Deserializer::from_slice(buf);
+    let ipld =
+        de::DeserializeSeed::deserialize(KindVisitor(&mut IpldKinds), &mut json_deserializer)?;
+    json_deserializer
+        .end()
+        .map_err(|_| DecodeError::TrailingData)?;
+    Ok(ipld)
+}
+
+/// Decodes [`Ipld`] from DAG-JSON data in a reader.
+#[cfg(feature = "io")]
+pub fn from_reader<R>(reader: R) -> Result<Ipld, DecodeError>
+where
+    R: io::Read,
+{
+    let mut json_deserializer = serde_json::Deserializer::from_reader(reader);
+    let ipld =
+        de::DeserializeSeed::deserialize(KindVisitor(&mut IpldKinds), &mut json_deserializer)?;
+    json_deserializer
+        .end()
+        .map_err(|_| DecodeError::TrailingData)?;
+    Ok(ipld)
+}
+
+/// Encodes [`Ipld`] as DAG-JSON into a vector.
+///
+/// # Examples
+///
+/// ```
+/// use ipld_core::ipld;
+///
+/// let encoded = serde_ipld_dagjson::ipld::to_vec(&ipld!({"a": [1, true]})).unwrap();
+/// assert_eq!(encoded, br#"{"a":[1,true]}"#);
+/// ```
+pub fn to_vec(ipld: &Ipld) -> Result<Vec<u8>, EncodeError> {
+    let mut writer = Vec::new();
+    let mut json_serializer = serde_json::Serializer::new(&mut writer);
+    SerializeIpld(ipld).serialize(&mut json_serializer)?;
+    Ok(writer)
+}
+
+/// Encodes [`Ipld`] as DAG-JSON into a writer.
+#[cfg(feature = "io")]
+pub fn to_writer<W>(writer: W, ipld: &Ipld) -> Result<(), EncodeError>
+where
+    W: io::Write,
+{
+    let mut json_serializer = serde_json::Serializer::new(writer);
+    Ok(SerializeIpld(ipld).serialize(&mut json_serializer)?)
+}
+
+/// Builds [`Ipld`].
+struct IpldKinds;
+
+impl Kinds for IpldKinds {
+    type Value = Ipld;
+    type Map = BTreeMap<String, Ipld>;
+
+    fn null(&mut self) -> Ipld {
+        Ipld::Null
+    }
+
+    fn bool(&mut self, value: bool) -> Ipld {
+        Ipld::Bool(value)
+    }
+
+    fn integer(&mut self, value: i128) -> Ipld {
+        Ipld::Integer(value)
+    }
+
+    #[cfg(feature = "float")]
+    fn float(&mut self, value: f64) -> Ipld {
+        Ipld::Float(value)
+    }
+
+    fn string(&mut self, value: String) -> Ipld {
+        Ipld::String(value)
+    }
+
+    fn bytes(&mut self, value: Vec<u8>) -> Ipld {
+        Ipld::Bytes(value)
+    }
+
+    fn list(&mut self, list: Vec<Ipld>) -> Ipld {
+        Ipld::List(list)
+    }
+
+    fn map(&mut self, map: Self::Map) -> Ipld {
+        Ipld::Map(map)
+    }
+
+    fn link(&mut self, cid: Cid) -> Ipld {
+        Ipld::Link(cid)
+    }
+
+    fn insert(map: &mut Self::Map, key: String, value: Ipld) -> bool {
+        map.insert(key, value).is_none()
+    }
+}
+
+/// [`Ipld`], where identical links share a single [`Cid`].
+///
+/// Blocks that reference the same CID thousands of times, e.g. indexes, need only a pointer per
+/// occurrence instead of a full CID. It's created with [`from_slice_interned`] and encoded with
+/// [`to_vec`](crate::to_vec).
+#[derive(Clone, Debug, PartialEq)]
+pub enum InternedIpld {
+    Null,
+    Bool(bool),
+    Integer(i128),
+    Float(f64),
+    String(String),
+    Bytes(Vec<u8>),
+    List(Vec<InternedIpld>),
+    Map(BTreeMap<String, InternedIpld>),
+    Link(Arc<Cid>),
+}
+
+impl From<InternedIpld> for Ipld {
+    fn from(interned: InternedIpld) -> Self {
+        match interned {
+            InternedIpld::Null => Ipld::Null,
+            InternedIpld::Bool(value) => Ipld::Bool(value),
+            InternedIpld::Integer(value) => Ipld::Integer(value),
+            InternedIpld::Float(value) => Ipld::Float(value),
+            InternedIpld::String(value) => Ipld::String(value),
+            InternedIpld::Bytes(value) => Ipld::Bytes(value),
+            InternedIpld::List(list) => Ipld::List(list.into_iter().map(Into::into).collect()),
+            InternedIpld::Map(map) => Ipld::Map(
+                map.into_iter()
+                    .map(|(key, value)| (key, value.into()))
+                    .collect(),
+            ),
+            InternedIpld::Link(cid) => Ipld::Link(*cid),
+        }
+    }
+}
+
+impl ser::Serialize for InternedIpld {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        match self {
+            Self::Null => serializer.serialize_none(),
+            Self::Bool(value) => serializer.serialize_bool(*value),
+            Self::Integer(value) => serializer.serialize_i128(*value),
+            Self::Float(value) => serializer.serialize_f64(*value),
+            Self::String(value) => serializer.serialize_str(value),
+            Self::Bytes(value) => serializer.serialize_bytes(value),
+            Self::List(list) => serializer.collect_seq(list),
+            Self::Map(map) => serializer.collect_map(map),
+            Self::Link(cid) => cid.serialize(serializer),
+        }
+    }
+}
+
+/// The set of distinct CIDs seen while decoding.
+///
+/// It may be reused across several decodes, so that links are shared between blocks.
+#[derive(Clone, Debug, Default)]
+pub struct CidInterner {
+    cids: HashSet<Arc<Cid>>,
+}
+
+impl CidInterner {
+    /// Creates a new, empty interner.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the shared instance of the given CID.
+    pub fn intern(&mut self, cid: Cid) -> Arc<Cid> {
+        match self.cids.get(&cid) {
+            Some(interned) => Arc::clone(interned),
+            None => {
+                let interned = Arc::new(cid);
+                self.cids.insert(Arc::clone(&interned));
+                interned
+            }
+        }
+    }
+
+    /// Returns the number of distinct CIDs.
+    pub fn len(&self) -> usize {
+        self.cids.len()
+    }
+
+    /// Returns whether no CID was interned yet.
+    pub fn is_empty(&self) -> bool {
+        self.cids.is_empty()
+    }
+}
+
+/// Decodes [`InternedIpld`] from DAG-JSON data in a slice.
+///
+/// # Examples
+///
+/// ```
+/// use std::sync::Arc;
+///
+/// use serde_ipld_dagjson::ipld::{from_slice_interned, CidInterner, InternedIpld};
+///
+/// let link = r#"{"/": "bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy"}"#;
+/// let input = format!("[{}, {}]", link, link);
+/// let mut interner = CidInterner::new();
+/// let decoded = from_slice_interned(input.as_bytes(), &mut interner).unwrap();
+/// let InternedIpld::List(list) = decoded else { unreachable!() };
+/// let (InternedIpld::Link(first), InternedIpld::Link(second)) = (&list[0], &list[1]) else {
+///     unreachable!()
+/// };
+/// assert!(Arc::ptr_eq(first, second));
+/// assert_eq!(interner.len(), 1);
+/// ```
+pub fn from_slice_interned(
+    buf: &[u8],
+    interner: &mut CidInterner,
+) -> Result<InternedIpld, DecodeError> {
+    from_slice_interned_with_options(buf, interner, &DecodeOptions::new())
+}
+
+/// Decodes [`InternedIpld`] from DAG-JSON data in a slice, with the given options.
+pub fn from_slice_interned_with_options(
+    buf: &[u8],
+    interner: &mut CidInterner,
+    options: &DecodeOptions,
+) -> Result<InternedIpld, DecodeError> {
+    from_slice_seed_with_options(KindVisitor(interner), buf, options)
+}
+
+/// Builds [`InternedIpld`], with links shared through the interner.
+impl Kinds for CidInterner {
+    type Value = InternedIpld;
+    type Map = BTreeMap<String, InternedIpld>;
+
+    fn null(&mut self) -> InternedIpld {
+        InternedIpld::Null
+    }
+
+    fn bool(&mut self, value: bool) -> InternedIpld {
+        InternedIpld::Bool(value)
+    }
+
+    fn integer(&mut self, value: i128) -> InternedIpld {
+        InternedIpld::Integer(value)
+    }
+
+    #[cfg(feature = "float")]
+    fn float(&mut self, value: f64) -> InternedIpld {
+        InternedIpld::Float(value)
+    }
+
+    fn string(&mut self, value: String) -> InternedIpld {
+        InternedIpld::String(value)
+    }
+
+    fn bytes(&mut self, value: Vec<u8>) -> InternedIpld {
+        InternedIpld::Bytes(value)
+    }
+
+    fn list(&mut self, list: Vec<InternedIpld>) -> InternedIpld {
+        InternedIpld::List(list)
+    }
+
+    fn map(&mut self, map: Self::Map) -> InternedIpld {
+        InternedIpld::Map(map)
+    }
+
+    fn link(&mut self, cid: Cid) -> InternedIpld {
+        InternedIpld::Link(self.intern(cid))
+    }
+
+    fn insert(map: &mut Self::Map, key: String, value: InternedIpld) -> bool {
+        map.insert(key, value).is_none()
+    }
+}
+
+/// [`Ipld`], where maps keep the order of their keys in the input.
+///
+/// DAG-JSON requires map keys to be sorted, but blocks that were produced by other tools aren't
+/// always. Decoding them into [`Ipld`] sorts the keys, so that re-encoding results in different
+/// bytes and hence in a different CID. An [`OrderedIpld`] is encoded with [`to_vec_ordered`] in the
+/// original key order instead, so that compact input can be passed on byte for byte, e.g. by
+/// proxies or archives. It's created with [`from_slice_ordered`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum OrderedIpld {
+    Null,
+    Bool(bool),
+    Integer(i128),
+    Float(f64),
+    String(String),
+    Bytes(Vec<u8>),
+    List(Vec<OrderedIpld>),
+    /// The entries of a map, in the order of the input.
+    Map(Vec<(String, OrderedIpld)>),
+    Link(Cid),
+}
+
+impl From<OrderedIpld> for Ipld {
+    fn from(ordered: OrderedIpld) -> Self {
+        match ordered {
+            OrderedIpld::Null => Ipld::Null,
+            OrderedIpld::Bool(value) => Ipld::Bool(value),
+            OrderedIpld::Integer(value) => Ipld::Integer(value),
+            OrderedIpld::Float(value) => Ipld::Float(value),
+            OrderedIpld::String(value) => Ipld::String(value),
+            OrderedIpld::Bytes(value) => Ipld::Bytes(value),
+            OrderedIpld::List(list) => Ipld::List(list.into_iter().map(Into::into).collect()),
+            OrderedIpld::Map(map) => Ipld::Map(
+                map.into_iter()
+                    .map(|(key, value)| (key, value.into()))
+                    .collect(),
+            ),
+            OrderedIpld::Link(cid) => Ipld::Link(cid),
+        }
+    }
+}
+
+impl From<Ipld> for OrderedIpld {
+    /// Converts [`Ipld`], its maps are in sorted key order.
+    fn from(ipld: Ipld) -> Self {
+        match ipld {
+            Ipld::Null => OrderedIpld::Null,
+            Ipld::Bool(value) => OrderedIpld::Bool(value),
+            Ipld::Integer(value) => OrderedIpld::Integer(value),
+            Ipld::Float(value) => OrderedIpld::Float(value),
+            Ipld::String(value) => OrderedIpld::String(value),
+            Ipld::Bytes(value) => OrderedIpld::Bytes(value),
+            Ipld::List(list) => OrderedIpld::List(list.into_iter().map(Into::into).collect()),
+            Ipld::Map(map) => OrderedIpld::Map(
+                map.into_iter()
+                    .map(|(key, value)| (key, value.into()))
+                    .collect(),
+            ),
+            Ipld::Link(cid) => OrderedIpld::Link(cid),
+        }
+    }
+}
+
+/// Decodes [`OrderedIpld`] from DAG-JSON data in a slice.
+///
+/// Apart from the key order, the same input is accepted as by [`from_slice`], e.g. duplicate keys
+/// are still an error.
+///
+/// # Examples
+///
+/// ```
+/// use serde_ipld_dagjson::ipld::{from_slice_ordered, to_vec_ordered};
+///
+/// let input = br#"{"z":1,"a":{"y":[true,null],"b":"c"}}"#;
+/// let ordered = from_slice_ordered(input).unwrap();
+/// assert_eq!(to_vec_ordered(&ordered).unwrap(), input);
+/// ```
+pub fn from_slice_ordered(buf: &[u8]) -> Result<OrderedIpld, DecodeError> {
+    let mut json_deserializer = serde_json::Deserializer::from_slice(buf);
+    let ipld =
+        de::DeserializeSeed::deserialize(KindVisitor(&mut OrderedKinds), &mut json_deserializer)?;
+    json_deserializer
+        .end()
+        .map_err(|_| DecodeError::TrailingData)?;
+    Ok(ipld)
+}
+
+/// Encodes [`OrderedIpld`] as DAG-JSON into a vector, with the map keys in their recorded order.
+///
+/// The result is only valid DAG-JSON if the keys are sorted. Input that was compact and used the
+/// same number and string formatting as this crate is reproduced exactly.
+pub fn to_vec_ordered(ipld: &OrderedIpld) -> Result<Vec<u8>, EncodeError> {
+    let mut writer = Vec::new();
+    let mut json_serializer = serde_json::Serializer::new(&mut writer);
+    SerializeOrdered(ipld).serialize(&mut json_serializer)?;
+    Ok(writer)
+}
+
+/// Builds [`OrderedIpld`].
+struct OrderedKinds;
+
+/// The entries of an [`OrderedIpld`] map while it's built.
+#[derive(Default)]
+struct OrderedEntries {
+    entries: Vec<(String, OrderedIpld)>,
+    keys: HashSet<String>,
+}
+
+impl Kinds for OrderedKinds {
+    type Value = OrderedIpld;
+    type Map = OrderedEntries;
+
+    fn null(&mut self) -> OrderedIpld {
+        OrderedIpld::Null
+    }
+
+    fn bool(&mut self, value: bool) -> OrderedIpld {
+        OrderedIpld::Bool(value)
+    }
+
+    fn integer(&mut self, value: i128) -> OrderedIpld {
+        OrderedIpld::Integer(value)
+    }
+
+    #[cfg(feature = "float")]
+    fn float(&mut self, value: f64) -> OrderedIpld {
+        OrderedIpld::Float(value)
+    }
+
+    fn string(&mut self, value: String) -> OrderedIpld {
+        OrderedIpld::String(value)
+    }
+
+    fn bytes(&mut self, value: Vec<u8>) -> OrderedIpld {
+        OrderedIpld::Bytes(value)
+    }
+
+    fn list(&mut self, list: Vec<OrderedIpld>) -> OrderedIpld {
+        OrderedIpld::List(list)
+    }
+
+    fn map(&mut self, map: OrderedEntries) -> OrderedIpld {
+        OrderedIpld::Map(map.entries)
+    }
+
+    fn link(&mut self, cid: Cid) -> OrderedIpld {
+        OrderedIpld::Link(cid)
+    }
+
+    fn insert(map: &mut OrderedEntries, key: String, value: OrderedIpld) -> bool {
+        if !map.keys.insert(key.clone()) {
+            return false;
+        }
+        map.entries.push((key, value));
+        true
+    }
+}
+
+/// Serializes [`OrderedIpld`] with links and bytes in their reserved DAG-JSON forms.
+struct SerializeOrdered<'a>(&'a OrderedIpld);
+
+impl ser::Serialize for SerializeOrdered<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        match self.0 {
+            OrderedIpld::Null => serializer.serialize_none(),
+            OrderedIpld::Bool(value) => serializer.serialize_bool(*value),
+            OrderedIpld::Integer(value) => serializer.serialize_i128(*value),
+            OrderedIpld::Float(value) => SerializeIpld(&Ipld::Float(*value)).serialize(serializer),
+            OrderedIpld::String(value) => serializer.serialize_str(value),
+            OrderedIpld::Bytes(value) => ReservedKeyMap {
+                _slash: ReservedKeyValue::Bytes {
+                    bytes: encode_base64(value),
+                },
+            }
+            .serialize(serializer),
+            OrderedIpld::List(list) => serializer.collect_seq(list.iter().map(SerializeOrdered)),
+            OrderedIpld::Map(map) => serializer.collect_map(
+                map.iter()
+                    .map(|(key, value)| (key, SerializeOrdered(value))),
+            ),
+            OrderedIpld::Link(cid) => ReservedKeyMap {
+                _slash: ReservedKeyValue::Cid(cid.to_string()),
+            }
+            .serialize(serializer),
+        }
+    }
+}
+
+/// Serializes [`Ipld`] with links and bytes in their reserved DAG-JSON forms.
+struct SerializeIpld<'a>(&'a Ipld);
+
+impl ser::Serialize for SerializeIpld<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        match self.0 {
+            Ipld::Null => serializer.serialize_none(),
+            Ipld::Bool(value) => serializer.serialize_bool(*value),
+            Ipld::Integer(value) => serializer.serialize_i128(*value),
+            #[cfg(not(feature = "float"))]
+            Ipld::Float(_) => Err(ser::Error::custom(FLOATS_UNSUPPORTED)),
+            #[cfg(feature = "float")]
+            Ipld::Float(value) => {
+                // In DAG-JSON only finite floats are supported.
+                if !value.is_finite() {
+                    Err(ser::Error::custom(
+                        "Float must be a finite number, not Infinity or NaN".to_string(),
+                    ))
+                } else {
+                    serialize_float(serializer, *value)
+                }
+            }
+            Ipld::String(value) => serializer.serialize_str(value),
+            Ipld::Bytes(value) => ReservedKeyMap {
+                _slash: ReservedKeyValue::Bytes {
+                    bytes: encode_base64(value),
+                },
+            }
+            .serialize(serializer),
+            Ipld::List(list) => serializer.collect_seq(list.iter().map(SerializeIpld)),
+            Ipld::Map(map) => {
+                serializer.collect_map(map.iter().map(|(key, value)| (key, SerializeIpld(value))))
+            }
+            Ipld::Link(cid) => ReservedKeyMap {
+                _slash: ReservedKeyValue::Cid(cid.to_string()),
+            }
+            .serialize(serializer),
+        }
+    }
+}
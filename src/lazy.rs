@@ -0,0 +1,466 @@
+//! Lazily parsed DAG-JSON documents.
+use std::{cell::OnceCell, collections::BTreeMap, fmt};
+
+use cid::Cid;
+#[cfg(feature = "ipld")]
+use ipld_core::ipld::Ipld;
+use serde::{
+    de,
+    ser::{self, SerializeMap},
+};
+use serde_json::value::RawValue;
+
+use crate::{
+    de::from_slice,
+    error::DecodeError,
+    shared::{decode_base64, KindVisitor, Kinds, ReservedKeyValue},
+};
+
+/// The newtype name [`LazyIpld`] uses to signal that reserved `/` maps shouldn't be decoded by the
+/// deserializer.
+pub(crate) const LAZY_IPLD_SERDE_PRIVATE_IDENTIFIER: &str =
+    "$__private__serde_ipld_dagjson_lazy_ipld";
+
+/// The top-level structure of a value, pointing to the raw, not yet parsed children.
+#[derive(Debug)]
+enum Index<'a> {
+    Map(BTreeMap<String, &'a RawValue>),
+    List(Vec<&'a RawValue>),
+    /// Any other kind, including links and bytes.
+    Scalar,
+}
+
+/// A DAG-JSON value that is only parsed as far as it's accessed.
+///
+/// Creating a `LazyValue` only validates that the input is well-formed JSON. Once a map or list is
+/// accessed, its top level gets indexed, but its children are kept unparsed until they are
+/// accessed themselves. Links and bytes are only decoded when [`LazyValue::decode`] is called.
+///
+/// # Examples
+///
+/// ```
+/// use serde_ipld_dagjson::lazy::LazyValue;
+///
+/// let input = br#"{"large": [1, 2, 3], "small": {"name": "foo"}}"#;
+/// let value = LazyValue::from_slice(input).unwrap();
+/// let small = value.get("small").unwrap().unwrap();
+/// let name: String = small.get("name").unwrap().unwrap().decode().unwrap();
+/// assert_eq!(name, "foo");
+/// ```
+#[derive(Debug)]
+pub struct LazyValue<'a> {
+    raw: &'a RawValue,
+    index: OnceCell<Index<'a>>,
+}
+
+impl<'a> LazyValue<'a> {
+    /// Creates a lazy value from DAG-JSON data in a slice.
+    pub fn from_slice(buf: &'a [u8]) -> Result<Self, DecodeError> {
+        let raw = serde_json::from_slice(buf)?;
+        Ok(Self::from_raw(raw))
+    }
+
+    fn from_raw(raw: &'a RawValue) -> Self {
+        Self {
+            raw,
+            index: OnceCell::new(),
+        }
+    }
+
+    /// Returns the raw, unparsed DAG-JSON of this value.
+    pub fn as_raw(&self) -> &'a str {
+        self.raw.get()
+    }
+
+    /// Decodes the whole value.
+    pub fn decode<T>(&self) -> Result<T, DecodeError>
+    where
+        T: de::Deserialize<'a>,
+    {
+        from_slice(self.as_raw().as_bytes())
+    }
+
+    /// Returns the value of the given key, if this value is a map that contains it.
+    pub fn get(&self, key: &str) -> Result<Option<LazyValue<'a>>, DecodeError> {
+        match self.index()? {
+            Index::Map(map) => Ok(map.get(key).map(|raw| Self::from_raw(raw))),
+            _ => Ok(None),
+        }
+    }
+
+    /// Returns the element at the given position, if this value is a list that is long enough.
+    pub fn get_index(&self, index: usize) -> Result<Option<LazyValue<'a>>, DecodeError> {
+        match self.index()? {
+            Index::List(list) => Ok(list.get(index).map(|raw| Self::from_raw(raw))),
+            _ => Ok(None),
+        }
+    }
+
+    /// Returns the keys of this value, if it's a map.
+    pub fn keys(&self) -> Result<Option<impl Iterator<Item = &str>>, DecodeError> {
+        match self.index()? {
+            Index::Map(map) => Ok(Some(map.keys().map(String::as_str))),
+            _ => Ok(None),
+        }
+    }
+
+    /// Returns the number of entries or elements, if this value is a map or a list.
+    pub fn len(&self) -> Result<Option<usize>, DecodeError> {
+        match self.index()? {
+            Index::Map(map) => Ok(Some(map.len())),
+            Index::List(list) => Ok(Some(list.len())),
+            Index::Scalar => Ok(None),
+        }
+    }
+
+    /// Returns whether this value is an empty map or an empty list.
+    pub fn is_empty(&self) -> Result<bool, DecodeError> {
+        Ok(self.len()? == Some(0))
+    }
+
+    fn index(&self) -> Result<&Index<'a>, DecodeError> {
+        if let Some(index) = self.index.get() {
+            return Ok(index);
+        }
+        let raw = self.as_raw();
+        let index = match raw.as_bytes().first() {
+            Some(b'{') => {
+                let map: BTreeMap<String, &'a RawValue> = serde_json::from_str(raw)?;
+                // A map with only the reserved key is a link or bytes.
+                if map.len() == 1 && map.contains_key("/") {
+                    Index::Scalar
+                } else {
+                    Index::Map(map)
+                }
+            }
+            Some(b'[') => Index::List(serde_json::from_str(raw)?),
+            _ => Index::Scalar,
+        };
+        Ok(self.index.get_or_init(|| index))
+    }
+}
+
+/// A link whose CID is only parsed when it's accessed.
+#[derive(Clone, Debug)]
+pub struct LazyLink {
+    encoded: String,
+    cid: OnceCell<Cid>,
+}
+
+impl LazyLink {
+    /// Creates a new link from the string representation of a CID.
+    pub fn new(encoded: String) -> Self {
+        Self {
+            encoded,
+            cid: OnceCell::new(),
+        }
+    }
+
+    /// Returns the string representation of the CID, as it was found in the document.
+    pub fn as_str(&self) -> &str {
+        &self.encoded
+    }
+
+    /// Returns the CID, it's parsed on the first call.
+    pub fn cid(&self) -> Result<Cid, DecodeError> {
+        if let Some(cid) = self.cid.get() {
+            return Ok(*cid);
+        }
+        let cid = Cid::try_from(&self.encoded[..])
+            .map_err(|_| DecodeError::Message(format!("Invalid CID `{}`", self.encoded)))?;
+        Ok(*self.cid.get_or_init(|| cid))
+    }
+}
+
+impl PartialEq for LazyLink {
+    fn eq(&self, other: &Self) -> bool {
+        self.encoded == other.encoded
+    }
+}
+
+/// A dynamic DAG-JSON value, like [`Ipld`], but with links that are only parsed when accessed.
+///
+/// This is useful when decoding documents only to inspect their structure, as parsing all the CIDs
+/// would be wasted work.
+///
+/// # Examples
+///
+/// ```
+/// use serde_ipld_dagjson::lazy::LazyIpld;
+///
+/// let input = br#"[{"/": "bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy"}]"#;
+/// let value: LazyIpld = serde_ipld_dagjson::from_slice(input).unwrap();
+/// let LazyIpld::List(list) = value else { panic!() };
+/// let LazyIpld::Link(link) = &list[0] else { panic!() };
+/// assert_eq!(link.cid().unwrap().version(), ipld_core::cid::Version::V1);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub enum LazyIpld {
+    Null,
+    Bool(bool),
+    Integer(i128),
+    Float(f64),
+    String(String),
+    Bytes(Vec<u8>),
+    List(Vec<LazyIpld>),
+    Map(BTreeMap<String, LazyIpld>),
+    Link(LazyLink),
+}
+
+#[cfg(feature = "ipld")]
+impl LazyIpld {
+    /// Converts into [`Ipld`], parsing all links.
+    pub fn into_ipld(self) -> Result<Ipld, DecodeError> {
+        Ok(match self {
+            Self::Null => Ipld::Null,
+            Self::Bool(value) => Ipld::Bool(value),
+            Self::Integer(value) => Ipld::Integer(value),
+            Self::Float(value) => Ipld::Float(value),
+            Self::String(value) => Ipld::String(value),
+            Self::Bytes(value) => Ipld::Bytes(value),
+            Self::List(list) => Ipld::List(
+                list.into_iter()
+                    .map(Self::into_ipld)
+                    .collect::<Result<_, _>>()?,
+            ),
+            Self::Map(map) => Ipld::Map(
+                map.into_iter()
+                    .map(|(key, value)| Ok((key, value.into_ipld()?)))
+                    .collect::<Result<_, DecodeError>>()?,
+            ),
+            Self::Link(link) => Ipld::Link(link.cid()?),
+        })
+    }
+}
+
+impl<'de> de::Deserialize<'de> for LazyIpld {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        de::DeserializeSeed::deserialize(KindVisitor(&mut LazyKinds), deserializer)
+    }
+}
+
+/// Builds [`LazyIpld`], the same way as [`Ipld`] is built, except that links aren't parsed.
+struct LazyKinds;
+
+impl Kinds for LazyKinds {
+    type Value = LazyIpld;
+    type Map = BTreeMap<String, LazyIpld>;
+
+    const NEWTYPE: Option<&'static str> = Some(LAZY_IPLD_SERDE_PRIVATE_IDENTIFIER);
+
+    fn null(&mut self) -> LazyIpld {
+        LazyIpld::Null
+    }
+
+    fn bool(&mut self, value: bool) -> LazyIpld {
+        LazyIpld::Bool(value)
+    }
+
+    fn integer(&mut self, value: i128) -> LazyIpld {
+        LazyIpld::Integer(value)
+    }
+
+    #[cfg(feature = "float")]
+    fn float(&mut self, value: f64) -> LazyIpld {
+        LazyIpld::Float(value)
+    }
+
+    fn string(&mut self, value: String) -> LazyIpld {
+        LazyIpld::String(value)
+    }
+
+    fn bytes(&mut self, value: Vec<u8>) -> LazyIpld {
+        LazyIpld::Bytes(value)
+    }
+
+    fn list(&mut self, list: Vec<LazyIpld>) -> LazyIpld {
+        LazyIpld::List(list)
+    }
+
+    fn map(&mut self, map: Self::Map) -> LazyIpld {
+        LazyIpld::Map(map)
+    }
+
+    fn link(&mut self, cid: Cid) -> LazyIpld {
+        LazyIpld::Link(LazyLink::new(cid.to_string()))
+    }
+
+    fn insert(map: &mut Self::Map, key: String, value: LazyIpld) -> bool {
+        map.insert(key, value).is_none()
+    }
+
+    fn reserved<E>(&mut self, value: ReservedKeyValue) -> Result<LazyIpld, E>
+    where
+        E: de::Error,
+    {
+        match value {
+            ReservedKeyValue::Cid(encoded) => Ok(LazyIpld::Link(LazyLink::new(encoded))),
+            ReservedKeyValue::Bytes { bytes } => {
+                let decoded = decode_base64(&bytes).ok_or_else(|| {
+                    de::Error::custom(format!("Cannot base decode bytes `{}`", bytes))
+                })?;
+                Ok(LazyIpld::Bytes(decoded))
+            }
+        }
+    }
+}
+
+/// The newtype name [`LazyBytes`] uses to signal that it decodes the reserved map itself.
+pub(crate) const LAZY_BYTES_SERDE_PRIVATE_IDENTIFIER: &str =
+    "$__private__serde_ipld_dagjson_lazy_bytes";
+
+/// The number of base64 characters [`LazyBytes::pieces`] decodes at once, it's a multiple of four,
+/// so that every piece decodes to whole bytes.
+const PIECE_LEN: usize = 16 * 1024;
+
+/// Bytes whose base64 string is borrowed from the input and only decoded when it's accessed.
+///
+/// Values that are only passed on, e.g. re-encoded or hashed as they are, skip the decoding
+/// entirely. The base64 string isn't validated when deserializing, an invalid one results in an
+/// error once it's decoded. As the string is borrowed, `LazyBytes` can only be decoded from a
+/// slice, not from a reader.
+///
+/// # Examples
+///
+/// ```
+/// use serde_ipld_dagjson::lazy::LazyBytes;
+///
+/// let input = br#"{"/": {"bytes": "aGVsbG8gd29ybGQ"}}"#;
+/// let bytes: LazyBytes = serde_ipld_dagjson::from_slice(input).unwrap();
+/// assert_eq!(bytes.as_base64(), "aGVsbG8gd29ybGQ");
+/// assert_eq!(bytes.decode().unwrap(), b"hello world");
+/// assert_eq!(serde_ipld_dagjson::to_vec(&bytes).unwrap(), br#"{"/":{"bytes":"aGVsbG8gd29ybGQ"}}"#);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct LazyBytes<'a> {
+    encoded: &'a str,
+}
+
+impl<'a> LazyBytes<'a> {
+    /// Creates new bytes from their base64 string (standard alphabet, no padding).
+    pub fn new(encoded: &'a str) -> Self {
+        Self { encoded }
+    }
+
+    /// Returns the base64 string, as it was found in the document.
+    pub fn as_base64(&self) -> &'a str {
+        self.encoded
+    }
+
+    /// Decodes all bytes at once.
+    pub fn decode(&self) -> Result<Vec<u8>, DecodeError> {
+        decode_base64(self.encoded).ok_or_else(|| {
+            DecodeError::Message(format!("Cannot base decode bytes `{}`", self.encoded))
+        })
+    }
+
+    /// Returns an iterator that decodes the bytes piece by piece, so that they don't need to be
+    /// held in memory at once.
+    pub fn pieces(&self) -> LazyBytesPieces<'a> {
+        LazyBytesPieces {
+            remaining: self.encoded.as_bytes(),
+        }
+    }
+}
+
+impl<'de: 'a, 'a> de::Deserialize<'de> for LazyBytes<'a> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer
+            .deserialize_newtype_struct(LAZY_BYTES_SERDE_PRIVATE_IDENTIFIER, LazyBytesVisitor)
+    }
+}
+
+impl ser::Serialize for LazyBytes<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(1))?;
+        map.serialize_entry("/", &EncodedBytes(self.encoded))?;
+        map.end()
+    }
+}
+
+/// The inner map `{"bytes": "..."}` of the reserved map.
+struct EncodedBytes<'a>(&'a str);
+
+impl ser::Serialize for EncodedBytes<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(1))?;
+        map.serialize_entry("bytes", self.0)?;
+        map.end()
+    }
+}
+
+/// Decodes the reserved map `{"/": {"bytes": "..."}}`, without decoding the base64 string.
+struct LazyBytesVisitor;
+
+impl<'de> de::Visitor<'de> for LazyBytesVisitor {
+    type Value = LazyBytes<'de>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("bytes")
+    }
+
+    /// Called by deserializers, which don't know about the identifier.
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_map(self)
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::MapAccess<'de>,
+    {
+        let encoded = match map.next_key::<&str>()? {
+            Some("/") => map.next_value::<EncodedMap>()?.bytes,
+            _ => return Err(de::Error::custom("Expected bytes")),
+        };
+        if map.next_key::<&str>()?.is_some() {
+            return Err(de::Error::custom("Expected bytes"));
+        }
+        Ok(LazyBytes::new(encoded))
+    }
+}
+
+/// The inner map of the reserved map, with the borrowed base64 string.
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EncodedMap<'a> {
+    bytes: &'a str,
+}
+
+/// An iterator over the decoded pieces of [`LazyBytes`], see [`LazyBytes::pieces`].
+#[derive(Clone, Debug)]
+pub struct LazyBytesPieces<'a> {
+    remaining: &'a [u8],
+}
+
+impl Iterator for LazyBytesPieces<'_> {
+    type Item = Result<Vec<u8>, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining.is_empty() {
+            return None;
+        }
+        let (piece, remaining) = self.remaining.split_at(PIECE_LEN.min(self.remaining.len()));
+        self.remaining = remaining;
+        let bytes = std::str::from_utf8(piece).ok().and_then(decode_base64);
+        if bytes.is_none() {
+            // There's no point in decoding the rest.
+            self.remaining = &[];
+        }
+        Some(bytes.ok_or_else(|| DecodeError::Message("Cannot base decode bytes".to_string())))
+    }
+}
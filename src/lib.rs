@@ -1,10 +1,82 @@
 //! DAG-JSON serialization and deserialization.
+#[cfg(feature = "ipld")]
+pub mod adl;
+#[cfg(feature = "codec")]
+pub mod block;
+pub mod blockstore;
+pub mod cancel;
+#[cfg(feature = "codec")]
+pub mod chunked;
+mod cid_cache;
+#[cfg(feature = "codec")]
 pub mod codec;
+pub mod dagjson;
 pub mod de;
+#[cfg(feature = "ipld")]
+pub mod diff;
+#[cfg(feature = "embedded-io")]
+pub mod embedded;
+#[cfg(feature = "erased")]
+pub mod erased;
 pub mod error;
+#[cfg(feature = "ipld")]
+pub mod explain;
+#[cfg(feature = "extensions")]
+pub mod ext;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(feature = "codec")]
+pub mod generate;
+pub mod helpers;
+pub mod index;
+#[cfg(feature = "ipld")]
+pub mod ipld;
+pub mod lazy;
+pub mod limits;
+pub mod maybe;
+pub mod multicodec;
+pub mod observe;
+#[cfg(feature = "ipld")]
+pub mod plain;
+pub mod profile;
+#[cfg(feature = "codec")]
+pub mod proof;
+pub mod schema;
 pub mod ser;
 mod shared;
+pub mod signing;
+#[cfg(feature = "io")]
+pub mod streaming;
+pub mod strict;
+#[cfg(feature = "testing")]
+pub mod testing;
+#[cfg(feature = "ipld")]
+pub mod traversal;
+pub mod union;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
-pub use crate::de::{from_reader, from_slice, Deserializer};
+#[cfg(feature = "bytes")]
+pub use crate::de::from_bytes;
+#[cfg(feature = "io")]
+pub use crate::de::{
+    from_file, from_reader, from_reader_with_deadline, from_reader_with_options,
+    from_reader_with_progress,
+};
+pub use crate::de::{
+    from_slice, from_slice_with_options, DecodeOptions, DecodePlan, Decoder, Deserializer,
+};
 pub use crate::error::{DecodeError, EncodeError};
-pub use crate::ser::{to_vec, to_writer, Serializer};
+pub use crate::limits::Limits;
+pub use crate::profile::Profile;
+#[cfg(feature = "bytes")]
+pub use crate::ser::{encode_into, to_bytes};
+#[cfg(feature = "io")]
+pub use crate::ser::{to_file, to_file_atomic, to_writer};
+pub use crate::ser::{to_string, to_vec, EncodeOptions, Serializer};
+
+#[cfg(feature = "codec")]
+pub use crate::block::DagJsonBlock;
+pub use crate::dagjson::DagJson;
+#[cfg(feature = "derive")]
+pub use serde_ipld_dagjson_derive::DagJsonBlock;
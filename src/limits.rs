@@ -0,0 +1,276 @@
+//! Limits for decoding untrusted input.
+//!
+//! [`Limits`] bundle the limits that protect a service against malicious or accidentally huge
+//! documents. They are applied with [`DecodeOptions::limits`](crate::DecodeOptions::limits). A
+//! [`LinkPolicy`] restricts which links are acceptable, it's applied with
+//! [`DecodeOptions::link_policy`](crate::DecodeOptions::link_policy) and
+//! [`EncodeOptions::link_policy`](crate::ser::EncodeOptions::link_policy).
+//!
+//! # Examples
+//!
+//! ```
+//! use serde_ipld_dagjson::{de, limits::Limits, DecodeOptions};
+//!
+//! let options = DecodeOptions::new().limits(Limits::strict_network());
+//! let value: Vec<u8> = de::from_slice_with_options(b"[1, 2, 3]", &options).unwrap();
+//! # assert_eq!(value, [1, 2, 3]);
+//! ```
+use std::fmt;
+
+use cid::Cid;
+
+/// The multihash code of the identity hash.
+pub(crate) const IDENTITY_CODE: u64 = 0x00;
+
+/// A set of limits, by default nothing is limited.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Limits {
+    pub(crate) max_depth: Option<usize>,
+    pub(crate) max_size: Option<usize>,
+    pub(crate) max_bytes_len: Option<usize>,
+    pub(crate) max_links: Option<usize>,
+    pub(crate) max_fuel: Option<usize>,
+}
+
+impl Limits {
+    /// Creates new limits, nothing is limited.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limits for data received from untrusted peers over the network.
+    ///
+    /// Documents may be at most 2 MiB, which is the largest block size commonly exchanged between
+    /// peers. The nesting depth is limited to 64, bytes values to 1 MiB and the number of links to
+    /// 4096.
+    pub fn strict_network() -> Self {
+        Self {
+            max_depth: Some(64),
+            max_size: Some(2 * 1024 * 1024),
+            max_bytes_len: Some(1024 * 1024),
+            max_links: Some(4096),
+            max_fuel: None,
+        }
+    }
+
+    /// Limits for reading archived data, which is more trusted, but still shouldn't exhaust the
+    /// memory.
+    ///
+    /// Documents may be at most 256 MiB, with a nesting depth of up to 128, bytes values of up to
+    /// 128 MiB and up to a million links.
+    pub fn archival() -> Self {
+        Self {
+            max_depth: Some(128),
+            max_size: Some(256 * 1024 * 1024),
+            max_bytes_len: Some(128 * 1024 * 1024),
+            max_links: Some(1_000_000),
+            max_fuel: None,
+        }
+    }
+
+    /// No limits at all, this is the default.
+    ///
+    /// Note that the underlying parser may still apply its own limits, e.g. `serde_json` limits
+    /// the nesting depth to 128.
+    pub fn unlimited() -> Self {
+        Self::default()
+    }
+
+    /// Sets the maximum nesting depth of lists and maps.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// Sets the maximum size (in bytes) of the encoded document.
+    pub fn max_size(mut self, size: usize) -> Self {
+        self.max_size = Some(size);
+        self
+    }
+
+    /// Sets the maximum length of a single bytes value, after decoding it.
+    pub fn max_bytes_len(mut self, len: usize) -> Self {
+        self.max_bytes_len = Some(len);
+        self
+    }
+
+    /// Sets the maximum number of links in a document.
+    pub fn max_links(mut self, links: usize) -> Self {
+        self.max_links = Some(links);
+        self
+    }
+
+    /// Sets the maximum amount of fuel a decode may consume.
+    ///
+    /// Fuel bounds the worst-case CPU time spent on adversarial input, independent of how the
+    /// input is shaped. Every decoded node consumes one unit, plus one unit for every
+    /// [`FUEL_BYTES_PER_UNIT`] bytes of its content, e.g. of a string.
+    pub fn max_fuel(mut self, fuel: usize) -> Self {
+        self.max_fuel = Some(fuel);
+        self
+    }
+}
+
+/// The number of content bytes that consume one additional unit of fuel, see
+/// [`Limits::max_fuel`].
+pub const FUEL_BYTES_PER_UNIT: usize = 64;
+
+/// A limit that was exceeded, together with its configured maximum.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Limit {
+    /// The nesting depth of lists and maps.
+    Depth(usize),
+    /// The size of the encoded document (in bytes).
+    Size(usize),
+    /// The length of a single bytes value.
+    BytesLen(usize),
+    /// The number of links.
+    Links(usize),
+    /// The fuel consumed by the decode.
+    Fuel(usize),
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Depth(max) => write!(f, "depth limit of {}", max),
+            Self::Size(max) => write!(f, "size limit of {} bytes", max),
+            Self::BytesLen(max) => write!(f, "bytes length limit of {}", max),
+            Self::Links(max) => write!(f, "limit of {} links", max),
+            Self::Fuel(max) => write!(f, "fuel limit of {} units", max),
+        }
+    }
+}
+
+/// A set of multicodec codes.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+enum Codes {
+    #[default]
+    Any,
+    Allow(Vec<u64>),
+    Deny(Vec<u64>),
+}
+
+impl Codes {
+    fn contains(&self, code: u64) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Allow(codes) => codes.contains(&code),
+            Self::Deny(codes) => !codes.contains(&code),
+        }
+    }
+}
+
+/// Restricts the codecs and hash functions of links, by default all links are accepted.
+///
+/// # Examples
+///
+/// ```
+/// use serde_ipld_dagjson::{
+///     de,
+///     limits::{LinkPolicy, RejectedLink},
+///     DecodeError, DecodeOptions,
+/// };
+///
+/// // Only DAG-JSON links hashed with SHA2-256.
+/// let policy = LinkPolicy::new().allow_codecs([0x0129]).allow_hashes([0x12]);
+/// let options = DecodeOptions::new().link_policy(policy);
+/// // A link with the raw codec.
+/// let input = br#"{"/": "bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy"}"#;
+/// let result: Result<ipld_core::cid::Cid, _> = de::from_slice_with_options(input, &options);
+/// assert!(matches!(
+///     result,
+///     Err(DecodeError::LinkRejected(RejectedLink::Codec(0x55)))
+/// ));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LinkPolicy {
+    codecs: Codes,
+    hashes: Codes,
+    max_identity_len: Option<usize>,
+}
+
+impl LinkPolicy {
+    /// Creates a new policy, which accepts all links.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only accepts links with one of the given codecs.
+    pub fn allow_codecs<I: IntoIterator<Item = u64>>(mut self, codecs: I) -> Self {
+        self.codecs = Codes::Allow(codecs.into_iter().collect());
+        self
+    }
+
+    /// Rejects links with any of the given codecs.
+    pub fn deny_codecs<I: IntoIterator<Item = u64>>(mut self, codecs: I) -> Self {
+        self.codecs = Codes::Deny(codecs.into_iter().collect());
+        self
+    }
+
+    /// Only accepts links, which are hashed with one of the given multihash functions.
+    pub fn allow_hashes<I: IntoIterator<Item = u64>>(mut self, hashes: I) -> Self {
+        self.hashes = Codes::Allow(hashes.into_iter().collect());
+        self
+    }
+
+    /// Rejects links, which are hashed with any of the given multihash functions.
+    pub fn deny_hashes<I: IntoIterator<Item = u64>>(mut self, hashes: I) -> Self {
+        self.hashes = Codes::Deny(hashes.into_iter().collect());
+        self
+    }
+
+    /// Rejects links using the identity hash, which inline more than `len` bytes of data.
+    pub fn max_identity_len(mut self, len: usize) -> Self {
+        self.max_identity_len = Some(len);
+        self
+    }
+
+    /// Checks whether a link is acceptable.
+    pub fn check(&self, cid: &Cid) -> Result<(), RejectedLink> {
+        if !self.codecs.contains(cid.codec()) {
+            return Err(RejectedLink::Codec(cid.codec()));
+        }
+        if !self.hashes.contains(cid.hash().code()) {
+            return Err(RejectedLink::Hash(cid.hash().code()));
+        }
+        if cid.hash().code() == IDENTITY_CODE
+            && self
+                .max_identity_len
+                .is_some_and(|max| usize::from(cid.hash().size()) > max)
+        {
+            return Err(RejectedLink::IdentityTooLarge(cid.hash().size().into()));
+        }
+        Ok(())
+    }
+}
+
+/// The reason a link was rejected by a [`LinkPolicy`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RejectedLink {
+    /// The multicodec code of the link isn't acceptable.
+    Codec(u64),
+    /// The multihash function of the link isn't acceptable.
+    Hash(u64),
+    /// The link is a CIDv0, see [`CidV0::Reject`](crate::de::CidV0::Reject).
+    Version0,
+    /// The link isn't encoded in the conventional multibase, see
+    /// [`DecodeOptions::strict_multibase`](crate::DecodeOptions::strict_multibase).
+    Multibase,
+    /// The link uses the identity hash and inlines more than the maximum number of bytes.
+    IdentityTooLarge(usize),
+}
+
+impl fmt::Display for RejectedLink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Codec(code) => write!(f, "link with codec 0x{:x} rejected", code),
+            Self::Hash(code) => write!(f, "link with hash function 0x{:x} rejected", code),
+            Self::Version0 => write!(f, "CIDv0 link rejected"),
+            Self::Multibase => write!(f, "link in unexpected multibase rejected"),
+            Self::IdentityTooLarge(len) => {
+                write!(f, "identity link with {} bytes of data rejected", len)
+            }
+        }
+    }
+}
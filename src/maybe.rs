@@ -0,0 +1,120 @@
+//! Distinguishing absent fields from explicit nulls.
+use serde::{de, ser, Deserialize, Serialize};
+
+/// A field that can be absent, null, or have a value.
+///
+/// IPLD distinguishes between an absent field and a field that is explicitly null, `Option`
+/// doesn't. Fields need to be annotated with `#[serde(default, skip_serializing_if =
+/// "Maybe::is_absent")]`, so that absent fields are neither encoded, nor required when decoding.
+///
+/// # Examples
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use serde_ipld_dagjson::maybe::Maybe;
+///
+/// #[derive(Debug, PartialEq, Deserialize, Serialize)]
+/// struct Update {
+///     #[serde(default, skip_serializing_if = "Maybe::is_absent")]
+///     name: Maybe<String>,
+///     #[serde(default, skip_serializing_if = "Maybe::is_absent")]
+///     email: Maybe<String>,
+/// }
+///
+/// // Set the name, clear the email.
+/// let update = Update { name: Maybe::Value("alice".into()), email: Maybe::Null };
+/// let encoded = serde_ipld_dagjson::to_vec(&update).unwrap();
+/// assert_eq!(encoded, br#"{"name":"alice","email":null}"#);
+///
+/// // Leave the email as is.
+/// let update: Update = serde_ipld_dagjson::from_slice(br#"{"name":"bob"}"#).unwrap();
+/// assert_eq!(update.email, Maybe::Absent);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Maybe<T> {
+    /// The field is not present.
+    #[default]
+    Absent,
+    /// The field is present and null.
+    Null,
+    /// The field is present and has a value.
+    Value(T),
+}
+
+impl<T> Maybe<T> {
+    /// Returns whether the field is absent.
+    pub fn is_absent(&self) -> bool {
+        matches!(self, Self::Absent)
+    }
+
+    /// Returns whether the field is present and null.
+    pub fn is_null(&self) -> bool {
+        matches!(self, Self::Null)
+    }
+
+    /// Returns the value, if there is one.
+    pub fn as_value(&self) -> Option<&T> {
+        match self {
+            Self::Value(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the value, if there is one.
+    pub fn into_value(self) -> Option<T> {
+        match self {
+            Self::Value(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Applies the field to an existing optional value.
+    ///
+    /// Absent fields leave it unchanged, null clears it and a value replaces it.
+    pub fn apply(self, target: &mut Option<T>) {
+        match self {
+            Self::Absent => {}
+            Self::Null => *target = None,
+            Self::Value(value) => *target = Some(value),
+        }
+    }
+}
+
+impl<T> From<Option<T>> for Maybe<T> {
+    fn from(option: Option<T>) -> Self {
+        match option {
+            Some(value) => Self::Value(value),
+            None => Self::Null,
+        }
+    }
+}
+
+impl<T> Serialize for Maybe<T>
+where
+    T: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        match self {
+            Self::Value(value) => serializer.serialize_some(value),
+            Self::Null => serializer.serialize_none(),
+            Self::Absent => Err(ser::Error::custom(
+                "Absent fields must be skipped with `skip_serializing_if = \"Maybe::is_absent\"`",
+            )),
+        }
+    }
+}
+
+impl<'de, T> Deserialize<'de> for Maybe<T>
+where
+    T: Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        Option::<T>::deserialize(deserializer).map(Self::from)
+    }
+}
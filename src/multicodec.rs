@@ -0,0 +1,145 @@
+//! Decoding blocks of different codecs.
+//!
+//! Blockstores usually contain blocks of several codecs. The functions in this module pick the
+//! right decoder, based on the codec of a CID or by sniffing the data. Besides DAG-JSON, DAG-CBOR
+//! is supported with the `dag-cbor` feature.
+//!
+//! # Examples
+//!
+//! ```
+//! use ipld_core::{
+//!     cid::{multihash::Multihash, Cid},
+//!     ipld::Ipld,
+//! };
+//! use serde_ipld_dagjson::multicodec;
+//!
+//! let data = br#"{"a": 1}"#;
+//! let cid = Cid::new_v1(multicodec::DAG_JSON, Multihash::wrap(0x00, data).unwrap());
+//! let ipld: Ipld = multicodec::decode(&cid, data).unwrap();
+//! assert_eq!(ipld, multicodec::decode_sniffed(data).unwrap());
+//! ```
+use std::{error, fmt};
+
+use cid::Cid;
+use serde::de;
+
+use crate::error::DecodeError;
+
+/// The multicodec code of DAG-JSON.
+pub const DAG_JSON: u64 = 0x0129;
+/// The multicodec code of DAG-CBOR.
+pub const DAG_CBOR: u64 = 0x71;
+/// The multicodec code of raw bytes.
+pub const RAW: u64 = 0x55;
+
+/// A codec blocks can be decoded with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Codec {
+    DagJson,
+    /// Decoding it requires the `dag-cbor` feature.
+    DagCbor,
+}
+
+impl Codec {
+    /// Returns the codec for a multicodec code, if it's a known one.
+    pub fn from_code(code: u64) -> Option<Self> {
+        match code {
+            DAG_JSON => Some(Self::DagJson),
+            DAG_CBOR => Some(Self::DagCbor),
+            _ => None,
+        }
+    }
+
+    /// Returns the multicodec code.
+    pub fn code(&self) -> u64 {
+        match self {
+            Self::DagJson => DAG_JSON,
+            Self::DagCbor => DAG_CBOR,
+        }
+    }
+
+    /// Guesses the codec of the given data.
+    ///
+    /// Data that starts like a JSON value is considered DAG-JSON, everything else DAG-CBOR. This
+    /// is a heuristic, whenever the CID is available, use [`Codec::from_code`] instead.
+    pub fn sniff(data: &[u8]) -> Self {
+        let first = data
+            .iter()
+            .find(|byte| !matches!(byte, b' ' | b'\t' | b'\n' | b'\r'));
+        match first {
+            Some(b'{' | b'[' | b'"' | b'-' | b'0'..=b'9' | b't' | b'f' | b'n')
+                if std::str::from_utf8(data).is_ok() =>
+            {
+                Self::DagJson
+            }
+            _ => Self::DagCbor,
+        }
+    }
+}
+
+/// An error when decoding a block with the codec it was dispatched to.
+#[derive(Debug)]
+pub enum DispatchError {
+    /// There is no decoder for the codec with the given multicodec code.
+    UnsupportedCodec(u64),
+    /// The block couldn't be decoded as DAG-JSON.
+    DagJson(DecodeError),
+    /// The block couldn't be decoded as DAG-CBOR.
+    #[cfg(feature = "dag-cbor")]
+    DagCbor(Box<serde_ipld_dagcbor::DecodeError<std::convert::Infallible>>),
+}
+
+impl fmt::Display for DispatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedCodec(code) => write!(f, "unsupported codec 0x{:x}", code),
+            Self::DagJson(error) => write!(f, "DAG-JSON decode error: {}", error),
+            #[cfg(feature = "dag-cbor")]
+            Self::DagCbor(error) => write!(f, "DAG-CBOR decode error: {}", error),
+        }
+    }
+}
+
+impl error::Error for DispatchError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::UnsupportedCodec(_) => None,
+            Self::DagJson(error) => Some(error),
+            #[cfg(feature = "dag-cbor")]
+            Self::DagCbor(error) => Some(error.as_ref()),
+        }
+    }
+}
+
+/// Decodes a block with the codec of its CID.
+pub fn decode<'a, T>(cid: &Cid, data: &'a [u8]) -> Result<T, DispatchError>
+where
+    T: de::Deserialize<'a>,
+{
+    let codec =
+        Codec::from_code(cid.codec()).ok_or(DispatchError::UnsupportedCodec(cid.codec()))?;
+    decode_with_codec(codec, data)
+}
+
+/// Decodes a block with the codec guessed by [`Codec::sniff`].
+pub fn decode_sniffed<'a, T>(data: &'a [u8]) -> Result<T, DispatchError>
+where
+    T: de::Deserialize<'a>,
+{
+    decode_with_codec(Codec::sniff(data), data)
+}
+
+/// Decodes a block with the given codec.
+pub fn decode_with_codec<'a, T>(codec: Codec, data: &'a [u8]) -> Result<T, DispatchError>
+where
+    T: de::Deserialize<'a>,
+{
+    match codec {
+        Codec::DagJson => crate::from_slice(data).map_err(DispatchError::DagJson),
+        #[cfg(feature = "dag-cbor")]
+        Codec::DagCbor => serde_ipld_dagcbor::from_slice(data)
+            .map_err(|error| DispatchError::DagCbor(Box::new(error))),
+        #[cfg(not(feature = "dag-cbor"))]
+        Codec::DagCbor => Err(DispatchError::UnsupportedCodec(DAG_CBOR)),
+    }
+}
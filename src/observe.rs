@@ -0,0 +1,86 @@
+//! Hooks for observing a decode.
+//!
+//! An observer, registered with [`DecodeOptions::observer`](crate::DecodeOptions::observer),
+//! receives an event for every decoded node and link. This enables custom metrics or anomaly
+//! detection, without parsing the input a second time.
+//!
+//! # Examples
+//!
+//! Count the links of a document:
+//!
+//! ```
+//! use std::sync::{
+//!     atomic::{AtomicUsize, Ordering},
+//!     Arc,
+//! };
+//!
+//! use ipld_core::ipld::Ipld;
+//! use serde_ipld_dagjson::{de, observe::DecodeEvent, DecodeOptions};
+//!
+//! let links = Arc::new(AtomicUsize::new(0));
+//! let counter = Arc::clone(&links);
+//! let options = DecodeOptions::new().observer(move |event: &DecodeEvent| {
+//!     if let DecodeEvent::Link(_) = event {
+//!         counter.fetch_add(1, Ordering::Relaxed);
+//!     }
+//! });
+//! let input = br#"[{"/": "bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy"}, 1]"#;
+//! let _: Ipld = de::from_slice_with_options(input, &options).unwrap();
+//! assert_eq!(links.load(Ordering::Relaxed), 1);
+//! ```
+use std::{fmt, sync::Arc};
+
+use cid::Cid;
+
+use crate::index::Kind;
+
+/// An event during a decode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecodeEvent<'a> {
+    /// A node of the given kind was decoded. Links are reported as nodes of kind [`Kind::Link`],
+    /// in addition to the [`DecodeEvent::Link`] event.
+    Node(Kind),
+    /// A link was decoded.
+    Link(&'a Cid),
+    /// The decode finished successfully, after consuming the given number of bytes.
+    Finished { bytes: usize },
+}
+
+/// An observer of decodes.
+///
+/// It's implemented for all closures taking a [`DecodeEvent`].
+pub trait DecodeObserver {
+    /// Called for every event, in the order of the input.
+    fn event(&self, event: &DecodeEvent<'_>);
+}
+
+impl<F> DecodeObserver for F
+where
+    F: Fn(&DecodeEvent<'_>),
+{
+    fn event(&self, event: &DecodeEvent<'_>) {
+        self(event)
+    }
+}
+
+/// The registered observer, by default there is none.
+#[derive(Clone, Default)]
+pub(crate) struct Observer(Option<Arc<dyn DecodeObserver + Send + Sync>>);
+
+impl Observer {
+    pub(crate) fn new(observer: Arc<dyn DecodeObserver + Send + Sync>) -> Self {
+        Self(Some(observer))
+    }
+
+    pub(crate) fn event(&self, event: DecodeEvent<'_>) {
+        if let Some(observer) = &self.0 {
+            observer.event(&event);
+        }
+    }
+}
+
+impl fmt::Debug for Observer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Observer({})", self.0.is_some())
+    }
+}
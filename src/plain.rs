@@ -0,0 +1,233 @@
+//! Conversion between DAG-JSON and plain JSON.
+//!
+//! Plain JSON has no links or bytes, so consumers like web frontends or exports would have to
+//! handle the reserved `{"/": ...}` maps themselves. [`to_plain_json`] replaces them with strings:
+//! links become their string form and bytes their base64 encoding (standard alphabet, no padding).
+//! This is lossy, a link and a string with the same content can't be told apart anymore, so the
+//! output is for display and export only.
+//!
+//! [`import_plain_json`] goes the other way, e.g. when migrating data from a REST API into IPLD.
+//! As plain JSON doesn't say which strings are links or bytes, [`ImportRules`] configure the paths
+//! of such fields, the result is valid DAG-JSON.
+//!
+//! # Examples
+//!
+//! ```
+//! use ipld_core::ipld::Ipld;
+//! use serde_ipld_dagjson::plain::{import_plain_json, to_plain_json, ImportRules};
+//!
+//! let input = br#"{
+//!     "data": {"/": {"bytes": "aGVsbG8"}},
+//!     "parent": {"/": "bafyreih4ia2w5w6x3fprtdlqhvs6o5etzixqvgorfdxpwfcqxpvnfwwzia"}
+//! }"#;
+//! let plain = to_plain_json(input).unwrap();
+//! assert_eq!(
+//!     plain.to_string(),
+//!     r#"{"data":"aGVsbG8","parent":"bafyreih4ia2w5w6x3fprtdlqhvs6o5etzixqvgorfdxpwfcqxpvnfwwzia"}"#
+//! );
+//!
+//! let rules = ImportRules::new().bytes("data").link("parent");
+//! let ipld = import_plain_json(plain, &rules).unwrap();
+//! assert_eq!(ipld, serde_ipld_dagjson::from_slice::<Ipld>(input).unwrap());
+//! ```
+use std::{collections::BTreeMap, fmt};
+
+use ipld_core::{cid::Cid, ipld::Ipld};
+use serde_json::{Number, Value};
+
+use crate::{
+    error::DecodeError,
+    index::{to_path, unescape_segment},
+    shared::{decode_base64, encode_base64},
+};
+
+/// Decodes a DAG-JSON document into plain JSON, with links and bytes replaced by strings.
+///
+/// Integers, which are out of the range JSON numbers are usually parsed into (`i64` and `u64`),
+/// are converted into strings as well.
+pub fn to_plain_json(buf: &[u8]) -> Result<Value, DecodeError> {
+    let ipld: Ipld = crate::from_slice(buf)?;
+    Ok(plain(ipld))
+}
+
+fn plain(ipld: Ipld) -> Value {
+    match ipld {
+        Ipld::Null => Value::Null,
+        Ipld::Bool(bool) => Value::Bool(bool),
+        Ipld::Integer(integer) => {
+            if let Ok(integer) = i64::try_from(integer) {
+                Value::from(integer)
+            } else if let Ok(integer) = u64::try_from(integer) {
+                Value::from(integer)
+            } else {
+                Value::String(integer.to_string())
+            }
+        }
+        // Decoded floats are always finite.
+        Ipld::Float(float) => Number::from_f64(float).map_or(Value::Null, Value::Number),
+        Ipld::String(string) => Value::String(string),
+        Ipld::Bytes(bytes) => Value::String(encode_base64(&bytes)),
+        Ipld::List(list) => Value::Array(list.into_iter().map(plain).collect()),
+        Ipld::Map(map) => Value::Object(
+            map.into_iter()
+                .map(|(key, value)| (key, plain(value)))
+                .collect(),
+        ),
+        Ipld::Link(cid) => Value::String(cid.to_string()),
+    }
+}
+
+/// Rules for [`import_plain_json`], which say which strings are links and bytes.
+///
+/// Paths are separated by `/`, a `*` segment matches any map key or list index, e.g.
+/// `items/*/thumbnail`. Keys that contain a `/` or `~` are
+/// [escaped](crate::index::escape_segment). By default all strings stay strings.
+#[derive(Clone, Debug, Default)]
+pub struct ImportRules {
+    links: Vec<Pattern>,
+    bytes: Vec<Pattern>,
+    detect_links: bool,
+}
+
+/// The unescaped segments of a path, `*` matches any segment.
+type Pattern = Vec<String>;
+
+/// Splits a path into its unescaped segments.
+fn pattern(path: &str) -> Pattern {
+    path.split('/')
+        .map(|segment| unescape_segment(segment).into_owned())
+        .collect()
+}
+
+impl ImportRules {
+    /// Creates new rules, which don't convert anything.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Converts the strings at the given path into links.
+    ///
+    /// A string at that path, which isn't a CID, results in an [`ImportError::InvalidLink`].
+    pub fn link(mut self, path: &str) -> Self {
+        self.links.push(pattern(path));
+        self
+    }
+
+    /// Converts the strings at the given path from base64 (standard alphabet, padding is
+    /// optional) into bytes.
+    ///
+    /// A string at that path, which isn't valid base64, results in an
+    /// [`ImportError::InvalidBytes`].
+    pub fn bytes(mut self, path: &str) -> Self {
+        self.bytes.push(pattern(path));
+        self
+    }
+
+    /// Converts all other strings into links, which are CIDs in their conventional string form,
+    /// i.e. CIDv1 in lower case base32 and CIDv0 in base58btc.
+    ///
+    /// This is a heuristic, a string that merely looks like a CID becomes a link as well.
+    pub fn detect_links(mut self, detect: bool) -> Self {
+        self.detect_links = detect;
+        self
+    }
+}
+
+/// Returns whether a path matches one of the patterns.
+fn matches_any(patterns: &[Pattern], path: &[String]) -> bool {
+    patterns.iter().any(|pattern| {
+        pattern.len() == path.len()
+            && pattern
+                .iter()
+                .zip(path)
+                .all(|(segment, key)| segment == "*" || segment == key)
+    })
+}
+
+/// An error when importing plain JSON.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ImportError {
+    /// The string at the given path should be a link, but isn't a CID.
+    InvalidLink { path: String },
+    /// The string at the given path should be bytes, but isn't valid base64.
+    InvalidBytes { path: String },
+    /// The map at the given path has a `/` key, which is reserved for links and bytes.
+    ReservedKey { path: String },
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidLink { path } => write!(f, "invalid link at path `{}`", path),
+            Self::InvalidBytes { path } => write!(f, "invalid base64 bytes at path `{}`", path),
+            Self::ReservedKey { path } => write!(f, "reserved key `/` in map at path `{}`", path),
+        }
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+/// Converts plain JSON into IPLD, with the strings matching the rules converted into links and
+/// bytes.
+pub fn import_plain_json(value: Value, rules: &ImportRules) -> Result<Ipld, ImportError> {
+    import(value, rules, &mut Vec::new())
+}
+
+fn import(value: Value, rules: &ImportRules, path: &mut Vec<String>) -> Result<Ipld, ImportError> {
+    match value {
+        Value::Null => Ok(Ipld::Null),
+        Value::Bool(bool) => Ok(Ipld::Bool(bool)),
+        Value::Number(number) => Ok(match (number.as_i64(), number.as_u64()) {
+            (Some(integer), _) => Ipld::Integer(integer.into()),
+            (None, Some(integer)) => Ipld::Integer(integer.into()),
+            _ => Ipld::Float(number.as_f64().unwrap_or_default()),
+        }),
+        Value::String(string) => {
+            if matches_any(&rules.links, path) {
+                Cid::try_from(string.as_str()).map(Ipld::Link).map_err(|_| {
+                    ImportError::InvalidLink {
+                        path: to_path(path),
+                    }
+                })
+            } else if matches_any(&rules.bytes, path) {
+                decode_base64(string.trim_end_matches('='))
+                    .map(Ipld::Bytes)
+                    .ok_or_else(|| ImportError::InvalidBytes {
+                        path: to_path(path),
+                    })
+            } else if rules.detect_links {
+                match Cid::try_from(string.as_str()) {
+                    Ok(cid) if cid.to_string() == string => Ok(Ipld::Link(cid)),
+                    _ => Ok(Ipld::String(string)),
+                }
+            } else {
+                Ok(Ipld::String(string))
+            }
+        }
+        Value::Array(array) => array
+            .into_iter()
+            .enumerate()
+            .map(|(index, element)| {
+                path.push(index.to_string());
+                let element = import(element, rules, path);
+                path.pop();
+                element
+            })
+            .collect::<Result<_, _>>()
+            .map(Ipld::List),
+        Value::Object(object) => {
+            if object.contains_key("/") {
+                return Err(ImportError::ReservedKey {
+                    path: to_path(path),
+                });
+            }
+            let mut map = BTreeMap::new();
+            for (key, value) in object {
+                path.push(key);
+                let value = import(value, rules, path)?;
+                map.insert(path.pop().unwrap(), value);
+            }
+            Ok(Ipld::Map(map))
+        }
+    }
+}
@@ -0,0 +1,79 @@
+//! Named presets of encode and decode options.
+//!
+//! Interoperability problems often come from services, which agree on a format but configure the
+//! individual options differently, e.g. one side accepts links in any multibase and the other one
+//! doesn't. A [`Profile`] bundles the options for a common use case, so that a single value can be
+//! configured in one place and passed around.
+//!
+//! # Examples
+//!
+//! ```
+//! use serde_ipld_dagjson::{de, profile::Profile};
+//!
+//! let options = Profile::Canonical.decode_options();
+//! assert!(de::from_slice_with_options::<Vec<u8>>(b"[1,2,3]", &options).is_ok());
+//! assert!(de::from_slice_with_options::<Vec<u8>>(b"[1, 2, 3]", &options).is_err());
+//! ```
+use crate::{
+    de::{CidV0, DecodeOptions},
+    ser::EncodeOptions,
+};
+
+/// A preset of encode and decode options.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Profile {
+    /// Only accepts input in the canonical form, e.g. for content addressed data, where the same
+    /// value must always result in the same bytes.
+    ///
+    /// The input must be [canonical](DecodeOptions::canonical) and links must be in their
+    /// [conventional string form](DecodeOptions::strict_multibase).
+    Canonical,
+    /// Only accepts input, which the JavaScript implementation (`@ipld/dag-json`) decodes with its
+    /// default settings.
+    ///
+    /// Links must be in their [conventional string form](DecodeOptions::strict_multibase), as
+    /// other multibases need additional decoders in JavaScript. Whitespace and unsorted keys are
+    /// accepted.
+    JsCompat,
+    /// Accepts data written by older producers.
+    ///
+    /// Links may be encoded in any multibase and CIDv0 links are
+    /// [upgraded](CidV0::Upgrade) to CIDv1, so that re-encoded data is uniform.
+    Legacy,
+}
+
+impl Profile {
+    /// Returns the decode options of the profile.
+    ///
+    /// They can be adjusted further, e.g. to apply [`Limits`](crate::Limits).
+    pub fn decode_options(&self) -> DecodeOptions {
+        match self {
+            Self::Canonical => DecodeOptions::new().canonical(true).strict_multibase(true),
+            Self::JsCompat => DecodeOptions::new().strict_multibase(true),
+            Self::Legacy => DecodeOptions::new().cid_v0(CidV0::Upgrade),
+        }
+    }
+
+    /// Returns the encode options of the profile.
+    ///
+    /// The canonical profile [sorts the keys](EncodeOptions::sort_keys) of maps and structs, so
+    /// that its output is accepted by its own decode options.
+    pub fn encode_options(&self) -> EncodeOptions {
+        match self {
+            Self::Canonical => EncodeOptions::new().sort_keys(true),
+            Self::JsCompat | Self::Legacy => EncodeOptions::new(),
+        }
+    }
+}
+
+impl From<Profile> for DecodeOptions {
+    fn from(profile: Profile) -> Self {
+        profile.decode_options()
+    }
+}
+
+impl From<Profile> for EncodeOptions {
+    fn from(profile: Profile) -> Self {
+        profile.encode_options()
+    }
+}
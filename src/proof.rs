@@ -0,0 +1,179 @@
+//! Merkle proofs for values within a DAG.
+//!
+//! A proof consists of the blocks along an IPLD path, starting at the root. Anyone knowing the
+//! root CID can verify that the value at the path is part of the DAG, without having access to any
+//! other block.
+//!
+//! # Examples
+//!
+//! ```
+//! use std::collections::HashMap;
+//!
+//! use ipld_core::{
+//!     cid::{multihash::Multihash, Cid},
+//!     ipld::Ipld,
+//! };
+//! use serde_ipld_dagjson::proof;
+//!
+//! let identity = |data: &[u8]| Multihash::wrap(0x00, data).unwrap();
+//! let leaf = br#"{"balance":5}"#.to_vec();
+//! let leaf_cid = Cid::new_v1(0x0129, identity(&leaf));
+//! let root = format!(r#"{{"alice":{{"/":"{}"}}}}"#, leaf_cid).into_bytes();
+//! let root_cid = Cid::new_v1(0x0129, identity(&root));
+//! let blocks = HashMap::from([(leaf_cid, leaf), (root_cid, root)]);
+//!
+//! let proof = proof::prove(root_cid, "alice/balance", &blocks).unwrap();
+//! assert_eq!(proof.len(), 2);
+//! let value = proof::verify(root_cid, "alice/balance", &proof, &identity).unwrap();
+//! assert_eq!(value, Ipld::Integer(5));
+//! ```
+use std::{collections::HashMap, error, fmt};
+
+use ipld_core::{cid::Cid, ipld::Ipld};
+
+use crate::{
+    block::{Block, BlockHasher},
+    error::DecodeError,
+    index::segments,
+    multicodec,
+    traversal::{join_path, BlockLoader},
+};
+
+/// An error when creating or verifying a proof.
+#[derive(Debug)]
+pub enum ProofError {
+    /// A block, which is linked at the given path, is neither available from the loader nor part
+    /// of the proof.
+    Missing { path: String, cid: Cid },
+    /// A block couldn't be decoded.
+    Decode { cid: Cid, error: Box<DecodeError> },
+    /// There is no value at the given path.
+    NotFound { path: String },
+    /// The data of a block within the proof doesn't match its CID.
+    HashMismatch { cid: Cid },
+    /// A block along the path isn't a DAG-JSON block.
+    UnsupportedCodec { cid: Cid },
+}
+
+impl fmt::Display for ProofError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing { path, cid } => write!(f, "missing block {} at path `{}`", cid, path),
+            Self::Decode { cid, error } => write!(f, "cannot decode block {}: {}", cid, error),
+            Self::NotFound { path } => write!(f, "no value at path `{}`", path),
+            Self::HashMismatch { cid } => write!(f, "data of block {} doesn't match its hash", cid),
+            Self::UnsupportedCodec { cid } => {
+                write!(
+                    f,
+                    "block {} has the unsupported codec {:#x}",
+                    cid,
+                    cid.codec()
+                )
+            }
+        }
+    }
+}
+
+impl error::Error for ProofError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::Decode { error, .. } => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// Returns the blocks that are needed to verify the value at the given path.
+///
+/// The path segments are separated by `/` and are [escaped](crate::index::escape_segment), links
+/// are followed transparently. The blocks are
+/// ordered from the root to the block containing the value. If the value itself is a link, the
+/// block it points to isn't part of the proof.
+pub fn prove<L>(root: Cid, path: &str, loader: L) -> Result<Vec<Block>, ProofError>
+where
+    L: BlockLoader,
+{
+    let mut blocks = Vec::new();
+    resolve(root, path, |cid, path| {
+        let data = loader.load(cid).ok_or_else(|| ProofError::Missing {
+            path: path.to_string(),
+            cid: *cid,
+        })?;
+        blocks.push((*cid, data.clone()));
+        Ok(data)
+    })?;
+    Ok(blocks)
+}
+
+/// Verifies a proof created by [`prove`] and returns the value at the given path.
+///
+/// The data of every block is hashed with the given hasher, which therefore needs to use the same
+/// hash function as the CIDs of the proof.
+pub fn verify<H>(root: Cid, path: &str, proof: &[Block], hasher: &H) -> Result<Ipld, ProofError>
+where
+    H: BlockHasher + ?Sized,
+{
+    let blocks = proof
+        .iter()
+        .map(|(cid, data)| (cid, data))
+        .collect::<HashMap<_, _>>();
+    resolve(root, path, |cid, path| {
+        let data = blocks.get(cid).ok_or_else(|| ProofError::Missing {
+            path: path.to_string(),
+            cid: *cid,
+        })?;
+        if hasher.digest(data) != *cid.hash() {
+            return Err(ProofError::HashMismatch { cid: *cid });
+        }
+        Ok(data.to_vec())
+    })
+}
+
+/// Resolves a path starting at a root block, `load` is called with the CID and path of every block
+/// along the path.
+///
+/// Only DAG-JSON blocks are resolved, others are rejected before they are loaded.
+fn resolve<F>(root: Cid, path: &str, mut load: F) -> Result<Ipld, ProofError>
+where
+    F: FnMut(&Cid, &str) -> Result<Vec<u8>, ProofError>,
+{
+    let mut load_and_decode = |cid: &Cid, path: &str| {
+        if cid.codec() != multicodec::DAG_JSON {
+            return Err(ProofError::UnsupportedCodec { cid: *cid });
+        }
+        let data = load(cid, path)?;
+        crate::from_slice::<Ipld>(&data).map_err(|error| ProofError::Decode {
+            cid: *cid,
+            error: Box::new(error),
+        })
+    };
+    let mut current = load_and_decode(&root, "")?;
+    let mut resolved = String::new();
+    for segment in segments(path) {
+        if let Ipld::Link(cid) = current {
+            current = load_and_decode(&cid, &resolved)?;
+        }
+        resolved = join_path(&resolved, &segment);
+        let next = match &mut current {
+            Ipld::Map(map) => map.remove(segment.as_ref()),
+            Ipld::List(list) => list_index(&segment)
+                .filter(|index| *index < list.len())
+                .map(|index| list.swap_remove(index)),
+            _ => None,
+        };
+        current = next.ok_or_else(|| ProofError::NotFound {
+            path: resolved.clone(),
+        })?;
+    }
+    Ok(current)
+}
+
+/// Parses a path segment as list index.
+///
+/// Only the canonical form is accepted, so that every list element has exactly one path. A leading
+/// `+` or leading zeros, which [`str::parse`] would allow, are rejected.
+fn list_index(segment: &str) -> Option<usize> {
+    let canonical = segment == "0"
+        || (!segment.starts_with('0') && segment.bytes().all(|byte| byte.is_ascii_digit()));
+    canonical.then(|| segment.parse().ok()).flatten()
+}
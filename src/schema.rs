@@ -0,0 +1,197 @@
+//! IPLD schemas.
+//!
+//! A small model of [IPLD schemas], covering the parts that are needed to describe DAG-JSON data:
+//! structs, maps, lists, links and kinded unions. A [`Schema`] is displayed in the schema DSL and
+//! parsed from it, Rust types can be generated with [`codegen`] and schemas can be generated from
+//! Rust types with [`schema_for`].
+//!
+//! [IPLD schemas]: https://ipld.io/docs/schemas/
+use std::fmt;
+
+pub mod codegen;
+#[cfg(feature = "ipld")]
+mod infer;
+mod parse;
+mod reflect;
+
+#[cfg(feature = "ipld")]
+pub use infer::SchemaInference;
+pub use parse::ParseError;
+pub use reflect::{schema_for, ReflectError};
+
+/// An IPLD schema, a list of type definitions.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Schema {
+    pub types: Vec<TypeDef>,
+}
+
+impl Schema {
+    /// Returns the type definition with the given name.
+    pub fn get(&self, name: &str) -> Option<&TypeDef> {
+        self.types.iter().find(|def| def.name == name)
+    }
+}
+
+/// A named type definition.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TypeDef {
+    pub name: String,
+    pub kind: TypeKind,
+}
+
+/// The kinds of type definitions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TypeKind {
+    /// A struct in the map representation.
+    Struct(Vec<Field>),
+    /// A type given by a type expression, like `[String]`, `{String:Int}` or `&Any`.
+    Basic(TypeRef),
+    /// A union in the kinded representation.
+    KindedUnion(Vec<UnionMember>),
+}
+
+/// A field of a struct.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Field {
+    pub name: String,
+    pub ty: TypeRef,
+    /// The field may be absent.
+    pub optional: bool,
+    /// The field may be null.
+    pub nullable: bool,
+}
+
+/// A member of a kinded union.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnionMember {
+    /// The name of the member type.
+    pub ty: String,
+    /// The data model kind that selects this member.
+    pub kind: Kind,
+}
+
+/// The kinds of the IPLD data model.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Kind {
+    Null,
+    Bool,
+    Int,
+    Float,
+    String,
+    Bytes,
+    List,
+    Map,
+    Link,
+}
+
+impl Kind {
+    /// Returns the lower case name of the kind, as used by union representations.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Null => "null",
+            Self::Bool => "bool",
+            Self::Int => "int",
+            Self::Float => "float",
+            Self::String => "string",
+            Self::Bytes => "bytes",
+            Self::List => "list",
+            Self::Map => "map",
+            Self::Link => "link",
+        }
+    }
+}
+
+/// A reference to a type.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TypeRef {
+    Bool,
+    Int,
+    Float,
+    String,
+    Bytes,
+    /// A link to data of any type.
+    Link,
+    Any,
+    /// An anonymous list type.
+    List {
+        value: Box<TypeRef>,
+        nullable: bool,
+    },
+    /// An anonymous map type with string keys.
+    Map {
+        value: Box<TypeRef>,
+        nullable: bool,
+    },
+    /// A type defined in the schema.
+    Named(String),
+}
+
+fn nullable(nullable: bool) -> &'static str {
+    if nullable {
+        "nullable "
+    } else {
+        ""
+    }
+}
+
+impl fmt::Display for TypeRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bool => f.write_str("Bool"),
+            Self::Int => f.write_str("Int"),
+            Self::Float => f.write_str("Float"),
+            Self::String => f.write_str("String"),
+            Self::Bytes => f.write_str("Bytes"),
+            Self::Link => f.write_str("&Any"),
+            Self::Any => f.write_str("Any"),
+            Self::List {
+                value,
+                nullable: is_nullable,
+            } => write!(f, "[{}{}]", nullable(*is_nullable), value),
+            Self::Map {
+                value,
+                nullable: is_nullable,
+            } => write!(f, "{{String:{}{}}}", nullable(*is_nullable), value),
+            Self::Named(name) => f.write_str(name),
+        }
+    }
+}
+
+impl fmt::Display for TypeDef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "type {} ", self.name)?;
+        match &self.kind {
+            TypeKind::Struct(fields) => {
+                f.write_str("struct {\n")?;
+                for field in fields {
+                    write!(f, "  {} ", field.name)?;
+                    if field.optional {
+                        f.write_str("optional ")?;
+                    }
+                    writeln!(f, "{}{}", nullable(field.nullable), field.ty)?;
+                }
+                f.write_str("}")
+            }
+            TypeKind::Basic(ty) => write!(f, "{}", ty),
+            TypeKind::KindedUnion(members) => {
+                f.write_str("union {\n")?;
+                for member in members {
+                    writeln!(f, "  | {} {}", member.ty, member.kind.as_str())?;
+                }
+                f.write_str("} representation kinded")
+            }
+        }
+    }
+}
+
+impl fmt::Display for Schema {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, def) in self.types.iter().enumerate() {
+            if index > 0 {
+                f.write_str("\n")?;
+            }
+            writeln!(f, "{}", def)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,208 @@
+//! Generation of Rust types from schemas.
+//!
+//! The generated code uses `serde`, `serde_bytes`, `ipld-core` and this crate, which need to be
+//! dependencies of the crate the code is included in.
+//!
+//! # Examples
+//!
+//! In a build script:
+//!
+//! ```no_run
+//! let out_dir = std::env::var("OUT_DIR").unwrap();
+//! serde_ipld_dagjson::schema::codegen::generate_file(
+//!     "schema.ipldsch",
+//!     format!("{}/schema.rs", out_dir),
+//! )
+//! .unwrap();
+//! ```
+//!
+//! And within the crate:
+//!
+//! ```ignore
+//! include!(concat!(env!("OUT_DIR"), "/schema.rs"));
+//! ```
+use std::{fmt::Write, fs, io, path::Path};
+
+use super::{Field, Kind, Schema, TypeDef, TypeKind, TypeRef, UnionMember};
+
+/// Rust keywords, which need to be escaped when used as field names.
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "gen", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut",
+    "pub", "ref", "return", "static", "struct", "trait", "true", "type", "unsafe", "use", "where",
+    "while", "abstract", "become", "box", "do", "final", "macro", "override", "priv", "try",
+    "typeof", "unsized", "virtual", "yield",
+];
+
+/// Generates Rust types for all types of the schema.
+///
+/// Structs become structs with their fields sorted by key, so that they are encoded canonically.
+/// Kinded unions become untagged enums and all other types become type aliases. Links are [`Cid`](ipld_core::cid::Cid)s, bytes are `serde_bytes::ByteBuf` and `Any`
+/// is [`Ipld`](ipld_core::ipld::Ipld). Optional fields are `Option`s, unless they are also
+/// nullable, then they are [`Maybe`](crate::maybe::Maybe)s.
+pub fn generate(schema: &Schema) -> String {
+    let mut code = String::from("// Generated from an IPLD schema by serde_ipld_dagjson.\n");
+    for def in &schema.types {
+        code.push('\n');
+        generate_type(&mut code, def);
+    }
+    code
+}
+
+/// Generates Rust types from a file containing a schema in the DSL and writes them to a file.
+///
+/// This is meant to be used from build scripts.
+pub fn generate_file<P, Q>(schema: P, out: Q) -> io::Result<()>
+where
+    P: AsRef<Path>,
+    Q: AsRef<Path>,
+{
+    let schema: Schema = fs::read_to_string(schema)?
+        .parse()
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+    fs::write(out, generate(&schema))
+}
+
+const DERIVE: &str = "#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]\n";
+
+fn generate_type(code: &mut String, def: &TypeDef) {
+    match &def.kind {
+        TypeKind::Struct(fields) => {
+            code.push_str(DERIVE);
+            writeln!(code, "pub struct {} {{", def.name).unwrap();
+            // Fields are serialized in order, DAG-JSON requires the keys to be sorted.
+            let mut fields = fields.iter().collect::<Vec<_>>();
+            fields.sort_by(|a, b| a.name.as_bytes().cmp(b.name.as_bytes()));
+            for field in fields {
+                generate_field(code, field);
+            }
+            code.push_str("}\n");
+        }
+        TypeKind::Basic(ty) => {
+            writeln!(code, "pub type {} = {};", def.name, rust_type(ty)).unwrap();
+        }
+        TypeKind::KindedUnion(members) => {
+            code.push_str(DERIVE);
+            code.push_str("#[serde(untagged)]\n");
+            writeln!(code, "pub enum {} {{", def.name).unwrap();
+            // Untagged variants are tried in order, hence put the stricter ones first.
+            let mut members = members.iter().collect::<Vec<_>>();
+            members.sort_by_key(|member| kind_order(member.kind));
+            for member in members {
+                generate_variant(code, member);
+            }
+            code.push_str("}\n");
+        }
+    }
+}
+
+fn generate_field(code: &mut String, field: &Field) {
+    let name = field_name(&field.name);
+    if name.trim_start_matches("r#") != field.name {
+        writeln!(
+            code,
+            "    #[serde(rename = \"{}\")]",
+            field.name.escape_default()
+        )
+        .unwrap();
+    }
+    let ty = rust_type(&field.ty);
+    let ty = match (field.optional, field.nullable) {
+        (true, true) => {
+            code.push_str(
+                "    #[serde(default, skip_serializing_if = \
+                 \"serde_ipld_dagjson::maybe::Maybe::is_absent\")]\n",
+            );
+            format!("serde_ipld_dagjson::maybe::Maybe<{}>", ty)
+        }
+        (true, false) => {
+            code.push_str("    #[serde(default, skip_serializing_if = \"Option::is_none\")]\n");
+            format!("Option<{}>", ty)
+        }
+        (false, true) => format!("Option<{}>", ty),
+        (false, false) => ty,
+    };
+    writeln!(code, "    pub {}: {},", name, ty).unwrap();
+}
+
+fn generate_variant(code: &mut String, member: &UnionMember) {
+    let ty = match member.ty.as_str() {
+        "Bool" => "bool".to_string(),
+        "Int" => "i64".to_string(),
+        "Float" => "f64".to_string(),
+        "Bytes" => rust_type(&TypeRef::Bytes),
+        // A plain `String` would also accept bytes that are valid UTF-8.
+        "String" if member.kind == Kind::String => {
+            "#[serde(with = \"serde_ipld_dagjson::union::string\")] String".to_string()
+        }
+        "String" => "String".to_string(),
+        "Any" => rust_type(&TypeRef::Any),
+        other => other.to_string(),
+    };
+    writeln!(code, "    {}({}),", member.ty, ty).unwrap();
+}
+
+/// The order of union variants, variants that accept less come first.
+fn kind_order(kind: Kind) -> u8 {
+    match kind {
+        Kind::Null => 0,
+        Kind::Bool => 1,
+        Kind::Int => 2,
+        Kind::Float => 3,
+        Kind::String => 4,
+        Kind::Bytes => 5,
+        Kind::Link => 6,
+        Kind::List => 7,
+        Kind::Map => 8,
+    }
+}
+
+fn rust_type(ty: &TypeRef) -> String {
+    match ty {
+        TypeRef::Bool => "bool".to_string(),
+        TypeRef::Int => "i64".to_string(),
+        TypeRef::Float => "f64".to_string(),
+        TypeRef::String => "String".to_string(),
+        TypeRef::Bytes => "serde_bytes::ByteBuf".to_string(),
+        TypeRef::Link => "ipld_core::cid::Cid".to_string(),
+        TypeRef::Any => "ipld_core::ipld::Ipld".to_string(),
+        TypeRef::List { value, nullable } => format!("Vec<{}>", nullable_type(value, *nullable)),
+        TypeRef::Map { value, nullable } => format!(
+            "std::collections::BTreeMap<String, {}>",
+            nullable_type(value, *nullable)
+        ),
+        TypeRef::Named(name) => name.clone(),
+    }
+}
+
+fn nullable_type(ty: &TypeRef, nullable: bool) -> String {
+    if nullable {
+        format!("Option<{}>", rust_type(ty))
+    } else {
+        rust_type(ty)
+    }
+}
+
+/// Turns a field name like `createdAt` into `created_at`, keywords are escaped.
+fn field_name(name: &str) -> String {
+    let mut snake_case = String::new();
+    for (index, char) in name.chars().enumerate() {
+        if char.is_ascii_uppercase() {
+            if index > 0 {
+                snake_case.push('_');
+            }
+            snake_case.push(char.to_ascii_lowercase());
+        } else if char.is_ascii_alphanumeric() || char == '_' {
+            snake_case.push(char);
+        } else {
+            snake_case.push('_');
+        }
+    }
+    if snake_case.starts_with(|char: char| char.is_ascii_digit()) || snake_case.is_empty() {
+        snake_case.insert(0, '_');
+    }
+    if KEYWORDS.contains(&snake_case.as_str()) {
+        snake_case.insert_str(0, "r#");
+    }
+    snake_case
+}
@@ -0,0 +1,308 @@
+//! Inference of schemas from sample data.
+use std::collections::{BTreeMap, HashSet};
+
+use ipld_core::ipld::Ipld;
+
+use super::{Field, Kind, Schema, TypeDef, TypeKind, TypeRef, UnionMember};
+use crate::error::DecodeError;
+
+/// Maps with more distinct keys than this are considered maps, not structs.
+const MAX_STRUCT_FIELDS: usize = 32;
+
+/// Infers a schema from sample documents.
+///
+/// All documents are assumed to be of the same type. Maps are inferred as structs, unless they
+/// have many distinct keys or keys that aren't identifiers. Fields that are missing in some
+/// samples are optional, fields that are null in some samples are nullable. Values of different
+/// kinds at the same position result in kinded unions.
+///
+/// # Examples
+///
+/// ```
+/// use serde_ipld_dagjson::schema::SchemaInference;
+///
+/// let mut inference = SchemaInference::new();
+/// inference.add_block(br#"{"name": "a", "tags": ["x"]}"#).unwrap();
+/// inference.add_block(br#"{"name": "b", "tags": [], "parent": null}"#).unwrap();
+/// let schema = inference.schema("Post");
+/// assert_eq!(
+///     schema.to_string(),
+///     "type Post struct {\n  name String\n  parent optional nullable Any\n  tags [String]\n}\n"
+/// );
+/// ```
+#[derive(Debug, Default)]
+pub struct SchemaInference {
+    root: Shape,
+}
+
+impl SchemaInference {
+    /// Creates a new inference without any samples.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a DAG-JSON encoded sample.
+    pub fn add_block(&mut self, data: &[u8]) -> Result<(), DecodeError> {
+        let ipld = crate::ipld::from_slice(data)?;
+        self.add_ipld(&ipld);
+        Ok(())
+    }
+
+    /// Adds an already decoded sample.
+    pub fn add_ipld(&mut self, ipld: &Ipld) {
+        self.root.observe(ipld);
+    }
+
+    /// Returns the inferred schema, the type of the documents is named `root_name`.
+    ///
+    /// Nested types are named after the path they were found at.
+    pub fn schema(&self, root_name: &str) -> Schema {
+        let mut builder = Builder::default();
+        let root = builder.type_ref(&self.root, root_name);
+        if root != TypeRef::Named(root_name.to_string()) {
+            builder.define(root_name, TypeKind::Basic(root));
+        }
+        // Make sure the root type comes first.
+        let mut types = builder.types;
+        if let Some(index) = types.iter().position(|def| def.name == root_name) {
+            let root = types.remove(index);
+            types.insert(0, root);
+        }
+        Schema { types }
+    }
+}
+
+/// The observed shape of the values at a certain position.
+#[derive(Clone, Debug, Default)]
+struct Shape {
+    kinds: BTreeMap<Kind, usize>,
+    list: Option<Box<Shape>>,
+    map: Option<MapShape>,
+}
+
+#[derive(Clone, Debug, Default)]
+struct MapShape {
+    count: usize,
+    fields: BTreeMap<String, FieldShape>,
+}
+
+#[derive(Clone, Debug, Default)]
+struct FieldShape {
+    count: usize,
+    shape: Shape,
+}
+
+impl Shape {
+    fn observe(&mut self, ipld: &Ipld) {
+        let kind = match ipld {
+            Ipld::Null => Kind::Null,
+            Ipld::Bool(_) => Kind::Bool,
+            Ipld::Integer(_) => Kind::Int,
+            Ipld::Float(_) => Kind::Float,
+            Ipld::String(_) => Kind::String,
+            Ipld::Bytes(_) => Kind::Bytes,
+            Ipld::Link(_) => Kind::Link,
+            Ipld::List(list) => {
+                let element = self.list.get_or_insert_with(Default::default);
+                for value in list {
+                    element.observe(value);
+                }
+                Kind::List
+            }
+            Ipld::Map(map) => {
+                let shape = self.map.get_or_insert_with(Default::default);
+                shape.count += 1;
+                for (key, value) in map {
+                    let field = shape.fields.entry(key.clone()).or_default();
+                    field.count += 1;
+                    field.shape.observe(value);
+                }
+                Kind::Map
+            }
+        };
+        *self.kinds.entry(kind).or_default() += 1;
+    }
+
+    fn merge(&mut self, other: &Shape) {
+        for (kind, count) in &other.kinds {
+            *self.kinds.entry(*kind).or_default() += count;
+        }
+        if let Some(other_list) = &other.list {
+            self.list
+                .get_or_insert_with(Default::default)
+                .merge(other_list);
+        }
+        if let Some(other_map) = &other.map {
+            let map = self.map.get_or_insert_with(Default::default);
+            map.count += other_map.count;
+            for (key, other_field) in &other_map.fields {
+                let field = map.fields.entry(key.clone()).or_default();
+                field.count += other_field.count;
+                field.shape.merge(&other_field.shape);
+            }
+        }
+    }
+}
+
+impl MapShape {
+    fn is_struct(&self) -> bool {
+        !self.fields.is_empty()
+            && self.fields.len() <= MAX_STRUCT_FIELDS
+            && self.fields.keys().all(|key| is_identifier(key))
+    }
+}
+
+fn is_identifier(key: &str) -> bool {
+    let mut chars = key.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|char| char.is_ascii_alphanumeric() || char == '_')
+}
+
+/// Turns a key like `created_at` into `CreatedAt`.
+fn type_name(key: &str) -> String {
+    key.split('_')
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+#[derive(Default)]
+struct Builder {
+    types: Vec<TypeDef>,
+    names: HashSet<String>,
+}
+
+impl Builder {
+    /// Returns a type name, based on the hint, that isn't used yet.
+    fn unique_name(&mut self, hint: &str) -> String {
+        let mut name = hint.to_string();
+        let mut suffix = 2;
+        while self.names.contains(&name) {
+            name = format!("{}{}", hint, suffix);
+            suffix += 1;
+        }
+        self.names.insert(name.clone());
+        name
+    }
+
+    fn define(&mut self, name: &str, kind: TypeKind) {
+        self.names.insert(name.to_string());
+        self.types.push(TypeDef {
+            name: name.to_string(),
+            kind,
+        });
+    }
+
+    /// Defines a type whose kind is only known later, so that types come before the types they
+    /// contain.
+    fn reserve(&mut self, name: &str) -> usize {
+        self.define(name, TypeKind::Basic(TypeRef::Any));
+        self.types.len() - 1
+    }
+
+    /// Returns the type of the shape and whether it's nullable.
+    fn type_ref_nullable(&mut self, shape: &Shape, hint: &str) -> (TypeRef, bool) {
+        let nullable = shape.kinds.contains_key(&Kind::Null);
+        (self.type_ref(shape, hint), nullable)
+    }
+
+    fn type_ref(&mut self, shape: &Shape, hint: &str) -> TypeRef {
+        let kinds = shape
+            .kinds
+            .keys()
+            .copied()
+            .filter(|kind| *kind != Kind::Null)
+            .collect::<Vec<_>>();
+        match kinds[..] {
+            [] => TypeRef::Any,
+            [kind] => self.kind_ref(kind, shape, hint),
+            _ => {
+                let name = self.unique_name(hint);
+                let index = self.reserve(&name);
+                let members = kinds
+                    .into_iter()
+                    .map(|kind| {
+                        let ty = match self.kind_ref(kind, shape, &format!("{}{:?}", name, kind)) {
+                            TypeRef::Named(ty) => ty,
+                            TypeRef::Bool => "Bool".to_string(),
+                            TypeRef::Int => "Int".to_string(),
+                            TypeRef::Float => "Float".to_string(),
+                            TypeRef::String => "String".to_string(),
+                            TypeRef::Bytes => "Bytes".to_string(),
+                            // Union members need to be named types.
+                            other => {
+                                let ty = self.unique_name(&format!("{}{:?}", name, kind));
+                                self.define(&ty, TypeKind::Basic(other));
+                                ty
+                            }
+                        };
+                        UnionMember { ty, kind }
+                    })
+                    .collect();
+                self.types[index].kind = TypeKind::KindedUnion(members);
+                TypeRef::Named(name)
+            }
+        }
+    }
+
+    fn kind_ref(&mut self, kind: Kind, shape: &Shape, hint: &str) -> TypeRef {
+        match kind {
+            Kind::Null => TypeRef::Any,
+            Kind::Bool => TypeRef::Bool,
+            Kind::Int => TypeRef::Int,
+            Kind::Float => TypeRef::Float,
+            Kind::String => TypeRef::String,
+            Kind::Bytes => TypeRef::Bytes,
+            Kind::Link => TypeRef::Link,
+            Kind::List => {
+                let element = shape.list.as_deref().cloned().unwrap_or_default();
+                let (value, nullable) = self.type_ref_nullable(&element, &format!("{}Item", hint));
+                TypeRef::List {
+                    value: Box::new(value),
+                    nullable,
+                }
+            }
+            Kind::Map => {
+                let map = shape.map.clone().unwrap_or_default();
+                if map.is_struct() {
+                    let name = self.unique_name(hint);
+                    let index = self.reserve(&name);
+                    let fields = map
+                        .fields
+                        .iter()
+                        .map(|(key, field)| {
+                            let field_hint = format!("{}{}", name, type_name(key));
+                            let (ty, nullable) = self.type_ref_nullable(&field.shape, &field_hint);
+                            Field {
+                                name: key.clone(),
+                                ty,
+                                optional: field.count < map.count,
+                                nullable,
+                            }
+                        })
+                        .collect();
+                    self.types[index].kind = TypeKind::Struct(fields);
+                    TypeRef::Named(name)
+                } else {
+                    let mut values = Shape::default();
+                    for field in map.fields.values() {
+                        values.merge(&field.shape);
+                    }
+                    let (value, nullable) =
+                        self.type_ref_nullable(&values, &format!("{}Value", hint));
+                    TypeRef::Map {
+                        value: Box::new(value),
+                        nullable,
+                    }
+                }
+            }
+        }
+    }
+}
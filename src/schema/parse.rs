@@ -0,0 +1,243 @@
+//! Parsing of the schema DSL.
+use std::{error, fmt, iter::Peekable, str::FromStr};
+
+use super::{Field, Kind, Schema, TypeDef, TypeKind, TypeRef, UnionMember};
+
+/// An error when parsing the schema DSL.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    /// The line (starting at 1) the error occurred at.
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at line {}", self.message, self.line)
+    }
+}
+
+impl error::Error for ParseError {}
+
+/// Parses the subset of the schema DSL that [`Schema`] can represent.
+///
+/// Comments are supported, typed links like `&Foo` are treated as `&Any`.
+impl FromStr for Schema {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            tokens: tokenize(s).into_iter().peekable(),
+            line: 1,
+        };
+        let mut types = Vec::new();
+        while parser.tokens.peek().is_some() {
+            types.push(parser.type_def()?);
+        }
+        Ok(Schema { types })
+    }
+}
+
+/// Splits the input into tokens, together with their line number.
+fn tokenize(input: &str) -> Vec<(usize, String)> {
+    let mut tokens = Vec::new();
+    for (index, line) in input.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.split('#').next().unwrap_or_default();
+        let mut current = String::new();
+        for char in line.chars() {
+            if char.is_whitespace() || "{}[]:|&".contains(char) {
+                if !current.is_empty() {
+                    tokens.push((line_number, std::mem::take(&mut current)));
+                }
+                if !char.is_whitespace() {
+                    tokens.push((line_number, char.to_string()));
+                }
+            } else {
+                current.push(char);
+            }
+        }
+        if !current.is_empty() {
+            tokens.push((line_number, current));
+        }
+    }
+    tokens
+}
+
+struct Parser<I: Iterator<Item = (usize, String)>> {
+    tokens: Peekable<I>,
+    line: usize,
+}
+
+impl<I> Parser<I>
+where
+    I: Iterator<Item = (usize, String)>,
+{
+    fn error<T>(&self, message: impl Into<String>) -> Result<T, ParseError> {
+        Err(ParseError {
+            line: self.line,
+            message: message.into(),
+        })
+    }
+
+    fn next(&mut self) -> Result<String, ParseError> {
+        match self.tokens.next() {
+            Some((line, token)) => {
+                self.line = line;
+                Ok(token)
+            }
+            None => self.error("unexpected end of input"),
+        }
+    }
+
+    fn peek(&mut self) -> Option<&str> {
+        self.tokens.peek().map(|(_, token)| token.as_str())
+    }
+
+    fn expect(&mut self, expected: &str) -> Result<(), ParseError> {
+        let token = self.next()?;
+        if token == expected {
+            Ok(())
+        } else {
+            self.error(format!("expected `{}`, found `{}`", expected, token))
+        }
+    }
+
+    fn type_def(&mut self) -> Result<TypeDef, ParseError> {
+        self.expect("type")?;
+        let name = self.next()?;
+        let kind = match self.peek() {
+            Some("struct") => {
+                self.next()?;
+                let fields = self.struct_fields()?;
+                if self.peek() == Some("representation") {
+                    self.next()?;
+                    self.expect("map")?;
+                }
+                TypeKind::Struct(fields)
+            }
+            Some("union") => {
+                self.next()?;
+                let members = self.union_members()?;
+                self.expect("representation")?;
+                let representation = self.next()?;
+                if representation != "kinded" {
+                    return self.error(format!(
+                        "unsupported union representation `{}`",
+                        representation
+                    ));
+                }
+                TypeKind::KindedUnion(members)
+            }
+            Some(keyword @ ("enum" | "unit")) => {
+                let message = format!("unsupported type kind `{}`", keyword);
+                return self.error(message);
+            }
+            _ => TypeKind::Basic(self.type_ref()?),
+        };
+        Ok(TypeDef { name, kind })
+    }
+
+    fn struct_fields(&mut self) -> Result<Vec<Field>, ParseError> {
+        self.expect("{")?;
+        let mut fields = Vec::new();
+        while self.peek() != Some("}") {
+            let name = self.next()?;
+            let mut optional = false;
+            let mut nullable = false;
+            loop {
+                match self.peek() {
+                    Some("optional") => optional = true,
+                    Some("nullable") => nullable = true,
+                    _ => break,
+                }
+                self.next()?;
+            }
+            let ty = self.type_ref()?;
+            fields.push(Field {
+                name,
+                ty,
+                optional,
+                nullable,
+            });
+        }
+        self.expect("}")?;
+        Ok(fields)
+    }
+
+    fn union_members(&mut self) -> Result<Vec<UnionMember>, ParseError> {
+        self.expect("{")?;
+        let mut members = Vec::new();
+        while self.peek() != Some("}") {
+            self.expect("|")?;
+            let ty = self.next()?;
+            let kind = match self.next()?.as_str() {
+                "null" => Kind::Null,
+                "bool" => Kind::Bool,
+                "int" => Kind::Int,
+                "float" => Kind::Float,
+                "string" => Kind::String,
+                "bytes" => Kind::Bytes,
+                "list" => Kind::List,
+                "map" => Kind::Map,
+                "link" => Kind::Link,
+                other => return self.error(format!("unknown kind `{}`", other)),
+            };
+            members.push(UnionMember { ty, kind });
+        }
+        self.expect("}")?;
+        Ok(members)
+    }
+
+    fn nullable(&mut self) -> Result<bool, ParseError> {
+        if self.peek() == Some("nullable") {
+            self.next()?;
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    fn type_ref(&mut self) -> Result<TypeRef, ParseError> {
+        let token = self.next()?;
+        let ty = match token.as_str() {
+            "Bool" => TypeRef::Bool,
+            "Int" => TypeRef::Int,
+            "Float" => TypeRef::Float,
+            "String" => TypeRef::String,
+            "Bytes" => TypeRef::Bytes,
+            "Any" => TypeRef::Any,
+            "Link" => TypeRef::Link,
+            "&" => {
+                // The type of the linked data isn't part of the data.
+                self.next()?;
+                TypeRef::Link
+            }
+            "[" => {
+                let nullable = self.nullable()?;
+                let value = self.type_ref()?;
+                self.expect("]")?;
+                TypeRef::List {
+                    value: Box::new(value),
+                    nullable,
+                }
+            }
+            "{" => {
+                self.expect("String")?;
+                self.expect(":")?;
+                let nullable = self.nullable()?;
+                let value = self.type_ref()?;
+                self.expect("}")?;
+                TypeRef::Map {
+                    value: Box::new(value),
+                    nullable,
+                }
+            }
+            name if name.starts_with(|char: char| char.is_ascii_alphabetic()) => {
+                TypeRef::Named(name.to_string())
+            }
+            other => return self.error(format!("unexpected `{}`", other)),
+        };
+        Ok(ty)
+    }
+}
@@ -0,0 +1,551 @@
+//! Generation of schemas from Rust types.
+use std::{collections::HashMap, error, fmt};
+
+use cid::serde::CID_SERDE_PRIVATE_IDENTIFIER;
+use serde::de::{
+    self, value::BytesDeserializer, DeserializeOwned, DeserializeSeed, IntoDeserializer,
+};
+
+use super::{Field, Schema, TypeDef, TypeKind, TypeRef};
+
+/// The bytes of a valid CID, which is passed on when a link is traced.
+const SAMPLE_CID: [u8; 4] = [0x01, 0x55, 0x00, 0x00];
+
+/// An error when generating a schema from a Rust type.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReflectError {
+    pub message: String,
+}
+
+impl fmt::Display for ReflectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl error::Error for ReflectError {}
+
+/// Generates the schema of a Rust type, as it's encoded by this crate.
+///
+/// The type is traced through its [`Deserialize`](serde::Deserialize) implementation, hence
+/// renamed fields and other serde attributes are taken into account. Structs become struct types
+/// named after the struct, [`Option`] fields are nullable, links become `&Any`. The root type
+/// needs to be a struct and comes first in the schema.
+///
+/// Tracing can't see everything: fields that are skipped when encoding aren't marked as optional,
+/// and enums aren't supported, as the schema model has no representation for them. Types whose
+/// `Deserialize` implementation validates its input, e.g. non-empty strings, fail to be traced.
+///
+/// # Examples
+///
+/// ```
+/// use ipld_core::cid::Cid;
+/// use serde::Deserialize;
+/// use serde_ipld_dagjson::schema::schema_for;
+///
+/// #[derive(Deserialize)]
+/// struct Post {
+///     title: String,
+///     #[serde(rename = "parent")]
+///     previous: Option<Cid>,
+///     comments: Vec<Comment>,
+/// }
+///
+/// #[derive(Deserialize)]
+/// struct Comment {
+///     text: String,
+///     replies: Vec<Comment>,
+/// }
+///
+/// assert_eq!(
+///     schema_for::<Post>().unwrap().to_string(),
+///     "\
+/// type Post struct {
+///   title String
+///   parent nullable &Any
+///   comments [Comment]
+/// }
+///
+/// type Comment struct {
+///   text String
+///   replies [Comment]
+/// }
+/// "
+/// );
+/// ```
+pub fn schema_for<T>() -> Result<Schema, ReflectError>
+where
+    T: DeserializeOwned,
+{
+    let mut state = State::default();
+    loop {
+        state.types.clear();
+        let mut traced = None;
+        let result = T::deserialize(Tracer {
+            state: &mut state,
+            traced: &mut traced,
+            field: None,
+        });
+        let message = match (result, traced) {
+            (
+                Ok(_),
+                Some(Traced {
+                    ty: TypeRef::Named(_),
+                    nullable: false,
+                }),
+            ) => return Ok(Schema { types: state.types }),
+            (Ok(_), Some(traced)) => {
+                format!("the root type needs to be a struct, not `{}`", traced.ty)
+            }
+            (Ok(_), None) => "the type doesn't request any data".to_string(),
+            // Another optional field is known to be recursive, trace again.
+            (Err(TraceError::Restart), _) => continue,
+            (Err(TraceError::Recursive), _) => {
+                "the type is recursive without indirection".to_string()
+            }
+            (Err(TraceError::Failed(message)), _) => message,
+        };
+        return Err(ReflectError { message });
+    }
+}
+
+/// A field of a struct, by the name of the struct and the name of the field.
+type FieldId = (&'static str, &'static str);
+
+/// The types that were found so far.
+#[derive(Default)]
+struct State {
+    types: Vec<TypeDef>,
+    /// The names of the structs that are currently traced.
+    in_progress: Vec<&'static str>,
+    /// The optional fields that lead back to the struct itself, with their type. Their sample
+    /// value is `None`, so that tracing ends.
+    recursive_options: HashMap<FieldId, Traced>,
+}
+
+/// The traced type of a value.
+#[derive(Clone)]
+struct Traced {
+    ty: TypeRef,
+    nullable: bool,
+}
+
+impl Traced {
+    fn new(ty: TypeRef) -> Self {
+        Self {
+            ty,
+            nullable: false,
+        }
+    }
+}
+
+#[derive(Debug)]
+enum TraceError {
+    /// A struct was reached again while tracing itself, the sample value needs to end here.
+    Recursive,
+    /// The tracing needs to start again, as a recursive optional field was found.
+    Restart,
+    Failed(String),
+}
+
+impl fmt::Display for TraceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Recursive => f.write_str("recursive type"),
+            Self::Restart => f.write_str("restart tracing"),
+            Self::Failed(message) => f.write_str(message),
+        }
+    }
+}
+
+impl error::Error for TraceError {}
+
+impl de::Error for TraceError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self::Failed(msg.to_string())
+    }
+}
+
+/// A deserializer, which records the type that is requested and passes a sample value on.
+struct Tracer<'a> {
+    state: &'a mut State,
+    traced: &'a mut Option<Traced>,
+    /// The field whose value is traced, if it's the value of a field.
+    field: Option<FieldId>,
+}
+
+impl<'a> Tracer<'a> {
+    fn record(&mut self, ty: TypeRef) {
+        *self.traced = Some(Traced::new(ty));
+    }
+
+    /// Traces a nested value, returns its type and its sample, if one could be created.
+    fn nested<'de, S>(
+        &mut self,
+        seed: S,
+        field: Option<FieldId>,
+    ) -> Result<(Traced, Option<S::Value>), TraceError>
+    where
+        S: DeserializeSeed<'de>,
+    {
+        let mut traced = None;
+        let result = seed.deserialize(Tracer {
+            state: &mut *self.state,
+            traced: &mut traced,
+            field,
+        });
+        let traced = traced.unwrap_or_else(|| Traced::new(TypeRef::Any));
+        match result {
+            Ok(value) => Ok((traced, Some(value))),
+            // The sample of a recursive type is left out, containers are empty instead.
+            Err(TraceError::Recursive) => Ok((traced, None)),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Traces a list, passing on `len` samples of its elements.
+    fn list<'de, V>(mut self, len: usize, visitor: V) -> Result<V::Value, TraceError>
+    where
+        V: de::Visitor<'de>,
+    {
+        let mut seq = SeqTracer {
+            tracer: Tracer {
+                state: &mut *self.state,
+                traced: &mut None,
+                field: None,
+            },
+            remaining: len,
+            types: Vec::new(),
+        };
+        let value = visitor.visit_seq(&mut seq)?;
+        let value_type = seq.common_type();
+        self.record(TypeRef::List {
+            value: Box::new(value_type.ty),
+            nullable: value_type.nullable,
+        });
+        Ok(value)
+    }
+}
+
+macro_rules! trace_scalar {
+    ($($method:ident => $ty:ident, $visit:ident($($value:expr)?);)*) => {
+        $(
+            fn $method<V>(mut self, visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: de::Visitor<'de>,
+            {
+                self.record(TypeRef::$ty);
+                visitor.$visit($($value)?)
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for Tracer<'_> {
+    type Error = TraceError;
+
+    trace_scalar! {
+        deserialize_bool => Bool, visit_bool(false);
+        deserialize_i8 => Int, visit_i8(0);
+        deserialize_i16 => Int, visit_i16(0);
+        deserialize_i32 => Int, visit_i32(0);
+        deserialize_i64 => Int, visit_i64(0);
+        deserialize_i128 => Int, visit_i128(0);
+        deserialize_u8 => Int, visit_u8(0);
+        deserialize_u16 => Int, visit_u16(0);
+        deserialize_u32 => Int, visit_u32(0);
+        deserialize_u64 => Int, visit_u64(0);
+        deserialize_u128 => Int, visit_u128(0);
+        deserialize_f32 => Float, visit_f32(0.0);
+        deserialize_f64 => Float, visit_f64(0.0);
+        deserialize_char => String, visit_char('a');
+        deserialize_str => String, visit_borrowed_str("");
+        deserialize_string => String, visit_string(String::new());
+        deserialize_identifier => String, visit_borrowed_str("");
+        deserialize_bytes => Bytes, visit_borrowed_bytes(&[]);
+        deserialize_byte_buf => Bytes, visit_byte_buf(Vec::new());
+        deserialize_any => Any, visit_unit();
+        deserialize_ignored_any => Any, visit_unit();
+        deserialize_unit => Any, visit_unit();
+    }
+
+    fn deserialize_unit_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_unit(visitor)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        let recursive = self
+            .field
+            .and_then(|field| self.state.recursive_options.get(&field));
+        if let Some(traced) = recursive {
+            *self.traced = Some(traced.clone());
+            return visitor.visit_none();
+        }
+
+        let mut inner = None;
+        let result = visitor.visit_some(Tracer {
+            state: &mut *self.state,
+            traced: &mut inner,
+            field: None,
+        });
+        let traced = Traced {
+            ty: inner.map_or(TypeRef::Any, |inner| inner.ty),
+            nullable: true,
+        };
+        // The visitor is consumed, the sample can only be `None` when tracing again.
+        if let (Err(TraceError::Recursive), Some(field)) = (&result, self.field) {
+            self.state.recursive_options.insert(field, traced);
+            return Err(TraceError::Restart);
+        }
+        *self.traced = Some(traced);
+        result
+    }
+
+    fn deserialize_newtype_struct<V>(
+        mut self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        if name == CID_SERDE_PRIVATE_IDENTIFIER {
+            self.record(TypeRef::Link);
+            return visitor.visit_newtype_struct(BytesDeserializer::new(&SAMPLE_CID));
+        }
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.list(1, visitor)
+    }
+
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.list(len, visitor)
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_map<V>(mut self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        let mut map = MapTracer {
+            tracer: Tracer {
+                state: self.state,
+                traced: &mut None,
+                field: None,
+            },
+            done: false,
+            value_type: None,
+        };
+        let value = visitor.visit_map(&mut map)?;
+        let value_type = map.value_type.unwrap_or_else(|| Traced::new(TypeRef::Any));
+        self.record(TypeRef::Map {
+            value: Box::new(value_type.ty),
+            nullable: value_type.nullable,
+        });
+        Ok(value)
+    }
+
+    fn deserialize_struct<V>(
+        mut self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.record(TypeRef::Named(name.to_string()));
+        if self.state.in_progress.contains(&name) {
+            return Err(TraceError::Recursive);
+        }
+        let index = self.state.types.iter().position(|def| def.name == name);
+        let index = match index {
+            Some(index) => index,
+            None => {
+                self.state.types.push(TypeDef {
+                    name: name.to_string(),
+                    kind: TypeKind::Struct(Vec::new()),
+                });
+                self.state.types.len() - 1
+            }
+        };
+
+        self.state.in_progress.push(name);
+        let mut access = StructTracer {
+            tracer: Tracer {
+                state: self.state,
+                traced: &mut None,
+                field: None,
+            },
+            name,
+            fields: fields.iter(),
+            current: None,
+            traced_fields: Vec::new(),
+        };
+        let result = visitor.visit_map(&mut access);
+        let traced_fields = access.traced_fields;
+        self.state.in_progress.pop();
+
+        self.state.types[index].kind = TypeKind::Struct(traced_fields);
+        result
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        _variants: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        Err(TraceError::Failed(format!(
+            "enums aren't supported, found `{}`",
+            name
+        )))
+    }
+}
+
+/// Passes samples of elements on to the visitor of a list.
+struct SeqTracer<'a> {
+    tracer: Tracer<'a>,
+    remaining: usize,
+    types: Vec<Traced>,
+}
+
+impl SeqTracer<'_> {
+    /// Returns the type of the elements, `Any` if they differ.
+    fn common_type(&mut self) -> Traced {
+        let mut types = self.types.drain(..);
+        let Some(first) = types.next() else {
+            return Traced::new(TypeRef::Any);
+        };
+        if types.all(|traced| traced.ty == first.ty && traced.nullable == first.nullable) {
+            first
+        } else {
+            Traced::new(TypeRef::Any)
+        }
+    }
+}
+
+impl<'de> de::SeqAccess<'de> for SeqTracer<'_> {
+    type Error = TraceError;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        let (traced, value) = self.tracer.nested(seed, None)?;
+        self.types.push(traced);
+        if value.is_none() {
+            self.remaining = 0;
+        }
+        Ok(value)
+    }
+}
+
+/// Passes a sample entry on to the visitor of a map.
+struct MapTracer<'a> {
+    tracer: Tracer<'a>,
+    done: bool,
+    value_type: Option<Traced>,
+}
+
+impl<'de> de::MapAccess<'de> for MapTracer<'_> {
+    type Error = TraceError;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        if self.done {
+            return Ok(None);
+        }
+        self.done = true;
+        let (_, key) = self.tracer.nested(seed, None)?;
+        Ok(key)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let (traced, value) = self.tracer.nested(seed, None)?;
+        self.value_type = Some(traced);
+        value.ok_or(TraceError::Recursive)
+    }
+}
+
+/// Passes samples of all fields on to the visitor of a struct.
+struct StructTracer<'a> {
+    tracer: Tracer<'a>,
+    name: &'static str,
+    fields: std::slice::Iter<'static, &'static str>,
+    current: Option<&'static str>,
+    traced_fields: Vec<Field>,
+}
+
+impl<'de> de::MapAccess<'de> for StructTracer<'_> {
+    type Error = TraceError;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        let Some(field) = self.fields.next() else {
+            return Ok(None);
+        };
+        self.current = Some(field);
+        seed.deserialize(field.into_deserializer()).map(Some)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        let field = self.current.map(|field| (self.name, field));
+        let (traced, value) = self.tracer.nested(seed, field)?;
+        if let Some(name) = self.current.take() {
+            self.traced_fields.push(Field {
+                name: name.to_string(),
+                ty: traced.ty,
+                optional: false,
+                nullable: traced.nullable,
+            });
+        }
+        value.ok_or(TraceError::Recursive)
+    }
+}
@@ -1,14 +1,31 @@
 //! Serialization.
-use std::{fmt, io};
+use std::{borrow::Cow, cell::RefCell, fmt, mem, rc::Rc};
+#[cfg(feature = "io")]
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+};
 
-use ipld_core::cid::{multibase::Base, serde::CID_SERDE_PRIVATE_IDENTIFIER, Cid};
+use cid::{serde::CID_SERDE_PRIVATE_IDENTIFIER, Cid};
 use serde::{ser, Serialize};
+use serde_json::value::RawValue;
 
+#[cfg(feature = "float")]
+use crate::shared::serialize_float;
+#[cfg(not(feature = "float"))]
+use crate::shared::FLOATS_UNSUPPORTED;
 use crate::{
     error::EncodeError,
-    shared::{ReservedKeyMap, ReservedKeyValue},
+    limits::{LinkPolicy, RejectedLink},
+    shared::{encode_base64, ReservedKeyMap, ReservedKeyValue},
 };
 
+mod ascii;
+mod null;
+
+pub use self::ascii::AsciiFormatter;
+
 /// Serializes a value to a vector.
 pub fn to_vec<T>(value: &T) -> Result<Vec<u8>, EncodeError>
 where
@@ -21,7 +38,55 @@ where
     Ok(writer)
 }
 
+/// Serializes a value to a string.
+///
+/// # Examples
+///
+/// ```
+/// use ipld_core::{cid::Cid, ipld};
+///
+/// let cid: Cid = "bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy".parse().unwrap();
+/// let encoded = serde_ipld_dagjson::to_string(&ipld!({"link": cid})).unwrap();
+/// assert_eq!(
+///     encoded,
+///     r#"{"link":{"/":"bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy"}}"#
+/// );
+/// ```
+pub fn to_string<T>(value: &T) -> Result<String, EncodeError>
+where
+    T: ser::Serialize + ?Sized,
+{
+    into_string(to_vec(value)?)
+}
+
+/// Serializes a value to a string, with newlines and an indentation of two spaces.
+///
+/// The result is meant for humans, e.g. for debugging. It's still valid DAG-JSON, but not in its
+/// canonical form, hence it results in a different CID than [`to_vec`].
+///
+/// # Examples
+///
+/// ```
+/// let encoded = serde_ipld_dagjson::ser::to_string_pretty(&vec![1, 2]).unwrap();
+/// assert_eq!(encoded, "[\n  1,\n  2\n]");
+/// ```
+pub fn to_string_pretty<T>(value: &T) -> Result<String, EncodeError>
+where
+    T: ser::Serialize + ?Sized,
+{
+    let mut writer = Vec::new();
+    let mut json_serializer = serde_json::Serializer::pretty(&mut writer);
+    value.serialize(Serializer::new(&mut json_serializer))?;
+    into_string(writer)
+}
+
+/// Converts the encoded data into a string, `serde_json` only ever writes UTF-8.
+fn into_string(encoded: Vec<u8>) -> Result<String, EncodeError> {
+    String::from_utf8(encoded).map_err(|error| EncodeError::Message(error.to_string()))
+}
+
 /// Serializes a value to a writer.
+#[cfg(feature = "io")]
 pub fn to_writer<W, T>(writer: W, value: &T) -> Result<(), EncodeError>
 where
     W: io::Write,
@@ -32,14 +97,755 @@ where
     Ok(value.serialize(serializer)?)
 }
 
+/// Serializes a value to a writer, with newlines and the given indentation, e.g. `"  "` or `"\t"`.
+///
+/// Like with [`to_string_pretty`], the result is valid DAG-JSON, but not in its canonical form.
+///
+/// # Examples
+///
+/// ```
+/// use ipld_core::ipld;
+///
+/// let mut output = Vec::new();
+/// serde_ipld_dagjson::ser::to_writer_pretty(&mut output, &ipld!({"a": [1]}), "\t").unwrap();
+/// assert_eq!(output, b"{\n\t\"a\": [\n\t\t1\n\t]\n}");
+/// ```
+#[cfg(feature = "io")]
+pub fn to_writer_pretty<W, T>(writer: W, value: &T, indent: &str) -> Result<(), EncodeError>
+where
+    W: io::Write,
+    T: ser::Serialize + ?Sized,
+{
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes());
+    let mut json_serializer = serde_json::Serializer::with_formatter(writer, formatter);
+    Ok(value.serialize(Serializer::new(&mut json_serializer))?)
+}
+
+/// The number of elements after which [`to_writer_seq`] flushes the writer.
+#[cfg(feature = "io")]
+const SEQ_FLUSH_INTERVAL: usize = 1024;
+
+/// Serializes the elements of an iterator as a list to a writer, without collecting them first.
+///
+/// Every element is encoded as soon as the iterator yields it, hence lists of unbounded size can
+/// be written with constant memory. The writer is flushed every 1024 elements and at the end, so
+/// that a consumer on the other end of e.g. a socket can make progress. If encoding an element
+/// fails, the list written so far is incomplete.
+///
+/// # Examples
+///
+/// ```
+/// let mut output = Vec::new();
+/// serde_ipld_dagjson::ser::to_writer_seq(&mut output, (1..=3).map(|i| i * 10)).unwrap();
+/// assert_eq!(output, b"[10,20,30]");
+/// ```
+#[cfg(feature = "io")]
+pub fn to_writer_seq<W, I>(mut writer: W, iter: I) -> Result<(), EncodeError>
+where
+    W: io::Write,
+    I: IntoIterator,
+    I::Item: ser::Serialize,
+{
+    writer.write_all(b"[")?;
+    for (index, element) in iter.into_iter().enumerate() {
+        if index > 0 {
+            writer.write_all(b",")?;
+            if index % SEQ_FLUSH_INTERVAL == 0 {
+                writer.flush()?;
+            }
+        }
+        to_writer(&mut writer, &element)?;
+    }
+    writer.write_all(b"]")?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Serializes a value into [`Bytes`](bytes::Bytes).
+///
+/// The encoded data isn't copied, the buffer is handed over to the returned value.
+#[cfg(feature = "bytes")]
+pub fn to_bytes<T>(value: &T) -> Result<bytes::Bytes, EncodeError>
+where
+    T: ser::Serialize + ?Sized,
+{
+    to_vec(value).map(bytes::Bytes::from)
+}
+
+/// Serializes a value and appends it to the given buffer.
+///
+/// The value is encoded directly into the buffer, which grows as needed. If encoding fails, the
+/// buffer is truncated to its previous length.
+///
+/// # Examples
+///
+/// ```
+/// use bytes::BytesMut;
+///
+/// let mut buf = BytesMut::from(&b"data: "[..]);
+/// serde_ipld_dagjson::ser::encode_into(&mut buf, &[1, 2]).unwrap();
+/// assert_eq!(&buf[..], b"data: [1,2]");
+/// ```
+#[cfg(feature = "bytes")]
+pub fn encode_into<T>(buf: &mut bytes::BytesMut, value: &T) -> Result<(), EncodeError>
+where
+    T: ser::Serialize + ?Sized,
+{
+    use bytes::BufMut;
+
+    let len = buf.len();
+    let mut json_serializer = serde_json::Serializer::new(buf.writer());
+    let result = value.serialize(Serializer::new(&mut json_serializer));
+    if result.is_err() {
+        buf.truncate(len);
+    }
+    Ok(result?)
+}
+
+/// Serializes a value to a vector, applying the given options.
+///
+/// # Examples
+///
+/// ```
+/// use ipld_core::{cid::Cid, ipld};
+/// use serde_ipld_dagjson::{limits::LinkPolicy, ser, EncodeError};
+///
+/// let cid: Cid = "bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy".parse().unwrap();
+/// let options = ser::EncodeOptions::new().link_policy(LinkPolicy::new().allow_codecs([0x0129]));
+/// let result = ser::to_vec_with_options(&ipld!({"files": [cid]}), &options);
+/// assert!(matches!(result, Err(EncodeError::LinkRejected { path, .. }) if path == "files/0"));
+/// ```
+pub fn to_vec_with_options<T>(value: &T, options: &EncodeOptions) -> Result<Vec<u8>, EncodeError>
+where
+    T: ser::Serialize + ?Sized,
+{
+    let mut writer = Vec::new();
+    write_with_options(&mut writer, value, options)?;
+    Ok(writer)
+}
+
+/// Serializes a value to a file, the file is created or truncated.
+///
+/// The file is written through a buffer. If writing fails, e.g. because the process is killed, a
+/// partially written file may remain, see [`to_file_atomic`] for an alternative.
+#[cfg(feature = "io")]
+pub fn to_file<P, T>(path: P, value: &T) -> Result<(), EncodeError>
+where
+    P: AsRef<Path>,
+    T: ser::Serialize + ?Sized,
+{
+    let mut writer = io::BufWriter::new(fs::File::create(path)?);
+    to_writer_with_options(&mut writer, value, &EncodeOptions::default())?;
+    io::Write::flush(&mut writer)?;
+    Ok(())
+}
+
+/// Serializes a value to a file, which is replaced atomically.
+///
+/// The value is written to a temporary file in the same directory first, which is synced to disk
+/// and then renamed to the given path. Readers see either the previous or the complete new file,
+/// never a partially written one. If anything fails, the temporary file is removed and the
+/// previous file stays as it was.
+///
+/// # Examples
+///
+/// ```no_run
+/// use ipld_core::ipld;
+///
+/// serde_ipld_dagjson::ser::to_file_atomic("state.json", &ipld!({"height": 42})).unwrap();
+/// ```
+#[cfg(feature = "io")]
+pub fn to_file_atomic<P, T>(path: P, value: &T) -> Result<(), EncodeError>
+where
+    P: AsRef<Path>,
+    T: ser::Serialize + ?Sized,
+{
+    let path = path.as_ref();
+    let temp_path = temp_path(path)?;
+    let result = write_synced(&temp_path, value).and_then(|()| Ok(fs::rename(&temp_path, path)?));
+    if result.is_err() {
+        // The original error is more useful than a failure to clean up.
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+/// Returns the path of a temporary file next to the given one, which is unique within the
+/// process.
+#[cfg(feature = "io")]
+fn temp_path(path: &Path) -> Result<PathBuf, EncodeError> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let file_name = path.file_name().ok_or_else(|| {
+        EncodeError::Io(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("`{}` isn't a file path", path.display()),
+        ))
+    })?;
+    let mut temp_name = std::ffi::OsString::from(".");
+    temp_name.push(file_name);
+    temp_name.push(format!(
+        ".{}.{}.tmp",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    Ok(path.with_file_name(temp_name))
+}
+
+/// Writes a value to a new file and syncs it to disk.
+#[cfg(feature = "io")]
+fn write_synced<T>(path: &Path, value: &T) -> Result<(), EncodeError>
+where
+    T: ser::Serialize + ?Sized,
+{
+    let file = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(path)?;
+    let mut writer = io::BufWriter::new(file);
+    to_writer_with_options(&mut writer, value, &EncodeOptions::default())?;
+    let file = writer
+        .into_inner()
+        .map_err(io::IntoInnerError::into_error)?;
+    file.sync_all()?;
+    Ok(())
+}
+
+/// Serializes a value to a writer, applying the given options.
+#[cfg(feature = "io")]
+pub fn to_writer_with_options<W, T>(
+    writer: W,
+    value: &T,
+    options: &EncodeOptions,
+) -> Result<(), EncodeError>
+where
+    W: io::Write,
+    T: ser::Serialize + ?Sized,
+{
+    write_with_options(writer, value, options)
+}
+
+/// Serializes a value into a [`serde_json::Value`], with links and bytes in their reserved
+/// `{"/": ...}` forms.
+///
+/// The result can be merged into larger JSON documents before it's encoded. Maps are sorted by
+/// their keys, also when `serde_json` preserves the insertion order, so encoding it with
+/// `serde_json` results in DAG-JSON, as long as nothing else was merged in.
+///
+/// # Examples
+///
+/// ```
+/// use ipld_core::cid::Cid;
+/// use serde_json::json;
+///
+/// let cid: Cid = "bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy".parse().unwrap();
+/// let value = serde_ipld_dagjson::ser::to_json_value(&cid).unwrap();
+/// assert_eq!(value, json!({"/": cid.to_string()}));
+/// ```
+pub fn to_json_value<T>(value: &T) -> Result<serde_json::Value, EncodeError>
+where
+    T: ser::Serialize + ?Sized,
+{
+    let mut json = serialize_with_options(
+        value,
+        serde_json::value::Serializer,
+        &EncodeOptions::default(),
+    )?;
+    sort_objects(&mut json);
+    Ok(json)
+}
+
+/// Sorts the entries of all objects within a JSON value by their keys.
+fn sort_objects(json: &mut serde_json::Value) {
+    match json {
+        serde_json::Value::Array(items) => items.iter_mut().for_each(sort_objects),
+        serde_json::Value::Object(map) => {
+            let mut entries = mem::take(map).into_iter().collect::<Vec<_>>();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+            for (_, value) in &mut entries {
+                sort_objects(value);
+            }
+            *map = entries.into_iter().collect();
+        }
+        _ => {}
+    }
+}
+
+/// Returns all links of a value, in the order they would be encoded.
+///
+/// The value is serialized like by [`to_vec`], but without producing any output. Errors are the
+/// same as when encoding the value.
+///
+/// # Examples
+///
+/// ```
+/// use ipld_core::{cid::Cid, ipld};
+///
+/// let cid: Cid = "bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy".parse().unwrap();
+/// let links = serde_ipld_dagjson::ser::extract_links(&ipld!({"a": [cid, 1, cid]})).unwrap();
+/// assert_eq!(links, [cid, cid]);
+/// ```
+pub fn extract_links<T>(value: &T) -> Result<Vec<Cid>, EncodeError>
+where
+    T: ser::Serialize + ?Sized,
+{
+    let state = Rc::new(EncodeState::new(&EncodeOptions::default()));
+    *state.links.borrow_mut() = Some(Vec::new());
+    traverse(value, &state)?;
+    let links = state.links.take();
+    Ok(links.unwrap_or_default())
+}
+
+/// Checks whether a value can be encoded as DAG-JSON, without producing any output.
+///
+/// This is a cheap pre-flight check, e.g. for values received through an API. It fails where
+/// [`to_vec`] fails, e.g. on floats that aren't finite or invalid links. Unlike [`to_vec`], which
+/// turns map keys like integers into strings, it also rejects all map keys that aren't strings.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::BTreeMap;
+///
+/// use serde_ipld_dagjson::ser::check_encodable;
+///
+/// assert!(check_encodable(&BTreeMap::from([("a", 1)])).is_ok());
+/// assert!(check_encodable(&BTreeMap::from([("a", f64::NAN)])).is_err());
+/// assert!(check_encodable(&BTreeMap::from([(1, 1)])).is_err());
+/// ```
+pub fn check_encodable<T>(value: &T) -> Result<(), EncodeError>
+where
+    T: ser::Serialize + ?Sized,
+{
+    let state = Rc::new(EncodeState::new(&EncodeOptions::default()));
+    traverse(value, &state)?;
+    match state.non_string_keys.take().first() {
+        Some(path) => Err(EncodeError::Message(format!(
+            "map key at path `{}` isn't a string",
+            path
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Serializes a value with all checks of an encode, but without producing any output.
+fn traverse<T>(value: &T, state: &Rc<EncodeState>) -> Result<(), EncodeError>
+where
+    T: ser::Serialize + ?Sized,
+{
+    serialize_with_state(value, null::NullSerializer, Rc::clone(state))
+}
+
+/// Serializes a value to a vector in the canonical form, i.e. with the map keys and struct fields
+/// sorted, see [`EncodeOptions::sort_keys`].
+///
+/// All canonical encodings of this crate, e.g. for signatures, go through this function.
+pub(crate) fn to_canonical_vec<T>(value: &T) -> Result<Vec<u8>, EncodeError>
+where
+    T: ser::Serialize + ?Sized,
+{
+    to_vec_with_options(value, &EncodeOptions::new().sort_keys(true))
+}
+
+/// Encodes the wrapped value in its canonical form, independent of how the rest of the document is
+/// encoded.
+///
+/// The value is encoded into a buffer first, with sorted map keys and no whitespace, which is then
+/// written as it is. This way a single field, e.g. one that is signed, is canonical, even if the
+/// document is written with [`to_writer_pretty`] or in the order of its entries. The buffer is
+/// encoded with the default options, the options of the document don't apply to it.
+///
+/// The buffer is written as a raw JSON value, hence a `Canonical` can only be serialized with
+/// `serde_json`. Deserializing a `Canonical` deserializes the wrapped value.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashMap;
+///
+/// use serde::Serialize;
+/// use serde_ipld_dagjson::ser::Canonical;
+///
+/// #[derive(Serialize)]
+/// struct Envelope {
+///     payload: Canonical<HashMap<&'static str, u32>>,
+/// }
+///
+/// let payload = HashMap::from([("b", 2), ("a", 1)]);
+/// let envelope = Envelope {
+///     payload: Canonical(payload),
+/// };
+/// let encoded = serde_ipld_dagjson::ser::to_string_pretty(&envelope).unwrap();
+/// assert_eq!(encoded, "{\n  \"payload\": {\"a\":1,\"b\":2}\n}");
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Canonical<T>(pub T);
+
+impl<T> Canonical<T> {
+    /// Returns the wrapped value.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> ser::Serialize for Canonical<T>
+where
+    T: ser::Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        let encoded = to_canonical_vec(&self.0).map_err(ser::Error::custom)?;
+        let encoded = String::from_utf8(encoded).map_err(ser::Error::custom)?;
+        serde_json::value::RawValue::from_string(encoded)
+            .map_err(ser::Error::custom)?
+            .serialize(serializer)
+    }
+}
+
+impl<'de, T> serde::Deserialize<'de> for Canonical<T>
+where
+    T: serde::Deserialize<'de>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        T::deserialize(deserializer).map(Canonical)
+    }
+}
+
+/// Serializes a value to a vector and returns the paths of all map keys, which aren't strings.
+///
+/// Such keys are turned into strings by the encoder, e.g. integers.
+#[cfg(feature = "testing")]
+pub(crate) fn to_vec_reporting_keys<T>(value: &T) -> Result<(Vec<u8>, Vec<String>), EncodeError>
+where
+    T: ser::Serialize + ?Sized,
+{
+    let mut writer = Vec::new();
+    let state = Rc::new(EncodeState::new(&EncodeOptions::default()));
+    serialize_with_state(
+        value,
+        &mut serde_json::Serializer::new(&mut writer),
+        Rc::clone(&state),
+    )?;
+    let keys = state.non_string_keys.take();
+    Ok((writer, keys))
+}
+
+/// Serializes a value with `serde_json`, using the formatter the options ask for.
+fn write_with_options<W, T>(
+    writer: W,
+    value: &T,
+    options: &EncodeOptions,
+) -> Result<(), EncodeError>
+where
+    W: std::io::Write,
+    T: ser::Serialize + ?Sized,
+{
+    if options.ascii_only {
+        let formatter = AsciiFormatter::new(serde_json::ser::CompactFormatter);
+        let mut json_serializer = serde_json::Serializer::with_formatter(writer, formatter);
+        serialize_with_options(value, &mut json_serializer, options)
+    } else {
+        serialize_with_options(value, &mut serde_json::Serializer::new(writer), options)
+    }
+}
+
+fn serialize_with_options<T, S>(
+    value: &T,
+    serializer: S,
+    options: &EncodeOptions,
+) -> Result<S::Ok, EncodeError>
+where
+    T: ser::Serialize + ?Sized,
+    S: ser::Serializer,
+    S::Error: Into<EncodeError>,
+{
+    serialize_with_state(value, serializer, Rc::new(EncodeState::new(options)))
+}
+
+fn serialize_with_state<T, S>(
+    value: &T,
+    serializer: S,
+    state: Rc<EncodeState>,
+) -> Result<S::Ok, EncodeError>
+where
+    T: ser::Serialize + ?Sized,
+    S: ser::Serializer,
+    S::Error: Into<EncodeError>,
+{
+    let serializer = Serializer::with_state(serializer, Some(Rc::clone(&state)));
+    // Errors detected by this crate are recorded in the state, as the error type of the underlying
+    // serializer can only carry a message.
+    value
+        .serialize(serializer)
+        .map_err(|error| state.take_error().unwrap_or_else(|| error.into()))
+}
+
+/// Options for encoding DAG-JSON.
+///
+/// By default every value that can be represented in DAG-JSON is encoded.
+#[derive(Clone, Debug, Default)]
+pub struct EncodeOptions {
+    link_policy: LinkPolicy,
+    sort_keys: bool,
+    ascii_only: bool,
+}
+
+impl EncodeOptions {
+    /// Creates new options with the default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only encodes links, which are accepted by the given policy.
+    ///
+    /// A rejected link results in an [`EncodeError::LinkRejected`], which contains the path of the
+    /// link within the encoded value.
+    pub fn link_policy(mut self, policy: LinkPolicy) -> Self {
+        self.link_policy = policy;
+        self
+    }
+
+    /// Whether the keys of maps are sorted, default `false`.
+    ///
+    /// Maps are encoded in the order their entries are serialized in. For e.g. a `HashMap` this
+    /// order differs between runs, hence the same value results in different bytes and a
+    /// different CID. With this option the entries of maps and the fields of structs are encoded
+    /// into a buffer each and written in the canonical order of their keys, i.e. sorted by their
+    /// UTF-8 bytes. The result is the canonical form of the value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    ///
+    /// use serde_ipld_dagjson::ser::{to_vec_with_options, EncodeOptions};
+    ///
+    /// let map: HashMap<_, _> = (0..10).map(|i| (format!("key{}", i), i)).collect();
+    /// let options = EncodeOptions::new().sort_keys(true);
+    /// let encoded = to_vec_with_options(&map, &options).unwrap();
+    /// assert!(encoded.starts_with(br#"{"key0":0,"key1":1,"key2":2"#));
+    /// ```
+    pub fn sort_keys(mut self, sort: bool) -> Self {
+        self.sort_keys = sort;
+        self
+    }
+
+    /// Whether all non-ASCII characters are escaped as `\uXXXX` sequences, default `false`.
+    ///
+    /// This applies to strings and map keys, see [`AsciiFormatter`] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use serde_ipld_dagjson::ser::{to_vec_with_options, EncodeOptions};
+    ///
+    /// let options = EncodeOptions::new().ascii_only(true);
+    /// let encoded = to_vec_with_options(&"crème brûlée 🍮", &options).unwrap();
+    /// assert_eq!(encoded, br#""cr\u00e8me br\u00fbl\u00e9e \ud83c\udf6e""#);
+    /// ```
+    pub fn ascii_only(mut self, ascii_only: bool) -> Self {
+        self.ascii_only = ascii_only;
+        self
+    }
+}
+
+/// State that is shared across all layers of a single encode.
+#[derive(Debug)]
+struct EncodeState {
+    options: EncodeOptions,
+    /// The path segments of the value that is currently encoded.
+    path: RefCell<Vec<String>>,
+    /// The paths of the map keys, which aren't strings.
+    non_string_keys: RefCell<Vec<String>>,
+    /// The encoded links, if they are collected.
+    links: RefCell<Option<Vec<Cid>>>,
+    error: RefCell<Option<EncodeError>>,
+}
+
+impl EncodeState {
+    fn new(options: &EncodeOptions) -> Self {
+        Self {
+            options: options.clone(),
+            path: RefCell::new(Vec::new()),
+            non_string_keys: RefCell::new(Vec::new()),
+            links: RefCell::new(None),
+            error: RefCell::new(None),
+        }
+    }
+
+    fn push(&self, segment: String) {
+        self.path.borrow_mut().push(segment);
+    }
+
+    fn pop(&self) {
+        self.path.borrow_mut().pop();
+    }
+
+    fn check_link<E>(&self, cid: &Cid) -> Result<(), E>
+    where
+        E: ser::Error,
+    {
+        self.options
+            .link_policy
+            .check(cid)
+            .map_err(|reason| self.fail(reason))?;
+        if let Some(links) = self.links.borrow_mut().as_mut() {
+            links.push(*cid);
+        }
+        Ok(())
+    }
+
+    fn fail<E>(&self, reason: RejectedLink) -> E
+    where
+        E: ser::Error,
+    {
+        let error = EncodeError::LinkRejected {
+            path: self.path.borrow().join("/"),
+            reason,
+        };
+        let message = error.to_string();
+        *self.error.borrow_mut() = Some(error.traced());
+        E::custom(message)
+    }
+
+    fn take_error(&self) -> Option<EncodeError> {
+        self.error.borrow_mut().take()
+    }
+
+    /// Returns the path segment of a map key and records keys, which aren't strings.
+    ///
+    /// Keys are checked with the rules of `serde_json`, see [`KeySerializer`].
+    fn key_segment<K, E>(&self, key: &K) -> Result<String, E>
+    where
+        K: ser::Serialize + ?Sized,
+        E: ser::Error,
+    {
+        match key.serialize(KeySerializer).map_err(E::custom)? {
+            MapKey::String(key) => Ok(key),
+            MapKey::Integer(segment) => {
+                let mut path = self.path.borrow().clone();
+                path.push(segment.clone());
+                self.non_string_keys.borrow_mut().push(path.join("/"));
+                Ok(segment)
+            }
+        }
+    }
+}
+
+/// Calls `f` with the given segment appended to the path, if the path is tracked.
+fn within<F, R>(state: Option<&Rc<EncodeState>>, segment: impl FnOnce() -> String, f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    match state {
+        Some(state) => {
+            state.push(segment());
+            let result = f();
+            state.pop();
+            result
+        }
+        None => f(),
+    }
+}
+
+/// Encoded entries of a map or struct, with their keys.
+type Entries = Vec<(Cow<'static, str>, Box<RawValue>)>;
+
 pub struct Serializer<S> {
     ser: S,
+    state: Option<Rc<EncodeState>>,
+    /// The index of the next element, if this is a sequence.
+    index: usize,
+    /// The path segment of the next value, if this is a map.
+    key: Option<String>,
+    /// The buffered, already encoded entries, if this is a map or struct whose keys are sorted.
+    entries: Option<Entries>,
 }
 
 impl<S> Serializer<S> {
     pub fn new(serializer: S) -> Self {
-        Self { ser: serializer }
+        Self::with_state(serializer, None)
+    }
+
+    fn with_state(serializer: S, state: Option<Rc<EncodeState>>) -> Self {
+        Self {
+            ser: serializer,
+            state,
+            index: 0,
+            key: None,
+            entries: None,
+        }
+    }
+
+    /// Returns the path segment of the next element of a sequence.
+    fn next_index(&mut self) -> impl FnOnce() -> String {
+        let index = self.index;
+        self.index += 1;
+        move || index.to_string()
+    }
+
+    /// Starts buffering the entries, if the keys are sorted.
+    fn sorted(mut self, len: usize) -> Self {
+        if let Some(state) = &self.state {
+            if state.options.sort_keys {
+                self.entries = Some(Vec::with_capacity(len));
+            }
+        }
+        self
     }
+
+    /// Encodes the value of an entry and buffers it, returns `false` if the keys aren't sorted.
+    fn buffer<T, E>(&mut self, key: Cow<'static, str>, value: &T) -> Result<bool, E>
+    where
+        T: ?Sized + ser::Serialize,
+        E: ser::Error,
+    {
+        let (Some(entries), Some(state)) = (&mut self.entries, &self.state) else {
+            return Ok(false);
+        };
+        let encoded = within(Some(state), || key.to_string(), || encode_raw(state, value))?;
+        entries.push((key, encoded));
+        Ok(true)
+    }
+
+    /// Returns the buffered entries, sorted by their keys.
+    fn sorted_entries<E>(&mut self) -> Result<Entries, E>
+    where
+        E: ser::Error,
+    {
+        let mut entries = self.entries.take().unwrap_or_default();
+        entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+        if entries.windows(2).any(|pair| pair[0].0 == pair[1].0) {
+            return Err(ser::Error::custom("Duplicate map key"));
+        }
+        Ok(entries)
+    }
+}
+
+/// Encodes a value into a buffer, with the same state and formatting as the rest of the encode.
+fn encode_raw<T, E>(state: &Rc<EncodeState>, value: &T) -> Result<Box<RawValue>, E>
+where
+    T: ?Sized + ser::Serialize,
+    E: ser::Error,
+{
+    let mut buffer = Vec::new();
+    let state = Some(Rc::clone(state));
+    let result = if state.as_ref().is_some_and(|state| state.options.ascii_only) {
+        let formatter = AsciiFormatter::new(serde_json::ser::CompactFormatter);
+        let mut json_serializer = serde_json::Serializer::with_formatter(&mut buffer, formatter);
+        value.serialize(Serializer::with_state(&mut json_serializer, state))
+    } else {
+        let mut json_serializer = serde_json::Serializer::new(&mut buffer);
+        value.serialize(Serializer::with_state(&mut json_serializer, state))
+    };
+    result.map_err(ser::Error::custom)?;
+    let encoded = String::from_utf8(buffer).map_err(ser::Error::custom)?;
+    RawValue::from_string(encoded).map_err(ser::Error::custom)
 }
 
 impl<S> ser::Serializer for Serializer<S>
@@ -101,6 +907,7 @@ where
         self.ser.serialize_u128(v)
     }
 
+    #[cfg(feature = "float")]
     fn serialize_f32(self, v: f32) -> Result<Self::Ok, Self::Error> {
         // In DAG-JSON only finite floats are supported.
         if !v.is_finite() {
@@ -108,10 +915,16 @@ where
                 "Float must be a finite number, not Infinity or NaN".to_string(),
             ))
         } else {
-            self.ser.serialize_f32(v)
+            serialize_float(self.ser, v)
         }
     }
 
+    #[cfg(not(feature = "float"))]
+    fn serialize_f32(self, _v: f32) -> Result<Self::Ok, Self::Error> {
+        Err(ser::Error::custom(FLOATS_UNSUPPORTED))
+    }
+
+    #[cfg(feature = "float")]
     fn serialize_f64(self, v: f64) -> Result<Self::Ok, Self::Error> {
         // In DAG-JSON only finite floats are supported.
         if !v.is_finite() {
@@ -119,10 +932,15 @@ where
                 "Float must be a finite number, not Infinity or NaN".to_string(),
             ))
         } else {
-            self.ser.serialize_f64(v)
+            serialize_float(self.ser, v)
         }
     }
 
+    #[cfg(not(feature = "float"))]
+    fn serialize_f64(self, _v: f64) -> Result<Self::Ok, Self::Error> {
+        Err(ser::Error::custom(FLOATS_UNSUPPORTED))
+    }
+
     fn serialize_char(self, v: char) -> Result<Self::Ok, Self::Error> {
         self.ser.serialize_char(v)
     }
@@ -134,7 +952,7 @@ where
     fn serialize_bytes(self, v: &[u8]) -> Result<Self::Ok, Self::Error> {
         let bytes = ReservedKeyMap {
             _slash: ReservedKeyValue::Bytes {
-                bytes: Base::Base64.encode(v),
+                bytes: encode_base64(v),
             },
         };
         bytes.serialize(self.ser)
@@ -148,7 +966,8 @@ where
     where
         T: ?Sized + ser::Serialize,
     {
-        self.ser.serialize_some(&SerializeRef::new(value))
+        self.ser
+            .serialize_some(&SerializeRef::with_state(value, self.state.as_ref()))
     }
 
     fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
@@ -178,10 +997,12 @@ where
         T: ?Sized + ser::Serialize,
     {
         if name == CID_SERDE_PRIVATE_IDENTIFIER {
-            value.serialize(CidSerializer(self.ser))
+            value.serialize(CidSerializer(self.ser, self.state))
         } else {
-            self.ser
-                .serialize_newtype_struct(name, &SerializeRef::new(value))
+            self.ser.serialize_newtype_struct(
+                name,
+                &SerializeRef::with_state(value, self.state.as_ref()),
+            )
         }
     }
 
@@ -195,16 +1016,33 @@ where
     where
         T: ?Sized + ser::Serialize,
     {
-        self.ser
-            .serialize_newtype_variant(name, variant_index, variant, &SerializeRef::new(value))
+        let state = self.state.as_ref();
+        within(
+            state,
+            || variant.to_string(),
+            || {
+                self.ser.serialize_newtype_variant(
+                    name,
+                    variant_index,
+                    variant,
+                    &SerializeRef::with_state(value, state),
+                )
+            },
+        )
     }
 
     fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
-        Ok(Self::SerializeSeq::new(self.ser.serialize_seq(len)?))
+        Ok(Self::SerializeSeq::with_state(
+            self.ser.serialize_seq(len)?,
+            self.state,
+        ))
     }
 
     fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Self::Error> {
-        Ok(Self::SerializeTuple::new(self.ser.serialize_tuple(len)?))
+        Ok(Self::SerializeTuple::with_state(
+            self.ser.serialize_tuple(len)?,
+            self.state,
+        ))
     }
 
     fn serialize_tuple_struct(
@@ -212,8 +1050,9 @@ where
         name: &'static str,
         len: usize,
     ) -> Result<Self::SerializeTupleStruct, Self::Error> {
-        Ok(Self::SerializeTupleStruct::new(
+        Ok(Self::SerializeTupleStruct::with_state(
             self.ser.serialize_tuple_struct(name, len)?,
+            self.state,
         ))
     }
 
@@ -224,14 +1063,21 @@ where
         variant: &'static str,
         len: usize,
     ) -> Result<Self::SerializeTupleVariant, Self::Error> {
-        Ok(Self::SerializeTupleVariant::new(
-            self.ser
-                .serialize_tuple_variant(name, variant_index, variant, len)?,
-        ))
+        let ser = self
+            .ser
+            .serialize_tuple_variant(name, variant_index, variant, len)?;
+        // The variant is part of the path until the end of the variant.
+        if let Some(state) = &self.state {
+            state.push(variant.to_string());
+        }
+        Ok(Self::SerializeTupleVariant::with_state(ser, self.state))
     }
 
     fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
-        Ok(Self::SerializeMap::new(self.ser.serialize_map(len)?))
+        Ok(
+            Self::SerializeMap::with_state(self.ser.serialize_map(len)?, self.state)
+                .sorted(len.unwrap_or(0)),
+        )
     }
 
     fn serialize_struct(
@@ -239,9 +1085,13 @@ where
         name: &'static str,
         len: usize,
     ) -> Result<Self::SerializeStruct, Self::Error> {
-        Ok(Self::SerializeStruct::new(
-            self.ser.serialize_struct(name, len)?,
-        ))
+        let serializer =
+            Self::SerializeStruct::with_state(self.ser.serialize_struct(name, len)?, self.state);
+        // Raw values, e.g. of `Canonical`, are written as they are.
+        if name == RAW_VALUE_TOKEN {
+            return Ok(serializer);
+        }
+        Ok(serializer.sorted(len))
     }
 
     fn serialize_struct_variant(
@@ -251,10 +1101,14 @@ where
         variant: &'static str,
         len: usize,
     ) -> Result<Self::SerializeStructVariant, Self::Error> {
-        Ok(Self::SerializeStructVariant::new(
-            self.ser
-                .serialize_struct_variant(name, variant_index, variant, len)?,
-        ))
+        let ser = self
+            .ser
+            .serialize_struct_variant(name, variant_index, variant, len)?;
+        // The variant is part of the path until the end of the variant.
+        if let Some(state) = &self.state {
+            state.push(variant.to_string());
+        }
+        Ok(Self::SerializeStructVariant::with_state(ser, self.state).sorted(len))
     }
 
     fn collect_seq<I>(self, iter: I) -> Result<Self::Ok, Self::Error>
@@ -262,6 +1116,15 @@ where
         I: IntoIterator,
         I::Item: ser::Serialize,
     {
+        // Tracking the path needs the individual elements.
+        if self.state.is_some() {
+            let iter = iter.into_iter();
+            let mut seq = ser::Serializer::serialize_seq(self, exact_len(&iter))?;
+            for item in iter {
+                ser::SerializeSeq::serialize_element(&mut seq, &item)?;
+            }
+            return ser::SerializeSeq::end(seq);
+        }
         let iter = iter.into_iter().map(SerializeSized::new);
         self.ser.collect_seq(iter)
     }
@@ -272,6 +1135,14 @@ where
         V: ser::Serialize,
         I: IntoIterator<Item = (K, V)>,
     {
+        if self.state.is_some() {
+            let iter = iter.into_iter();
+            let mut map = ser::Serializer::serialize_map(self, exact_len(&iter))?;
+            for (key, value) in iter {
+                ser::SerializeMap::serialize_entry(&mut map, &key, &value)?;
+            }
+            return ser::SerializeMap::end(map);
+        }
         let iter = iter
             .into_iter()
             .map(|(k, v)| (SerializeSized::new(k), SerializeSized::new(v)));
@@ -290,13 +1161,45 @@ where
     }
 }
 
-struct SerializeRef<'a, T: ?Sized> {
+/// Returns the length of an iterator, if it's known exactly.
+fn exact_len<I: Iterator>(iter: &I) -> Option<usize> {
+    match iter.size_hint() {
+        (lower, Some(upper)) if lower == upper => Some(lower),
+        _ => None,
+    }
+}
+
+/// A reference to a value, which is serialized with the DAG-JSON semantics, whatever serializer it
+/// is passed to.
+///
+/// Bytes are serialized as `{"/": {"bytes": "..."}}` and links as `{"/": "..."}`. This makes it
+/// possible to embed a value with the DAG-JSON semantics into another format layer, e.g. a
+/// serializer that encrypts the fields of an envelope. Values are serialized without
+/// [options](EncodeOptions).
+///
+/// # Examples
+///
+/// ```
+/// use serde_bytes::ByteBuf;
+/// use serde_ipld_dagjson::ser::SerializeRef;
+///
+/// let bytes = ByteBuf::from(vec![1, 2]);
+/// let value = serde_json::to_value(SerializeRef::new(&bytes)).unwrap();
+/// assert_eq!(value, serde_json::json!({"/": {"bytes": "AQI"}}));
+/// ```
+pub struct SerializeRef<'a, T: ?Sized> {
     value: &'a T,
+    state: Option<&'a Rc<EncodeState>>,
 }
 
 impl<'a, T: ?Sized> SerializeRef<'a, T> {
-    fn new(value: &'a T) -> Self {
-        Self { value }
+    /// Wraps a reference to the given value.
+    pub fn new(value: &'a T) -> Self {
+        Self::with_state(value, None)
+    }
+
+    fn with_state(value: &'a T, state: Option<&'a Rc<EncodeState>>) -> Self {
+        Self { value, state }
     }
 }
 
@@ -308,7 +1211,10 @@ where
     where
         S: ser::Serializer,
     {
-        ser::Serialize::serialize(self.value, Serializer::new(serializer))
+        ser::Serialize::serialize(
+            self.value,
+            Serializer::with_state(serializer, self.state.cloned()),
+        )
     }
 }
 
@@ -345,7 +1251,12 @@ where
     where
         T: ?Sized + ser::Serialize,
     {
-        self.ser.serialize_element(&SerializeRef::new(value))
+        let index = self.next_index();
+        let state = self.state.as_ref();
+        within(state, index, || {
+            self.ser
+                .serialize_element(&SerializeRef::with_state(value, state))
+        })
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
@@ -364,7 +1275,12 @@ where
     where
         T: ?Sized + ser::Serialize,
     {
-        self.ser.serialize_element(&SerializeRef::new(value))
+        let index = self.next_index();
+        let state = self.state.as_ref();
+        within(state, index, || {
+            self.ser
+                .serialize_element(&SerializeRef::with_state(value, state))
+        })
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
@@ -383,7 +1299,12 @@ where
     where
         T: ?Sized + ser::Serialize,
     {
-        self.ser.serialize_field(&SerializeRef::new(value))
+        let index = self.next_index();
+        let state = self.state.as_ref();
+        within(state, index, || {
+            self.ser
+                .serialize_field(&SerializeRef::with_state(value, state))
+        })
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
@@ -402,10 +1323,18 @@ where
     where
         T: ?Sized + ser::Serialize,
     {
-        self.ser.serialize_field(&SerializeRef::new(value))
+        let index = self.next_index();
+        let state = self.state.as_ref();
+        within(state, index, || {
+            self.ser
+                .serialize_field(&SerializeRef::with_state(value, state))
+        })
     }
 
     fn end(self) -> Result<Self::Ok, Self::Error> {
+        if let Some(state) = &self.state {
+            state.pop();
+        }
         self.ser.end()
     }
 }
@@ -421,17 +1350,40 @@ where
     where
         T: ?Sized + ser::Serialize,
     {
-        self.ser.serialize_key(&SerializeRef::new(key))
+        if let Some(state) = &self.state {
+            self.key = Some(state.key_segment(key)?);
+        }
+        if self.entries.is_some() {
+            return Ok(());
+        }
+        self.ser
+            .serialize_key(&SerializeRef::with_state(key, self.state.as_ref()))
     }
 
     fn serialize_value<T>(&mut self, value: &T) -> Result<(), Self::Error>
     where
         T: ?Sized + ser::Serialize,
     {
-        self.ser.serialize_value(&SerializeRef::new(value))
+        let key = self.key.take().unwrap_or_default();
+        if self.entries.is_some() {
+            self.buffer(Cow::Owned(key), value)?;
+            return Ok(());
+        }
+        let state = self.state.as_ref();
+        within(
+            state,
+            || key,
+            || {
+                self.ser
+                    .serialize_value(&SerializeRef::with_state(value, state))
+            },
+        )
     }
 
-    fn end(self) -> Result<Self::Ok, Self::Error> {
+    fn end(mut self) -> Result<Self::Ok, Self::Error> {
+        for (key, value) in self.sorted_entries()? {
+            self.ser.serialize_entry(&key, &value)?;
+        }
         self.ser.end()
     }
 
@@ -440,8 +1392,22 @@ where
         K: ?Sized + ser::Serialize,
         V: ?Sized + ser::Serialize,
     {
-        self.ser
-            .serialize_entry(&SerializeRef::new(key), &SerializeRef::new(value))
+        if self.entries.is_some() {
+            self.serialize_key(key)?;
+            return self.serialize_value(value);
+        }
+        let state = self.state.as_ref();
+        let segment = state.map(|state| state.key_segment(key)).transpose()?;
+        within(
+            state,
+            || segment.unwrap_or_default(),
+            || {
+                self.ser.serialize_entry(
+                    &SerializeRef::with_state(key, state),
+                    &SerializeRef::with_state(value, state),
+                )
+            },
+        )
     }
 }
 
@@ -456,10 +1422,24 @@ where
     where
         T: ?Sized + ser::Serialize,
     {
-        self.ser.serialize_field(key, &SerializeRef::new(value))
+        if self.buffer(Cow::Borrowed(key), value)? {
+            return Ok(());
+        }
+        let state = self.state.as_ref();
+        within(
+            state,
+            || key.to_string(),
+            || {
+                self.ser
+                    .serialize_field(key, &SerializeRef::with_state(value, state))
+            },
+        )
     }
 
-    fn end(self) -> Result<Self::Ok, Self::Error> {
+    fn end(mut self) -> Result<Self::Ok, Self::Error> {
+        for (key, value) in self.sorted_entries()? {
+            self.ser.serialize_field(static_key(key), &value)?;
+        }
         self.ser.end()
     }
 
@@ -479,10 +1459,27 @@ where
     where
         T: ?Sized + ser::Serialize,
     {
-        self.ser.serialize_field(key, &SerializeRef::new(value))
+        if self.buffer(Cow::Borrowed(key), value)? {
+            return Ok(());
+        }
+        let state = self.state.as_ref();
+        within(
+            state,
+            || key.to_string(),
+            || {
+                self.ser
+                    .serialize_field(key, &SerializeRef::with_state(value, state))
+            },
+        )
     }
 
-    fn end(self) -> Result<Self::Ok, Self::Error> {
+    fn end(mut self) -> Result<Self::Ok, Self::Error> {
+        if let Some(state) = &self.state {
+            state.pop();
+        }
+        for (key, value) in self.sorted_entries()? {
+            self.ser.serialize_field(static_key(key), &value)?;
+        }
         self.ser.end()
     }
 
@@ -491,8 +1488,19 @@ where
     }
 }
 
+/// The struct name `serde_json` uses for a [`RawValue`].
+const RAW_VALUE_TOKEN: &str = "$serde_json::private::RawValue";
+
+/// Returns the key of a buffered struct field, which is always the static field name.
+fn static_key(key: Cow<'static, str>) -> &'static str {
+    match key {
+        Cow::Borrowed(key) => key,
+        Cow::Owned(_) => unreachable!("struct fields have static names"),
+    }
+}
+
 /// Serializing a CID correctly as DAG-JSON.
-struct CidSerializer<S>(S);
+struct CidSerializer<S>(S, Option<Rc<EncodeState>>);
 
 impl<S> ser::Serializer for CidSerializer<S>
 where
@@ -551,6 +1559,9 @@ where
 
     fn serialize_bytes(self, value: &[u8]) -> Result<Self::Ok, Self::Error> {
         let cid = Cid::try_from(value).map_err(|_| ser::Error::custom("Invalid CID"))?;
+        if let Some(state) = &self.1 {
+            state.check_link(&cid)?;
+        }
         let cid_json = ReservedKeyMap {
             _slash: ReservedKeyValue::Cid(cid.to_string()),
         };
@@ -639,3 +1650,170 @@ where
         Err(ser::Error::custom("unreachable"))
     }
 }
+
+/// A map key, as serialized by the [`KeySerializer`].
+enum MapKey {
+    String(String),
+    /// An integer, which is encoded as string.
+    Integer(String),
+}
+
+/// Serializes map keys with the rules of `serde_json`: strings, chars and integers are accepted,
+/// everything else is an error.
+///
+/// The keys are checked with it before the entries are buffered for sorting, so that the sorted
+/// and the unsorted encoding accept the same keys.
+struct KeySerializer;
+
+impl KeySerializer {
+    fn key_must_be_a_string() -> serde_json::Error {
+        ser::Error::custom("key must be a string")
+    }
+
+    fn integer(value: impl fmt::Display) -> Result<MapKey, serde_json::Error> {
+        Ok(MapKey::Integer(value.to_string()))
+    }
+}
+
+impl ser::Serializer for KeySerializer {
+    type Ok = MapKey;
+    type Error = serde_json::Error;
+
+    type SerializeSeq = ser::Impossible<Self::Ok, Self::Error>;
+    type SerializeTuple = ser::Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleStruct = ser::Impossible<Self::Ok, Self::Error>;
+    type SerializeTupleVariant = ser::Impossible<Self::Ok, Self::Error>;
+    type SerializeMap = ser::Impossible<Self::Ok, Self::Error>;
+    type SerializeStruct = ser::Impossible<Self::Ok, Self::Error>;
+    type SerializeStructVariant = ser::Impossible<Self::Ok, Self::Error>;
+
+    fn serialize_bool(self, _value: bool) -> Result<Self::Ok, Self::Error> {
+        Err(Self::key_must_be_a_string())
+    }
+    fn serialize_i8(self, value: i8) -> Result<Self::Ok, Self::Error> {
+        Self::integer(value)
+    }
+    fn serialize_i16(self, value: i16) -> Result<Self::Ok, Self::Error> {
+        Self::integer(value)
+    }
+    fn serialize_i32(self, value: i32) -> Result<Self::Ok, Self::Error> {
+        Self::integer(value)
+    }
+    fn serialize_i64(self, value: i64) -> Result<Self::Ok, Self::Error> {
+        Self::integer(value)
+    }
+    fn serialize_i128(self, value: i128) -> Result<Self::Ok, Self::Error> {
+        Self::integer(value)
+    }
+    fn serialize_u8(self, value: u8) -> Result<Self::Ok, Self::Error> {
+        Self::integer(value)
+    }
+    fn serialize_u16(self, value: u16) -> Result<Self::Ok, Self::Error> {
+        Self::integer(value)
+    }
+    fn serialize_u32(self, value: u32) -> Result<Self::Ok, Self::Error> {
+        Self::integer(value)
+    }
+    fn serialize_u64(self, value: u64) -> Result<Self::Ok, Self::Error> {
+        Self::integer(value)
+    }
+    fn serialize_u128(self, value: u128) -> Result<Self::Ok, Self::Error> {
+        Self::integer(value)
+    }
+    fn serialize_f32(self, _value: f32) -> Result<Self::Ok, Self::Error> {
+        Err(Self::key_must_be_a_string())
+    }
+    fn serialize_f64(self, _value: f64) -> Result<Self::Ok, Self::Error> {
+        Err(Self::key_must_be_a_string())
+    }
+    fn serialize_char(self, value: char) -> Result<Self::Ok, Self::Error> {
+        Ok(MapKey::String(value.to_string()))
+    }
+    fn serialize_str(self, value: &str) -> Result<Self::Ok, Self::Error> {
+        Ok(MapKey::String(value.to_string()))
+    }
+    fn serialize_bytes(self, _value: &[u8]) -> Result<Self::Ok, Self::Error> {
+        Err(Self::key_must_be_a_string())
+    }
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        Err(Self::key_must_be_a_string())
+    }
+    fn serialize_some<T: ?Sized + ser::Serialize>(
+        self,
+        _value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        Err(Self::key_must_be_a_string())
+    }
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        Err(Self::key_must_be_a_string())
+    }
+    fn serialize_unit_struct(self, _name: &str) -> Result<Self::Ok, Self::Error> {
+        Err(Self::key_must_be_a_string())
+    }
+    fn serialize_unit_variant(
+        self,
+        _name: &str,
+        _variant_index: u32,
+        variant: &str,
+    ) -> Result<Self::Ok, Self::Error> {
+        Ok(MapKey::String(variant.to_string()))
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + ser::Serialize>(
+        self,
+        _name: &str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        value.serialize(self)
+    }
+    fn serialize_newtype_variant<T: ?Sized + ser::Serialize>(
+        self,
+        _name: &str,
+        _variant_index: u32,
+        _variant: &str,
+        _value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        Err(Self::key_must_be_a_string())
+    }
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Err(Self::key_must_be_a_string())
+    }
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        Err(Self::key_must_be_a_string())
+    }
+    fn serialize_tuple_struct(
+        self,
+        _name: &str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        Err(Self::key_must_be_a_string())
+    }
+    fn serialize_tuple_variant(
+        self,
+        _name: &str,
+        _variant_index: u32,
+        _variant: &str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Err(Self::key_must_be_a_string())
+    }
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Err(Self::key_must_be_a_string())
+    }
+    fn serialize_struct(
+        self,
+        _name: &str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Err(Self::key_must_be_a_string())
+    }
+    fn serialize_struct_variant(
+        self,
+        _name: &str,
+        _variant_index: u32,
+        _variant: &str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Err(Self::key_must_be_a_string())
+    }
+}
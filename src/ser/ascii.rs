@@ -0,0 +1,136 @@
+//! Encoding with only ASCII characters.
+use std::io;
+
+use serde_json::ser::{CharEscape, CompactFormatter, Formatter};
+
+/// A [`Formatter`] that escapes all non-ASCII characters of strings and raw values as `\uXXXX` sequences.
+///
+/// The output survives transports that aren't UTF-8 clean, e.g. latin-1 channels or legacy log
+/// systems. It decodes to the same data, but isn't in the canonical form, hence it results in a
+/// different CID. All other formatting, e.g. indentation, is done by the wrapped formatter.
+///
+/// It's used by [`EncodeOptions::ascii_only`](crate::ser::EncodeOptions::ascii_only), it can also
+/// be combined with other formatters directly:
+///
+/// ```
+/// use serde::Serialize;
+/// use serde_ipld_dagjson::{ser::AsciiFormatter, Serializer};
+/// use serde_json::ser::PrettyFormatter;
+///
+/// let mut output = Vec::new();
+/// let formatter = AsciiFormatter::new(PrettyFormatter::new());
+/// let mut json_serializer = serde_json::Serializer::with_formatter(&mut output, formatter);
+/// vec!["größe"].serialize(Serializer::new(&mut json_serializer)).unwrap();
+/// assert_eq!(output, b"[\n  \"gr\\u00f6\\u00dfe\"\n]");
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct AsciiFormatter<F = CompactFormatter> {
+    inner: F,
+}
+
+impl<F> AsciiFormatter<F> {
+    /// Creates a new formatter, which wraps the given one.
+    pub fn new(inner: F) -> Self {
+        Self { inner }
+    }
+}
+
+/// Forwards methods to the wrapped formatter.
+macro_rules! delegate {
+    ($($method:ident($($arg:ident: $ty:ty),*);)*) => {
+        $(
+            fn $method<W>(&mut self, writer: &mut W, $($arg: $ty),*) -> io::Result<()>
+            where
+                W: ?Sized + io::Write,
+            {
+                self.inner.$method(writer, $($arg),*)
+            }
+        )*
+    };
+}
+
+impl<F> Formatter for AsciiFormatter<F>
+where
+    F: Formatter,
+{
+    delegate! {
+        write_null();
+        write_bool(value: bool);
+        write_i8(value: i8);
+        write_i16(value: i16);
+        write_i32(value: i32);
+        write_i64(value: i64);
+        write_i128(value: i128);
+        write_u8(value: u8);
+        write_u16(value: u16);
+        write_u32(value: u32);
+        write_u64(value: u64);
+        write_u128(value: u128);
+        write_f32(value: f32);
+        write_f64(value: f64);
+        write_number_str(value: &str);
+        begin_string();
+        end_string();
+        write_char_escape(char_escape: CharEscape);
+        write_byte_array(value: &[u8]);
+        begin_array();
+        end_array();
+        begin_array_value(first: bool);
+        end_array_value();
+        begin_object();
+        end_object();
+        begin_object_key(first: bool);
+        end_object_key();
+        begin_object_value();
+        end_object_value();
+    }
+
+    fn write_string_fragment<W>(&mut self, writer: &mut W, fragment: &str) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        let inner = &mut self.inner;
+        escape(writer, fragment, |writer, ascii| {
+            inner.write_string_fragment(writer, ascii)
+        })
+    }
+
+    /// Raw values, e.g. of [`Canonical`](crate::ser::Canonical), are valid JSON, hence non-ASCII
+    /// characters can only be within their strings, where they can be escaped.
+    fn write_raw_fragment<W>(&mut self, writer: &mut W, fragment: &str) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        let inner = &mut self.inner;
+        escape(writer, fragment, |writer, ascii| {
+            inner.write_raw_fragment(writer, ascii)
+        })
+    }
+}
+
+/// Writes the non-ASCII characters of a fragment as `\uXXXX` sequences, the rest with `write`.
+fn escape<W, F>(writer: &mut W, fragment: &str, mut write: F) -> io::Result<()>
+where
+    W: ?Sized + io::Write,
+    F: FnMut(&mut W, &str) -> io::Result<()>,
+{
+    let mut start = 0;
+    for (index, char) in fragment.char_indices() {
+        if char.is_ascii() {
+            continue;
+        }
+        if start < index {
+            write(writer, &fragment[start..index])?;
+        }
+        // Characters outside of the Basic Multilingual Plane are written as surrogate pair.
+        let mut units = [0; 2];
+        for unit in char.encode_utf16(&mut units) {
+            write!(writer, "\\u{:04x}", unit)?;
+        }
+        start = index + char.len_utf8();
+    }
+    if start < fragment.len() {
+        write(writer, &fragment[start..])?;
+    }
+    Ok(())
+}
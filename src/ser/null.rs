@@ -0,0 +1,205 @@
+//! A serializer that produces no output.
+use serde::ser::{self, Serialize};
+
+use crate::error::EncodeError;
+
+/// A [`Serializer`](ser::Serializer) that accepts everything and produces no output.
+///
+/// Wrapped in this crate's serializer, it traverses a value with all checks of an encode, e.g. of
+/// floats, links and map keys, without the cost of formatting any output.
+pub(crate) struct NullSerializer;
+
+impl ser::Serializer for NullSerializer {
+    type Ok = ();
+    type Error = EncodeError;
+
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    fn serialize_bool(self, _value: bool) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+    fn serialize_i8(self, _value: i8) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+    fn serialize_i16(self, _value: i16) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+    fn serialize_i32(self, _value: i32) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+    fn serialize_i64(self, _value: i64) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+    fn serialize_i128(self, _value: i128) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+    fn serialize_u8(self, _value: u8) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+    fn serialize_u16(self, _value: u16) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+    fn serialize_u32(self, _value: u32) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+    fn serialize_u64(self, _value: u64) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+    fn serialize_u128(self, _value: u128) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+    fn serialize_f32(self, _value: f32) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+    fn serialize_f64(self, _value: f64) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+    fn serialize_char(self, _value: char) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+    fn serialize_str(self, _value: &str) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+    fn serialize_bytes(self, _value: &[u8]) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+    fn serialize_none(self) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Self::Ok, Self::Error> {
+        value.serialize(self)
+    }
+    fn serialize_unit(self) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<Self::Ok, Self::Error> {
+        Ok(())
+    }
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        value.serialize(self)
+    }
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        value: &T,
+    ) -> Result<Self::Ok, Self::Error> {
+        value.serialize(self)
+    }
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Self::Error> {
+        Ok(self)
+    }
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Self::Error> {
+        Ok(self)
+    }
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Self::Error> {
+        Ok(self)
+    }
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Self::Error> {
+        Ok(self)
+    }
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Self::Error> {
+        Ok(self)
+    }
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Self::Error> {
+        Ok(self)
+    }
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Self::Error> {
+        Ok(self)
+    }
+}
+
+/// Implements the compound traits, all elements are traversed.
+macro_rules! traverse {
+    ($($trait:ident::$method:ident($($key:ident: $key_ty:ty)?);)*) => {
+        $(
+            impl ser::$trait for NullSerializer {
+                type Ok = ();
+                type Error = EncodeError;
+
+                fn $method<T>(&mut self, $($key: $key_ty,)? value: &T) -> Result<(), EncodeError>
+                where
+                    T: ?Sized + Serialize,
+                {
+                    $(let _ = $key;)?
+                    value.serialize(NullSerializer)
+                }
+
+                fn end(self) -> Result<(), EncodeError> {
+                    Ok(())
+                }
+            }
+        )*
+    };
+}
+
+traverse! {
+    SerializeSeq::serialize_element();
+    SerializeTuple::serialize_element();
+    SerializeTupleStruct::serialize_field();
+    SerializeTupleVariant::serialize_field();
+    SerializeStruct::serialize_field(key: &'static str);
+    SerializeStructVariant::serialize_field(key: &'static str);
+}
+
+impl ser::SerializeMap for NullSerializer {
+    type Ok = ();
+    type Error = EncodeError;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), EncodeError>
+    where
+        T: ?Sized + Serialize,
+    {
+        key.serialize(NullSerializer)
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), EncodeError>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(NullSerializer)
+    }
+
+    fn end(self) -> Result<(), EncodeError> {
+        Ok(())
+    }
+}
@@ -1,5 +1,59 @@
-use ipld_core::cid::{multibase::Base, Cid};
+use cid::Cid;
+use std::fmt;
+
+#[cfg(feature = "float")]
+use serde::ser;
 use serde::{de, Deserialize, Serialize};
+#[cfg(feature = "float")]
+use serde_json::value::RawValue;
+
+/// The error when encountering a float without the `float` feature.
+#[cfg(not(feature = "float"))]
+pub(crate) const FLOATS_UNSUPPORTED: &str = "Floats are not supported, enable the `float` feature";
+
+/// Returns the canonical spelling of a finite float.
+///
+/// It's the shortest spelling that parses back into the same value, as produced by `ryu`, e.g.
+/// `1.0`, `0.1` or `1e100`. It's defined here and not by `serde_json`, as its float formatting
+/// differs between versions, e.g. newer ones write `1e+100`.
+pub(crate) fn format_float<F: ryu::Float>(float: F) -> String {
+    ryu::Buffer::new().format_finite(float).to_string()
+}
+
+/// Serializes a finite float with its [canonical spelling](format_float).
+#[cfg(feature = "float")]
+pub(crate) fn serialize_float<S, F>(serializer: S, float: F) -> Result<S::Ok, S::Error>
+where
+    S: ser::Serializer,
+    F: ryu::Float,
+{
+    let raw = RawValue::from_string(format_float(float)).map_err(ser::Error::custom)?;
+    raw.serialize(serializer)
+}
+
+/// Base64 encodes bytes the way DAG-JSON expects it (standard alphabet, no padding).
+#[cfg(not(feature = "base64-simd"))]
+pub(crate) fn encode_base64(bytes: &[u8]) -> String {
+    cid::multibase::Base::Base64.encode(bytes)
+}
+
+/// Base64 encodes bytes the way DAG-JSON expects it (standard alphabet, no padding).
+#[cfg(feature = "base64-simd")]
+pub(crate) fn encode_base64(bytes: &[u8]) -> String {
+    base64_simd::STANDARD_NO_PAD.encode_to_string(bytes)
+}
+
+/// Decodes base64 encoded bytes (standard alphabet, no padding).
+#[cfg(not(feature = "base64-simd"))]
+pub(crate) fn decode_base64(encoded: &str) -> Option<Vec<u8>> {
+    cid::multibase::Base::Base64.decode(encoded).ok()
+}
+
+/// Decodes base64 encoded bytes (standard alphabet, no padding).
+#[cfg(feature = "base64-simd")]
+pub(crate) fn decode_base64(encoded: &str) -> Option<Vec<u8>> {
+    base64_simd::STANDARD_NO_PAD.decode_to_vec(encoded).ok()
+}
 
 /// Result of deserializing a DAG-JSON map consisting of the reserved key `/`.
 ///
@@ -40,7 +94,7 @@ impl ReservedKeyValue {
             ReservedKeyValue::Bytes {
                 bytes: base_encoded_bytes,
             } => {
-                let bytes = Base::Base64.decode(&base_encoded_bytes[..]).map_err(|_| {
+                let bytes = decode_base64(base_encoded_bytes).ok_or_else(|| {
                     de::Error::custom(format!("Cannot base decode bytes `{}`", base_encoded_bytes))
                 })?;
                 Ok(ReservedKeyValueParsed::Bytes(bytes))
@@ -48,3 +102,196 @@ impl ReservedKeyValue {
         }
     }
 }
+
+/// Turns the bytes a CID is deserialized into back into a CID.
+pub(crate) struct CidBytesVisitor;
+
+impl de::Visitor<'_> for CidBytesVisitor {
+    type Value = Cid;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("CID bytes")
+    }
+
+    fn visit_bytes<E>(self, value: &[u8]) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Cid::try_from(value).map_err(|_| de::Error::custom("Cannot decode CID"))
+    }
+}
+
+/// Builds the values of a dynamic DAG-JSON representation, e.g. `Ipld`, for a [`KindVisitor`].
+pub(crate) trait Kinds {
+    /// The value that is built.
+    type Value;
+    /// The entries of a map while it's built.
+    type Map: Default;
+
+    /// The name of the newtype that is deserialized instead of any value. It's set if the reserved
+    /// `/` maps should be passed on as they are, also by this crate's deserializer.
+    const NEWTYPE: Option<&'static str> = None;
+
+    fn null(&mut self) -> Self::Value;
+    fn bool(&mut self, value: bool) -> Self::Value;
+    fn integer(&mut self, value: i128) -> Self::Value;
+    #[cfg(feature = "float")]
+    fn float(&mut self, value: f64) -> Self::Value;
+    fn string(&mut self, value: String) -> Self::Value;
+    fn bytes(&mut self, value: Vec<u8>) -> Self::Value;
+    fn list(&mut self, list: Vec<Self::Value>) -> Self::Value;
+    fn map(&mut self, map: Self::Map) -> Self::Value;
+    fn link(&mut self, cid: Cid) -> Self::Value;
+
+    /// Adds an entry to a map, returns `false` if the key was already in it.
+    fn insert(map: &mut Self::Map, key: String, value: Self::Value) -> bool;
+
+    /// Builds the value of a reserved `/` map, i.e. a link or bytes.
+    fn reserved<E>(&mut self, value: ReservedKeyValue) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Ok(match value.parse()? {
+            ReservedKeyValueParsed::Cid(cid) => self.link(cid),
+            ReservedKeyValueParsed::Bytes(bytes) => self.bytes(bytes),
+        })
+    }
+}
+
+/// Deserializes any [`Kinds`], treating maps with the reserved `/` key as links or bytes.
+///
+/// It works directly on top of `serde_json`, as well as on top of this crate's deserializer, which
+/// passes on links and bytes it already decoded.
+pub(crate) struct KindVisitor<'a, K>(pub(crate) &'a mut K);
+
+impl<'de, K: Kinds> de::DeserializeSeed<'de> for KindVisitor<'_, K> {
+    type Value = K::Value;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        match K::NEWTYPE {
+            Some(name) => deserializer.deserialize_newtype_struct(name, self),
+            None => deserializer.deserialize_any(self),
+        }
+    }
+}
+
+impl<'de, K: Kinds> de::Visitor<'de> for KindVisitor<'_, K> {
+    type Value = K::Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("any valid DAG-JSON kind")
+    }
+
+    fn visit_bool<E>(self, value: bool) -> Result<Self::Value, E> {
+        Ok(self.0.bool(value))
+    }
+
+    fn visit_i64<E>(self, value: i64) -> Result<Self::Value, E> {
+        Ok(self.0.integer(value.into()))
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E> {
+        Ok(self.0.integer(value.into()))
+    }
+
+    fn visit_i128<E>(self, value: i128) -> Result<Self::Value, E> {
+        Ok(self.0.integer(value))
+    }
+
+    #[cfg(feature = "float")]
+    fn visit_f64<E>(self, value: f64) -> Result<Self::Value, E> {
+        Ok(self.0.float(value))
+    }
+
+    #[cfg(not(feature = "float"))]
+    fn visit_f64<E>(self, _value: f64) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        Err(de::Error::custom(FLOATS_UNSUPPORTED))
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Self::Value, E> {
+        Ok(self.0.string(value.to_string()))
+    }
+
+    fn visit_string<E>(self, value: String) -> Result<Self::Value, E> {
+        Ok(self.0.string(value))
+    }
+
+    fn visit_bytes<E>(self, value: &[u8]) -> Result<Self::Value, E> {
+        Ok(self.0.bytes(value.to_vec()))
+    }
+
+    fn visit_byte_buf<E>(self, value: Vec<u8>) -> Result<Self::Value, E> {
+        Ok(self.0.bytes(value))
+    }
+
+    fn visit_unit<E>(self) -> Result<Self::Value, E> {
+        Ok(self.0.null())
+    }
+
+    fn visit_none<E>(self) -> Result<Self::Value, E> {
+        Ok(self.0.null())
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        de::DeserializeSeed::deserialize(self, deserializer)
+    }
+
+    /// Newtype structs are either the requested [`Kinds::NEWTYPE`] or links.
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        if K::NEWTYPE.is_some() {
+            return deserializer.deserialize_any(self);
+        }
+        let cid = deserializer.deserialize_bytes(CidBytesVisitor)?;
+        Ok(self.0.link(cid))
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        let kinds = self.0;
+        let mut list = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(element) = seq.next_element_seed(KindVisitor(&mut *kinds))? {
+            list.push(element);
+        }
+        Ok(kinds.list(list))
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::MapAccess<'de>,
+    {
+        let kinds = self.0;
+        let mut entries = K::Map::default();
+        match map.next_key::<String>()? {
+            Some(key) if key == "/" => {
+                let value: ReservedKeyValue = map.next_value()?;
+                return kinds.reserved(value);
+            }
+            Some(key) => {
+                let value = map.next_value_seed(KindVisitor(&mut *kinds))?;
+                K::insert(&mut entries, key, value);
+            }
+            None => return Ok(kinds.map(entries)),
+        }
+        while let Some(key) = map.next_key::<String>()? {
+            let value = map.next_value_seed(KindVisitor(&mut *kinds))?;
+            if !K::insert(&mut entries, key, value) {
+                return Err(de::Error::custom("Duplicate map key"));
+            }
+        }
+        Ok(kinds.map(entries))
+    }
+}
@@ -0,0 +1,109 @@
+//! Signing values in their canonical form.
+//!
+//! Signing the JSON text of a value breaks as soon as it's formatted differently, e.g. with other
+//! whitespace or key order. Instead, the functions of this module sign the canonical DAG-JSON
+//! encoding of a value, so any encoding of the same data verifies. The encoding is prefixed with a
+//! domain, which separates signatures of different purposes, so that a signature for one purpose
+//! can't be replayed for another one:
+//!
+//! ```text
+//! <domain> 0x00 <canonical DAG-JSON>
+//! ```
+//!
+//! The cryptography is pluggable through the [`Signer`] and [`Verifier`] traits.
+//!
+//! # Examples
+//!
+//! ```
+//! use ipld_core::ipld::Ipld;
+//! use serde_ipld_dagjson::signing::{sign, verify};
+//!
+//! // A toy signature, for the sake of the example. Use a real signature scheme like Ed25519.
+//! let signer = |message: &[u8]| message.iter().map(|byte| byte ^ 0x55).collect::<Vec<u8>>();
+//! let verifier = |message: &[u8], signature: &[u8]| signer(message) == signature;
+//!
+//! let signature = sign("example/v1", &Ipld::from("hello"), &signer).unwrap();
+//! let received: Ipld = serde_ipld_dagjson::from_slice(br#" "hello" "#).unwrap();
+//! assert!(verify("example/v1", &received, &signature, &verifier).unwrap());
+//! assert!(!verify("other/v1", &received, &signature, &verifier).unwrap());
+//! ```
+use serde::Serialize;
+
+use crate::error::EncodeError;
+
+/// Signs messages, e.g. with a private key.
+///
+/// It's implemented for all functions and closures returning the signature of a message.
+pub trait Signer {
+    /// Returns the signature of the given message.
+    fn sign(&self, message: &[u8]) -> Vec<u8>;
+}
+
+impl<F> Signer for F
+where
+    F: Fn(&[u8]) -> Vec<u8>,
+{
+    fn sign(&self, message: &[u8]) -> Vec<u8> {
+        self(message)
+    }
+}
+
+/// Verifies signatures of messages, e.g. with a public key.
+///
+/// It's implemented for all functions and closures returning whether a signature of a message is
+/// valid.
+pub trait Verifier {
+    /// Returns whether the signature of the given message is valid.
+    fn verify(&self, message: &[u8], signature: &[u8]) -> bool;
+}
+
+impl<F> Verifier for F
+where
+    F: Fn(&[u8], &[u8]) -> bool,
+{
+    fn verify(&self, message: &[u8], signature: &[u8]) -> bool {
+        self(message, signature)
+    }
+}
+
+/// Returns the message that is signed for a value: the domain, a zero byte and the canonical
+/// DAG-JSON encoding of the value.
+///
+/// The domain must not contain a zero byte.
+pub fn signing_bytes<T>(domain: &str, value: &T) -> Result<Vec<u8>, EncodeError>
+where
+    T: Serialize + ?Sized,
+{
+    if domain.contains('\0') {
+        return Err(EncodeError::Message(
+            "signing domain must not contain a zero byte".to_string(),
+        ));
+    }
+    let mut message = domain.as_bytes().to_vec();
+    message.push(0);
+    message.extend(crate::ser::to_canonical_vec(value)?);
+    Ok(message)
+}
+
+/// Signs the canonical form of a value, see [`signing_bytes`].
+pub fn sign<T, S>(domain: &str, value: &T, signer: &S) -> Result<Vec<u8>, EncodeError>
+where
+    T: Serialize + ?Sized,
+    S: Signer + ?Sized,
+{
+    Ok(signer.sign(&signing_bytes(domain, value)?))
+}
+
+/// Verifies the signature of a value, which is brought into its canonical form first.
+pub fn verify<T, V>(
+    domain: &str,
+    value: &T,
+    signature: &[u8],
+    verifier: &V,
+) -> Result<bool, EncodeError>
+where
+    T: Serialize + ?Sized,
+    V: Verifier + ?Sized,
+{
+    Ok(verifier.verify(&signing_bytes(domain, value)?, signature))
+}
@@ -0,0 +1,314 @@
+//! Streaming large bytes values.
+//!
+//! Documents may embed large payloads as bytes. Decoding them into a `Vec<u8>` needs as much
+//! memory as the payload. A [`BytesSink`] instead decodes the base64 string in small pieces and
+//! writes them to a writer, e.g. a file. When decoding from a slice the base64 string isn't copied
+//! either, when decoding from a reader it's buffered by the JSON parser.
+//!
+//! The other way round, a [`BytesReader`] encodes the bytes of a reader piece by piece, directly
+//! into the output.
+//!
+//! # Examples
+//!
+//! [`BytesSink`] is a [`DeserializeSeed`](de::DeserializeSeed), combined with an
+//! [`IndexedDocument`](crate::index::IndexedDocument) the bytes at a specific path can be streamed:
+//!
+//! ```
+//! use serde::de::DeserializeSeed;
+//! use serde_ipld_dagjson::{
+//!     index::IndexedDocument,
+//!     streaming::{BytesReader, BytesSink},
+//!     Deserializer,
+//! };
+//!
+//! let input = br#"{"name": "hello.txt", "data": {"/": {"bytes": "aGVsbG8gd29ybGQ"}}}"#;
+//! let document = IndexedDocument::new(input).unwrap();
+//! let raw = document.raw("data").unwrap();
+//!
+//! let mut file = Vec::new();
+//! let mut deserializer = serde_json::Deserializer::from_slice(raw);
+//! let written = BytesSink::new(&mut file)
+//!     .deserialize(Deserializer::new(&mut deserializer))
+//!     .unwrap();
+//! assert_eq!(written, 11);
+//! assert_eq!(file, b"hello world");
+//!
+//! let encoded = serde_ipld_dagjson::to_vec(&BytesReader::new(&file[..])).unwrap();
+//! assert_eq!(encoded, br#"{"/":{"bytes":"aGVsbG8gd29ybGQ"}}"#);
+//! ```
+use std::{
+    cell::RefCell,
+    fmt,
+    io::{self, Read},
+};
+
+use serde::{
+    de,
+    ser::{self, SerializeMap},
+};
+
+use crate::shared::{decode_base64, encode_base64};
+
+/// The newtype name [`BytesSink`] uses to signal that it decodes the reserved map itself.
+pub(crate) const BYTES_SINK_SERDE_PRIVATE_IDENTIFIER: &str =
+    "$__private__serde_ipld_dagjson_bytes_sink";
+
+/// The number of base64 characters that are decoded at once, it's a multiple of four, so that
+/// every piece decodes to whole bytes.
+const PIECE_LEN: usize = 16 * 1024;
+/// The number of bytes that are encoded at once, they result in a piece of base64 characters.
+const CHUNK_LEN: usize = PIECE_LEN / 4 * 3;
+
+/// Decodes bytes into a writer, instead of into memory.
+///
+/// The value is the number of bytes written. Limits on the length of bytes don't apply, as the
+/// bytes are never held in memory.
+#[derive(Debug)]
+pub struct BytesSink<W> {
+    writer: W,
+}
+
+impl<W> BytesSink<W>
+where
+    W: io::Write,
+{
+    /// Creates a new sink, which writes the decoded bytes to the given writer.
+    pub fn new(writer: W) -> Self {
+        Self { writer }
+    }
+
+    /// Decodes the base64 string piece by piece and writes it.
+    fn write<E>(mut self, encoded: &str) -> Result<u64, E>
+    where
+        E: de::Error,
+    {
+        let mut written = 0;
+        for piece in encoded.as_bytes().chunks(PIECE_LEN) {
+            let bytes = std::str::from_utf8(piece)
+                .ok()
+                .and_then(decode_base64)
+                .ok_or_else(|| de::Error::custom("Cannot base decode bytes"))?;
+            self.writer.write_all(&bytes).map_err(de::Error::custom)?;
+            written += bytes.len() as u64;
+        }
+        self.writer.flush().map_err(de::Error::custom)?;
+        Ok(written)
+    }
+}
+
+impl<'de, W> de::DeserializeSeed<'de> for BytesSink<W>
+where
+    W: io::Write,
+{
+    type Value = u64;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_newtype_struct(BYTES_SINK_SERDE_PRIVATE_IDENTIFIER, self)
+    }
+}
+
+impl<'de, W> de::Visitor<'de> for BytesSink<W>
+where
+    W: io::Write,
+{
+    type Value = u64;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("bytes")
+    }
+
+    /// Called by deserializers, which don't know about the identifier.
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_map(self)
+    }
+
+    /// Called with the reserved map `{"/": {"bytes": "..."}}`.
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::MapAccess<'de>,
+    {
+        match map.next_key::<String>()?.as_deref() {
+            Some("/") => map.next_value_seed(Inner(self)),
+            _ => Err(de::Error::custom("Expected bytes")),
+        }
+    }
+}
+
+/// Decodes the inner map `{"bytes": "..."}` of the reserved map.
+struct Inner<W>(BytesSink<W>);
+
+impl<'de, W> de::DeserializeSeed<'de> for Inner<W>
+where
+    W: io::Write,
+{
+    type Value = u64;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de, W> de::Visitor<'de> for Inner<W>
+where
+    W: io::Write,
+{
+    type Value = u64;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a map with the base64 encoded bytes")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::MapAccess<'de>,
+    {
+        match map.next_key::<String>()?.as_deref() {
+            Some("bytes") => map.next_value_seed(Encoded(self.0)),
+            _ => Err(de::Error::custom("Expected bytes")),
+        }
+    }
+}
+
+/// Decodes the base64 string of the reserved map.
+struct Encoded<W>(BytesSink<W>);
+
+impl<'de, W> de::DeserializeSeed<'de> for Encoded<W>
+where
+    W: io::Write,
+{
+    type Value = u64;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_str(self)
+    }
+}
+
+impl<W> de::Visitor<'_> for Encoded<W>
+where
+    W: io::Write,
+{
+    type Value = u64;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a base64 encoded string")
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        self.0.write(value)
+    }
+}
+
+/// Encodes the bytes of a reader, instead of bytes from memory.
+///
+/// The reader is consumed when the value is serialized, hence it can be serialized only once.
+/// Errors of the reader result in an encode error.
+#[derive(Debug)]
+pub struct BytesReader<R> {
+    reader: RefCell<R>,
+    error: RefCell<Option<io::Error>>,
+}
+
+impl<R> BytesReader<R>
+where
+    R: io::Read,
+{
+    /// Creates a new value, which encodes the bytes of the given reader.
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader: RefCell::new(reader),
+            error: RefCell::new(None),
+        }
+    }
+}
+
+impl<R> ser::Serialize for BytesReader<R>
+where
+    R: io::Read,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(1))?;
+        map.serialize_entry("/", &ReaderInner(self))?;
+        map.end()
+    }
+}
+
+/// Encodes the inner map `{"bytes": "..."}` of the reserved map.
+struct ReaderInner<'a, R>(&'a BytesReader<R>);
+
+impl<R> ser::Serialize for ReaderInner<'_, R>
+where
+    R: io::Read,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        let mut map = serializer.serialize_map(Some(1))?;
+        map.serialize_entry("bytes", &ReaderEncoded(self.0))?;
+        map.end()
+    }
+}
+
+/// Encodes the base64 string of the reserved map.
+struct ReaderEncoded<'a, R>(&'a BytesReader<R>);
+
+impl<R> ser::Serialize for ReaderEncoded<'_, R>
+where
+    R: io::Read,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        // Serializers like the one of `serde_json` write the string piece by piece.
+        let ok = serializer.collect_str(self)?;
+        match self.0.error.borrow_mut().take() {
+            Some(error) => Err(ser::Error::custom(error)),
+            None => Ok(ok),
+        }
+    }
+}
+
+impl<R> fmt::Display for ReaderEncoded<'_, R>
+where
+    R: io::Read,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut reader = self.0.reader.borrow_mut();
+        let mut chunk = Vec::with_capacity(CHUNK_LEN);
+        loop {
+            chunk.clear();
+            // A read error is reported after the string was written, as some serializers panic
+            // on a formatting error that doesn't come from their writer.
+            if let Err(error) = (&mut *reader)
+                .take(CHUNK_LEN as u64)
+                .read_to_end(&mut chunk)
+            {
+                *self.0.error.borrow_mut() = Some(error);
+                return Ok(());
+            }
+            f.write_str(&encode_base64(&chunk))?;
+            if chunk.len() < CHUNK_LEN {
+                return Ok(());
+            }
+        }
+    }
+}
@@ -0,0 +1,721 @@
+//! A strict JSON parser, tuned for DAG-JSON.
+//!
+//! `serde_json` accepts some input that DAG-JSON forbids, e.g. duplicate map keys, integers that
+//! silently become floats when they are too large, or non-string map keys when decoding into
+//! maps with integer keys. [`StrictDeserializer`] is a drop-in replacement for the underlying
+//! parser, which rejects all of that:
+//!
+//!  - map keys are always strings and must be unique,
+//!  - integers must fit into 128 bits, floats must be finite,
+//!  - strings must be valid UTF-8 and must not contain unpaired surrogates.
+//!
+//! With [strict numbers](StrictDeserializer::strict_numbers), it additionally rejects negative
+//! zero and floats that aren't in their shortest form. In [canonical](StrictDeserializer::canonical)
+//! mode, it rejects whitespace and unsorted map keys on top of that. Errors are
+//! reported as [`DecodeError::Syntax`], which contains the byte offset of the offending input.
+//!
+//! # Examples
+//!
+//! ```
+//! use std::collections::BTreeMap;
+//!
+//! use serde_ipld_dagjson::strict;
+//!
+//! let map: BTreeMap<String, u8> = strict::from_slice(br#"{"a": 1}"#).unwrap();
+//! assert_eq!(map["a"], 1);
+//! assert!(strict::from_slice::<BTreeMap<String, u8>>(br#"{"a": 1, "a": 2}"#).is_err());
+//! ```
+use std::collections::HashSet;
+
+use serde::de::{
+    self,
+    value::{BorrowedStrDeserializer, StringDeserializer},
+    Deserialize, IntoDeserializer,
+};
+
+use crate::{de::DecodeOptions, error::DecodeError, shared::format_float};
+
+/// The maximum nesting of lists and maps, deeper nesting would risk a stack overflow.
+const MAX_DEPTH: usize = 128;
+
+/// Decodes a value from DAG-JSON data in a slice, using the strict parser.
+pub fn from_slice<'a, T>(buf: &'a [u8]) -> Result<T, DecodeError>
+where
+    T: Deserialize<'a>,
+{
+    from_slice_with_options(buf, &DecodeOptions::default())
+}
+
+/// Decodes a value from DAG-JSON data in a slice, using the strict parser with the given options.
+pub fn from_slice_with_options<'a, T>(
+    buf: &'a [u8],
+    options: &DecodeOptions,
+) -> Result<T, DecodeError>
+where
+    T: Deserialize<'a>,
+{
+    crate::de::check_size(buf.len(), options)?;
+    let mut parser = StrictDeserializer::from_slice(buf)
+        .canonical(options.is_canonical())
+        .strict_numbers(options.has_strict_numbers());
+    let value = crate::de::deserialize_with_options(&mut parser, options)?;
+    parser.end()?;
+    Ok(value)
+}
+
+/// Checks that the input is in the canonical form or that its numbers are, as required by the
+/// options, without decoding it.
+pub(crate) fn check_syntax(buf: &[u8], options: &DecodeOptions) -> Result<(), DecodeError> {
+    let mut parser = StrictDeserializer::from_slice(buf)
+        .canonical(options.is_canonical())
+        .strict_numbers(options.has_strict_numbers());
+    de::IgnoredAny::deserialize(&mut parser)?;
+    parser.end()
+}
+
+/// A strict JSON parser, which is meant to be wrapped by [`Deserializer`](crate::Deserializer).
+///
+/// # Examples
+///
+/// ```
+/// use serde::Deserialize;
+/// use serde_ipld_dagjson::{strict::StrictDeserializer, Deserializer};
+///
+/// let mut parser = StrictDeserializer::from_slice(br#"{"a":[1,2]}"#).canonical(true);
+/// let value = ipld_core::ipld::Ipld::deserialize(Deserializer::new(&mut parser)).unwrap();
+/// parser.end().unwrap();
+/// ```
+#[derive(Debug)]
+pub struct StrictDeserializer<'de> {
+    tokens: Tokenizer<'de>,
+    canonical: bool,
+    strict_numbers: bool,
+    depth: usize,
+}
+
+impl<'de> StrictDeserializer<'de> {
+    /// Creates a new parser for the given input.
+    pub fn from_slice(input: &'de [u8]) -> Self {
+        Self {
+            tokens: Tokenizer::new(input),
+            canonical: false,
+            strict_numbers: false,
+            depth: 0,
+        }
+    }
+
+    /// Only accepts input in the canonical form, the default is `false`.
+    ///
+    /// Canonical input contains no whitespace, its map keys are sorted by their bytes and its
+    /// numbers are [strict](Self::strict_numbers).
+    pub fn canonical(mut self, canonical: bool) -> Self {
+        self.canonical = canonical;
+        self
+    }
+
+    /// Only accepts numbers, which are spelled the way the encoder writes them, the default is
+    /// `false`.
+    ///
+    /// Negative zero is rejected and floats must be in their shortest form, e.g. `1.0` and
+    /// `1e100`, but not `1.50`, `1E2` or `1e+100`. The spelling is defined by this crate, it
+    /// doesn't depend on the `serde_json` version.
+    pub fn strict_numbers(mut self, strict: bool) -> Self {
+        self.strict_numbers = strict;
+        self
+    }
+
+    /// Returns the byte offset of the next input to be parsed.
+    pub fn offset(&self) -> usize {
+        self.tokens.offset
+    }
+
+    /// Checks that there is no more input, apart from whitespace.
+    pub fn end(&mut self) -> Result<(), DecodeError> {
+        self.skip_whitespace()?;
+        if self.tokens.offset < self.tokens.input.len() {
+            Err(DecodeError::TrailingData)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn error_at(&self, offset: usize, message: impl Into<String>) -> DecodeError {
+        self.tokens.error_at(offset, message)
+    }
+
+    fn error(&self, message: impl Into<String>) -> DecodeError {
+        self.tokens.error(message)
+    }
+
+    /// Adds the position to errors that don't have one, e.g. the ones created by visitors.
+    fn fix_position(&self, error: DecodeError, offset: usize) -> DecodeError {
+        match error {
+            DecodeError::Message(message) => self.error_at(offset, message),
+            error => error,
+        }
+    }
+
+    fn skip_whitespace(&mut self) -> Result<(), DecodeError> {
+        self.tokens.skip_whitespace(!self.canonical)
+    }
+
+    /// Skips whitespace and returns the next byte, without consuming it.
+    fn peek_token(&mut self) -> Result<u8, DecodeError> {
+        self.skip_whitespace()?;
+        self.tokens
+            .peek()
+            .ok_or_else(|| self.error("unexpected end of input"))
+    }
+
+    fn expect(&mut self, expected: u8) -> Result<(), DecodeError> {
+        if self.peek_token()? == expected {
+            self.tokens.offset += 1;
+            Ok(())
+        } else {
+            Err(self.error(format!("expected `{}`", expected as char)))
+        }
+    }
+
+    fn enter(&mut self) -> Result<(), DecodeError> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(self.error("recursion limit exceeded"));
+        }
+        Ok(())
+    }
+
+    fn parse_number<V>(&mut self, visitor: V) -> Result<V::Value, DecodeError>
+    where
+        V: de::Visitor<'de>,
+    {
+        let start = self.tokens.offset;
+        let (text, is_float) = self.tokens.scan_number()?;
+        if is_float {
+            let float: f64 = text
+                .parse()
+                .map_err(|_| self.error_at(start, "invalid float"))?;
+            if !float.is_finite() {
+                return Err(self.error_at(start, "float out of range"));
+            }
+            if (self.canonical || self.strict_numbers) && format_float(float) != text {
+                return Err(self.error_at(start, "float is not in its shortest form"));
+            }
+            return visitor.visit_f64(float);
+        }
+        if (self.canonical || self.strict_numbers) && text == "-0" {
+            return Err(self.error_at(start, "negative zero is not canonical"));
+        }
+        let integer: i128 = text
+            .parse()
+            .map_err(|_| self.error_at(start, "integer out of range"))?;
+        if let Ok(integer) = u64::try_from(integer) {
+            visitor.visit_u64(integer)
+        } else if let Ok(integer) = i64::try_from(integer) {
+            visitor.visit_i64(integer)
+        } else {
+            visitor.visit_i128(integer)
+        }
+    }
+
+    fn parse_value<V>(&mut self, visitor: V) -> Result<V::Value, DecodeError>
+    where
+        V: de::Visitor<'de>,
+    {
+        let start = self.tokens.offset;
+        let result = match self.peek_token()? {
+            b'n' => {
+                self.tokens.expect_literal(b"null")?;
+                visitor.visit_unit()
+            }
+            b't' => {
+                self.tokens.expect_literal(b"true")?;
+                visitor.visit_bool(true)
+            }
+            b'f' => {
+                self.tokens.expect_literal(b"false")?;
+                visitor.visit_bool(false)
+            }
+            b'"' => {
+                self.tokens.offset += 1;
+                match self.tokens.parse_string()? {
+                    Str::Borrowed(string) => visitor.visit_borrowed_str(string),
+                    Str::Owned(string) => visitor.visit_string(string),
+                }
+            }
+            b'-' | b'0'..=b'9' => self.parse_number(visitor),
+            b'[' => {
+                self.tokens.offset += 1;
+                self.enter()?;
+                let mut seq = SeqAccess {
+                    de: self,
+                    first: true,
+                    done: false,
+                };
+                let value = visitor.visit_seq(&mut seq)?;
+                if !seq.done {
+                    self.expect(b']')?;
+                }
+                self.depth -= 1;
+                Ok(value)
+            }
+            b'{' => {
+                self.tokens.offset += 1;
+                self.enter()?;
+                let mut map = MapAccess {
+                    de: self,
+                    first: true,
+                    done: false,
+                    keys: HashSet::new(),
+                    previous: None,
+                };
+                let value = visitor.visit_map(&mut map)?;
+                if !map.done {
+                    self.expect(b'}')?;
+                }
+                self.depth -= 1;
+                Ok(value)
+            }
+            _ => Err(self.error("expected value")),
+        };
+        result.map_err(|error| self.fix_position(error, start))
+    }
+}
+
+/// The tokenizer of the strict parser, the [index](crate::index) and the
+/// [iterators](crate::de::seq_iter) scan documents with it as well.
+#[derive(Debug)]
+pub(crate) struct Tokenizer<'de> {
+    pub(crate) input: &'de [u8],
+    /// The byte offset of the next input to be tokenized.
+    pub(crate) offset: usize,
+}
+
+impl<'de> Tokenizer<'de> {
+    pub(crate) fn new(input: &'de [u8]) -> Self {
+        Self { input, offset: 0 }
+    }
+
+    pub(crate) fn error_at(&self, offset: usize, message: impl Into<String>) -> DecodeError {
+        DecodeError::Syntax {
+            offset,
+            message: message.into(),
+        }
+        .traced()
+    }
+
+    pub(crate) fn error(&self, message: impl Into<String>) -> DecodeError {
+        self.error_at(self.offset, message)
+    }
+
+    pub(crate) fn peek(&self) -> Option<u8> {
+        self.input.get(self.offset).copied()
+    }
+
+    /// Skips whitespace, it's an error if there is some, but it isn't allowed.
+    pub(crate) fn skip_whitespace(&mut self, allowed: bool) -> Result<(), DecodeError> {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.peek() {
+            if !allowed {
+                return Err(self.error("whitespace is not canonical"));
+            }
+            self.offset += 1;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn expect_literal(&mut self, literal: &[u8]) -> Result<(), DecodeError> {
+        if self.input[self.offset..].starts_with(literal) {
+            self.offset += literal.len();
+            Ok(())
+        } else {
+            Err(self.error("expected value"))
+        }
+    }
+
+    /// Parses a string, the opening quote must already be consumed.
+    pub(crate) fn parse_string(&mut self) -> Result<Str<'de>, DecodeError> {
+        let start = self.offset;
+        let mut owned: Option<Vec<u8>> = None;
+        let mut segment_start = start;
+        loop {
+            let Some(byte) = self.peek() else {
+                return Err(self.error("unterminated string"));
+            };
+            match byte {
+                b'"' => {
+                    let segment = &self.input[segment_start..self.offset];
+                    self.offset += 1;
+                    return match owned {
+                        None => std::str::from_utf8(segment)
+                            .map(Str::Borrowed)
+                            .map_err(|error| {
+                                self.error_at(start + error.valid_up_to(), "invalid UTF-8")
+                            }),
+                        Some(mut bytes) => {
+                            bytes.extend_from_slice(segment);
+                            String::from_utf8(bytes)
+                                .map(Str::Owned)
+                                .map_err(|_| self.error_at(start, "invalid UTF-8"))
+                        }
+                    };
+                }
+                b'\\' => {
+                    let bytes = owned.get_or_insert_with(Vec::new);
+                    bytes.extend_from_slice(&self.input[segment_start..self.offset]);
+                    self.offset += 1;
+                    let escaped = self.parse_escape()?;
+                    bytes.extend_from_slice(escaped.encode_utf8(&mut [0; 4]).as_bytes());
+                    segment_start = self.offset;
+                }
+                0x00..=0x1f => return Err(self.error("control character in string")),
+                _ => self.offset += 1,
+            }
+        }
+    }
+
+    /// Parses an escape sequence, the backslash must already be consumed.
+    fn parse_escape(&mut self) -> Result<char, DecodeError> {
+        let start = self.offset - 1;
+        let Some(byte) = self.peek() else {
+            return Err(self.error("unterminated string"));
+        };
+        self.offset += 1;
+        let char = match byte {
+            b'"' => '"',
+            b'\\' => '\\',
+            b'/' => '/',
+            b'b' => '\x08',
+            b'f' => '\x0c',
+            b'n' => '\n',
+            b'r' => '\r',
+            b't' => '\t',
+            b'u' => {
+                let high = self.parse_hex()?;
+                let code_point = match high {
+                    0xd800..=0xdbff => {
+                        if !self.input[self.offset..].starts_with(b"\\u") {
+                            return Err(self.error_at(start, "unpaired surrogate"));
+                        }
+                        self.offset += 2;
+                        let low = self.parse_hex()?;
+                        if !(0xdc00..=0xdfff).contains(&low) {
+                            return Err(self.error_at(start, "unpaired surrogate"));
+                        }
+                        0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)
+                    }
+                    0xdc00..=0xdfff => return Err(self.error_at(start, "unpaired surrogate")),
+                    _ => high,
+                };
+                char::from_u32(code_point).expect("surrogates are handled")
+            }
+            _ => return Err(self.error_at(start, "invalid escape")),
+        };
+        Ok(char)
+    }
+
+    fn parse_hex(&mut self) -> Result<u32, DecodeError> {
+        let digits = self
+            .input
+            .get(self.offset..self.offset + 4)
+            .and_then(|digits| std::str::from_utf8(digits).ok())
+            .and_then(|digits| u32::from_str_radix(digits, 16).ok())
+            .ok_or_else(|| self.error("invalid unicode escape"))?;
+        self.offset += 4;
+        Ok(digits)
+    }
+
+    fn skip_digits(&mut self) -> usize {
+        let start = self.offset;
+        while let Some(b'0'..=b'9') = self.peek() {
+            self.offset += 1;
+        }
+        self.offset - start
+    }
+
+    /// Scans a number, returns its text and whether it's a float.
+    pub(crate) fn scan_number(&mut self) -> Result<(&'de str, bool), DecodeError> {
+        let start = self.offset;
+        if self.peek() == Some(b'-') {
+            self.offset += 1;
+        }
+        let int_start = self.offset;
+        match self.skip_digits() {
+            0 => return Err(self.error("expected digit")),
+            1 => {}
+            _ if self.input[int_start] == b'0' => {
+                return Err(self.error_at(int_start, "leading zero"));
+            }
+            _ => {}
+        }
+        let mut is_float = false;
+        if self.peek() == Some(b'.') {
+            self.offset += 1;
+            if self.skip_digits() == 0 {
+                return Err(self.error("expected digit"));
+            }
+            is_float = true;
+        }
+        if let Some(b'e' | b'E') = self.peek() {
+            self.offset += 1;
+            if let Some(b'+' | b'-') = self.peek() {
+                self.offset += 1;
+            }
+            if self.skip_digits() == 0 {
+                return Err(self.error("expected digit"));
+            }
+            is_float = true;
+        }
+        let text = std::str::from_utf8(&self.input[start..self.offset]).expect("number is ASCII");
+        Ok((text, is_float))
+    }
+}
+
+/// A parsed string, which is borrowed from the input if it doesn't contain escape sequences.
+pub(crate) enum Str<'de> {
+    Borrowed(&'de str),
+    Owned(String),
+}
+
+impl Str<'_> {
+    pub(crate) fn as_str(&self) -> &str {
+        match self {
+            Self::Borrowed(string) => string,
+            Self::Owned(string) => string,
+        }
+    }
+}
+
+impl<'de> de::Deserializer<'de> for &mut StrictDeserializer<'de> {
+    type Error = DecodeError;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.parse_value(visitor)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        if self.peek_token()? == b'n' {
+            self.tokens.expect_literal(b"null")?;
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        let start = self.tokens.offset;
+        let result = match self.peek_token()? {
+            b'"' => {
+                self.tokens.offset += 1;
+                match self.tokens.parse_string()? {
+                    Str::Borrowed(string) => {
+                        visitor.visit_enum(BorrowedStrDeserializer::new(string))
+                    }
+                    Str::Owned(string) => visitor
+                        .visit_enum(IntoDeserializer::<DecodeError>::into_deserializer(string)),
+                }
+            }
+            b'{' => {
+                self.tokens.offset += 1;
+                self.enter()?;
+                let value = visitor.visit_enum(EnumAccess { de: self })?;
+                self.expect(b'}')?;
+                self.depth -= 1;
+                Ok(value)
+            }
+            _ => Err(self.error("expected string or map")),
+        };
+        result.map_err(|error| self.fix_position(error, start))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf unit
+        unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+struct SeqAccess<'a, 'de> {
+    de: &'a mut StrictDeserializer<'de>,
+    first: bool,
+    done: bool,
+}
+
+impl<'de> de::SeqAccess<'de> for &mut SeqAccess<'_, 'de> {
+    type Error = DecodeError;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        if self.done {
+            return Ok(None);
+        }
+        if self.de.peek_token()? == b']' {
+            self.de.tokens.offset += 1;
+            self.done = true;
+            return Ok(None);
+        }
+        if !self.first {
+            self.de.expect(b',')?;
+            if self.de.peek_token()? == b']' {
+                return Err(self.de.error("trailing comma"));
+            }
+        }
+        self.first = false;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+}
+
+struct MapAccess<'a, 'de> {
+    de: &'a mut StrictDeserializer<'de>,
+    first: bool,
+    done: bool,
+    keys: HashSet<String>,
+    /// The previous key, for checking the order of keys in canonical mode.
+    previous: Option<String>,
+}
+
+impl<'de> de::MapAccess<'de> for &mut MapAccess<'_, 'de> {
+    type Error = DecodeError;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Self::Error>
+    where
+        K: de::DeserializeSeed<'de>,
+    {
+        if self.done {
+            return Ok(None);
+        }
+        if self.de.peek_token()? == b'}' {
+            self.de.tokens.offset += 1;
+            self.done = true;
+            return Ok(None);
+        }
+        if !self.first {
+            self.de.expect(b',')?;
+        }
+        self.first = false;
+
+        match self.de.peek_token()? {
+            b'"' => {}
+            b'}' => return Err(self.de.error("trailing comma")),
+            _ => return Err(self.de.error("map keys must be strings")),
+        }
+        let start = self.de.tokens.offset;
+        self.de.tokens.offset += 1;
+        let key = self.de.tokens.parse_string()?;
+        let key_str = key.as_str();
+        if !self.keys.insert(key_str.to_string()) {
+            return Err(self.de.error_at(start, "duplicate map key"));
+        }
+        if self.de.canonical {
+            if let Some(previous) = &self.previous {
+                if previous.as_bytes() > key_str.as_bytes() {
+                    return Err(self.de.error_at(start, "map keys are not sorted"));
+                }
+            }
+            self.previous = Some(key_str.to_string());
+        }
+        self.de.expect(b':')?;
+
+        // Keys are always strings, whatever type is requested.
+        let value = match key {
+            Str::Borrowed(key) => seed.deserialize(BorrowedStrDeserializer::new(key)),
+            Str::Owned(key) => seed.deserialize(StringDeserializer::new(key)),
+        };
+        value
+            .map(Some)
+            .map_err(|error| self.de.fix_position(error, start))
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        seed.deserialize(&mut *self.de)
+    }
+}
+
+struct EnumAccess<'a, 'de> {
+    de: &'a mut StrictDeserializer<'de>,
+}
+
+impl<'a, 'de> de::EnumAccess<'de> for EnumAccess<'a, 'de> {
+    type Error = DecodeError;
+    type Variant = &'a mut StrictDeserializer<'de>;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self::Variant), Self::Error>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        if self.de.peek_token()? != b'"' {
+            return Err(self.de.error("expected variant name"));
+        }
+        self.de.tokens.offset += 1;
+        let variant = match self.de.tokens.parse_string()? {
+            Str::Borrowed(variant) => {
+                seed.deserialize(BorrowedStrDeserializer::<DecodeError>::new(variant))?
+            }
+            Str::Owned(variant) => {
+                seed.deserialize(StringDeserializer::<DecodeError>::new(variant))?
+            }
+        };
+        self.de.expect(b':')?;
+        Ok((variant, self.de))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for &mut StrictDeserializer<'de> {
+    type Error = DecodeError;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        Deserialize::deserialize(self)
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Self::Error>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        de::Deserializer::deserialize_any(self, visitor)
+    }
+
+    fn struct_variant<V>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        de::Deserializer::deserialize_any(self, visitor)
+    }
+}
@@ -0,0 +1,1001 @@
+//! Utilities for testing code that uses DAG-JSON.
+//!
+//! It contains [proptest] strategies, which only generate data that can be encoded as DAG-JSON
+//! and decoded back into the same value, as well as assertions on the encoded [`Token`]s of a
+//! value, on its canonical form and on round trips. It also contains a runner for the fixtures of
+//! the [ipld/codec-fixtures] conformance corpus, a harness for differential testing against other
+//! DAG-JSON implementations and snapshot assertions against checked-in fixtures.
+//!
+//! [proptest]: https://docs.rs/proptest
+//! [ipld/codec-fixtures]: https://github.com/ipld/codec-fixtures
+use std::{
+    collections::BTreeMap,
+    env, error,
+    ffi::OsString,
+    fmt, fs,
+    io::{self, Write},
+    iter,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use ipld_core::{
+    cid::{multihash::Multihash, Cid},
+    codec::Codec,
+    ipld::Ipld,
+};
+use multihash_codetable::{Code, MultihashDigest};
+use proptest::{collection, num, prelude::*};
+use serde::{
+    de::{self, DeserializeOwned, DeserializeSeed},
+    Serialize,
+};
+
+use crate::{
+    codec::DagJsonCodec,
+    de::Deserializer,
+    error::{DecodeError, EncodeError},
+    shared::CidBytesVisitor,
+};
+
+/// The multihash code of SHA2-256.
+const SHA2_256: u64 = 0x12;
+
+/// Generates CIDs, version 0 as well as version 1 with the raw, DAG-CBOR or DAG-JSON codec.
+///
+/// The digests are random, they aren't the hash of any actual data.
+pub fn arb_cid() -> impl Strategy<Value = Cid> {
+    let digest = any::<[u8; 32]>().prop_map(|digest| {
+        Multihash::wrap(SHA2_256, &digest).expect("digest fits into a multihash")
+    });
+    prop_oneof![
+        digest
+            .clone()
+            .prop_map(|hash| Cid::new_v0(hash).expect("SHA2-256 digest is valid")),
+        (prop_oneof![Just(0x55), Just(0x71), Just(0x0129)], digest)
+            .prop_map(|(codec, hash)| Cid::new_v1(codec, hash)),
+    ]
+}
+
+/// Generates floats that DAG-JSON supports, i.e. no infinity and no NaN.
+pub fn arb_float() -> impl Strategy<Value = f64> {
+    num::f64::NORMAL | num::f64::SUBNORMAL | num::f64::ZERO
+}
+
+/// Generates integers within the range DAG-JSON supports, from `i64::MIN` to `u64::MAX`.
+pub fn arb_integer() -> impl Strategy<Value = i128> {
+    prop_oneof![
+        any::<i64>().prop_map(i128::from),
+        any::<u64>().prop_map(i128::from),
+    ]
+}
+
+/// Generates [`Ipld`] that roundtrips through DAG-JSON.
+///
+/// Map keys are never the reserved key `/`, as such maps would be decoded as links or bytes.
+pub fn arb_ipld() -> impl Strategy<Value = Ipld> {
+    let leaf = prop_oneof![
+        Just(Ipld::Null),
+        any::<bool>().prop_map(Ipld::Bool),
+        arb_integer().prop_map(Ipld::Integer),
+        arb_float().prop_map(Ipld::Float),
+        any::<String>().prop_map(Ipld::String),
+        collection::vec(any::<u8>(), 0..64).prop_map(Ipld::Bytes),
+        arb_cid().prop_map(Ipld::Link),
+    ];
+    leaf.prop_recursive(4, 64, 8, |inner| {
+        prop_oneof![
+            collection::vec(inner.clone(), 0..8).prop_map(Ipld::List),
+            collection::btree_map(arb_key(), inner, 0..8)
+                .prop_map(|map: BTreeMap<String, Ipld>| Ipld::Map(map)),
+        ]
+    })
+}
+
+/// Generates map keys, everything but the reserved key `/`.
+fn arb_key() -> impl Strategy<Value = String> {
+    any::<String>().prop_filter("reserved key", |key| key != "/")
+}
+
+/// A single token of an encoded DAG-JSON document.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Token {
+    Null,
+    Bool(bool),
+    Integer(i128),
+    Float(f64),
+    /// A string, map keys are strings as well.
+    String(String),
+    Bytes(Vec<u8>),
+    Link(Cid),
+    /// The start of a list with the given number of elements.
+    List {
+        len: usize,
+    },
+    ListEnd,
+    /// The start of a map with the given number of entries.
+    Map {
+        len: usize,
+    },
+    MapEnd,
+}
+
+/// Returns the tokens of an encoded DAG-JSON document, in the order they appear in it.
+pub fn tokenize(data: &[u8]) -> Result<Vec<Token>, DecodeError> {
+    let mut tokens = Vec::new();
+    let mut json_deserializer = serde_json::Deserializer::from_slice(data);
+    TokenVisitor::new(&mut tokens).deserialize(Deserializer::new(&mut json_deserializer))?;
+    json_deserializer
+        .end()
+        .map_err(|_| DecodeError::TrailingData)?;
+    Ok(tokens)
+}
+
+/// Asserts that a value is encoded as the given tokens.
+///
+/// # Panics
+///
+/// If the value cannot be encoded or the tokens don't match.
+///
+/// # Examples
+///
+/// ```
+/// use serde::Serialize;
+/// use serde_ipld_dagjson::testing::{assert_ser_tokens, Token};
+///
+/// #[derive(Serialize)]
+/// struct File {
+///     name: String,
+///     #[serde(with = "serde_bytes")]
+///     data: Vec<u8>,
+/// }
+///
+/// let file = File { name: "a".into(), data: vec![1] };
+/// assert_ser_tokens(
+///     &file,
+///     &[
+///         Token::Map { len: 2 },
+///         Token::String("name".into()),
+///         Token::String("a".into()),
+///         Token::String("data".into()),
+///         Token::Bytes(vec![1]),
+///         Token::MapEnd,
+///     ],
+/// );
+/// ```
+#[track_caller]
+pub fn assert_ser_tokens<T>(value: &T, expected: &[Token])
+where
+    T: Serialize + ?Sized,
+{
+    let encoded = match crate::to_vec(value) {
+        Ok(encoded) => encoded,
+        Err(error) => panic!("value cannot be encoded: {}", error),
+    };
+    let tokens = match tokenize(&encoded) {
+        Ok(tokens) => tokens,
+        Err(error) => panic!("encoded value cannot be decoded: {}", error),
+    };
+    assert_eq!(tokens, expected, "tokens don't match");
+}
+
+/// A value that doesn't survive a round trip, see [`check_roundtrip`].
+#[derive(Debug)]
+pub enum RoundtripError {
+    /// The value cannot be encoded.
+    Encode(EncodeError),
+    /// The encoded value cannot be decoded.
+    Decode {
+        encoded: Vec<u8>,
+        error: DecodeError,
+    },
+    /// The decoded value isn't equal to the original one, contains both in their debug format.
+    Value {
+        encoded: Vec<u8>,
+        original: String,
+        decoded: String,
+    },
+    /// Encoding the decoded value results in different bytes.
+    Unstable {
+        encoded: Vec<u8>,
+        reencoded: Vec<u8>,
+    },
+}
+
+impl fmt::Display for RoundtripError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Encode(error) => write!(f, "cannot encode: {}", error),
+            Self::Decode { encoded, error } => write!(
+                f,
+                "cannot decode: {}\n  encoded: {}",
+                error,
+                String::from_utf8_lossy(encoded)
+            ),
+            Self::Value {
+                encoded,
+                original,
+                decoded,
+            } => write!(
+                f,
+                "decoded value differs\n  original: {}\n   decoded: {}\n   encoded: {}",
+                original,
+                decoded,
+                String::from_utf8_lossy(encoded)
+            ),
+            Self::Unstable { encoded, reencoded } => {
+                let offset = encoded
+                    .iter()
+                    .zip(reencoded)
+                    .position(|(a, b)| a != b)
+                    .unwrap_or_else(|| encoded.len().min(reencoded.len()));
+                write!(f, "re-encoding differs at byte {}", offset)?;
+                if let Some(difference) = first_difference(encoded, reencoded) {
+                    write!(f, ", {}", difference)?;
+                }
+                write!(
+                    f,
+                    "\n    encoded: {}\n  reencoded: {}",
+                    String::from_utf8_lossy(encoded),
+                    String::from_utf8_lossy(reencoded)
+                )
+            }
+        }
+    }
+}
+
+impl error::Error for RoundtripError {}
+
+/// Encodes a value, decodes it and encodes it again.
+///
+/// It checks that the decoded value is equal to the original one and that both encodings are
+/// byte for byte the same. It returns the encoded value.
+pub fn check_roundtrip<T>(value: &T) -> Result<Vec<u8>, RoundtripError>
+where
+    T: Serialize + DeserializeOwned + PartialEq + fmt::Debug,
+{
+    let encoded = crate::to_vec(value).map_err(RoundtripError::Encode)?;
+    let decoded: T = match crate::from_slice(&encoded) {
+        Ok(decoded) => decoded,
+        Err(error) => return Err(RoundtripError::Decode { encoded, error }),
+    };
+    if &decoded != value {
+        return Err(RoundtripError::Value {
+            encoded,
+            original: format!("{:?}", value),
+            decoded: format!("{:?}", decoded),
+        });
+    }
+    let reencoded = crate::to_vec(&decoded).map_err(RoundtripError::Encode)?;
+    if reencoded != encoded {
+        return Err(RoundtripError::Unstable { encoded, reencoded });
+    }
+    Ok(encoded)
+}
+
+/// Asserts that a value survives a round trip, see [`check_roundtrip`].
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::BTreeMap;
+///
+/// use serde_ipld_dagjson::testing::assert_roundtrip;
+///
+/// assert_roundtrip(&BTreeMap::from([("a".to_string(), vec![1.5, -2.0])]));
+/// ```
+#[track_caller]
+pub fn assert_roundtrip<T>(value: &T)
+where
+    T: Serialize + DeserializeOwned + PartialEq + fmt::Debug,
+{
+    if let Err(error) = check_roundtrip(value) {
+        panic!("round trip failed: {}", error);
+    }
+}
+
+/// A deviation from the canonical form of DAG-JSON, see [`check_canonical`].
+///
+/// The path of the affected value consists of map keys and list indices, separated by `/`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Violation {
+    /// The map key at the path is encoded after a key, which sorts after it. Structs encode their
+    /// fields in declaration order, which often isn't the canonical one.
+    UnsortedKey { path: String, previous: String },
+    /// A float, its encoding isn't guaranteed to be the same across implementations.
+    Float { path: String, value: f64 },
+    /// A map key, which isn't a string, it's converted into one when encoding.
+    NonStringKey { path: String },
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsortedKey { path, previous } => write!(
+                f,
+                "key at path `{}` is encoded after the key `{}`",
+                path, previous
+            ),
+            Self::Float { path, value } => write!(f, "float {} at path `{}`", value, path),
+            Self::NonStringKey { path } => write!(f, "non-string key at path `{}`", path),
+        }
+    }
+}
+
+/// A value that isn't encoded canonically, see [`check_canonical`].
+#[derive(Debug)]
+pub enum CanonicalError {
+    /// The value cannot be encoded.
+    Encode(EncodeError),
+    /// The encoded value cannot be decoded.
+    Decode {
+        encoded: Vec<u8>,
+        error: DecodeError,
+    },
+    /// The encoded value violates the canonical form.
+    Violations {
+        encoded: Vec<u8>,
+        violations: Vec<Violation>,
+    },
+}
+
+impl fmt::Display for CanonicalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Encode(error) => write!(f, "cannot encode: {}", error),
+            Self::Decode { encoded, error } => write!(
+                f,
+                "cannot decode: {}\n  encoded: {}",
+                error,
+                String::from_utf8_lossy(encoded)
+            ),
+            Self::Violations {
+                encoded,
+                violations,
+            } => {
+                write!(f, "encoding isn't canonical")?;
+                for violation in violations {
+                    write!(f, "\n  {}", violation)?;
+                }
+                write!(f, "\n  encoded: {}", String::from_utf8_lossy(encoded))
+            }
+        }
+    }
+}
+
+impl error::Error for CanonicalError {}
+
+/// Encodes a value and checks that the result is canonical DAG-JSON.
+///
+/// Map keys, including struct fields, need to be sorted by their bytes, floats and map keys that
+/// aren't strings are reported as well. Unlike decoding and re-encoding the value, this finds
+/// the types whose encoding depends on the order of their fields. It returns the encoded value.
+pub fn check_canonical<T>(value: &T) -> Result<Vec<u8>, CanonicalError>
+where
+    T: Serialize + ?Sized,
+{
+    let (encoded, keys) =
+        crate::ser::to_vec_reporting_keys(value).map_err(CanonicalError::Encode)?;
+    let mut violations: Vec<_> = keys
+        .into_iter()
+        .map(|path| Violation::NonStringKey { path })
+        .collect();
+    let tokens = match tokenize(&encoded) {
+        Ok(tokens) => tokens,
+        Err(error) => return Err(CanonicalError::Decode { encoded, error }),
+    };
+    find_violations(
+        &mut tokens.iter().peekable(),
+        &mut Vec::new(),
+        &mut violations,
+    );
+    if !violations.is_empty() {
+        return Err(CanonicalError::Violations {
+            encoded,
+            violations,
+        });
+    }
+    Ok(encoded)
+}
+
+/// Asserts that a value is encoded canonically, see [`check_canonical`].
+///
+/// # Examples
+///
+/// ```
+/// use serde::Serialize;
+/// use serde_ipld_dagjson::testing::assert_canonical;
+///
+/// #[derive(Serialize)]
+/// struct Entry {
+///     hash: String,
+///     name: String,
+/// }
+///
+/// assert_canonical(&Entry { hash: "a".into(), name: "b".into() });
+/// ```
+#[track_caller]
+pub fn assert_canonical<T>(value: &T)
+where
+    T: Serialize + ?Sized,
+{
+    if let Err(error) = check_canonical(value) {
+        panic!("{}", error);
+    }
+}
+
+/// Checks the next value of the tokens, `path` is the path of that value.
+fn find_violations<'a, I>(
+    tokens: &mut iter::Peekable<I>,
+    path: &mut Vec<String>,
+    violations: &mut Vec<Violation>,
+) where
+    I: Iterator<Item = &'a Token>,
+{
+    match tokens.next() {
+        Some(Token::Float(value)) => violations.push(Violation::Float {
+            path: path.join("/"),
+            value: *value,
+        }),
+        Some(Token::List { .. }) => {
+            let mut index = 0;
+            while tokens
+                .peek()
+                .is_some_and(|token| !matches!(token, Token::ListEnd))
+            {
+                path.push(index.to_string());
+                find_violations(tokens, path, violations);
+                path.pop();
+                index += 1;
+            }
+            tokens.next();
+        }
+        Some(Token::Map { .. }) => {
+            let mut previous: Option<&String> = None;
+            while let Some(Token::String(key)) = tokens.next() {
+                path.push(key.clone());
+                if let Some(previous) =
+                    previous.filter(|previous| previous.as_bytes() > key.as_bytes())
+                {
+                    violations.push(Violation::UnsortedKey {
+                        path: path.join("/"),
+                        previous: previous.clone(),
+                    });
+                }
+                previous = Some(key);
+                find_violations(tokens, path, violations);
+                path.pop();
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Checks that a value round-trips, see [`check_roundtrip`], and that its encoding is
+/// reproducible.
+///
+/// On top of the round trip, the original value is encoded a second time, which has to result in
+/// the same bytes, e.g. it fails on maps that are encoded in their iteration order. This is the
+/// invariant that makes hashes of encoded values reproducible. It returns the encoded value, on
+/// failure the error describes the first differing value and its path.
+///
+/// # Examples
+///
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use serde_ipld_dagjson::testing::check_stable;
+///
+/// #[derive(Debug, Deserialize, PartialEq, Serialize)]
+/// struct Block {
+///     links: Vec<String>,
+///     size: u64,
+/// }
+///
+/// let block = Block { links: vec!["a".into()], size: 1 };
+/// assert_eq!(check_stable(&block).unwrap(), br#"{"links":["a"],"size":1}"#);
+/// ```
+pub fn check_stable<T>(value: &T) -> Result<Vec<u8>, RoundtripError>
+where
+    T: Serialize + DeserializeOwned + PartialEq + fmt::Debug,
+{
+    let encoded = check_roundtrip(value)?;
+    let reencoded = crate::to_vec(value).map_err(RoundtripError::Encode)?;
+    if reencoded != encoded {
+        return Err(RoundtripError::Unstable { encoded, reencoded });
+    }
+    Ok(encoded)
+}
+
+/// Asserts that a value is stable across a round trip, see [`check_stable`].
+#[track_caller]
+pub fn assert_stable<T>(value: &T)
+where
+    T: Serialize + DeserializeOwned + PartialEq + fmt::Debug,
+{
+    if let Err(error) = check_stable(value) {
+        panic!("round trip isn't stable: {}", error);
+    }
+}
+
+/// Describes the first token that differs between two encoded documents, including its path.
+fn first_difference(a: &[u8], b: &[u8]) -> Option<String> {
+    let a = tokenize(a).ok()?;
+    let b = tokenize(b).ok()?;
+    let mut paths = Vec::new();
+    token_paths(&mut a.iter().peekable(), &mut Vec::new(), &mut paths);
+    let index = a
+        .iter()
+        .zip(&b)
+        .position(|(a, b)| a != b)
+        .unwrap_or_else(|| a.len().min(b.len()));
+    let path = paths.get(index).or(paths.last())?;
+    let describe = |token: Option<&Token>| match token {
+        Some(token) => format!("{:?}", token),
+        None => "nothing".to_string(),
+    };
+    Some(format!(
+        "at path `{}`: {} became {}",
+        path,
+        describe(a.get(index)),
+        describe(b.get(index))
+    ))
+}
+
+/// Records the path of every token of the next value, `path` is the path of that value.
+fn token_paths<'a, I>(
+    tokens: &mut iter::Peekable<I>,
+    path: &mut Vec<String>,
+    paths: &mut Vec<String>,
+) where
+    I: Iterator<Item = &'a Token>,
+{
+    let Some(token) = tokens.next() else {
+        return;
+    };
+    paths.push(path.join("/"));
+    match token {
+        Token::List { .. } => {
+            let mut index = 0;
+            while tokens
+                .peek()
+                .is_some_and(|token| !matches!(token, Token::ListEnd))
+            {
+                path.push(index.to_string());
+                token_paths(tokens, path, paths);
+                path.pop();
+                index += 1;
+            }
+            if tokens.next().is_some() {
+                paths.push(path.join("/"));
+            }
+        }
+        Token::Map { .. } => {
+            while let Some(token) = tokens.next() {
+                let Token::String(key) = token else {
+                    paths.push(path.join("/"));
+                    break;
+                };
+                path.push(key.clone());
+                paths.push(path.join("/"));
+                token_paths(tokens, path, paths);
+                path.pop();
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Records the tokens of a document.
+struct TokenVisitor<'a> {
+    tokens: &'a mut Vec<Token>,
+}
+
+impl<'a> TokenVisitor<'a> {
+    fn new(tokens: &'a mut Vec<Token>) -> Self {
+        Self { tokens }
+    }
+
+    fn push<E>(self, token: Token) -> Result<(), E> {
+        self.tokens.push(token);
+        Ok(())
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for TokenVisitor<'_> {
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> de::Visitor<'de> for TokenVisitor<'_> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("any valid DAG-JSON kind")
+    }
+
+    fn visit_bool<E>(self, value: bool) -> Result<Self::Value, E> {
+        self.push(Token::Bool(value))
+    }
+
+    fn visit_i64<E>(self, value: i64) -> Result<Self::Value, E> {
+        self.push(Token::Integer(value.into()))
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E> {
+        self.push(Token::Integer(value.into()))
+    }
+
+    fn visit_f64<E>(self, value: f64) -> Result<Self::Value, E> {
+        self.push(Token::Float(value))
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Self::Value, E> {
+        self.push(Token::String(value.to_string()))
+    }
+
+    fn visit_byte_buf<E>(self, value: Vec<u8>) -> Result<Self::Value, E> {
+        self.push(Token::Bytes(value))
+    }
+
+    fn visit_unit<E>(self) -> Result<Self::Value, E> {
+        self.push(Token::Null)
+    }
+
+    fn visit_none<E>(self) -> Result<Self::Value, E> {
+        self.push(Token::Null)
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        // DAG-JSON has no concept of newtype structs, hence visiting one means it's a CID.
+        let cid = deserializer.deserialize_bytes(CidBytesVisitor)?;
+        self.push(Token::Link(cid))
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        let start = self.tokens.len();
+        self.tokens.push(Token::List { len: 0 });
+        let mut len = 0;
+        while seq
+            .next_element_seed(TokenVisitor::new(self.tokens))?
+            .is_some()
+        {
+            len += 1;
+        }
+        self.tokens[start] = Token::List { len };
+        self.push(Token::ListEnd)
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::MapAccess<'de>,
+    {
+        let start = self.tokens.len();
+        self.tokens.push(Token::Map { len: 0 });
+        let mut len = 0;
+        while let Some(key) = map.next_key::<String>()? {
+            self.tokens.push(Token::String(key));
+            map.next_value_seed(TokenVisitor::new(self.tokens))?;
+            len += 1;
+        }
+        self.tokens[start] = Token::Map { len };
+        self.push(Token::MapEnd)
+    }
+}
+
+/// The file extension of DAG-JSON fixtures.
+const FIXTURE_EXTENSION: &str = "dag-json";
+
+/// A fixture that failed the conformance checks.
+#[derive(Debug)]
+pub struct FixtureError {
+    /// The fixture file.
+    pub path: PathBuf,
+    /// What went wrong.
+    pub message: String,
+}
+
+impl FixtureError {
+    fn new(path: &Path, message: impl fmt::Display) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            message: message.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for FixtureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path.display(), self.message)
+    }
+}
+
+impl error::Error for FixtureError {}
+
+/// Runs the conformance checks on fixtures in the layout of [ipld/codec-fixtures].
+///
+/// The path is either a single fixture file, named `<cid>.dag-json`, or a directory, in which
+/// case all the DAG-JSON fixtures within it are checked, other files are ignored. For every
+/// fixture it's checked that:
+///
+///  - it decodes into [`Ipld`],
+///  - encoding the decoded data results in exactly the same bytes,
+///  - the CID of the data matches its file name.
+///
+/// It returns the number of checked fixtures.
+///
+/// [ipld/codec-fixtures]: https://github.com/ipld/codec-fixtures
+pub fn run_fixture<P>(path: P) -> Result<usize, FixtureError>
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    if !path.is_dir() {
+        check_fixture(path)?;
+        return Ok(1);
+    }
+
+    let entries = fs::read_dir(path).map_err(|error| FixtureError::new(path, error))?;
+    let mut files = Vec::new();
+    for entry in entries {
+        let file = entry
+            .map_err(|error| FixtureError::new(path, error))?
+            .path();
+        if file.extension().is_some_and(|ext| ext == FIXTURE_EXTENSION) {
+            files.push(file);
+        }
+    }
+    // Check them in a stable order, so that failures are reproducible.
+    files.sort();
+    for file in &files {
+        check_fixture(file)?;
+    }
+    Ok(files.len())
+}
+
+fn check_fixture(path: &Path) -> Result<(), FixtureError> {
+    let expected_cid = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .and_then(|name| name.strip_suffix(FIXTURE_EXTENSION))
+        .and_then(|name| name.strip_suffix('.'))
+        .ok_or_else(|| {
+            FixtureError::new(
+                path,
+                format!("file name isn't `<cid>.{}`", FIXTURE_EXTENSION),
+            )
+        })?;
+    let expected_cid = Cid::try_from(expected_cid)
+        .map_err(|error| FixtureError::new(path, format!("invalid CID in file name: {}", error)))?;
+
+    let data = fs::read(path).map_err(|error| FixtureError::new(path, error))?;
+    let ipld = crate::ipld::from_slice(&data)
+        .map_err(|error| FixtureError::new(path, format!("cannot decode: {}", error)))?;
+    let encoded = crate::ipld::to_vec(&ipld)
+        .map_err(|error| FixtureError::new(path, format!("cannot encode: {}", error)))?;
+    if encoded != data {
+        return Err(FixtureError::new(
+            path,
+            format!(
+                "encoded data differs, got `{}`",
+                String::from_utf8_lossy(&encoded)
+            ),
+        ));
+    }
+
+    let code = Code::try_from(expected_cid.hash().code())
+        .map_err(|error| FixtureError::new(path, format!("unsupported hash: {}", error)))?;
+    let codec = <DagJsonCodec as Codec<Ipld>>::CODE;
+    let cid = Cid::new(expected_cid.version(), codec, code.digest(&encoded))
+        .map_err(|error| FixtureError::new(path, error))?;
+    if cid != expected_cid {
+        return Err(FixtureError::new(
+            path,
+            format!("CID differs, got `{}`", cid),
+        ));
+    }
+    Ok(())
+}
+
+/// Another DAG-JSON implementation, which is run as external program.
+///
+/// The program is expected to read a DAG-JSON document from stdin, decode it and write it encoded
+/// as DAG-JSON to stdout. With the JavaScript implementation this could look like:
+///
+/// ```text
+/// node --input-type=module -e "
+///   import * as dagJson from '@ipld/dag-json'
+///   import { text } from 'node:stream/consumers'
+///   const input = new TextEncoder().encode(await text(process.stdin))
+///   process.stdout.write(dagJson.encode(dagJson.decode(input)))
+/// "
+/// ```
+#[derive(Clone, Debug)]
+pub struct Reference {
+    program: OsString,
+    args: Vec<OsString>,
+}
+
+impl Reference {
+    /// Creates a new reference implementation that runs the given program.
+    pub fn new(program: impl Into<OsString>) -> Self {
+        Self {
+            program: program.into(),
+            args: Vec::new(),
+        }
+    }
+
+    /// Adds an argument that is passed on to the program.
+    pub fn arg(mut self, arg: impl Into<OsString>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    /// Runs the program with the given DAG-JSON data and returns its output.
+    pub fn reencode(&self, data: &[u8]) -> Result<Vec<u8>, DifferentialError> {
+        let mut child = Command::new(&self.program)
+            .args(&self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        // Writing happens on a separate thread, so that a program writing a lot of output before
+        // consuming all of its input doesn't dead-lock.
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let input = data.to_vec();
+        let writer = std::thread::spawn(move || stdin.write_all(&input));
+        let output = child.wait_with_output()?;
+        let written = writer.join().expect("writer thread doesn't panic");
+        // A program that fails early may not consume its input, the failure is what matters then.
+        if !output.status.success() {
+            return Err(DifferentialError::Reference(
+                String::from_utf8_lossy(&output.stderr).into_owned(),
+            ));
+        }
+        written?;
+        Ok(output.stdout)
+    }
+}
+
+/// Errors of the differential testing.
+#[derive(Debug)]
+pub enum DifferentialError {
+    /// The value cannot be encoded by this implementation.
+    Encode(EncodeError),
+    /// The reference implementation couldn't be run.
+    Io(io::Error),
+    /// The reference implementation failed, contains its error output.
+    Reference(String),
+    /// The implementations produced different encodings.
+    Mismatch { ours: Vec<u8>, reference: Vec<u8> },
+}
+
+impl fmt::Display for DifferentialError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Encode(error) => write!(f, "cannot encode: {}", error),
+            Self::Io(error) => write!(f, "cannot run reference implementation: {}", error),
+            Self::Reference(stderr) => write!(f, "reference implementation failed: {}", stderr),
+            Self::Mismatch { ours, reference } => write!(
+                f,
+                "encodings differ, ours: `{}`, reference: `{}`",
+                String::from_utf8_lossy(ours),
+                String::from_utf8_lossy(reference)
+            ),
+        }
+    }
+}
+
+impl error::Error for DifferentialError {}
+
+impl From<EncodeError> for DifferentialError {
+    fn from(error: EncodeError) -> Self {
+        Self::Encode(error)
+    }
+}
+
+impl From<io::Error> for DifferentialError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+/// Encodes a value with this crate, lets the reference implementation re-encode it and checks
+/// that both encodings are byte for byte the same.
+///
+/// Combined with [`arb_ipld`] this finds canonicalization divergences between the
+/// implementations.
+///
+/// # Examples
+///
+/// ```no_run
+/// use proptest::prelude::*;
+/// use serde_ipld_dagjson::testing::{arb_ipld, check_differential, Reference};
+///
+/// let reference = Reference::new("./dag-json-reencode");
+/// proptest!(|(ipld in arb_ipld())| {
+///     check_differential(&reference, &ipld).unwrap();
+/// });
+/// ```
+pub fn check_differential(reference: &Reference, ipld: &Ipld) -> Result<(), DifferentialError> {
+    let ours = crate::ipld::to_vec(ipld)?;
+    let reference = reference.reencode(&ours)?;
+    if ours != reference {
+        return Err(DifferentialError::Mismatch { ours, reference });
+    }
+    Ok(())
+}
+
+/// The environment variable that makes [`assert_matches_fixture`] rewrite the snapshots.
+pub const UPDATE_FIXTURES_ENV: &str = "DAGJSON_UPDATE_FIXTURES";
+
+/// Asserts that the canonical encoding of a value matches a checked-in snapshot.
+///
+/// The value is encoded with the map keys sorted, so that the snapshot doesn't depend on the
+/// order of struct fields. Relative paths are relative to the current directory, which is the
+/// root of the package when running `cargo test`.
+///
+/// If the environment variable [`UPDATE_FIXTURES_ENV`] is set, the snapshot is written instead,
+/// missing parent directories are created.
+///
+/// # Examples
+///
+/// ```no_run
+/// use serde_ipld_dagjson::testing::assert_matches_fixture;
+///
+/// assert_matches_fixture(&vec![1, 2, 3], "tests/fixtures/list.dagjson");
+/// ```
+#[track_caller]
+pub fn assert_matches_fixture<T, P>(value: &T, path: P)
+where
+    T: Serialize + ?Sized,
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    let encoded = match crate::ser::to_canonical_vec(value) {
+        Ok(encoded) => encoded,
+        Err(error) => panic!("value cannot be encoded: {}", error),
+    };
+
+    if env::var_os(UPDATE_FIXTURES_ENV).is_some_and(|update| !update.is_empty()) {
+        if let Some(parent) = path.parent() {
+            if let Err(error) = fs::create_dir_all(parent) {
+                panic!("cannot create {}: {}", parent.display(), error);
+            }
+        }
+        if let Err(error) = fs::write(path, &encoded) {
+            panic!("cannot write {}: {}", path.display(), error);
+        }
+        return;
+    }
+
+    let expected = match fs::read(path) {
+        Ok(expected) => expected,
+        Err(error) => panic!(
+            "cannot read {}: {}, set {}=1 to create it",
+            path.display(),
+            error,
+            UPDATE_FIXTURES_ENV
+        ),
+    };
+    if encoded != expected {
+        panic!(
+            "encoding doesn't match {}, set {}=1 to update it\n  actual: {}\nexpected: {}",
+            path.display(),
+            UPDATE_FIXTURES_ENV,
+            String::from_utf8_lossy(&encoded),
+            String::from_utf8_lossy(&expected),
+        );
+    }
+}
@@ -0,0 +1,739 @@
+//! Traversal of DAGs made of DAG-JSON blocks.
+//!
+//! Blocks are loaded through a [`BlockLoader`] and visited one by one, either breadth- or
+//! depth-first. Every block is visited at most once, even if it's linked from several places (or
+//! a broken loader returns cyclic data), and [`TraversalLimits`] bound how far a walk may go.
+//!
+//! Only links to DAG-JSON blocks are followed, links to blocks of other codecs, e.g. raw leaves,
+//! are recorded but never loaded.
+//!
+//! The loader is trusted: the data it returns is **not** checked against the hash of the CID it
+//! was requested with, unless the traversal is configured to
+//! [verify the blocks](Traversal::verify).
+//!
+//! # Examples
+//!
+//! ```
+//! use std::collections::HashMap;
+//!
+//! use ipld_core::cid::{multihash::Multihash, Cid};
+//! use serde_ipld_dagjson::traversal::Traversal;
+//!
+//! let identity = |data: &[u8]| Multihash::<64>::wrap(0x00, data).unwrap();
+//! let leaf = br#""leaf""#.to_vec();
+//! let leaf_cid = Cid::new_v1(0x0129, identity(&leaf));
+//! let root = format!(r#"{{"child":{{"/":"{}"}}}}"#, leaf_cid).into_bytes();
+//! let root_cid = Cid::new_v1(0x0129, identity(&root));
+//!
+//! let blocks = HashMap::from([(leaf_cid, leaf), (root_cid, root)]);
+//! let paths = Traversal::new(&blocks)
+//!     .walk(root_cid)
+//!     .map(|visit| visit.unwrap().path)
+//!     .collect::<Vec<_>>();
+//! assert_eq!(paths, ["", "child"]);
+//! ```
+use std::{
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    error,
+    fmt::{self, Write},
+};
+
+use ipld_core::{cid::Cid, ipld::Ipld};
+
+#[cfg(feature = "hashes")]
+use crate::block::{BlockHasher, HasherRegistry};
+use crate::{
+    cancel::CancellationToken,
+    de::{from_slice_with_options, DecodeOptions},
+    error::DecodeError,
+    index::push_segment,
+    multicodec,
+};
+
+/// Loads the encoded data of blocks.
+///
+/// It's implemented for maps from CIDs to data and for all functions and closures returning the
+/// data of a block, if it's available.
+pub trait BlockLoader {
+    /// Returns the encoded data of the block with the given CID, or `None` if it's not available.
+    fn load(&self, cid: &Cid) -> Option<Vec<u8>>;
+}
+
+impl<F> BlockLoader for F
+where
+    F: Fn(&Cid) -> Option<Vec<u8>>,
+{
+    fn load(&self, cid: &Cid) -> Option<Vec<u8>> {
+        self(cid)
+    }
+}
+
+impl<S: std::hash::BuildHasher> BlockLoader for HashMap<Cid, Vec<u8>, S> {
+    fn load(&self, cid: &Cid) -> Option<Vec<u8>> {
+        self.get(cid).cloned()
+    }
+}
+
+impl BlockLoader for BTreeMap<Cid, Vec<u8>> {
+    fn load(&self, cid: &Cid) -> Option<Vec<u8>> {
+        self.get(cid).cloned()
+    }
+}
+
+impl<S: std::hash::BuildHasher> BlockLoader for &HashMap<Cid, Vec<u8>, S> {
+    fn load(&self, cid: &Cid) -> Option<Vec<u8>> {
+        self.get(cid).cloned()
+    }
+}
+
+impl BlockLoader for &BTreeMap<Cid, Vec<u8>> {
+    fn load(&self, cid: &Cid) -> Option<Vec<u8>> {
+        self.get(cid).cloned()
+    }
+}
+
+/// The order blocks are visited in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Order {
+    /// All blocks linked from a block are visited before the blocks they link to.
+    #[default]
+    BreadthFirst,
+    /// The blocks linked from a block are visited one after another, each of them followed by
+    /// everything it links to.
+    DepthFirst,
+}
+
+/// Limits of a traversal, by default nothing is limited.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TraversalLimits {
+    pub(crate) max_depth: Option<usize>,
+    pub(crate) max_blocks: Option<usize>,
+    pub(crate) max_bytes: Option<usize>,
+    pub(crate) max_nodes: Option<usize>,
+}
+
+impl TraversalLimits {
+    /// Creates new limits, nothing is limited.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Don't follow links of blocks at the given depth, the root block has depth zero.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// Fail with [`TraversalError::TooManyBlocks`] if more blocks would be visited.
+    pub fn max_blocks(mut self, blocks: usize) -> Self {
+        self.max_blocks = Some(blocks);
+        self
+    }
+
+    /// Fail with [`TraversalError::TooManyBytes`] if the data of all visited blocks together
+    /// would be larger.
+    pub fn max_bytes(mut self, bytes: usize) -> Self {
+        self.max_bytes = Some(bytes);
+        self
+    }
+
+    /// Fail with [`TraversalError::TooManyNodes`] if the decoded blocks would have more nodes all
+    /// together, e.g. maps, lists or strings.
+    pub fn max_nodes(mut self, nodes: usize) -> Self {
+        self.max_nodes = Some(nodes);
+        self
+    }
+}
+
+/// What a traversal used up of its limits.
+#[derive(Debug, Default)]
+struct Budget {
+    blocks: usize,
+    bytes: usize,
+    nodes: usize,
+}
+
+impl Budget {
+    /// Accounts for a block of the given size.
+    fn block(&mut self, limits: &TraversalLimits, size: usize) -> Result<(), TraversalError> {
+        if let Some(max) = limits.max_blocks {
+            if self.blocks >= max {
+                return Err(TraversalError::TooManyBlocks(max));
+            }
+        }
+        self.blocks += 1;
+        self.bytes = self.bytes.saturating_add(size);
+        match limits.max_bytes {
+            Some(max) if self.bytes > max => Err(TraversalError::TooManyBytes(max)),
+            _ => Ok(()),
+        }
+    }
+
+    /// Accounts for the given number of nodes.
+    fn nodes(&mut self, limits: &TraversalLimits, nodes: usize) -> Result<(), TraversalError> {
+        self.nodes = self.nodes.saturating_add(nodes);
+        match limits.max_nodes {
+            Some(max) if self.nodes > max => Err(TraversalError::TooManyNodes(max)),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// An error during a traversal.
+#[derive(Debug)]
+pub enum TraversalError {
+    /// The loader doesn't have the block, which is linked at the given path.
+    Missing { path: String, cid: Cid },
+    /// A block couldn't be decoded.
+    Decode { cid: Cid, error: Box<DecodeError> },
+    /// The traversal would visit more blocks than the limit.
+    TooManyBlocks(usize),
+    /// The traversal would visit more bytes of block data than the limit.
+    TooManyBytes(usize),
+    /// The traversal would decode more nodes than the limit.
+    TooManyNodes(usize),
+    /// The traversal was cancelled through its [`CancellationToken`].
+    Cancelled,
+    /// The data of a block doesn't match the hash of its CID.
+    HashMismatch { cid: Cid },
+    /// The hash function of a CID isn't available to verify its block.
+    UnsupportedHash { cid: Cid },
+}
+
+impl fmt::Display for TraversalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing { path, cid } => write!(f, "missing block {} at path `{}`", cid, path),
+            Self::Decode { cid, error } => write!(f, "cannot decode block {}: {}", cid, error),
+            Self::TooManyBlocks(max) => write!(f, "limit of {} blocks exceeded", max),
+            Self::TooManyBytes(max) => write!(f, "limit of {} bytes exceeded", max),
+            Self::TooManyNodes(max) => write!(f, "limit of {} nodes exceeded", max),
+            Self::Cancelled => write!(f, "traversal cancelled"),
+            Self::HashMismatch { cid } => write!(f, "block {} doesn't match its hash", cid),
+            Self::UnsupportedHash { cid } => write!(
+                f,
+                "cannot verify block {}, unsupported hash function {:#x}",
+                cid,
+                cid.hash().code()
+            ),
+        }
+    }
+}
+
+impl error::Error for TraversalError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::Decode { error, .. } => Some(error.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// A visited block.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Visit {
+    /// The path from the root, through all blocks, to the link of this block. The segments are
+    /// separated by `/` and are [escaped](crate::index::escape_segment), the root block has an
+    /// empty path.
+    pub path: String,
+    /// The number of links followed to reach this block, the root block has depth zero.
+    pub depth: usize,
+    pub cid: Cid,
+    /// The decoded block.
+    pub block: Ipld,
+}
+
+/// A configurable traversal of a DAG.
+#[derive(Clone, Debug)]
+pub struct Traversal<L> {
+    loader: L,
+    order: Order,
+    limits: TraversalLimits,
+    options: DecodeOptions,
+    cancellation: Option<CancellationToken>,
+    #[cfg(feature = "hashes")]
+    hashers: Option<HasherRegistry>,
+}
+
+impl<L> Traversal<L>
+where
+    L: BlockLoader,
+{
+    /// Creates a new breadth-first traversal without limits.
+    pub fn new(loader: L) -> Self {
+        Self {
+            loader,
+            order: Order::default(),
+            limits: TraversalLimits::default(),
+            options: DecodeOptions::default(),
+            cancellation: None,
+            #[cfg(feature = "hashes")]
+            hashers: None,
+        }
+    }
+
+    /// Sets the order blocks are visited in.
+    pub fn order(mut self, order: Order) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// Sets the limits of the traversal.
+    pub fn limits(mut self, limits: TraversalLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Sets the options every block is decoded with, e.g. to limit the size of blocks.
+    pub fn decode_options(mut self, options: DecodeOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Stops the traversal with a [`TraversalError::Cancelled`], once the given token is
+    /// cancelled.
+    ///
+    /// The token is checked before every block and while decoding it.
+    pub fn cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Verifies the data of every block against the hash of its CID, before it's decoded.
+    ///
+    /// Blocks that don't match fail with [`TraversalError::HashMismatch`], blocks whose hash
+    /// function isn't in the registry with [`TraversalError::UnsupportedHash`].
+    #[cfg(feature = "hashes")]
+    pub fn verify(mut self, hashers: HasherRegistry) -> Self {
+        self.hashers = Some(hashers);
+        self
+    }
+
+    /// Prepares the traversal for loading blocks.
+    fn prepare(mut self) -> Self {
+        // Blocks are decoded with the same token, so that large blocks can be aborted early.
+        if let Some(token) = &self.cancellation {
+            self.options = self.options.cancellation(token.clone());
+        }
+        self
+    }
+
+    /// Loads and decodes a block, the error is `None` if the block is missing.
+    fn load(&self, cid: &Cid, budget: &mut Budget) -> Result<Ipld, Option<TraversalError>> {
+        if self
+            .cancellation
+            .as_ref()
+            .is_some_and(CancellationToken::is_cancelled)
+        {
+            return Err(Some(TraversalError::Cancelled));
+        }
+        let data = self.loader.load(cid).ok_or(None)?;
+        budget.block(&self.limits, data.len())?;
+        #[cfg(feature = "hashes")]
+        if let Some(hashers) = &self.hashers {
+            verify(hashers, cid, &data)?;
+        }
+        from_slice_with_options(&data, &self.options).map_err(|error| {
+            Some(match error {
+                DecodeError::Cancelled => TraversalError::Cancelled,
+                error => TraversalError::Decode {
+                    cid: *cid,
+                    error: Box::new(error),
+                },
+            })
+        })
+    }
+
+    /// Replaces the links within a value with the blocks they point to, see [`expand`].
+    ///
+    /// The depth limit is the depth of the expansion, e.g. with a depth of one only the links of
+    /// the value itself are expanded. The other limits apply to the expansion as a whole. Every
+    /// expanded link counts, even if it links to a block that was expanded before: the result of
+    /// a DAG, that links to the same blocks many times, can be exponentially larger than the DAG
+    /// itself.
+    pub fn expand(self, value: &Ipld) -> Result<Ipld, TraversalError> {
+        let mut expander = Expander {
+            traversal: self.prepare(),
+            budget: Budget::default(),
+            ancestors: Vec::new(),
+        };
+        let depth = expander.traversal.limits.max_depth.unwrap_or(usize::MAX);
+        expander.expand(value, String::new(), depth)
+    }
+
+    /// Walks the DAG starting at the block with the given CID, which is visited first.
+    ///
+    /// Nothing is visited if the root isn't a DAG-JSON block.
+    pub fn walk(self, root: Cid) -> Walk<L> {
+        let mut walk = Walk::new(self);
+        walk.seen.insert(root);
+        if is_dag_json(&root) {
+            walk.queue.push_back(Pending {
+                path: String::new(),
+                depth: 0,
+                cid: root,
+            });
+        }
+        walk
+    }
+
+    /// Walks the DAG starting at the blocks linked from the given value, which itself isn't a
+    /// block. Its links have depth zero.
+    pub fn walk_value(self, root: &Ipld) -> Walk<L> {
+        let mut walk = Walk::new(self);
+        walk.enqueue(root, String::new(), 0);
+        walk
+    }
+}
+
+/// A block that still needs to be visited.
+#[derive(Debug)]
+struct Pending {
+    path: String,
+    depth: usize,
+    cid: Cid,
+}
+
+/// An iterator over the blocks of a DAG, created by [`Traversal::walk`].
+///
+/// Iteration stops after the first error.
+#[derive(Debug)]
+pub struct Walk<L> {
+    traversal: Traversal<L>,
+    queue: VecDeque<Pending>,
+    seen: LinkSet,
+    budget: Budget,
+    failed: bool,
+}
+
+impl<L> Walk<L>
+where
+    L: BlockLoader,
+{
+    fn new(traversal: Traversal<L>) -> Self {
+        Self {
+            traversal: traversal.prepare(),
+            queue: VecDeque::new(),
+            seen: LinkSet::new(),
+            budget: Budget::default(),
+            failed: false,
+        }
+    }
+
+    /// Queues all links of a value, that weren't seen before.
+    fn enqueue(&mut self, value: &Ipld, path: String, depth: usize) {
+        let mut links = Vec::new();
+        collect_links(value, path, &mut links);
+        let pending = links
+            .into_iter()
+            .filter(|(_, cid)| self.seen.insert(*cid) && is_dag_json(cid))
+            .map(|(path, cid)| Pending { path, depth, cid });
+        match self.traversal.order {
+            Order::BreadthFirst => self.queue.extend(pending),
+            // The queue is used as a stack, the first link needs to end up on top.
+            Order::DepthFirst => {
+                for pending in pending.collect::<Vec<_>>().into_iter().rev() {
+                    self.queue.push_front(pending);
+                }
+            }
+        }
+    }
+
+    fn visit(&mut self, pending: Pending) -> Result<Visit, TraversalError> {
+        let Pending { path, depth, cid } = pending;
+        let block = match self.traversal.load(&cid, &mut self.budget) {
+            Ok(block) => block,
+            Err(None) => return Err(TraversalError::Missing { path, cid }),
+            Err(Some(error)) => return Err(error),
+        };
+        self.budget
+            .nodes(&self.traversal.limits, count_nodes(&block))?;
+        if self
+            .traversal
+            .limits
+            .max_depth
+            .is_none_or(|max| depth < max)
+        {
+            self.enqueue(&block, path.clone(), depth + 1);
+        }
+        Ok(Visit {
+            path,
+            depth,
+            cid,
+            block,
+        })
+    }
+}
+
+impl<L> Iterator for Walk<L>
+where
+    L: BlockLoader,
+{
+    type Item = Result<Visit, TraversalError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let pending = self.queue.pop_front()?;
+        let result = self.visit(pending);
+        self.failed = result.is_err();
+        Some(result)
+    }
+}
+
+/// A set of CIDs.
+pub type LinkSet = HashSet<Cid>;
+
+/// Returns the CIDs of all blocks reachable from the given root, including the root itself.
+///
+/// Blocks of other codecs than DAG-JSON are included, but as they aren't loaded, the blocks they
+/// might link to aren't.
+///
+/// Blocks are loaded one after another and dropped right after their links were collected, so only
+/// the CIDs are kept in memory, never the whole DAG. This is what's needed to calculate pin sets or
+/// to find out which blocks can be garbage collected.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashMap;
+///
+/// use ipld_core::cid::{multihash::Multihash, Cid};
+/// use serde_ipld_dagjson::traversal::{reachable_cids, TraversalLimits};
+///
+/// let identity = |data: &[u8]| Multihash::<64>::wrap(0x00, data).unwrap();
+/// let leaf = b"1".to_vec();
+/// let leaf_cid = Cid::new_v1(0x0129, identity(&leaf));
+/// let root = format!(r#"[{{"/":"{}"}},{{"/":"{}"}}]"#, leaf_cid, leaf_cid).into_bytes();
+/// let root_cid = Cid::new_v1(0x0129, identity(&root));
+///
+/// let blocks = HashMap::from([(leaf_cid, leaf), (root_cid, root)]);
+/// let reachable = reachable_cids(root_cid, &blocks, TraversalLimits::new()).unwrap();
+/// assert_eq!(reachable.len(), 2);
+/// assert!(reachable.contains(&leaf_cid));
+/// ```
+pub fn reachable_cids<L>(
+    root: Cid,
+    loader: L,
+    limits: TraversalLimits,
+) -> Result<LinkSet, TraversalError>
+where
+    L: BlockLoader,
+{
+    let mut walk = Traversal::new(loader).limits(limits).walk(root);
+    for visit in &mut walk {
+        visit?;
+    }
+    // All seen DAG-JSON blocks were visited, as links beyond the depth limit are never queued.
+    Ok(walk.seen)
+}
+
+/// Renders the link graph of the DAG starting at the given root as a Mermaid flowchart.
+///
+/// Every block is a node labeled with its CID, every link an edge labeled with its path within
+/// the block. Blocks beyond the depth limit are included as nodes, but their links aren't. The
+/// output can be pasted into Markdown, e.g. in a code block with the `mermaid` language.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashMap;
+///
+/// use ipld_core::cid::{multihash::Multihash, Cid};
+/// use serde_ipld_dagjson::traversal::{to_mermaid, TraversalLimits};
+///
+/// let identity = |data: &[u8]| Multihash::<64>::wrap(0x00, data).unwrap();
+/// let leaf = b"1".to_vec();
+/// let leaf_cid = Cid::new_v1(0x0129, identity(&leaf));
+/// let root = format!(r#"{{"a":{{"/":"{}"}}}}"#, leaf_cid).into_bytes();
+/// let root_cid = Cid::new_v1(0x0129, identity(&root));
+///
+/// let blocks = HashMap::from([(leaf_cid, leaf), (root_cid, root)]);
+/// let mermaid = to_mermaid(root_cid, &blocks, TraversalLimits::new()).unwrap();
+/// assert_eq!(
+///     mermaid,
+///     format!(
+///         "flowchart TD\n  n0[\"{}\"]\n  n1[\"{}\"]\n  n0 -- \"a\" --> n1\n",
+///         root_cid, leaf_cid
+///     )
+/// );
+/// ```
+pub fn to_mermaid<L>(
+    root: Cid,
+    loader: L,
+    limits: TraversalLimits,
+) -> Result<String, TraversalError>
+where
+    L: BlockLoader,
+{
+    let mut ids = HashMap::new();
+    let mut nodes = String::new();
+    let mut edges = String::new();
+    let mut id = |cid: Cid, nodes: &mut String| {
+        let next = ids.len();
+        *ids.entry(cid).or_insert_with(|| {
+            writeln!(nodes, "  n{}[\"{}\"]", next, cid).unwrap();
+            next
+        })
+    };
+    id(root, &mut nodes);
+    let max_depth = limits.max_depth;
+    for visit in Traversal::new(loader).limits(limits).walk(root) {
+        let visit = visit?;
+        if max_depth.is_some_and(|max| visit.depth >= max) {
+            continue;
+        }
+        let from = id(visit.cid, &mut nodes);
+        let mut links = Vec::new();
+        collect_links(&visit.block, String::new(), &mut links);
+        for (path, cid) in links {
+            let to = id(cid, &mut nodes);
+            let label = path.replace('"', "#quot;");
+            writeln!(edges, "  n{} -- \"{}\" --> n{}", from, label, to).unwrap();
+        }
+    }
+    Ok(format!("flowchart TD\n{}{}", nodes, edges))
+}
+
+/// Replaces the links within a value with the blocks they point to, down to the given depth.
+///
+/// With a depth limit of one only the links of the value itself are expanded, with a depth of two
+/// also the links within those blocks, and so on. Links beyond that depth, links to blocks of
+/// other codecs than DAG-JSON, and links that would lead into a cycle, are kept as they are.
+///
+/// The other limits apply to the expansion as a whole, see [`Traversal::expand`], which also
+/// allows to set the options the blocks are decoded with.
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::HashMap;
+///
+/// use ipld_core::{
+///     cid::{multihash::Multihash, Cid},
+///     ipld::Ipld,
+/// };
+/// use serde_ipld_dagjson::traversal::{expand, TraversalLimits};
+///
+/// let identity = |data: &[u8]| Multihash::<64>::wrap(0x00, data).unwrap();
+/// let author = br#"{"name":"alice"}"#.to_vec();
+/// let author_cid = Cid::new_v1(0x0129, identity(&author));
+/// let blocks = HashMap::from([(author_cid, author)]);
+///
+/// let post = Ipld::List(vec![Ipld::Link(author_cid)]);
+/// let limits = TraversalLimits::new().max_depth(1).max_nodes(1000);
+/// let expanded = expand(&post, &blocks, limits).unwrap();
+/// assert_eq!(serde_ipld_dagjson::ipld::to_vec(&expanded).unwrap(), br#"[{"name":"alice"}]"#);
+/// ```
+pub fn expand<L>(value: &Ipld, loader: L, limits: TraversalLimits) -> Result<Ipld, TraversalError>
+where
+    L: BlockLoader,
+{
+    Traversal::new(loader).limits(limits).expand(value)
+}
+
+struct Expander<L> {
+    traversal: Traversal<L>,
+    budget: Budget,
+    /// The CIDs of the blocks that are currently being expanded.
+    ancestors: Vec<Cid>,
+}
+
+impl<L> Expander<L>
+where
+    L: BlockLoader,
+{
+    fn expand(&mut self, value: &Ipld, path: String, depth: usize) -> Result<Ipld, TraversalError> {
+        match value {
+            Ipld::Link(cid) if depth > 0 && is_dag_json(cid) && !self.ancestors.contains(cid) => {
+                let block = match self.traversal.load(cid, &mut self.budget) {
+                    Ok(block) => block,
+                    Err(None) => return Err(TraversalError::Missing { path, cid: *cid }),
+                    Err(Some(error)) => return Err(error),
+                };
+                self.ancestors.push(*cid);
+                let expanded = self.expand(&block, path, depth - 1);
+                self.ancestors.pop();
+                expanded
+            }
+            Ipld::List(list) => {
+                self.budget.nodes(&self.traversal.limits, 1)?;
+                list.iter()
+                    .enumerate()
+                    .map(|(index, item)| {
+                        self.expand(item, join_path(&path, &index.to_string()), depth)
+                    })
+                    .collect::<Result<_, _>>()
+                    .map(Ipld::List)
+            }
+            Ipld::Map(map) => {
+                self.budget.nodes(&self.traversal.limits, 1)?;
+                map.iter()
+                    .map(|(key, item)| {
+                        let item = self.expand(item, join_path(&path, key), depth)?;
+                        Ok((key.clone(), item))
+                    })
+                    .collect::<Result<_, _>>()
+                    .map(Ipld::Map)
+            }
+            _ => {
+                self.budget.nodes(&self.traversal.limits, 1)?;
+                Ok(value.clone())
+            }
+        }
+    }
+}
+
+/// Returns the number of nodes of a value, including itself.
+fn count_nodes(value: &Ipld) -> usize {
+    match value {
+        Ipld::List(list) => 1 + list.iter().map(count_nodes).sum::<usize>(),
+        Ipld::Map(map) => 1 + map.values().map(count_nodes).sum::<usize>(),
+        _ => 1,
+    }
+}
+
+/// Returns whether the CID is the one of a DAG-JSON block, only those are loaded and decoded.
+fn is_dag_json(cid: &Cid) -> bool {
+    cid.codec() == multicodec::DAG_JSON
+}
+
+/// Checks the data of a block against the hash of its CID.
+#[cfg(feature = "hashes")]
+fn verify(hashers: &HasherRegistry, cid: &Cid, data: &[u8]) -> Result<(), TraversalError> {
+    let hasher = hashers
+        .hasher(cid.hash().code())
+        .ok_or(TraversalError::UnsupportedHash { cid: *cid })?;
+    if hasher.digest(data) == *cid.hash() {
+        Ok(())
+    } else {
+        Err(TraversalError::HashMismatch { cid: *cid })
+    }
+}
+
+/// Appends a path segment, which is [escaped](crate::index::escape_segment), the root has an
+/// empty path.
+pub(crate) fn join_path(path: &str, segment: &str) -> String {
+    let mut joined = path.to_string();
+    push_segment(&mut joined, segment);
+    joined
+}
+
+/// Collects all links within a value together with their paths, in the order they are encoded.
+fn collect_links(value: &Ipld, path: String, links: &mut Vec<(String, Cid)>) {
+    match value {
+        Ipld::Link(cid) => links.push((path, *cid)),
+        Ipld::List(list) => {
+            for (index, item) in list.iter().enumerate() {
+                collect_links(item, join_path(&path, &index.to_string()), links);
+            }
+        }
+        Ipld::Map(map) => {
+            for (key, item) in map {
+                collect_links(item, join_path(&path, key), links);
+            }
+        }
+        _ => {}
+    }
+}
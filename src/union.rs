@@ -0,0 +1,90 @@
+//! IPLD union representations.
+//!
+//! The [IPLD schema union representations] map onto the enum representations of serde, hence
+//! they don't need hand-written `Serialize` and `Deserialize` implementations:
+//!
+//!  - keyed: the default, externally tagged, representation.
+//!  - envelope: adjacently tagged, `#[serde(tag = "<discriminant>", content = "<content>")]`.
+//!  - inline: internally tagged, `#[serde(tag = "<discriminant>")]`.
+//!  - kinded: untagged, `#[serde(untagged)]`.
+//!
+//! Variants of kinded unions are tried in order, contrary to IPLD schemas, where the kind of the
+//! data determines the variant. Put links before maps and use [`string`] for string variants, so
+//! that they don't accept bytes.
+//!
+//! [IPLD schema union representations]: https://ipld.io/docs/schemas/features/representation-strategies/#union-representations
+//!
+//! # Examples
+//!
+//! ```
+//! use ipld_core::cid::Cid;
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Debug, PartialEq, Deserialize, Serialize)]
+//! #[serde(tag = "tag", content = "content")]
+//! enum Envelope {
+//!     #[serde(rename = "int")]
+//!     Int(i64),
+//!     #[serde(rename = "link")]
+//!     Link(Cid),
+//! }
+//!
+//! #[derive(Debug, PartialEq, Deserialize, Serialize)]
+//! #[serde(untagged)]
+//! enum Kinded {
+//!     Bytes(#[serde(with = "serde_bytes")] Vec<u8>),
+//!     #[serde(with = "serde_ipld_dagjson::union::string")]
+//!     String(String),
+//!     Link(Cid),
+//!     Map(std::collections::BTreeMap<String, Kinded>),
+//! }
+//!
+//! let envelope = serde_ipld_dagjson::to_vec(&Envelope::Int(1)).unwrap();
+//! assert_eq!(envelope, br#"{"tag":"int","content":1}"#);
+//!
+//! let kinded: Kinded = serde_ipld_dagjson::from_slice(br#"{"/": {"bytes": "dm14"}}"#).unwrap();
+//! assert_eq!(kinded, Kinded::Bytes(b"vmx".to_vec()));
+//! ```
+use std::fmt;
+
+use serde::{de, ser};
+
+/// Serializes and deserializes a [`String`], which is only deserialized from strings.
+///
+/// The default [`String`] deserialization also accepts bytes that are valid UTF-8. Use this for
+/// the string variants of kinded unions.
+pub mod string {
+    use super::*;
+
+    pub fn serialize<S>(value: &str, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: ser::Serializer,
+    {
+        serializer.serialize_str(value)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<String, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_string(StringVisitor)
+    }
+}
+
+struct StringVisitor;
+
+impl de::Visitor<'_> for StringVisitor {
+    type Value = String;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a string")
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Self::Value, E> {
+        Ok(value.to_string())
+    }
+
+    fn visit_string<E>(self, value: String) -> Result<Self::Value, E> {
+        Ok(value)
+    }
+}
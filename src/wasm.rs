@@ -0,0 +1,131 @@
+//! Conversions between DAG-JSON and JavaScript values.
+//!
+//! Decoding directly into a [`JsValue`] avoids a round trip through a string and `JSON.parse()`,
+//! it also keeps the IPLD kinds that JSON cannot express:
+//!
+//!  - Bytes are a `Uint8Array`.
+//!  - Links are an object with the CID string at the key `/`, e.g. `{"/": "bafy..."}`, the same
+//!    shape as in DAG-JSON.
+//!  - Integers are a `number` if they are safe integers, else a `BigInt`.
+//!
+//! Maps are plain objects and lists are arrays.
+use std::collections::BTreeMap;
+
+use ipld_core::{cid::Cid, ipld::Ipld};
+use js_sys::{Array, Number, Object, Reflect, Uint8Array};
+use wasm_bindgen::{JsCast, JsValue};
+
+use crate::error::{DecodeError, EncodeError};
+
+/// The largest integer that a JavaScript `number` can represent exactly.
+const MAX_SAFE_INTEGER: i128 = (1 << 53) - 1;
+
+/// Decodes DAG-JSON data into a JavaScript value.
+pub fn from_slice(buf: &[u8]) -> Result<JsValue, DecodeError> {
+    let ipld = crate::ipld::from_slice(buf)?;
+    Ok(ipld_to_js(&ipld))
+}
+
+/// Encodes a JavaScript value as DAG-JSON.
+pub fn to_vec(value: &JsValue) -> Result<Vec<u8>, EncodeError> {
+    let ipld = js_to_ipld(value)?;
+    crate::ipld::to_vec(&ipld)
+}
+
+/// Converts IPLD data into a JavaScript value.
+pub fn ipld_to_js(ipld: &Ipld) -> JsValue {
+    match ipld {
+        Ipld::Null => JsValue::NULL,
+        Ipld::Bool(bool) => JsValue::from_bool(*bool),
+        Ipld::Integer(integer) if integer.abs() <= MAX_SAFE_INTEGER => {
+            JsValue::from_f64(*integer as f64)
+        }
+        Ipld::Integer(integer) => JsValue::from(*integer),
+        Ipld::Float(float) => JsValue::from_f64(*float),
+        Ipld::String(string) => JsValue::from_str(string),
+        Ipld::Bytes(bytes) => Uint8Array::from(&bytes[..]).into(),
+        Ipld::List(list) => list.iter().map(ipld_to_js).collect::<Array>().into(),
+        Ipld::Map(map) => {
+            let object = Object::new();
+            for (key, value) in map {
+                set(&object, key, &ipld_to_js(value));
+            }
+            object.into()
+        }
+        Ipld::Link(cid) => {
+            let object = Object::new();
+            set(&object, "/", &JsValue::from_str(&cid.to_string()));
+            object.into()
+        }
+    }
+}
+
+/// Converts a JavaScript value into IPLD data.
+///
+/// Numbers that are integers within the safe range of JavaScript (up to 2^53 - 1 in magnitude)
+/// become integers, all other numbers become floats, as they may not be exact. `undefined`,
+/// functions and symbols cannot be converted.
+pub fn js_to_ipld(value: &JsValue) -> Result<Ipld, EncodeError> {
+    if value.is_null() {
+        Ok(Ipld::Null)
+    } else if let Some(bool) = value.as_bool() {
+        Ok(Ipld::Bool(bool))
+    } else if let Some(number) = value.as_f64() {
+        if Number::is_safe_integer(value) {
+            Ok(Ipld::Integer(number as i128))
+        } else {
+            Ok(Ipld::Float(number))
+        }
+    } else if value.is_bigint() {
+        i128::try_from(value.clone())
+            .map(Ipld::Integer)
+            .map_err(|_| EncodeError::Message("BigInt out of range".to_string()))
+    } else if let Some(string) = value.as_string() {
+        Ok(Ipld::String(string))
+    } else if let Some(bytes) = value.dyn_ref::<Uint8Array>() {
+        Ok(Ipld::Bytes(bytes.to_vec()))
+    } else if Array::is_array(value) {
+        Array::from(value)
+            .iter()
+            .map(|item| js_to_ipld(&item))
+            .collect::<Result<_, _>>()
+            .map(Ipld::List)
+    } else if let Some(object) = value.dyn_ref::<Object>() {
+        object_to_ipld(object)
+    } else {
+        Err(EncodeError::Message(format!(
+            "cannot convert {:?} to IPLD",
+            value
+        )))
+    }
+}
+
+fn object_to_ipld(object: &Object) -> Result<Ipld, EncodeError> {
+    let entries = Object::entries(object);
+    if entries.length() == 1 {
+        let entry = Array::from(&entries.get(0));
+        if entry.get(0).as_string().as_deref() == Some("/") {
+            if let Some(cid) = entry.get(1).as_string() {
+                return Cid::try_from(cid)
+                    .map(Ipld::Link)
+                    .map_err(|error| EncodeError::Message(format!("invalid link: {}", error)));
+            }
+        }
+    }
+
+    let mut map = BTreeMap::new();
+    for entry in entries.iter() {
+        let entry = Array::from(&entry);
+        let key = entry
+            .get(0)
+            .as_string()
+            .ok_or_else(|| EncodeError::Message("map keys must be strings".to_string()))?;
+        map.insert(key, js_to_ipld(&entry.get(1))?);
+    }
+    Ok(Ipld::Map(map))
+}
+
+fn set(object: &Object, key: &str, value: &JsValue) {
+    Reflect::set(object, &JsValue::from_str(key), value)
+        .expect("setting a property of a plain object cannot fail");
+}
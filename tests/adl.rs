@@ -0,0 +1,82 @@
+#![cfg(feature = "ipld")]
+use std::collections::BTreeMap;
+
+use ipld_core::ipld::Ipld;
+use serde::Deserialize;
+use serde_ipld_dagjson::{adl::Adl, de, DecodeOptions, DecodePlan};
+
+/// A toy sharded map, whose nodes are `{"shards": [...]}`, where each shard is either a map or
+/// another node.
+struct Sharded;
+
+impl Adl for Sharded {
+    fn matches(&self, first_key: &str) -> bool {
+        first_key == "shards"
+    }
+
+    fn reify(&self, mut node: BTreeMap<String, Ipld>) -> Result<Ipld, String> {
+        let Some(Ipld::List(shards)) = node.remove("shards") else {
+            return Err("shards must be a list".to_string());
+        };
+        let mut map = BTreeMap::new();
+        for shard in shards {
+            // Nested nodes were already reified.
+            match shard {
+                Ipld::Map(shard) => map.extend(shard),
+                _ => return Err("shards must be maps".to_string()),
+            }
+        }
+        Ok(Ipld::Map(map))
+    }
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Directory {
+    name: String,
+    entries: BTreeMap<String, u32>,
+}
+
+const INPUT: &[u8] =
+    br#"{"entries": {"shards": [{"a": 1}, {"shards": [{"b": 2}, {"c": 3}]}]}, "name": "dir"}"#;
+
+fn expected() -> Directory {
+    Directory {
+        name: "dir".to_string(),
+        entries: BTreeMap::from([
+            ("a".to_string(), 1),
+            ("b".to_string(), 2),
+            ("c".to_string(), 3),
+        ]),
+    }
+}
+
+#[test]
+fn test_adl() {
+    let options = DecodeOptions::new().adl(Sharded);
+    let directory: Directory = de::from_slice_with_options(INPUT, &options).unwrap();
+    assert_eq!(directory, expected());
+
+    // Without the layout, the data is decoded as it is.
+    let ipld: Ipld = de::from_slice(INPUT).unwrap();
+    let entries = ipld.get("entries").unwrap().unwrap();
+    assert!(entries.get("shards").unwrap().is_some());
+    assert!(de::from_slice::<Directory>(INPUT).is_err());
+}
+
+#[test]
+fn test_adl_decode_plan() {
+    let plan = DecodePlan::<Directory>::with_options(DecodeOptions::new().adl(Sharded));
+    for _ in 0..2 {
+        assert_eq!(plan.decode_slice(INPUT).unwrap(), expected());
+    }
+}
+
+#[test]
+fn test_adl_error() {
+    let options = DecodeOptions::new().adl(Sharded);
+    let result: Result<Ipld, _> = de::from_slice_with_options(br#"{"shards": 1}"#, &options);
+    assert!(result
+        .unwrap_err()
+        .to_string()
+        .contains("shards must be a list"));
+}
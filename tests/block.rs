@@ -0,0 +1,169 @@
+#![cfg(feature = "derive")]
+use std::str::FromStr;
+
+use ipld_core::cid::{multihash::Multihash, Cid};
+use serde::{Deserialize, Serialize};
+use serde_ipld_dagjson::{limits::Limits, DagJsonBlock, DecodeOptions};
+
+/// A toy hash function, which sums up the bytes.
+fn sum(data: &[u8]) -> Multihash<64> {
+    let sum = data.iter().map(|byte| u64::from(*byte)).sum::<u64>();
+    Multihash::wrap(0x300000, &sum.to_be_bytes()).unwrap()
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize, DagJsonBlock)]
+struct Node {
+    name: String,
+    children: Vec<Cid>,
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize, DagJsonBlock)]
+struct Wrapper<T> {
+    inner: T,
+}
+
+#[test]
+fn test_derive_dagjson_block() {
+    let child =
+        Cid::from_str("bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy").unwrap();
+    let node = Node {
+        name: "root".to_string(),
+        children: vec![child, child],
+    };
+    let (cid, bytes) = node.to_dagjson_block(&sum).unwrap();
+    assert_eq!(cid.codec(), 0x0129);
+    assert_eq!(cid.hash(), &sum(&bytes));
+    assert_eq!(Node::from_dagjson_block(&bytes).unwrap(), node);
+    assert_eq!(node.links().unwrap(), vec![child, child]);
+
+    let options = DecodeOptions::new().limits(Limits::new().max_links(1));
+    assert!(node.links_with_options(&options).is_err());
+}
+
+#[test]
+fn test_derive_dagjson_block_generic() {
+    let wrapper = Wrapper { inner: 1u8 };
+    let (_, bytes) = wrapper.to_dagjson_block(&sum).unwrap();
+    assert_eq!(bytes, br#"{"inner":1}"#);
+    assert_eq!(Wrapper::<u8>::from_dagjson_block(&bytes).unwrap(), wrapper);
+}
+
+#[cfg(feature = "hashes")]
+#[test]
+fn test_cid_of() {
+    use serde_ipld_dagjson::block::{cid_of, recompute_cid, CidError};
+
+    let node = Node {
+        name: "leaf".to_string(),
+        children: Vec::new(),
+    };
+    let (cid, bytes) = node
+        .to_dagjson_block(&|data: &[u8]| {
+            use multihash_codetable::{Code, MultihashDigest};
+            Code::Sha2_256.digest(data)
+        })
+        .unwrap();
+    assert_eq!(cid_of(&bytes, 0x12).unwrap(), cid);
+    assert_eq!(recompute_cid(&node, 0x12).unwrap(), cid);
+    assert_eq!(
+        cid.to_string(),
+        "baguqeeraoomn5hqybevdptjvrdgsspj2czoflmg3fn65mpsaie2ddzvaakwa"
+    );
+
+    assert!(matches!(
+        cid_of(&bytes, 0x300000),
+        Err(CidError::UnsupportedHash(0x300000))
+    ));
+}
+
+#[cfg(feature = "hashes")]
+#[test]
+fn test_hasher_registry() {
+    use serde_ipld_dagjson::block::{cid_of, CidError, DagJsonBlock as _, HasherRegistry};
+
+    let bytes = br#"{"children":[],"name":"leaf"}"#;
+    let registry = HasherRegistry::new();
+    assert_eq!(
+        registry.cid_of(bytes, 0x12).unwrap(),
+        cid_of(bytes, 0x12).unwrap()
+    );
+    let blake3 = registry.cid_of(bytes, 0x1e).unwrap();
+    assert_eq!(blake3.hash().code(), 0x1e);
+    assert_eq!(blake3.hash().size(), 32);
+
+    // Custom hashers are used for their code and replace default ones.
+    let sum_as_sha2 = |data: &[u8]| Multihash::wrap(0x12, sum(data).digest()).unwrap();
+    let registry = registry
+        .register(0x300000, sum)
+        .unwrap()
+        .register(0x12, sum_as_sha2)
+        .unwrap();
+    assert_eq!(
+        registry.cid_of(bytes, 0x300000).unwrap().hash(),
+        &sum(bytes)
+    );
+    assert_eq!(
+        registry.cid_of(bytes, 0x12).unwrap().hash(),
+        &sum_as_sha2(bytes)
+    );
+    assert_eq!(registry.cid_of(bytes, 0x1e).unwrap(), blake3);
+
+    let node = Node {
+        name: "leaf".to_string(),
+        children: Vec::new(),
+    };
+    let hasher = registry.hasher(0x1e).unwrap();
+    let (cid, _) = node.to_dagjson_block(&hasher).unwrap();
+    assert_eq!(registry.recompute_cid(&node, 0x1e).unwrap(), cid);
+
+    // Hashers that return another code than the one they are registered for are rejected.
+    assert!(matches!(
+        HasherRegistry::new().register(0x12, sum),
+        Err(CidError::HashCodeMismatch {
+            registered: 0x12,
+            returned: 0x300000
+        })
+    ));
+
+    let empty = HasherRegistry::empty().register(0x300000, sum).unwrap();
+    assert!(empty.hasher(0x300000).is_some());
+    assert!(matches!(
+        empty.cid_of(bytes, 0x12),
+        Err(CidError::UnsupportedHash(0x12))
+    ));
+}
+
+#[cfg(feature = "dag-cbor")]
+#[test]
+fn test_encode_dual() {
+    use ipld_core::ipld::Ipld;
+    use serde_ipld_dagjson::block::encode_dual;
+
+    let child =
+        Cid::from_str("bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy").unwrap();
+    let node = Node {
+        name: "root".to_string(),
+        children: vec![child],
+    };
+    let blocks = encode_dual(&node, &sum).unwrap();
+
+    let (json_cid, json) = &blocks.dag_json;
+    assert_eq!(json_cid.codec(), 0x0129);
+    assert_eq!(json_cid.hash(), &sum(json));
+    // The fields are sorted.
+    assert_eq!(
+        json,
+        format!(r#"{{"children":[{{"/":"{}"}}],"name":"root"}}"#, child).as_bytes()
+    );
+
+    let (cbor_cid, cbor) = &blocks.dag_cbor;
+    assert_eq!(cbor_cid.codec(), 0x71);
+    assert_eq!(cbor_cid.hash(), &sum(cbor));
+    let decoded: Node = serde_ipld_dagcbor::from_slice(cbor).unwrap();
+    assert_eq!(decoded, node);
+    let json_ipld: Ipld = serde_ipld_dagjson::from_slice(json).unwrap();
+    let cbor_ipld: Ipld = serde_ipld_dagcbor::from_slice(cbor).unwrap();
+    assert_eq!(json_ipld, cbor_ipld);
+
+    assert!(encode_dual(&vec![f64::NAN], &sum).is_err());
+}
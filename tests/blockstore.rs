@@ -0,0 +1,75 @@
+use std::{sync::Arc, thread};
+
+use ipld_core::cid::{multihash::Multihash, Cid};
+use serde_ipld_dagjson::blockstore::{Blockstore, MemoryBlockstore};
+
+fn identity_cid(data: &[u8]) -> Cid {
+    Cid::new_v1(0x0129, Multihash::<64>::wrap(0x00, data).unwrap())
+}
+
+#[test]
+fn test_memory_blockstore() {
+    let store = MemoryBlockstore::new();
+    assert!(store.is_empty());
+
+    let data = br#""leaf""#.to_vec();
+    let cid = identity_cid(&data);
+    assert_eq!(store.get(&cid).unwrap(), None);
+    assert!(!store.has(&cid).unwrap());
+
+    store.put(cid, data.clone()).unwrap();
+    assert_eq!(store.get(&cid).unwrap(), Some(data.clone()));
+    assert!(store.has(&cid).unwrap());
+    assert_eq!(store.cids(), [cid]);
+
+    // Storing a block again replaces it.
+    store.put(cid, data.clone()).unwrap();
+    assert_eq!(store.len(), 1);
+
+    // Through a reference.
+    store_block(&store, b"1".to_vec());
+    assert_eq!(store.len(), 2);
+}
+
+fn store_block<B: Blockstore>(store: B, data: Vec<u8>) -> Cid {
+    let cid = identity_cid(&data);
+    store.put(cid, data).unwrap();
+    cid
+}
+
+#[test]
+fn test_memory_blockstore_shared() {
+    let store = Arc::new(MemoryBlockstore::new());
+    let handles = (0..4)
+        .map(|i| {
+            let store = Arc::clone(&store);
+            thread::spawn(move || {
+                store_block(&*store, i.to_string().into_bytes());
+            })
+        })
+        .collect::<Vec<_>>();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    assert_eq!(store.len(), 4);
+}
+
+#[cfg(feature = "ipld")]
+#[test]
+fn test_memory_blockstore_traversal() {
+    use serde_ipld_dagjson::traversal::Traversal;
+
+    let store = MemoryBlockstore::new();
+    let leaf = br#""leaf""#.to_vec();
+    let leaf_cid = identity_cid(&leaf);
+    let root = format!(r#"{{"child":{{"/":"{}"}}}}"#, leaf_cid).into_bytes();
+    let root_cid = identity_cid(&root);
+    store.put(leaf_cid, leaf).unwrap();
+    store.put(root_cid, root).unwrap();
+
+    let cids = Traversal::new(&store)
+        .walk(root_cid)
+        .map(|visit| visit.unwrap().cid)
+        .collect::<Vec<_>>();
+    assert_eq!(cids, [root_cid, leaf_cid]);
+}
@@ -0,0 +1,72 @@
+#[cfg(feature = "io")]
+use std::io;
+use std::thread;
+
+use ipld_core::ipld::Ipld;
+use serde_ipld_dagjson::{cancel::CancellationToken, de, DecodeError, DecodeOptions};
+
+/// A never ending stream of list elements, which cancels the token after some data was read.
+#[cfg(feature = "io")]
+struct Stream {
+    token: CancellationToken,
+    read: usize,
+}
+
+#[cfg(feature = "io")]
+impl io::Read for Stream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let Some(byte) = buf.first_mut() else {
+            return Ok(0);
+        };
+        *byte = match self.read {
+            0 => b'[',
+            read if read % 2 == 1 => b'1',
+            _ => b',',
+        };
+        self.read += 1;
+        if self.read > 1000 {
+            self.token.cancel();
+        }
+        Ok(1)
+    }
+}
+
+#[cfg(feature = "io")]
+#[test]
+fn test_cancel_reader() {
+    let token = CancellationToken::new();
+    let options = DecodeOptions::new().cancellation(token.clone());
+    let stream = Stream {
+        token: token.clone(),
+        read: 0,
+    };
+    let result: Result<Vec<u8>, _> = de::from_reader_with_options(stream, &options);
+    assert!(matches!(result, Err(DecodeError::Cancelled)));
+    assert!(token.is_cancelled());
+}
+
+#[cfg(feature = "io")]
+#[test]
+fn test_cancel_decoder_reader() {
+    let token = CancellationToken::new();
+    let mut decoder = de::Decoder::with_options(DecodeOptions::new().cancellation(token.clone()));
+    let stream = Stream {
+        token: token.clone(),
+        read: 0,
+    };
+    let result = decoder.decode_reader::<Vec<u8>, _>(stream);
+    assert!(matches!(result, Err(DecodeError::Cancelled)));
+    assert!(token.is_cancelled());
+}
+
+#[test]
+fn test_cancel_slice() {
+    let token = CancellationToken::new();
+    let options = DecodeOptions::new().cancellation(token.clone());
+    let input = br#"{"a": [1, 2, 3]}"#;
+    assert!(de::from_slice_with_options::<Ipld>(input, &options).is_ok());
+
+    thread::spawn(move || token.cancel()).join().unwrap();
+    let result = de::from_slice_with_options::<Ipld>(input, &options);
+    assert!(matches!(result, Err(DecodeError::Cancelled)));
+}
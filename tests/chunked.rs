@@ -0,0 +1,85 @@
+#![cfg(feature = "codec")]
+use std::{
+    collections::HashMap,
+    io::{self, Read},
+};
+
+use ipld_core::cid::{multihash::Multihash, Cid};
+use serde_ipld_dagjson::chunked::{ChunkedReader, Chunker};
+
+/// A toy hash function, 64-bit FNV-1a.
+fn fnv(data: &[u8]) -> Multihash<64> {
+    let hash = data.iter().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+    });
+    Multihash::wrap(0x300000, &hash.to_be_bytes()).unwrap()
+}
+
+fn encode(
+    data: &[u8],
+    chunker: &Chunker<fn(&[u8]) -> Multihash<64>>,
+) -> (Cid, HashMap<Cid, Vec<u8>>) {
+    let mut blocks = HashMap::new();
+    let root = chunker
+        .encode(data, |cid, block| {
+            blocks.insert(cid, block);
+        })
+        .unwrap();
+    (root, blocks)
+}
+
+fn read(blocks: &HashMap<Cid, Vec<u8>>, root: Cid) -> io::Result<Vec<u8>> {
+    let mut data = Vec::new();
+    ChunkedReader::new(blocks, root).read_to_end(&mut data)?;
+    Ok(data)
+}
+
+#[test]
+fn test_chunked_roundtrip() {
+    let chunker = Chunker::new(fnv as fn(&[u8]) -> Multihash<64>)
+        .chunk_size(3)
+        .fanout(2);
+    for len in [0, 1, 3, 4, 12, 25, 100] {
+        let data = (0..len).map(|byte| byte as u8).collect::<Vec<_>>();
+        let (root, blocks) = encode(&data, &chunker);
+        assert_eq!(read(&blocks, root).unwrap(), data, "length {}", len);
+    }
+}
+
+#[test]
+fn test_chunked_layout() {
+    let chunker = Chunker::new(fnv as fn(&[u8]) -> Multihash<64>)
+        .chunk_size(2)
+        .fanout(3);
+    let (root, blocks) = encode(b"abcdefgh", &chunker);
+    // Four chunks, a full list of three of them and the root, which links the list and the last
+    // chunk.
+    assert_eq!(blocks.len(), 6);
+    let root_block = String::from_utf8(blocks[&root].clone()).unwrap();
+    assert!(root_block.starts_with(r#"[{"/":"#), "{}", root_block);
+
+    let (root, blocks) = encode(b"a", &chunker);
+    assert_eq!(blocks.len(), 1);
+    assert_eq!(blocks[&root], br#"{"/":{"bytes":"YQ"}}"#);
+}
+
+#[test]
+fn test_chunked_errors() {
+    let chunker = Chunker::new(fnv as fn(&[u8]) -> Multihash<64>).chunk_size(2);
+    let (root, mut blocks) = encode(b"abcdef", &chunker);
+    let chunk = *blocks.keys().find(|cid| **cid != root).unwrap();
+
+    blocks.insert(chunk, br#"{"a": 1}"#.to_vec());
+    let error = read(&blocks, root).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+    blocks.remove(&chunk);
+    let error = read(&blocks, root).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::NotFound);
+
+    // A list that links itself.
+    let cid = Cid::new_v1(0x0129, fnv(b"cycle"));
+    let cyclic = HashMap::from([(cid, format!(r#"[{{"/":"{}"}}]"#, cid).into_bytes())]);
+    let error = read(&cyclic, cid).unwrap_err();
+    assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+}
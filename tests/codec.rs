@@ -1,12 +1,16 @@
+#![cfg(feature = "codec")]
+// The tests predate `iter::repeat_n`.
+#![allow(clippy::manual_repeat_n)]
+
 use std::iter;
 
 use ipld_core::{
-    cid::Cid,
+    cid::{multihash::Multihash, Cid},
     codec::{Codec, Links},
     ipld,
     ipld::Ipld,
 };
-use serde_ipld_dagjson::codec::DagJsonCodec;
+use serde_ipld_dagjson::{block::encode_blocks, codec::DagJsonCodec, error::CodecError};
 
 #[test]
 fn test_codec_encode() {
@@ -26,6 +30,26 @@ fn test_codec_decode() {
     assert_eq!(decoded, expected);
 }
 
+#[test]
+fn test_codec_ipld() {
+    let cid = Cid::try_from("bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy").unwrap();
+    let data = ipld!({"b": [1, -2, "x", null], "a": {"link": cid, "bytes": [1u8, 2]}, "c": true});
+
+    // The `Codec` trait uses the code path specialized on `Ipld`, with the same result.
+    let encoded = <DagJsonCodec as Codec<Ipld>>::encode_to_vec(&data).unwrap();
+    let mut expected = Vec::new();
+    DagJsonCodec::encode_ipld(&mut expected, &data).unwrap();
+    assert_eq!(encoded, expected);
+    assert_eq!(encoded, serde_ipld_dagjson::to_vec(&data).unwrap());
+
+    let decoded: Ipld = DagJsonCodec::decode_from_slice(&encoded).unwrap();
+    assert_eq!(decoded, data);
+    assert_eq!(decoded, DagJsonCodec::decode_ipld(&encoded[..]).unwrap());
+
+    let result: Result<Ipld, _> = DagJsonCodec::decode_from_slice(b"[1,");
+    assert!(matches!(result, Err(CodecError::Decode(_))));
+}
+
 #[test]
 fn test_codec_links() {
     let cid = Cid::try_from("bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy").unwrap();
@@ -36,3 +60,134 @@ fn test_codec_links() {
     let links = DagJsonCodec::links(&encoded).unwrap().collect::<Vec<_>>();
     assert_eq!(links, expected);
 }
+
+#[test]
+fn test_codec_for_each_link() {
+    let cid = Cid::try_from("bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy").unwrap();
+    let data: Ipld = ipld!({"some": {"nested": cid}, "or": [cid, 1, {"/": {"bytes": "dm14"}}]});
+    let encoded = DagJsonCodec::encode_to_vec(&data).unwrap();
+
+    let mut links = Vec::new();
+    DagJsonCodec::for_each_link(&encoded, |link| links.push(link)).unwrap();
+    assert_eq!(links, vec![cid, cid]);
+
+    assert!(DagJsonCodec::for_each_link(b"[1] 2", |_| {}).is_err());
+}
+
+#[test]
+fn test_codec_decode_many() {
+    let blocks = [&br#""hello""#[..], &b"[1,2]"[..], &b"invalid"[..]];
+    let decoded = DagJsonCodec::decode_many::<Ipld, _>(blocks);
+    assert_eq!(decoded.len(), 3);
+    assert_eq!(
+        decoded[0].as_ref().unwrap(),
+        &Ipld::String("hello".to_string())
+    );
+    assert_eq!(decoded[1].as_ref().unwrap(), &ipld!([1, 2]));
+    assert!(decoded[2].is_err());
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn test_codec_par_decode_many() {
+    let blocks = (0..100).map(|ii| ii.to_string()).collect::<Vec<_>>();
+    let decoded = DagJsonCodec::par_decode_many::<u32, _>(
+        blocks
+            .iter()
+            .map(|block| block.as_bytes())
+            .collect::<Vec<_>>(),
+    );
+    let expected = (0..100).collect::<Vec<_>>();
+    assert_eq!(
+        decoded.into_iter().map(Result::unwrap).collect::<Vec<_>>(),
+        expected
+    );
+}
+
+/// A toy hash function, which sums up the bytes.
+fn sum(data: &[u8]) -> Multihash<64> {
+    let sum = data.iter().map(|byte| u64::from(*byte)).sum::<u64>();
+    Multihash::wrap(0x300000, &sum.to_be_bytes()).unwrap()
+}
+
+#[test]
+fn test_encode_blocks() {
+    let values = [ipld!({"a": 1}), ipld!([true]), ipld!("x")];
+    let blocks = encode_blocks(&values, &sum).unwrap();
+    assert_eq!(blocks.len(), 3);
+    for ((cid, data), value) in blocks.iter().zip(&values) {
+        assert_eq!(data, &DagJsonCodec::encode_to_vec(value).unwrap());
+        assert_eq!(cid.hash(), &sum(data));
+        assert_eq!(cid.codec(), 0x0129);
+    }
+
+    assert!(encode_blocks(&[f64::NAN], &sum).is_err());
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn test_par_encode_blocks() {
+    let values = (0..100).collect::<Vec<u32>>();
+    let blocks = serde_ipld_dagjson::block::par_encode_blocks(&values, &sum).unwrap();
+    assert_eq!(blocks, encode_blocks(&values, &sum).unwrap());
+}
+
+#[test]
+fn test_codec_for_each_link_with_options() {
+    use serde_ipld_dagjson::{
+        limits::{Limit, Limits},
+        DecodeError, DecodeOptions,
+    };
+
+    let cid = Cid::try_from("bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy").unwrap();
+    let data: Ipld = ipld!({"some": {"nested": cid}, "or": [cid, cid]});
+    let encoded = DagJsonCodec::encode_to_vec(&data).unwrap();
+
+    let options = DecodeOptions::new().limits(Limits::new().max_links(3));
+    let mut count = 0;
+    DagJsonCodec::for_each_link_with_options(&encoded, &options, |_| count += 1).unwrap();
+    assert_eq!(count, 3);
+
+    let options = DecodeOptions::new().limits(Limits::new().max_links(2));
+    let mut count = 0;
+    let result = DagJsonCodec::for_each_link_with_options(&encoded, &options, |_| count += 1);
+    assert!(matches!(
+        result,
+        Err(CodecError::Decode(DecodeError::LimitExceeded(
+            Limit::Links(2)
+        )))
+    ));
+    assert_eq!(count, 2);
+}
+
+#[test]
+fn test_pretty_codec() {
+    use serde_ipld_dagjson::codec::DagJsonPrettyCodec;
+
+    let cid = Cid::try_from("bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy").unwrap();
+    let data: Ipld = ipld!({"link": cid, "list": [1, "a"]});
+
+    let encoded = DagJsonPrettyCodec::encode_to_vec(&data).unwrap();
+    assert_eq!(
+        String::from_utf8(encoded.clone()).unwrap(),
+        r#"{
+  "link": {
+    "/": "bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy"
+  },
+  "list": [
+    1,
+    "a"
+  ]
+}"#
+    );
+    let decoded: Ipld = DagJsonPrettyCodec::decode_from_slice(&encoded).unwrap();
+    assert_eq!(decoded, data);
+    assert_eq!(
+        <DagJsonPrettyCodec as Codec<Ipld>>::CODE,
+        <DagJsonCodec as Codec<Ipld>>::CODE
+    );
+    let links = DagJsonPrettyCodec::links(&encoded)
+        .unwrap()
+        .collect::<Vec<_>>();
+    assert_eq!(links, [cid]);
+}
@@ -0,0 +1,40 @@
+use std::collections::BTreeMap;
+
+use ipld_core::ipld::Ipld;
+use serde::{Deserialize, Serialize};
+use serde_ipld_dagjson::DagJson;
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+struct Config {
+    filter: DagJson<BTreeMap<String, u32>>,
+}
+
+#[test]
+fn test_dagjson_display_from_str() {
+    let value: DagJson<Ipld> = r#"{"a": [1, {"/": {"bytes": "dm14"}}]}"#.parse().unwrap();
+    assert_eq!(
+        value.to_string().unwrap(),
+        r#"{"a":[1,{"/":{"bytes":"dm14"}}]}"#
+    );
+    assert_eq!(value, value.to_string().unwrap().parse().unwrap());
+    assert!(DagJson(f64::NAN).to_string().is_err());
+
+    assert!("[1,".parse::<DagJson<Ipld>>().is_err());
+    assert!(r#""a""#.parse::<DagJson<u8>>().is_err());
+}
+
+#[test]
+fn test_dagjson_embedded() {
+    let config = Config {
+        filter: DagJson(BTreeMap::from([("a".to_string(), 1)])),
+    };
+    let encoded = serde_ipld_dagjson::to_vec(&config).unwrap();
+    assert_eq!(encoded, br#"{"filter":"{\"a\":1}"}"#);
+    let decoded: Config = serde_ipld_dagjson::from_slice(&encoded).unwrap();
+    assert_eq!(decoded, config);
+
+    let plain_json = serde_json::to_string(&config).unwrap();
+    assert_eq!(plain_json, r#"{"filter":"{\"a\":1}"}"#);
+
+    assert!(serde_ipld_dagjson::from_slice::<Config>(br#"{"filter":"[1]"}"#).is_err());
+}
@@ -117,6 +117,7 @@ fn test_numbers3() {
     assert_eq!(ipld, Ipld::Integer(-2015));
 }
 
+#[cfg(feature = "float")]
 #[test]
 fn test_numbers_large_negative() {
     // With serde_json, large negative numbers become floats.
@@ -185,12 +186,14 @@ fn test_empty_map() {
     assert_eq!(ipld, expected);
 }
 
+#[cfg(feature = "float")]
 #[test]
 fn test_float() {
     let ipld: Ipld = de::from_slice(b"100000.0").unwrap();
     assert_eq!(ipld, Ipld::Float(100000.0));
 }
 
+#[cfg(feature = "float")]
 #[test]
 fn test_crazy_list() {
     //let slice = b"[123456789959,-34567897654325468,-456787678,true,null,23456543.5]";
@@ -289,3 +292,805 @@ fn test_reserved_trailing() {
     let ipld: Result<Ipld, _> = de::from_slice(data);
     assert!(ipld.is_err());
 }
+
+#[test]
+fn test_decoder_reuse() {
+    let mut decoder = de::Decoder::new();
+    #[cfg(feature = "io")]
+    {
+        let first: Ipld = decoder.decode_reader(&b"[1,2,3]"[..]).unwrap();
+        assert_eq!(
+            first,
+            Ipld::List(vec![Ipld::Integer(1), Ipld::Integer(2), Ipld::Integer(3)])
+        );
+        let second: Ipld = decoder.decode_reader(&b"true"[..]).unwrap();
+        assert_eq!(second, Ipld::Bool(true));
+
+        let trailing: Result<Ipld, _> = decoder.decode_reader(&b"falsetrailing"[..]);
+        assert!(matches!(trailing.unwrap_err(), DecodeError::TrailingData));
+    }
+
+    decoder.reset();
+    let third: String = decoder.decode_slice(br#""foobar""#).unwrap();
+    assert_eq!(third, "foobar");
+
+    // The state of a failed decode doesn't leak into the next one.
+    let reserved: Result<Ipld, _> = decoder.decode_slice(br#"[{"/": 5}]"#);
+    assert!(reserved.is_err());
+    let fourth: Ipld = decoder.decode_slice(br#"[{"a": 5}]"#).unwrap();
+    assert_eq!(
+        fourth,
+        Ipld::List(vec![Ipld::Map(
+            [("a".to_string(), Ipld::Integer(5))].into_iter().collect()
+        )])
+    );
+}
+
+#[test]
+fn test_memory_budget() {
+    let data = br#"{"a": "some string", "b": [1, 2, 3], "c": {"/": {"bytes": "dm14"}}}"#;
+    let within = de::DecodeOptions::new().memory_budget(1024);
+    let ipld: Ipld = de::from_slice_with_options(data, &within).unwrap();
+    assert_eq!(ipld, de::from_slice::<Ipld>(data).unwrap());
+
+    let exceeded = de::DecodeOptions::new().memory_budget(64);
+    let result: Result<Ipld, _> = de::from_slice_with_options(data, &exceeded);
+    assert!(matches!(
+        result.unwrap_err(),
+        DecodeError::MemoryBudgetExceeded(64)
+    ));
+
+    #[cfg(feature = "io")]
+    {
+        let result: Result<Ipld, _> = de::from_reader_with_options(&data[..], &exceeded);
+        assert!(matches!(
+            result.unwrap_err(),
+            DecodeError::MemoryBudgetExceeded(64)
+        ));
+    }
+}
+
+#[test]
+fn test_cid_cache() {
+    let cid = Cid::from_str("bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy").unwrap();
+    let other =
+        Cid::from_str("bafyreih4b2rbwbhcu5v2ysxsmoxmvaej4jvkwjbkeloyukodzuxjyb66pa").unwrap();
+    let data = to_vec(&vec![cid, other, cid, cid, other]).unwrap();
+
+    for capacity in [1, 2, 16] {
+        let options = de::DecodeOptions::new().cid_cache(capacity);
+        let links: Vec<Cid> = de::from_slice_with_options(&data, &options).unwrap();
+        assert_eq!(links, vec![cid, other, cid, cid, other]);
+    }
+
+    let invalid = br#"[{"/": "invalid"}, {"/": "invalid"}]"#;
+    let options = de::DecodeOptions::new().cid_cache(2);
+    let result: Result<Ipld, _> = de::from_slice_with_options(invalid, &options);
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_decode_plan() {
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Entry {
+        name: String,
+        link: Cid,
+        data: ByteBuf,
+        children: Vec<Entry>,
+    }
+
+    let cid = Cid::from_str("bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy").unwrap();
+    let data = br#"{"name": "root", "link": {"/": "bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy"}, "data": {"/": {"bytes": "dm14"}}, "children": [{"name": "child", "link": {"/": "bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy"}, "data": {"/": {"bytes": ""}}, "children": []}]}"#;
+    let expected = Entry {
+        name: "root".to_string(),
+        link: cid,
+        data: ByteBuf::from(b"vmx"),
+        children: vec![Entry {
+            name: "child".to_string(),
+            link: cid,
+            data: ByteBuf::new(),
+            children: Vec::new(),
+        }],
+    };
+
+    let plan = de::DecodePlan::<Entry>::new();
+    for _ in 0..3 {
+        assert_eq!(plan.decode_slice(data).unwrap(), expected);
+        #[cfg(feature = "io")]
+        assert_eq!(plan.decode_reader(&data[..]).unwrap(), expected);
+    }
+
+    let link_instead_of_struct =
+        br#"{"/": "bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy"}"#;
+    assert!(plan.decode_slice(link_instead_of_struct).is_err());
+
+    // Plans can be shared between threads.
+    fn assert_send_sync<T: Send + Sync>(_: &T) {}
+    assert_send_sync(&plan);
+}
+
+#[test]
+fn test_decode_plan_same_results() {
+    mod first {
+        #[derive(Debug, Default, PartialEq, serde::Deserialize)]
+        #[serde(default)]
+        pub struct Shape {
+            pub name: String,
+        }
+    }
+    mod second {
+        #[derive(Debug, Default, PartialEq, serde::Deserialize)]
+        #[serde(default)]
+        pub struct Shape {
+            pub size: u64,
+        }
+    }
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Both {
+        first: first::Shape,
+        second: second::Shape,
+    }
+
+    let plan = de::DecodePlan::<Both>::new();
+    let data = br#"{"first": {"name": "a"}, "second": {"size": 1}}"#;
+    let expected = Both {
+        first: first::Shape {
+            name: "a".to_string(),
+        },
+        second: second::Shape { size: 1 },
+    };
+    for _ in 0..2 {
+        assert_eq!(plan.decode_slice(data).unwrap(), expected);
+    }
+
+    // A link isn't a struct, even if all of its fields have defaults.
+    let link = br#"{"first": {"/": "bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy"}, "second": {}}"#;
+    assert!(de::from_slice::<Both>(link).is_err());
+    assert!(plan.decode_slice(link).is_err());
+}
+
+#[cfg(feature = "ipld")]
+#[test]
+fn test_inline_identity() {
+    use ipld_core::cid::multihash::Multihash;
+
+    let identity = |codec, data: &[u8]| Cid::new_v1(codec, Multihash::wrap(0x00, data).unwrap());
+    let dagjson = identity(0x0129, br#"{"a":[1,2]}"#);
+    let raw = identity(0x55, b"vmx");
+    let other = identity(0x71, b"\x01");
+    let data = format!(
+        r#"[{{"/":"{}"}},{{"/":"{}"}},{{"/":"{}"}}]"#,
+        dagjson, raw, other
+    );
+
+    let options = de::DecodeOptions::new().inline_identity(true);
+    let ipld: Ipld = de::from_slice_with_options(data.as_bytes(), &options).unwrap();
+    let expected = Ipld::List(vec![
+        Ipld::Map(BTreeMap::from([(
+            "a".to_string(),
+            Ipld::List(vec![Ipld::Integer(1), Ipld::Integer(2)]),
+        )])),
+        Ipld::Bytes(b"vmx".to_vec()),
+        Ipld::Link(other),
+    ]);
+    assert_eq!(ipld, expected);
+
+    // Values that are explicitly CIDs stay links.
+    let cids: Vec<Cid> = de::from_slice_with_options(data.as_bytes(), &options).unwrap();
+    assert_eq!(cids, [dagjson, raw, other]);
+
+    // The embedded data counts towards the limits of the whole decode.
+    use serde_ipld_dagjson::limits::{Limit, Limits};
+    let nested = format!(r#"[[{{"/":"{}"}}]]"#, dagjson);
+    let limited = options.clone().limits(Limits::new().max_depth(3));
+    assert!(matches!(
+        de::from_slice_with_options::<Ipld>(nested.as_bytes(), &limited),
+        Err(DecodeError::LimitExceeded(Limit::Depth(3)))
+    ));
+    let limited = options.clone().limits(Limits::new().max_depth(4));
+    assert!(de::from_slice_with_options::<Ipld>(nested.as_bytes(), &limited).is_ok());
+
+    // By default, identity CIDs are just links.
+    let ipld: Ipld = de::from_slice(data.as_bytes()).unwrap();
+    assert_eq!(
+        ipld,
+        Ipld::List(vec![
+            Ipld::Link(dagjson),
+            Ipld::Link(raw),
+            Ipld::Link(other)
+        ])
+    );
+}
+
+#[test]
+fn test_cid_v0() {
+    use serde_ipld_dagjson::{de::CidV0, limits::RejectedLink};
+
+    let v0 = Cid::from_str("QmdfTbBqBPQ7VNxZEYEj14VmRuZBkqFbiwReogJgS1zR1n").unwrap();
+    let v1 = v0.into_v1().unwrap();
+    let data = format!(r#"{{"link":{{"/":"{}"}}}}"#, v0);
+
+    let ipld: Ipld = de::from_slice(data.as_bytes()).unwrap();
+    assert_eq!(
+        ipld,
+        Ipld::Map(BTreeMap::from([("link".to_string(), Ipld::Link(v0))]))
+    );
+
+    let upgrade = de::DecodeOptions::new().cid_v0(CidV0::Upgrade);
+    let ipld: Ipld = de::from_slice_with_options(data.as_bytes(), &upgrade).unwrap();
+    assert_eq!(
+        ipld,
+        Ipld::Map(BTreeMap::from([("link".to_string(), Ipld::Link(v1))]))
+    );
+    assert_eq!(
+        String::from_utf8(to_vec(&ipld).unwrap()).unwrap(),
+        format!(r#"{{"link":{{"/":"{}"}}}}"#, v1)
+    );
+    assert!(v1.to_string().starts_with('b'));
+
+    let reject = de::DecodeOptions::new().cid_v0(CidV0::Reject);
+    let result: Result<BTreeMap<String, Cid>, _> =
+        de::from_slice_with_options(data.as_bytes(), &reject);
+    assert!(matches!(
+        result,
+        Err(DecodeError::LinkRejected(RejectedLink::Version0))
+    ));
+    // CIDv1 are still fine.
+    let data = format!(r#"{{"link":{{"/":"{}"}}}}"#, v1);
+    let map: BTreeMap<String, Cid> = de::from_slice_with_options(data.as_bytes(), &reject).unwrap();
+    assert_eq!(map["link"], v1);
+}
+
+#[test]
+fn test_strict_multibase() {
+    use ipld_core::cid::multibase::Base;
+    use serde_ipld_dagjson::limits::RejectedLink;
+
+    let cid = Cid::from_str("bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy").unwrap();
+    let v0 = Cid::from_str("QmdfTbBqBPQ7VNxZEYEj14VmRuZBkqFbiwReogJgS1zR1n").unwrap();
+    let strict = de::DecodeOptions::new().strict_multibase(true);
+    let decode = |encoded: &str, options: &de::DecodeOptions| {
+        let data = format!(r#"{{"/":"{}"}}"#, encoded);
+        de::from_slice_with_options::<Cid>(data.as_bytes(), options)
+    };
+
+    assert_eq!(decode(&cid.to_string(), &strict).unwrap(), cid);
+    assert_eq!(decode(&v0.to_string(), &strict).unwrap(), v0);
+    for encoded in [
+        cid.to_string_of_base(Base::Base58Btc).unwrap(),
+        cid.to_string_of_base(Base::Base32Upper).unwrap(),
+        cid.to_string_of_base(Base::Base64).unwrap(),
+    ] {
+        // The permissive parsing is the default.
+        assert_eq!(decode(&encoded, &de::DecodeOptions::new()).unwrap(), cid);
+        assert!(matches!(
+            decode(&encoded, &strict),
+            Err(DecodeError::LinkRejected(RejectedLink::Multibase))
+        ));
+    }
+}
+
+#[test]
+fn test_observer() {
+    use std::sync::{Arc, Mutex};
+
+    use serde_ipld_dagjson::{index::Kind, observe::DecodeEvent};
+
+    #[derive(Debug, Default, PartialEq)]
+    struct Counters {
+        nodes: Vec<Kind>,
+        links: usize,
+        bytes: Option<usize>,
+    }
+
+    let counters = Arc::new(Mutex::new(Counters::default()));
+    let observed = Arc::clone(&counters);
+    let options = de::DecodeOptions::new().observer(move |event: &DecodeEvent| {
+        let mut counters = observed.lock().unwrap();
+        match event {
+            DecodeEvent::Node(kind) => counters.nodes.push(*kind),
+            DecodeEvent::Link(_) => counters.links += 1,
+            DecodeEvent::Finished { bytes } => counters.bytes = Some(*bytes),
+        }
+    });
+    let input = br#"{"a": [1, "b", null], "c": {"/": "bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy"}}"#;
+
+    let _: Ipld = de::from_slice_with_options(input, &options).unwrap();
+    let expected = Counters {
+        nodes: vec![
+            Kind::Map,
+            Kind::String,
+            Kind::List,
+            Kind::Integer,
+            Kind::String,
+            Kind::Null,
+            Kind::String,
+            Kind::Link,
+        ],
+        links: 1,
+        bytes: Some(input.len()),
+    };
+    assert_eq!(*counters.lock().unwrap(), expected);
+
+    #[cfg(feature = "io")]
+    {
+        *counters.lock().unwrap() = Counters::default();
+        let _: Ipld = de::from_reader_with_options(&input[..], &options).unwrap();
+        assert_eq!(*counters.lock().unwrap(), expected);
+    }
+}
+
+#[cfg(feature = "io")]
+#[test]
+fn test_from_reader_with_progress() {
+    use serde_ipld_dagjson::de::{from_reader_with_progress, Progress, PROGRESS_INTERVAL};
+
+    let value = vec!["a".repeat(1000); 200];
+    let input = serde_ipld_dagjson::to_vec(&value).unwrap();
+    assert!(input.len() > 3 * PROGRESS_INTERVAL);
+
+    let mut reports = Vec::new();
+    let decoded: Vec<String> = from_reader_with_progress(
+        &input[..],
+        Some(input.len()),
+        &de::DecodeOptions::new(),
+        |progress| reports.push(progress),
+    )
+    .unwrap();
+    assert_eq!(decoded, value);
+    assert_eq!(reports.len(), input.len() / PROGRESS_INTERVAL + 1);
+    assert_eq!(reports[0].consumed, PROGRESS_INTERVAL);
+    assert!(reports
+        .windows(2)
+        .all(|pair| pair[0].consumed < pair[1].consumed));
+    assert_eq!(
+        reports.last(),
+        Some(&Progress {
+            consumed: input.len(),
+            total: Some(input.len())
+        })
+    );
+    assert_eq!(reports.last().unwrap().percent(), Some(100));
+
+    // Without a known length there is no percentage.
+    let mut percents = Vec::new();
+    let _: Vec<String> =
+        from_reader_with_progress(&input[..], None, &de::DecodeOptions::new(), |progress| {
+            percents.push(progress.percent())
+        })
+        .unwrap();
+    assert!(percents.iter().all(Option::is_none));
+}
+
+#[test]
+fn test_deadline() {
+    use std::time::{Duration, Instant};
+
+    use serde_ipld_dagjson::DecodeError;
+
+    let input = br#"{"a": [1, 2, 3]}"#;
+    let past = Instant::now();
+    let future = past + Duration::from_secs(3600);
+
+    let options = de::DecodeOptions::new().deadline(future);
+    assert!(de::from_slice_with_options::<Ipld>(input, &options).is_ok());
+    #[cfg(feature = "io")]
+    assert!(de::from_reader_with_deadline::<Ipld, _>(&input[..], future, &options).is_ok());
+
+    let options = de::DecodeOptions::new().deadline(past);
+    let result = de::from_slice_with_options::<Ipld>(input, &options);
+    assert!(matches!(result, Err(DecodeError::TimedOut)));
+    #[cfg(feature = "io")]
+    {
+        let result =
+            de::from_reader_with_deadline::<Ipld, _>(&input[..], past, &de::DecodeOptions::new());
+        assert!(matches!(result, Err(DecodeError::TimedOut)));
+    }
+}
+
+#[test]
+fn test_canonical() {
+    use serde::Deserialize;
+    use serde_ipld_dagjson::DecodeOptions;
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Entry {
+        name: String,
+        hash: String,
+    }
+
+    let options = DecodeOptions::new().canonical(true);
+    let entry: Entry =
+        de::from_slice_with_options(br#"{"hash":"a","name":"b"}"#, &options).unwrap();
+    assert_eq!(entry.name, "b");
+
+    for input in [
+        &br#"{"name":"b","hash":"a"}"#[..],
+        br#"{"hash": "a","name":"b"}"#,
+        br#"{"hash":"a","name":"b"} "#,
+    ] {
+        let result: Result<Entry, _> = de::from_slice_with_options(input, &options);
+        assert!(matches!(result, Err(DecodeError::Syntax { .. })));
+        #[cfg(feature = "io")]
+        {
+            let result: Result<Entry, _> = de::from_reader_with_options(input, &options);
+            assert!(matches!(result, Err(DecodeError::Syntax { .. })));
+        }
+        // Without the option, the input is accepted.
+        let _: Entry = de::from_slice(input).unwrap();
+    }
+
+    let result: Result<f64, _> = de::from_slice_with_options(b"1.50", &options);
+    assert!(matches!(result, Err(DecodeError::Syntax { offset: 0, .. })));
+    #[cfg(feature = "io")]
+    {
+        let result: Result<i64, _> = de::from_reader_with_options(&b"-0"[..], &options);
+        assert!(result.is_err());
+    }
+
+    // Only one exponent spelling is canonical, the one the encoder writes.
+    #[cfg(feature = "float")]
+    {
+        assert_eq!(to_vec(&1e100).unwrap(), b"1e100");
+        let float: f64 = de::from_slice_with_options(b"1e100", &options).unwrap();
+        assert_eq!(float, 1e100);
+        let result: Result<f64, _> = de::from_slice_with_options(b"1e+100", &options);
+        assert!(matches!(result, Err(DecodeError::Syntax { offset: 0, .. })));
+    }
+}
+
+#[cfg(feature = "float")]
+#[test]
+fn test_strict_numbers() {
+    use serde_ipld_dagjson::DecodeOptions;
+
+    let options = DecodeOptions::new().strict_numbers(true);
+    let ipld: Ipld =
+        de::from_slice_with_options(br#"{"b": [1.5, 1e100, -1], "a": 0}"#, &options).unwrap();
+    assert_eq!(
+        ipld.take("b").unwrap().unwrap(),
+        Ipld::List(vec![
+            Ipld::Float(1.5),
+            Ipld::Float(1e100),
+            Ipld::Integer(-1)
+        ])
+    );
+
+    for input in [&b"[1E2]"[..], b"[1.50]", b"[-0]", b"[1e+100]"] {
+        let result: Result<Ipld, _> = de::from_slice_with_options(input, &options);
+        assert!(
+            matches!(result, Err(DecodeError::Syntax { offset: 1, .. })),
+            "{}",
+            String::from_utf8_lossy(input)
+        );
+        #[cfg(feature = "io")]
+        {
+            let result: Result<Ipld, _> = de::from_reader_with_options(input, &options);
+            assert!(matches!(result, Err(DecodeError::Syntax { .. })));
+        }
+        // Without the option, the input is accepted.
+        let _: Ipld = de::from_slice(input).unwrap();
+    }
+
+    // What the encoder writes is always accepted.
+    let floats = [1e100, 1e-100, 1.5, 0.1];
+    let encoded = to_vec(&floats).unwrap();
+    assert_eq!(encoded, b"[1e100,1e-100,1.5,0.1]");
+    let decoded: Vec<f64> = de::from_slice_with_options(&encoded, &options).unwrap();
+    assert_eq!(decoded, floats);
+}
+
+#[test]
+fn test_from_json_value() {
+    use serde_ipld_dagjson::de::from_json_value;
+    use serde_json::json;
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Attachment {
+        name: String,
+        #[serde(with = "serde_bytes")]
+        data: Vec<u8>,
+        source: Cid,
+        size: Option<u64>,
+    }
+
+    let cid = Cid::from_str("bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy").unwrap();
+    let value = json!({
+        "name": "hello.txt",
+        "data": {"/": {"bytes": "aGVsbG8"}},
+        "source": {"/": cid.to_string()},
+        "size": null
+    });
+    let expected = Attachment {
+        name: "hello.txt".to_string(),
+        data: b"hello".to_vec(),
+        source: cid,
+        size: None,
+    };
+    assert_eq!(
+        from_json_value::<Attachment>(value.clone()).unwrap(),
+        expected
+    );
+
+    let ipld: Ipld = from_json_value(value.clone()).unwrap();
+    assert_eq!(
+        ipld,
+        de::from_slice::<Ipld>(&serde_json::to_vec(&value).unwrap()).unwrap()
+    );
+
+    assert!(from_json_value::<Cid>(json!({"/": "invalid"})).is_err());
+    assert!(from_json_value::<Ipld>(json!({"/": {"bytes": "!"}})).is_err());
+}
+
+#[cfg(feature = "float")]
+#[test]
+fn test_strict_kinds() {
+    use std::collections::HashMap;
+
+    use serde_ipld_dagjson::{index::Kind, DecodeOptions};
+
+    #[derive(Debug, serde::Deserialize, PartialEq)]
+    struct Sample {
+        count: u64,
+        ratio: f64,
+    }
+
+    let options = DecodeOptions::new().strict_kinds(true);
+    let sample: Sample =
+        de::from_slice_with_options(br#"{"count": 3, "ratio": 0.5}"#, &options).unwrap();
+    assert_eq!(
+        sample,
+        Sample {
+            count: 3,
+            ratio: 0.5
+        }
+    );
+
+    // By default the integer is accepted as float.
+    let input = br#"{"samples": [{"count": 3, "ratio": 1.0}, {"ratio": 2, "count": 4}]}"#;
+    let _: HashMap<String, Vec<Sample>> = de::from_slice(input).unwrap();
+    let result: Result<HashMap<String, Vec<Sample>>, _> =
+        de::from_slice_with_options(input, &options);
+    assert!(
+        matches!(
+            &result,
+            Err(DecodeError::KindMismatch {
+                path,
+                expected: Kind::Float,
+                found: Kind::Integer,
+            }) if path == "samples/1/ratio"
+        ),
+        "{:?}",
+        result
+    );
+    #[cfg(feature = "io")]
+    {
+        let result: Result<HashMap<String, Vec<Sample>>, _> =
+            de::from_reader_with_options(&input[..], &options);
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "expected float, found integer at path `samples/1/ratio`"
+        );
+    }
+
+    let result: Result<Vec<u32>, _> = de::from_slice_with_options(b"[1, 3.0]", &options);
+    assert!(matches!(
+        result,
+        Err(DecodeError::KindMismatch { path, expected: Kind::Integer, found: Kind::Float })
+            if path == "1"
+    ));
+
+    // Values without a specific type aren't affected.
+    let ipld: Ipld = de::from_slice_with_options(b"[1, 3.0]", &options).unwrap();
+    assert_eq!(ipld, Ipld::List(vec![Ipld::Integer(1), Ipld::Float(3.0)]));
+}
+
+#[test]
+fn test_public_adapters() {
+    use std::fmt;
+
+    use serde::de::{self, Deserializer as _, MapAccess as _, SeqAccess as _};
+    use serde_ipld_dagjson::de::{MapAccess, SeqAccess, Visitor};
+
+    /// An envelope layer, which reads `{"encrypted": [...]}` and hands the access of the list
+    /// to the adapters.
+    struct Envelope;
+
+    impl<'de> de::Visitor<'de> for Envelope {
+        type Value = Vec<Ipld>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str("an envelope")
+        }
+
+        fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+        where
+            A: de::MapAccess<'de>,
+        {
+            let mut map = MapAccess::new(map);
+            let mut payload = Vec::new();
+            while let Some(key) = map.next_key::<String>()? {
+                assert_eq!(key, "encrypted");
+                payload = map.next_value::<Vec<Ipld>>()?;
+            }
+            Ok(payload)
+        }
+
+        fn visit_seq<A>(self, seq: A) -> Result<Self::Value, A::Error>
+        where
+            A: de::SeqAccess<'de>,
+        {
+            let mut seq = SeqAccess::new(seq);
+            let mut payload = Vec::new();
+            while let Some(element) = seq.next_element::<Ipld>()? {
+                payload.push(element);
+            }
+            Ok(payload)
+        }
+    }
+
+    let expected = vec![
+        Ipld::Bytes(vec![1, 2]),
+        Ipld::Link(
+            Cid::from_str("bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy").unwrap(),
+        ),
+    ];
+    let elements = r#"[{"/": {"bytes": "AQI"}}, {"/": "bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy"}]"#;
+
+    let input = format!(r#"{{"encrypted": {}}}"#, elements);
+    let mut json = serde_json::Deserializer::from_str(&input);
+    assert_eq!(json.deserialize_map(Envelope).unwrap(), expected);
+
+    let mut json = serde_json::Deserializer::from_str(elements);
+    assert_eq!(json.deserialize_seq(Envelope).unwrap(), expected);
+
+    // Without the adapters, the reserved maps aren't recognized.
+    let mut json = serde_json::Deserializer::from_str(elements);
+    let plain: Vec<Ipld> = serde::Deserialize::deserialize(&mut json).unwrap();
+    assert!(matches!(plain[0], Ipld::Map(_)));
+
+    // The visitor itself detects reserved maps.
+    let mut json = serde_json::Deserializer::from_str(r#"{"/": {"bytes": "AQI"}}"#);
+    let error = json.deserialize_any(Visitor::new(Envelope)).unwrap_err();
+    assert!(
+        error.to_string().starts_with("invalid type: byte array"),
+        "{}",
+        error
+    );
+}
+
+#[cfg(all(feature = "io", feature = "float"))]
+#[test]
+fn test_seq_iter() {
+    use serde_ipld_dagjson::de::seq_iter;
+
+    let input = br#" [ {"/": {"bytes": "AQI"}}, {"a": [1, "]"]}, -1.5 , "\"],", null, true ] "#;
+    let elements = seq_iter::<Ipld, _>(&input[..])
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(elements, de::from_slice::<Vec<Ipld>>(input).unwrap());
+
+    assert_eq!(seq_iter::<Ipld, _>(&b"[]"[..]).count(), 0);
+
+    // Elements that can't be decoded don't end the iteration.
+    let results = seq_iter::<u8, _>(&b"[1,256,3]"[..]).collect::<Vec<_>>();
+    assert_eq!(results.len(), 3);
+    assert!(results[1].is_err());
+    assert_eq!(results[2].as_ref().unwrap(), &3);
+
+    // Invalid input does.
+    for (input, offset) in [
+        (&b"{}"[..], 0),
+        (b"[1 2]", 3),
+        (b"[1,]", 3),
+        (b"[,1]", 1),
+        (b"[1", 2),
+        (br#"["a]"#, 4),
+    ] {
+        let mut iter = seq_iter::<Ipld, _>(input);
+        let error = iter.find_map(Result::err).unwrap();
+        assert!(
+            matches!(error, DecodeError::Syntax { offset: found, .. } if found == offset),
+            "{}: {:?}",
+            String::from_utf8_lossy(input),
+            error
+        );
+        assert!(iter.next().is_none());
+    }
+    let results = seq_iter::<Ipld, _>(&b"[1] 2"[..]).collect::<Vec<_>>();
+    assert!(matches!(results[1], Err(DecodeError::TrailingData)));
+}
+
+#[cfg(all(feature = "io", feature = "float"))]
+#[test]
+fn test_map_iter() {
+    use serde_ipld_dagjson::de::map_iter;
+
+    let input = br#" { "b" : {"/": {"bytes": "AQI"}}, "a\"": [1, "}"], "c":-1.5 } "#;
+    let entries = map_iter::<Ipld, _>(&input[..])
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    let expected = de::from_slice::<BTreeMap<String, Ipld>>(input).unwrap();
+    assert_eq!(entries[0].0, "b");
+    assert_eq!(entries[1].0, "a\"");
+    assert_eq!(entries.into_iter().collect::<BTreeMap<_, _>>(), expected);
+
+    assert_eq!(map_iter::<Ipld, _>(&b"{}"[..]).count(), 0);
+
+    // Values that can't be decoded don't end the iteration.
+    let results = map_iter::<u8, _>(&br#"{"a":1,"b":256,"c":3}"#[..]).collect::<Vec<_>>();
+    assert_eq!(results.len(), 3);
+    assert!(results[1].is_err());
+    assert_eq!(results[2].as_ref().unwrap(), &("c".to_string(), 3));
+
+    // Invalid input does.
+    for (input, offset) in [
+        (&b"[]"[..], 0),
+        (br#"{"a" 1}"#, 5),
+        (br#"{"a":1 "b":2}"#, 7),
+        (br#"{"a":1,}"#, 7),
+        (br#"{1:2}"#, 1),
+        (
+            br#"{ "/": "bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy"}"#,
+            2,
+        ),
+        (br#"{"a":1"#, 6),
+    ] {
+        let mut iter = map_iter::<Ipld, _>(input);
+        let error = iter.find_map(Result::err).unwrap();
+        assert!(
+            matches!(error, DecodeError::Syntax { offset: found, .. } if found == offset),
+            "{}: {:?}",
+            String::from_utf8_lossy(input),
+            error
+        );
+        assert!(iter.next().is_none());
+    }
+}
+
+#[cfg(all(feature = "io", feature = "float"))]
+#[test]
+fn test_iter_small_reads() {
+    use serde_ipld_dagjson::de::{map_iter, seq_iter};
+    use std::io;
+
+    /// Returns a single byte per read, hence every token spans several reads.
+    struct ByteByByte<'a>(&'a [u8]);
+
+    impl io::Read for ByteByByte<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            match (self.0.split_first(), buf.first_mut()) {
+                (Some((&byte, rest)), Some(first)) => {
+                    *first = byte;
+                    self.0 = rest;
+                    Ok(1)
+                }
+                _ => Ok(0),
+            }
+        }
+    }
+
+    let input = br#"[123456, -1.5e10, "\ud83d\ude00", false, {"a": [null, "]"]}]"#;
+    let elements = seq_iter::<Ipld, _>(ByteByByte(input))
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(elements, de::from_slice::<Vec<Ipld>>(input).unwrap());
+
+    let input = br#"{"\ud83d\ude00": 123456, "b": {"c": true}}"#;
+    let entries = map_iter::<Ipld, _>(ByteByByte(input))
+        .collect::<Result<BTreeMap<_, _>, _>>()
+        .unwrap();
+    assert_eq!(
+        entries,
+        de::from_slice::<BTreeMap<String, Ipld>>(input).unwrap()
+    );
+
+    let error = seq_iter::<Ipld, _>(ByteByByte(br#"[1, "\ud83d"]"#))
+        .find_map(Result::err)
+        .unwrap();
+    assert!(
+        matches!(error, DecodeError::Syntax { offset: 5, .. }),
+        "{:?}",
+        error
+    );
+}
@@ -0,0 +1,89 @@
+#![cfg(feature = "ipld")]
+use std::collections::BTreeMap;
+
+use ipld_core::ipld::Ipld;
+use serde_ipld_dagjson::diff::{diff, render_diff, Change};
+
+#[test]
+fn test_diff() {
+    let old = Ipld::Map(BTreeMap::from([
+        ("a".to_string(), Ipld::List(vec![Ipld::Integer(1)])),
+        ("b".to_string(), Ipld::Bool(true)),
+    ]));
+    let new = Ipld::Map(BTreeMap::from([
+        (
+            "a".to_string(),
+            Ipld::List(vec![Ipld::Integer(2), Ipld::Null]),
+        ),
+        ("c".to_string(), Ipld::String("c".to_string())),
+    ]));
+    let changes = diff(&old, &new);
+    assert_eq!(
+        changes,
+        [
+            Change::Changed {
+                path: "a/0".to_string(),
+                old: Ipld::Integer(1),
+                new: Ipld::Integer(2)
+            },
+            Change::Added {
+                path: "a/1".to_string(),
+                value: Ipld::Null
+            },
+            Change::Removed {
+                path: "b".to_string(),
+                value: Ipld::Bool(true)
+            },
+            Change::Added {
+                path: "c".to_string(),
+                value: Ipld::String("c".to_string())
+            },
+        ]
+    );
+    assert_eq!(
+        changes.iter().map(Change::path).collect::<Vec<_>>(),
+        ["a/0", "a/1", "b", "c"]
+    );
+    assert!(diff(&old, &old).is_empty());
+}
+
+#[test]
+fn test_render_diff() {
+    let old = br#"{
+        "data": {"/": {"bytes": "aGVsbG8"}},
+        "parent": {"/": "bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy"},
+        "meta": {"x": 1}
+    }"#;
+    let new = br#"{
+        "data": {"/": {"bytes": "aGVscA"}},
+        "parent": {"/": "bafyreih4ia2w5w6x3fprtdlqhvs6o5etzixqvgorfdxpwfcqxpvnfwwzia"},
+        "meta": "none"
+    }"#;
+    assert_eq!(
+        render_diff(old, new).unwrap(),
+        r#"--- old
++++ new
+@@ data @@
+-bytes (5 bytes) 68656c6c6f
++bytes (4 bytes) 68656c70
+ first difference at byte 3
+@@ meta @@
+-{"x":1}
++"none"
+@@ parent @@
+-link bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy (raw)
++link bafyreih4ia2w5w6x3fprtdlqhvs6o5etzixqvgorfdxpwfcqxpvnfwwzia (dag-cbor)
+"#
+    );
+
+    assert_eq!(
+        render_diff(b"[1]", b"[2]").unwrap(),
+        "--- old\n+++ new\n@@ 0 @@\n-1\n+2\n"
+    );
+    assert_eq!(
+        render_diff(b"1", b"true").unwrap(),
+        "--- old\n+++ new\n@@ (root) @@\n-1\n+true\n"
+    );
+    assert_eq!(render_diff(b"[1, 2]", b"[1,2]").unwrap(), "");
+    assert!(render_diff(b"[1", b"[1]").is_err());
+}
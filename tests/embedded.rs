@@ -0,0 +1,29 @@
+#![cfg(feature = "embedded-io")]
+use std::collections::BTreeMap;
+
+use serde_ipld_dagjson::{embedded, DecodeError};
+
+#[test]
+fn test_embedded_roundtrip() {
+    let value = BTreeMap::from([("a".to_string(), vec![1, 2]), ("b".to_string(), vec![])]);
+    let mut buf = [0u8; 64];
+    let mut writer = &mut buf[..];
+    embedded::to_writer(&mut writer, &value).unwrap();
+    let len = 64 - writer.len();
+    assert_eq!(&buf[..len], br#"{"a":[1,2],"b":[]}"#);
+
+    let decoded: BTreeMap<String, Vec<u8>> = embedded::from_reader(&buf[..len]).unwrap();
+    assert_eq!(decoded, value);
+}
+
+#[test]
+fn test_embedded_errors() {
+    // The buffer is too small for the whole document.
+    let mut buf = [0u8; 4];
+    assert!(embedded::to_writer(&mut buf[..], &"a long string").is_err());
+
+    let result: Result<Vec<u8>, _> = embedded::from_reader(&b"[1, 2"[..]);
+    assert!(result.is_err());
+    let result: Result<u8, _> = embedded::from_reader(&b"1 2"[..]);
+    assert!(matches!(result, Err(DecodeError::TrailingData)));
+}
@@ -0,0 +1,27 @@
+#![cfg(feature = "erased")]
+
+use std::str::FromStr;
+
+use ipld_core::{cid::Cid, ipld, ipld::Ipld};
+use serde_ipld_dagjson::{erased, to_vec, DecodeError};
+
+#[test]
+fn test_erased_roundtrip() {
+    let cid = Cid::from_str("bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy").unwrap();
+    let data: Ipld = ipld!({"link": cid, "bytes": Ipld::Bytes(vec![1, 2, 3]), "list": [1, 2.5]});
+
+    let encoded = erased::to_vec(&data).unwrap();
+    assert_eq!(encoded, to_vec(&data).unwrap());
+
+    let decoded: Ipld = erased::from_slice(&encoded).unwrap();
+    assert_eq!(decoded, data);
+    let decoded: Ipld = erased::from_reader(&mut &encoded[..]).unwrap();
+    assert_eq!(decoded, data);
+}
+
+#[test]
+fn test_erased_errors() {
+    assert!(erased::to_vec(&f64::NAN).is_err());
+    let trailing: Result<bool, _> = erased::from_slice(b"falsetrailing");
+    assert!(matches!(trailing.unwrap_err(), DecodeError::TrailingData));
+}
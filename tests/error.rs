@@ -0,0 +1,29 @@
+#![cfg(feature = "backtrace")]
+use serde_ipld_dagjson::{de, ser, DecodeError, EncodeError};
+
+#[test]
+fn test_last_backtrace() {
+    std::env::set_var("RUST_LIB_BACKTRACE", "1");
+    assert!(DecodeError::last_backtrace().is_none());
+
+    let result: Result<u8, _> = de::from_slice(b"[1]");
+    assert!(result.is_err());
+    let backtrace = DecodeError::last_backtrace().unwrap();
+    assert_eq!(
+        backtrace.status(),
+        std::backtrace::BacktraceStatus::Captured
+    );
+
+    // Errors recorded by the decode state and the strict parser are traced as well.
+    let options = de::DecodeOptions::new().memory_budget(1);
+    let result: Result<Vec<u8>, _> = de::from_slice_with_options(b"[1, 2]", &options);
+    assert!(matches!(result, Err(DecodeError::MemoryBudgetExceeded(1))));
+    assert!(!std::sync::Arc::ptr_eq(
+        &backtrace,
+        &DecodeError::last_backtrace().unwrap()
+    ));
+
+    assert!(EncodeError::last_backtrace().is_none());
+    assert!(ser::to_vec(&f64::NAN).is_err());
+    assert!(EncodeError::last_backtrace().is_some());
+}
@@ -0,0 +1,32 @@
+#![cfg(feature = "ipld")]
+use serde_ipld_dagjson::explain::explain;
+
+#[cfg(feature = "float")]
+#[test]
+fn test_explain() {
+    let long = "a".repeat(40);
+    let input = format!(
+        r#"[null, true, -1, 1.5, "{}", {{"/": {{"bytes": "AAECAwQFBgcICQoLDA0ODxA"}}}}, {{"/": "bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy"}}, {{}}, []]"#,
+        long
+    );
+    assert_eq!(
+        explain(input.as_bytes()).unwrap(),
+        "\
+list (9 elements)
+  0: null
+  1: bool true
+  2: integer -1
+  3: float 1.5
+  4: string (40 bytes) \"aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa\"...
+  5: bytes (17 bytes) 000102030405060708090a0b0c0d0e0f...
+  6: link bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy (raw)
+  7: map (0 entries)
+  8: list (0 elements)
+"
+    );
+}
+
+#[test]
+fn test_explain_invalid() {
+    assert!(explain(b"[1,").is_err());
+}
@@ -0,0 +1,106 @@
+#![cfg(feature = "extensions")]
+
+use std::collections::BTreeMap;
+
+use ipld_core::ipld::Ipld;
+use serde::{Deserialize, Serialize};
+use serde_ipld_dagjson::{
+    de,
+    ext::{Ext, Extension},
+    to_vec, DecodeOptions,
+};
+
+/// A decimal, represented by its string form.
+#[derive(Clone, Debug, PartialEq)]
+struct Decimal(String);
+
+impl Extension for Decimal {
+    const TAG: &'static str = "decimal";
+
+    fn encode(&self) -> String {
+        self.0.clone()
+    }
+
+    fn decode(value: &str) -> Result<Self, String> {
+        if value.chars().all(|c| c.is_ascii_digit() || c == '.') {
+            Ok(Self(value.to_string()))
+        } else {
+            Err(format!("invalid decimal `{}`", value))
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+struct Flag;
+
+impl Extension for Flag {
+    const TAG: &'static str = "flag";
+
+    fn encode(&self) -> String {
+        String::new()
+    }
+
+    fn decode(_value: &str) -> Result<Self, String> {
+        Ok(Self)
+    }
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+struct Invoice {
+    amount: Ext<Decimal>,
+    items: Vec<String>,
+}
+
+#[test]
+fn test_roundtrip() {
+    let invoice = Invoice {
+        amount: Ext(Decimal("12.50".to_string())),
+        items: vec!["a".to_string()],
+    };
+    let encoded = to_vec(&invoice).unwrap();
+    assert_eq!(
+        encoded,
+        br#"{"amount":{"/":{"ext":"decimal","value":"12.50"}},"items":["a"]}"#
+    );
+
+    let options = DecodeOptions::new().extension::<Decimal>();
+    let decoded: Invoice = de::from_slice_with_options(&encoded, &options).unwrap();
+    assert_eq!(decoded, invoice);
+
+    // Other deserializers see the plain wrapper map.
+    let decoded: Invoice = serde_json::from_slice(&encoded).unwrap();
+    assert_eq!(decoded, invoice);
+}
+
+#[test]
+fn test_unregistered() {
+    let encoded = to_vec(&Ext(Decimal("1".to_string()))).unwrap();
+
+    let error = de::from_slice::<Ext<Decimal>>(&encoded).unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("extension `decimal` isn't registered"));
+    let options = DecodeOptions::new().extension::<Flag>();
+    assert!(de::from_slice_with_options::<Ext<Decimal>>(&encoded, &options).is_err());
+
+    // Extensions are never decoded as part of the data model.
+    let options = DecodeOptions::new().extension::<Decimal>();
+    assert!(de::from_slice_with_options::<Ipld>(&encoded, &options).is_err());
+    assert!(de::from_slice_with_options::<BTreeMap<String, Ipld>>(&encoded, &options).is_err());
+}
+
+#[test]
+fn test_wrong_tag_and_value() {
+    let options = DecodeOptions::new()
+        .extension::<Decimal>()
+        .extension::<Flag>();
+    let flag = to_vec(&Ext(Flag)).unwrap();
+    let error = de::from_slice_with_options::<Ext<Decimal>>(&flag, &options).unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("expected extension `decimal`, found `flag`"));
+
+    let invalid = br#"{"/":{"ext":"decimal","value":"abc"}}"#;
+    let error = de::from_slice_with_options::<Ext<Decimal>>(invalid, &options).unwrap_err();
+    assert!(error.to_string().contains("invalid decimal `abc`"));
+}
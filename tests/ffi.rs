@@ -0,0 +1,71 @@
+#![cfg(feature = "ffi")]
+use std::{ptr, slice};
+
+use serde_ipld_dagjson::ffi::{
+    dagjson_decode_to_json, dagjson_encode, dagjson_free, dagjson_links, DAGJSON_ERR_DECODE,
+    DAGJSON_ERR_NULL_POINTER, DAGJSON_ERR_TRAILING_DATA, DAGJSON_OK,
+};
+
+type Function = unsafe extern "C" fn(*const u8, usize, *mut *mut u8, *mut usize) -> i32;
+
+fn call(function: Function, input: &[u8]) -> Result<String, i32> {
+    let mut out = ptr::null_mut();
+    let mut out_len = 0;
+    let status = unsafe { function(input.as_ptr(), input.len(), &mut out, &mut out_len) };
+    if status != DAGJSON_OK {
+        assert!(out.is_null());
+        return Err(status);
+    }
+    let output = unsafe { slice::from_raw_parts(out, out_len + 1) };
+    assert_eq!(output[out_len], 0, "output is NUL-terminated");
+    let string = String::from_utf8(output[..out_len].to_vec()).unwrap();
+    unsafe { dagjson_free(out, out_len) };
+    Ok(string)
+}
+
+const LINK: &str = "bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy";
+
+#[test]
+fn test_ffi_encode() {
+    let input = format!(r#"{{ "b": {{"/": "{}"}}, "a": [1, 2] }}"#, LINK);
+    assert_eq!(
+        call(dagjson_encode, input.as_bytes()).unwrap(),
+        format!(r#"{{"a":[1,2],"b":{{"/":"{}"}}}}"#, LINK)
+    );
+    assert_eq!(call(dagjson_encode, b"{").unwrap_err(), DAGJSON_ERR_DECODE);
+}
+
+#[test]
+fn test_ffi_decode_to_json() {
+    let input = format!(
+        r#"{{"b": {{"/": {{"bytes": "AQI"}}}}, "a": {{"/": "{}"}}}}"#,
+        LINK
+    );
+    assert_eq!(
+        call(dagjson_decode_to_json, input.as_bytes()).unwrap(),
+        format!(r#"{{"a":"{}","b":"AQI"}}"#, LINK)
+    );
+    assert_eq!(
+        call(dagjson_decode_to_json, b"1 2").unwrap_err(),
+        DAGJSON_ERR_TRAILING_DATA
+    );
+}
+
+#[test]
+fn test_ffi_links() {
+    let input = format!(r#"[{{"/": "{0}"}}, {{"a": {{"/": "{0}"}}}}]"#, LINK);
+    assert_eq!(
+        call(dagjson_links, input.as_bytes()).unwrap(),
+        format!("{0}\n{0}", LINK)
+    );
+    assert_eq!(call(dagjson_links, b"[]").unwrap(), "");
+}
+
+#[test]
+fn test_ffi_null_pointers() {
+    let mut out_len = 0;
+    let status = unsafe { dagjson_encode(ptr::null(), 1, ptr::null_mut(), &mut out_len) };
+    assert_eq!(status, DAGJSON_ERR_NULL_POINTER);
+    // Freeing a null pointer does nothing.
+    unsafe { dagjson_free(ptr::null_mut(), 0) };
+}
@@ -0,0 +1,30 @@
+// Generated from an IPLD schema by serde_ipld_dagjson.
+
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+pub struct Entry {
+    #[serde(rename = "createdAt")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<serde_bytes::ByteBuf>,
+    pub meta: std::collections::BTreeMap<String, Option<ipld_core::ipld::Ipld>>,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "serde_ipld_dagjson::maybe::Maybe::is_absent")]
+    pub note: serde_ipld_dagjson::maybe::Maybe<String>,
+    pub size: Option<i64>,
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<ipld_core::cid::Cid>,
+    pub r#type: EntryType,
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+#[serde(untagged)]
+pub enum EntryType {
+    Int(i64),
+    Float(f64),
+    String(#[serde(with = "serde_ipld_dagjson::union::string")] String),
+    Bytes(serde_bytes::ByteBuf),
+}
+
+pub type Entries = Vec<Entry>;
@@ -0,0 +1,66 @@
+#![cfg(feature = "codec")]
+use ipld_core::ipld::Ipld;
+use serde_ipld_dagjson::generate::{self, GenConfig};
+
+fn count_links(ipld: &Ipld) -> usize {
+    ipld.iter()
+        .filter(|node| matches!(node, Ipld::Link(_)))
+        .count()
+}
+
+fn depth(ipld: &Ipld) -> usize {
+    match ipld {
+        Ipld::List(list) => 1 + list.iter().map(depth).max().unwrap_or(0),
+        Ipld::Map(map) => 1 + map.values().map(depth).max().unwrap_or(0),
+        _ => 0,
+    }
+}
+
+#[test]
+fn test_gen_deterministic() {
+    let config = GenConfig::new();
+    assert_eq!(
+        generate::document(1, &config),
+        generate::document(1, &config)
+    );
+    assert_ne!(
+        generate::document(1, &config),
+        generate::document(2, &config)
+    );
+}
+
+#[test]
+fn test_gen_valid() {
+    let config = GenConfig::new().max_depth(6).link_density(0.3);
+    for seed in 0..100 {
+        let document = generate::document(seed, &config);
+        let decoded: Ipld = serde_ipld_dagjson::from_slice(&document).unwrap();
+        assert_eq!(decoded, generate::ipld(seed, &config));
+        assert!(depth(&decoded) <= 6);
+    }
+}
+
+#[test]
+fn test_gen_config() {
+    let scalar = generate::ipld(1, &GenConfig::new().max_depth(0));
+    assert!(!matches!(scalar, Ipld::List(_) | Ipld::Map(_)));
+
+    let all_links = GenConfig::new().max_len(16).link_density(1.0);
+    let no_links = GenConfig::new().max_len(16).link_density(0.0);
+    let (mut with, mut without) = (0, 0);
+    for seed in 0..20 {
+        with += count_links(&generate::ipld(seed, &all_links));
+        without += count_links(&generate::ipld(seed, &no_links));
+    }
+    assert!(with > 0);
+    assert_eq!(without, 0);
+
+    let small_bytes = GenConfig::new().max_bytes_len(2).link_density(0.0);
+    for seed in 0..20 {
+        for node in generate::ipld(seed, &small_bytes).iter() {
+            if let Ipld::Bytes(bytes) = node {
+                assert!(bytes.len() <= 2);
+            }
+        }
+    }
+}
@@ -0,0 +1,281 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    str::FromStr,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use ipld_core::cid::Cid;
+use serde::{Deserialize, Serialize};
+use serde_ipld_dagjson::helpers;
+
+const CID: &str = "bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy";
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+struct Links {
+    #[serde(with = "helpers::cid_as_string")]
+    plain: Cid,
+    #[serde(with = "helpers::opt_cid")]
+    parent: Option<Cid>,
+    #[serde(with = "helpers::opt_cid")]
+    missing: Option<Cid>,
+    #[serde(with = "helpers::vec_cid")]
+    children: Vec<Cid>,
+    #[serde(with = "helpers::btreemap_values_cid")]
+    named: BTreeMap<String, Cid>,
+}
+
+fn links() -> Links {
+    let cid = Cid::from_str(CID).unwrap();
+    Links {
+        plain: cid,
+        parent: Some(cid),
+        missing: None,
+        children: vec![cid, cid],
+        named: BTreeMap::from([("a".to_string(), cid)]),
+    }
+}
+
+#[test]
+fn test_helpers_foreign_format() {
+    let json = serde_json::to_string(&links()).unwrap();
+    let expected = format!(
+        r#"{{"plain":"{0}","parent":{{"/":"{0}"}},"missing":null,"children":[{{"/":"{0}"}},{{"/":"{0}"}}],"named":{{"a":{{"/":"{0}"}}}}}}"#,
+        CID
+    );
+    assert_eq!(json, expected);
+    let decoded: Links = serde_json::from_str(&json).unwrap();
+    assert_eq!(decoded, links());
+}
+
+#[test]
+fn test_helpers_dagjson() {
+    let encoded = serde_ipld_dagjson::to_vec(&links()).unwrap();
+    assert_eq!(encoded, serde_json::to_vec(&links()).unwrap());
+    let decoded: Links = serde_ipld_dagjson::from_slice(&encoded).unwrap();
+    assert_eq!(decoded, links());
+}
+
+#[test]
+fn test_helpers_invalid_link() {
+    let json = format!(
+        r#"{{"plain":"{0}","parent":{{"/":"invalid"}},"missing":null,"children":[],"named":{{}}}}"#,
+        CID
+    );
+    assert!(serde_json::from_str::<Links>(&json).is_err());
+    assert!(serde_ipld_dagjson::from_slice::<Links>(json.as_bytes()).is_err());
+}
+
+#[test]
+fn test_cid_key_map() {
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    struct Metadata {
+        #[serde(with = "helpers::cid_key_map")]
+        sizes: HashMap<Cid, u64>,
+        #[serde(with = "helpers::cid_key_map")]
+        names: BTreeMap<Cid, String>,
+    }
+
+    let cid = Cid::from_str(CID).unwrap();
+    let metadata = Metadata {
+        sizes: HashMap::from([(cid, 42)]),
+        names: BTreeMap::from([(cid, "block".to_string())]),
+    };
+    let encoded = serde_ipld_dagjson::to_vec(&metadata).unwrap();
+    let expected = format!(r#"{{"sizes":{{"{0}":42}},"names":{{"{0}":"block"}}}}"#, CID);
+    assert_eq!(String::from_utf8(encoded.clone()).unwrap(), expected);
+    let decoded: Metadata = serde_ipld_dagjson::from_slice(&encoded).unwrap();
+    assert_eq!(decoded, metadata);
+    let decoded: Metadata = serde_json::from_slice(&encoded).unwrap();
+    assert_eq!(decoded, metadata);
+
+    let invalid = br#"{"sizes":{"invalid":1},"names":{}}"#;
+    assert!(serde_ipld_dagjson::from_slice::<Metadata>(invalid).is_err());
+}
+
+#[test]
+fn test_cid_key_map_sorted() {
+    use ipld_core::cid::multihash::Multihash;
+
+    #[derive(Serialize)]
+    struct Sizes(#[serde(with = "helpers::cid_key_map")] HashMap<Cid, u64>);
+
+    let sizes = Sizes(
+        (0..8u8)
+            .map(|byte| {
+                let hash = Multihash::wrap(0x12, &[byte; 32]).unwrap();
+                (Cid::new_v1(0x55, hash), u64::from(byte))
+            })
+            .collect(),
+    );
+    let mut keys: Vec<_> = sizes.0.keys().map(Cid::to_string).collect();
+    keys.sort();
+    let entries: Vec<_> = keys
+        .iter()
+        .map(|key| format!(r#""{}":{}"#, key, sizes.0[&Cid::from_str(key).unwrap()]))
+        .collect();
+    let expected = format!("{{{}}}", entries.join(","));
+    let encoded = serde_ipld_dagjson::to_vec(&sizes).unwrap();
+    assert_eq!(String::from_utf8(encoded).unwrap(), expected);
+}
+
+#[test]
+fn test_timestamps() {
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    struct Event {
+        #[serde(with = "helpers::timestamp_seconds")]
+        seconds: SystemTime,
+        #[serde(with = "helpers::timestamp_millis")]
+        millis: SystemTime,
+    }
+
+    let time = UNIX_EPOCH + Duration::from_millis(1_700_000_000_123);
+    let event = Event {
+        seconds: time,
+        millis: time,
+    };
+    let encoded = serde_ipld_dagjson::to_vec(&event).unwrap();
+    assert_eq!(encoded, br#"{"seconds":1700000000,"millis":1700000000123}"#);
+    let decoded: Event = serde_ipld_dagjson::from_slice(&encoded).unwrap();
+    assert_eq!(
+        decoded.seconds,
+        UNIX_EPOCH + Duration::from_secs(1_700_000_000)
+    );
+    assert_eq!(decoded.millis, time);
+
+    // Times before the epoch are truncated towards the past.
+    let before = UNIX_EPOCH - Duration::from_micros(1_500);
+    let event = Event {
+        seconds: before,
+        millis: before,
+    };
+    let encoded = serde_ipld_dagjson::to_vec(&event).unwrap();
+    assert_eq!(encoded, br#"{"seconds":-1,"millis":-2}"#);
+    let decoded: Event = serde_ipld_dagjson::from_slice(&encoded).unwrap();
+    assert_eq!(decoded.millis, UNIX_EPOCH - Duration::from_millis(2));
+
+    assert!(serde_ipld_dagjson::from_slice::<Event>(br#"{"seconds":1.5,"millis":0}"#).is_err());
+}
+
+#[test]
+fn test_tuple_repr() {
+    use helpers::tuple_repr::TupleRepr;
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    struct Entry {
+        name: String,
+        size: u64,
+        link: Cid,
+    }
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    struct Directory {
+        #[serde(with = "helpers::tuple_repr")]
+        entry: Entry,
+        entries: Vec<TupleRepr<Entry>>,
+    }
+
+    let cid = Cid::from_str(CID).unwrap();
+    let entry = || Entry {
+        name: "a".to_string(),
+        size: 1,
+        link: cid,
+    };
+    let directory = Directory {
+        entry: entry(),
+        entries: vec![TupleRepr(entry())],
+    };
+    let encoded = serde_ipld_dagjson::to_vec(&directory).unwrap();
+    let expected = format!(
+        r#"{{"entry":["a",1,{{"/":"{0}"}}],"entries":[["a",1,{{"/":"{0}"}}]]}}"#,
+        CID
+    );
+    assert_eq!(String::from_utf8(encoded.clone()).unwrap(), expected);
+    let decoded: Directory = serde_ipld_dagjson::from_slice(&encoded).unwrap();
+    assert_eq!(decoded, directory);
+
+    // Lists of the wrong length and non-structs are rejected.
+    assert!(serde_ipld_dagjson::from_slice::<TupleRepr<Entry>>(br#"["a",1]"#).is_err());
+    assert!(serde_ipld_dagjson::to_vec(&TupleRepr(1)).is_err());
+}
+
+#[test]
+fn test_tuple_repr_skipped_field() {
+    #[derive(Serialize)]
+    struct Skipping {
+        a: u8,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        b: Option<u8>,
+    }
+
+    let value = helpers::tuple_repr::TupleRepr(Skipping { a: 1, b: None });
+    assert!(serde_ipld_dagjson::to_vec(&value).is_err());
+}
+
+#[test]
+fn test_stringjoin() {
+    use helpers::stringjoin::StringJoin;
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    struct Range {
+        start: String,
+        end: String,
+    }
+
+    let range = StringJoin::<_, '-'>(Range {
+        start: "a".to_string(),
+        end: "z".to_string(),
+    });
+    let encoded = serde_ipld_dagjson::to_vec(&range).unwrap();
+    assert_eq!(encoded, br#""a-z""#);
+    let decoded: StringJoin<Range, '-'> = serde_ipld_dagjson::from_slice(&encoded).unwrap();
+    assert_eq!(decoded, range);
+
+    // Wrong number of fields.
+    assert!(serde_ipld_dagjson::from_slice::<StringJoin<Range, '-'>>(br#""a-b-c""#).is_err());
+    assert!(serde_ipld_dagjson::from_slice::<StringJoin<Range, '-'>>(br#""a""#).is_err());
+
+    // Fields must not contain the delimiter.
+    let invalid = StringJoin::<_, '-'>(Range {
+        start: "a-b".to_string(),
+        end: "z".to_string(),
+    });
+    assert!(serde_ipld_dagjson::to_vec(&invalid).is_err());
+
+    #[derive(Serialize)]
+    struct Numbers {
+        a: u8,
+    }
+    assert!(serde_ipld_dagjson::to_vec(&StringJoin::<_, '-'>(Numbers { a: 1 })).is_err());
+}
+
+#[test]
+fn test_listpairs() {
+    use helpers::listpairs::ListPairs;
+
+    #[derive(Debug, PartialEq, Deserialize, Serialize)]
+    struct Table {
+        #[serde(with = "helpers::listpairs")]
+        sizes: HashMap<String, u64>,
+        links: ListPairs<BTreeMap<String, Cid>>,
+    }
+
+    let cid = Cid::from_str(CID).unwrap();
+    let table = Table {
+        sizes: HashMap::from([("a".to_string(), 1)]),
+        links: ListPairs(BTreeMap::from([
+            ("x".to_string(), cid),
+            ("y".to_string(), cid),
+        ])),
+    };
+    let encoded = serde_ipld_dagjson::to_vec(&table).unwrap();
+    let expected = format!(
+        r#"{{"sizes":[["a",1]],"links":[["x",{{"/":"{0}"}}],["y",{{"/":"{0}"}}]]}}"#,
+        CID
+    );
+    assert_eq!(String::from_utf8(encoded.clone()).unwrap(), expected);
+    let decoded: Table = serde_ipld_dagjson::from_slice(&encoded).unwrap();
+    assert_eq!(decoded, table);
+
+    let invalid = br#"{"sizes":[["a",1,2]],"links":[]}"#;
+    assert!(serde_ipld_dagjson::from_slice::<Table>(invalid).is_err());
+}
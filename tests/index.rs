@@ -0,0 +1,116 @@
+use std::str::FromStr;
+
+use ipld_core::cid::Cid;
+use serde_bytes::ByteBuf;
+use serde_ipld_dagjson::{
+    index::{IndexedDocument, Kind},
+    DecodeError,
+};
+
+#[test]
+fn test_index_paths() {
+    let data = br#"{"a": [1, 2.5, {"b": null}], "c\"d": "e", "link": {"/": "bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy"}, "bytes": {"/": {"bytes": "dm14"}}, "map": {"/": 1}}"#;
+    let document = IndexedDocument::new(data).unwrap();
+
+    let kinds = document
+        .nodes()
+        .iter()
+        .map(|node| (node.path.as_str(), node.kind))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        vec![
+            ("", Kind::Map),
+            ("a", Kind::List),
+            ("a/0", Kind::Integer),
+            ("a/1", Kind::Float),
+            ("a/2", Kind::Map),
+            ("a/2/b", Kind::Null),
+            ("c\"d", Kind::String),
+            ("link", Kind::Link),
+            ("bytes", Kind::Bytes),
+            ("map", Kind::Map),
+            ("map/~1", Kind::Integer),
+        ]
+    );
+
+    assert_eq!(document.raw("a/2").unwrap(), br#"{"b": null}"#);
+    #[cfg(feature = "float")]
+    assert_eq!(document.get::<f64>("a/1").unwrap(), Some(2.5));
+    assert_eq!(document.get::<String>("c\"d").unwrap().unwrap(), "e");
+    assert_eq!(
+        document.get::<Cid>("link").unwrap().unwrap(),
+        Cid::from_str("bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy").unwrap()
+    );
+    assert_eq!(
+        document.get::<ByteBuf>("bytes").unwrap().unwrap(),
+        ByteBuf::from(b"vmx")
+    );
+    assert_eq!(document.get::<bool>("missing").unwrap(), None);
+}
+
+#[test]
+fn test_index_invalid() {
+    assert!(IndexedDocument::new(b"[1, 2").is_err());
+    assert!(IndexedDocument::new(b"[01]").is_err());
+    assert!(IndexedDocument::new(b"{1: 2}").is_err());
+    assert!(IndexedDocument::new(br#""\x""#).is_err());
+    assert!(matches!(
+        IndexedDocument::new(b"true false").unwrap_err(),
+        DecodeError::TrailingData
+    ));
+    let deep = "[".repeat(200) + &"]".repeat(200);
+    assert!(IndexedDocument::new(deep.as_bytes()).is_err());
+}
+
+#[test]
+fn test_index_escaped_paths() {
+    let input = br#"{"a/b": 1, "a": {"b": 2}, "~": 3, "\u0063": 4}"#;
+    let document = IndexedDocument::new(input).unwrap();
+    assert_eq!(document.get::<u8>("a~1b").unwrap(), Some(1));
+    assert_eq!(document.get::<u8>("a/b").unwrap(), Some(2));
+    assert_eq!(document.get::<u8>("~0").unwrap(), Some(3));
+    assert_eq!(document.get::<u8>("~").unwrap(), None);
+    let paths = document
+        .nodes()
+        .iter()
+        .map(|node| node.path.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(paths, ["", "a~1b", "a", "a/b", "~0", "c"]);
+
+    // Empty keys are escaped, hence an empty key of the root map doesn't have the root's path.
+    let input = br#"{"": {"": 1, "b": 2}, "b": 3, "~2": 4}"#;
+    let document = IndexedDocument::new(input).unwrap();
+    assert_eq!(document.node("").unwrap().span, 0..input.len());
+    assert_eq!(document.get::<u8>("~2/~2").unwrap(), Some(1));
+    assert_eq!(document.get::<u8>("~2/b").unwrap(), Some(2));
+    assert_eq!(document.get::<u8>("b").unwrap(), Some(3));
+    assert_eq!(document.get::<u8>("~02").unwrap(), Some(4));
+    assert_eq!(
+        document.nodes().len(),
+        document
+            .nodes()
+            .iter()
+            .map(|node| &node.path)
+            .collect::<std::collections::HashSet<_>>()
+            .len()
+    );
+    // Invalid input is rejected just like by the strict parser.
+    assert!(IndexedDocument::new(br#""\ud800""#).is_err());
+}
+
+#[test]
+fn test_dump() {
+    let input = br#"{"a":{"/":"bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy"},"b":[[true]]}"#;
+    let document = IndexedDocument::new(input).unwrap();
+    assert_eq!(
+        document.dump(),
+        "\
+0..86  map
+5..72    link    a = {\"/\":\"bafkreibme22gw2h7y2h7tg2fhqotaqjuc...
+77..85   list    b
+78..84     list    b/0
+79..83       bool    b/0/0 = true
+"
+    );
+}
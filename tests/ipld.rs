@@ -0,0 +1,163 @@
+#![cfg(feature = "ipld")]
+use std::str::FromStr;
+
+use ipld_core::{cid::Cid, ipld, ipld::Ipld};
+use serde_ipld_dagjson::{de, ipld as dagjson_ipld, to_vec, DecodeError};
+
+#[cfg(feature = "float")]
+#[test]
+fn test_ipld_roundtrip() {
+    let cid = Cid::from_str("bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy").unwrap();
+    let ipld = ipld!({
+        "null": null,
+        "bool": true,
+        "integer": -23567997,
+        "float": 12.3,
+        "string": "foobar",
+        "bytes": Ipld::Bytes(b"vmx".to_vec()),
+        "list": [1, [2, [3]]],
+        "link": cid,
+        "empty": {},
+    });
+
+    let encoded = dagjson_ipld::to_vec(&ipld).unwrap();
+    assert_eq!(encoded, to_vec(&ipld).unwrap());
+
+    let decoded = dagjson_ipld::from_slice(&encoded).unwrap();
+    assert_eq!(decoded, ipld);
+    assert_eq!(decoded, de::from_slice::<Ipld>(&encoded).unwrap());
+
+    #[cfg(feature = "io")]
+    {
+        let decoded_reader = dagjson_ipld::from_reader(&encoded[..]).unwrap();
+        assert_eq!(decoded_reader, ipld);
+    }
+}
+
+#[test]
+fn test_ipld_non_finite_float() {
+    let encoded = dagjson_ipld::to_vec(&Ipld::Float(f64::NAN));
+    assert!(encoded.is_err(), "Only finite numbers are supported.");
+}
+
+#[test]
+fn test_ipld_reserved_trailing() {
+    let data =
+        br#"{"/": "bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy", "trailing": 123}"#;
+    assert!(dagjson_ipld::from_slice(data).is_err());
+}
+
+#[test]
+fn test_ipld_trailing_bytes() {
+    let result = dagjson_ipld::from_slice(b"falsetrailing");
+    assert!(matches!(result.unwrap_err(), DecodeError::TrailingData));
+}
+
+#[test]
+fn test_ipld_duplicate_keys() {
+    let data = br#"{"a": 1, "a": 2}"#;
+    // Same behaviour as the generic code path.
+    assert!(de::from_slice::<Ipld>(data).is_err());
+    assert!(dagjson_ipld::from_slice(data).is_err());
+}
+
+#[test]
+fn test_from_slice_interned() {
+    use std::sync::Arc;
+
+    use serde_ipld_dagjson::ipld::{CidInterner, InternedIpld};
+
+    let first =
+        Cid::from_str("bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy").unwrap();
+    let second =
+        Cid::from_str("bafyreibvjvcv745gig4mvqs4hctx4zfkono4rjejm2ta6gtyzkqxfjeily").unwrap();
+    let ipld = ipld!({
+        "a": [first, second, first],
+        "b": {"c": first, "d": Ipld::Bytes(b"vmx".to_vec())},
+    });
+    let encoded = dagjson_ipld::to_vec(&ipld).unwrap();
+
+    let mut interner = CidInterner::new();
+    let decoded = dagjson_ipld::from_slice_interned(&encoded, &mut interner).unwrap();
+    assert_eq!(interner.len(), 2);
+
+    let InternedIpld::Map(map) = &decoded else {
+        panic!("expected a map");
+    };
+    let InternedIpld::List(list) = &map["a"] else {
+        panic!("expected a list");
+    };
+    match (&list[0], &list[2]) {
+        (InternedIpld::Link(a), InternedIpld::Link(b)) => assert!(Arc::ptr_eq(a, b)),
+        _ => panic!("expected links"),
+    }
+    assert_eq!(Ipld::from(decoded.clone()), ipld);
+
+    // Links are shared across decodes with the same interner.
+    let other = dagjson_ipld::from_slice_interned(&to_vec(&first).unwrap(), &mut interner).unwrap();
+    assert_eq!(interner.len(), 2);
+    match (&list[0], other) {
+        (InternedIpld::Link(a), InternedIpld::Link(b)) => assert!(Arc::ptr_eq(a, &b)),
+        _ => panic!("expected links"),
+    }
+}
+
+#[test]
+fn test_interned_roundtrip() {
+    use serde_ipld_dagjson::ipld::CidInterner;
+
+    let cid = Cid::from_str("bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy").unwrap();
+    let ipld = ipld!({"a": [cid, 1, "b", null], "c": Ipld::Bytes(b"vmx".to_vec())});
+    let encoded = to_vec(&ipld).unwrap();
+    let interned = dagjson_ipld::from_slice_interned(&encoded, &mut CidInterner::new()).unwrap();
+    assert_eq!(to_vec(&interned).unwrap(), encoded);
+}
+
+#[test]
+fn test_interned_invalid() {
+    use serde_ipld_dagjson::{ipld::CidInterner, limits::Limits, DecodeOptions};
+
+    for input in [
+        &br#"{"/": 5}"#[..],
+        br#"{"a": 1, "a": 2}"#,
+        br#"{"/": "notacid"}"#,
+    ] {
+        let interned = dagjson_ipld::from_slice_interned(input, &mut CidInterner::new());
+        assert!(interned.is_err());
+        assert!(dagjson_ipld::from_slice(input).is_err());
+    }
+
+    let options = DecodeOptions::new().limits(Limits::new().max_depth(2));
+    let result = dagjson_ipld::from_slice_interned_with_options(
+        b"[[[1]]]",
+        &mut CidInterner::new(),
+        &options,
+    );
+    assert!(matches!(result, Err(DecodeError::LimitExceeded(_))));
+}
+
+#[cfg(feature = "float")]
+#[test]
+fn test_from_slice_ordered() {
+    use serde_ipld_dagjson::ipld::OrderedIpld;
+
+    let input = br#"{"z":{"/":"bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy"},"b":[{"y":1,"x":{"/":{"bytes":"dm14"}}}],"a":1.5}"#;
+    let ordered = dagjson_ipld::from_slice_ordered(input).unwrap();
+    let OrderedIpld::Map(entries) = &ordered else {
+        panic!("expected a map");
+    };
+    let keys: Vec<&str> = entries.iter().map(|(key, _)| key.as_str()).collect();
+    assert_eq!(keys, ["z", "b", "a"]);
+    assert_eq!(dagjson_ipld::to_vec_ordered(&ordered).unwrap(), input);
+
+    // Converting to `Ipld` sorts the keys.
+    let ipld = Ipld::from(ordered);
+    assert_eq!(ipld, dagjson_ipld::from_slice(input).unwrap());
+    let sorted = OrderedIpld::from(ipld.clone());
+    assert_eq!(
+        dagjson_ipld::to_vec_ordered(&sorted).unwrap(),
+        dagjson_ipld::to_vec(&ipld).unwrap()
+    );
+
+    assert!(dagjson_ipld::from_slice_ordered(br#"{"b": 1, "a": 2, "b": 3}"#).is_err());
+}
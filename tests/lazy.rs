@@ -0,0 +1,135 @@
+use std::str::FromStr;
+
+use ipld_core::{cid::Cid, ipld::Ipld};
+use serde::{Deserialize, Serialize};
+use serde_ipld_dagjson::lazy::LazyValue;
+
+#[test]
+fn test_lazy_map() {
+    let data = br#"{"a": [1, {"b": true}], "link": {"/": "bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy"}, "bytes": {"/": {"bytes": "dm14"}}}"#;
+    let value = LazyValue::from_slice(data).unwrap();
+    assert_eq!(value.len().unwrap(), Some(3));
+    assert_eq!(
+        value.keys().unwrap().unwrap().collect::<Vec<_>>(),
+        vec!["a", "bytes", "link"]
+    );
+
+    let list = value.get("a").unwrap().unwrap();
+    assert_eq!(list.len().unwrap(), Some(2));
+    let nested = list.get_index(1).unwrap().unwrap();
+    assert_eq!(nested.as_raw(), r#"{"b": true}"#);
+    let flag: bool = nested.get("b").unwrap().unwrap().decode().unwrap();
+    assert!(flag);
+    assert!(list.get_index(2).unwrap().is_none());
+
+    let link = value.get("link").unwrap().unwrap();
+    assert_eq!(link.len().unwrap(), None);
+    let cid: Cid = link.decode().unwrap();
+    assert_eq!(
+        cid,
+        Cid::from_str("bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy").unwrap()
+    );
+
+    let bytes: Ipld = value.get("bytes").unwrap().unwrap().decode().unwrap();
+    assert_eq!(bytes, Ipld::Bytes(b"vmx".to_vec()));
+
+    assert!(value.get("missing").unwrap().is_none());
+    assert!(value.get_index(0).unwrap().is_none());
+}
+
+#[test]
+fn test_lazy_invalid() {
+    assert!(LazyValue::from_slice(b"[1, 2").is_err());
+    assert!(LazyValue::from_slice(b"true false").is_err());
+}
+
+#[cfg(all(feature = "ipld", feature = "float"))]
+#[test]
+fn test_lazy_ipld() {
+    use serde_ipld_dagjson::lazy::LazyIpld;
+
+    let data = br#"{"link": {"/": "bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy"}, "bytes": {"/": {"bytes": "dm14"}}, "invalid": {"/": "notacid"}, "list": [1, 2.5, null, "a"]}"#;
+    let value: LazyIpld = serde_ipld_dagjson::from_slice(data).unwrap();
+    let LazyIpld::Map(map) = &value else {
+        panic!("expected a map");
+    };
+    let LazyIpld::Link(link) = &map["link"] else {
+        panic!("expected a link");
+    };
+    assert_eq!(
+        link.as_str(),
+        "bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy"
+    );
+    assert_eq!(
+        link.cid().unwrap(),
+        Cid::from_str("bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy").unwrap()
+    );
+    assert_eq!(map["bytes"], LazyIpld::Bytes(b"vmx".to_vec()));
+
+    // Invalid CIDs are only detected on access.
+    let LazyIpld::Link(invalid) = &map["invalid"] else {
+        panic!("expected a link");
+    };
+    assert!(invalid.cid().is_err());
+    assert!(value.clone().into_ipld().is_err());
+
+    let valid = br#"[{"/": "bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy"}, {"a": {"/": {"bytes": "dm14"}}}]"#;
+    let lazy: LazyIpld = serde_ipld_dagjson::from_slice(valid).unwrap();
+    let eager: Ipld = serde_ipld_dagjson::from_slice(valid).unwrap();
+    assert_eq!(lazy.into_ipld().unwrap(), eager);
+}
+
+#[cfg(feature = "ipld")]
+#[test]
+fn test_lazy_ipld_invalid() {
+    use serde_ipld_dagjson::lazy::LazyIpld;
+
+    for input in [
+        &br#"{"/": 5}"#[..],
+        br#"{"/": {"bytes": 5}}"#,
+        br#"{"a": 1, "a": 2}"#,
+        br#"[{"b": null, "b": null}]"#,
+    ] {
+        let lazy = serde_ipld_dagjson::from_slice::<LazyIpld>(input).unwrap_err();
+        let eager = serde_ipld_dagjson::ipld::from_slice(input).unwrap_err();
+        assert_eq!(lazy.to_string(), eager.to_string());
+    }
+}
+
+#[test]
+fn test_lazy_bytes() {
+    use serde_ipld_dagjson::lazy::LazyBytes;
+
+    #[derive(Debug, Deserialize, Serialize)]
+    struct File<'a> {
+        #[serde(borrow)]
+        data: LazyBytes<'a>,
+        name: &'a str,
+    }
+
+    let data = br#"{"data":{"/":{"bytes":"aGVsbG8gd29ybGQ"}},"name":"hello.txt"}"#;
+    let file: File = serde_ipld_dagjson::from_slice(data).unwrap();
+    assert_eq!(file.data.as_base64(), "aGVsbG8gd29ybGQ");
+    assert_eq!(file.data.decode().unwrap(), b"hello world");
+    assert_eq!(serde_ipld_dagjson::to_vec(&file).unwrap(), data);
+
+    // Large bytes are decoded in pieces.
+    let large = vec![0xab; 100_000];
+    let encoded = serde_ipld_dagjson::to_vec(&Ipld::Bytes(large.clone())).unwrap();
+    let bytes: LazyBytes = serde_ipld_dagjson::from_slice(&encoded).unwrap();
+    let pieces = bytes.pieces().collect::<Result<Vec<_>, _>>().unwrap();
+    assert!(pieces.len() > 1);
+    assert_eq!(pieces.concat(), large);
+
+    // Invalid base64 is only detected when decoding.
+    let invalid: LazyBytes = serde_ipld_dagjson::from_slice(br#"{"/": {"bytes": "!!"}}"#).unwrap();
+    assert!(invalid.decode().is_err());
+    assert!(invalid.pieces().next().unwrap().is_err());
+
+    assert!(serde_ipld_dagjson::from_slice::<LazyBytes>(br#""aGVsbG8""#).is_err());
+    assert!(serde_ipld_dagjson::from_slice::<LazyBytes>(br#"{"/": "aGVsbG8"}"#).is_err());
+    assert!(
+        serde_ipld_dagjson::from_slice::<LazyBytes>(br#"{"/": {"bytes": "aGVsbG8", "x": 1}}"#)
+            .is_err()
+    );
+}
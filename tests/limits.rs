@@ -0,0 +1,171 @@
+use ipld_core::ipld::Ipld;
+use serde_ipld_dagjson::{
+    de::{self, DecodeOptions},
+    limits::{Limit, Limits, LinkPolicy, RejectedLink, FUEL_BYTES_PER_UNIT},
+    DecodeError,
+};
+
+const CID: &str = r#"{"/": "bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy"}"#;
+
+fn decode(data: &[u8], limits: Limits) -> Result<Ipld, DecodeError> {
+    de::from_slice_with_options(data, &DecodeOptions::new().limits(limits))
+}
+
+fn assert_exceeded(result: Result<Ipld, DecodeError>, expected: Limit) {
+    match result {
+        Err(DecodeError::LimitExceeded(limit)) => assert_eq!(limit, expected),
+        other => panic!("expected {:?} to be exceeded, got {:?}", expected, other),
+    }
+}
+
+#[test]
+fn test_presets() {
+    let data = format!(
+        r#"{{"a": [1, {{"b": {}}}], "c": {{"/": {{"bytes": "dm14"}}}}}}"#,
+        CID
+    );
+    let expected: Ipld = de::from_slice(data.as_bytes()).unwrap();
+    for limits in [
+        Limits::strict_network(),
+        Limits::archival(),
+        Limits::unlimited(),
+    ] {
+        assert_eq!(decode(data.as_bytes(), limits).unwrap(), expected);
+    }
+    assert_eq!(Limits::unlimited(), Limits::default());
+}
+
+#[test]
+fn test_max_depth() {
+    let limits = Limits::unlimited().max_depth(2);
+    assert!(decode(br#"[[1], {"a": 2}]"#, limits.clone()).is_ok());
+    assert_exceeded(decode(b"[[[1]]]", limits.clone()), Limit::Depth(2));
+    assert_exceeded(
+        decode(br#"{"a": {"b": {}}}"#, limits.clone()),
+        Limit::Depth(2),
+    );
+    // Links and bytes are not nested maps.
+    let data = format!(r#"[[{}]]"#, CID);
+    assert!(decode(data.as_bytes(), limits).is_ok());
+
+    let deep = format!("{}{}", "[".repeat(65), "]".repeat(65));
+    assert_exceeded(
+        decode(deep.as_bytes(), Limits::strict_network()),
+        Limit::Depth(64),
+    );
+}
+
+#[test]
+fn test_max_size() {
+    let data = br#"["some", "strings"]"#;
+    let limits = Limits::unlimited().max_size(data.len());
+    assert!(decode(data, limits.clone()).is_ok());
+    let limits = limits.max_size(data.len() - 1);
+    assert_exceeded(decode(data, limits.clone()), Limit::Size(data.len() - 1));
+
+    let options = DecodeOptions::new().limits(limits);
+    let result: Result<Ipld, _> =
+        serde_ipld_dagjson::strict::from_slice_with_options(data, &options);
+    assert_exceeded(result, Limit::Size(data.len() - 1));
+}
+
+#[cfg(feature = "io")]
+#[test]
+fn test_max_size_reader() {
+    let data = br#"["some", "strings"]"#;
+    let options = DecodeOptions::new().limits(Limits::unlimited().max_size(data.len()));
+    let ipld: Ipld = de::from_reader_with_options(&data[..], &options).unwrap();
+    assert_eq!(ipld, de::from_slice::<Ipld>(data).unwrap());
+
+    let options = DecodeOptions::new().limits(Limits::unlimited().max_size(8));
+    let result: Result<Ipld, _> = de::from_reader_with_options(&data[..], &options);
+    assert_exceeded(result, Limit::Size(8));
+
+    let mut decoder = de::Decoder::with_options(options);
+    assert_exceeded(decoder.decode_reader(&data[..]), Limit::Size(8));
+}
+
+#[test]
+fn test_max_bytes_len() {
+    let data = br#"[{"/": {"bytes": "dm14"}}]"#;
+    assert!(decode(data, Limits::unlimited().max_bytes_len(3)).is_ok());
+    assert_exceeded(
+        decode(data, Limits::unlimited().max_bytes_len(2)),
+        Limit::BytesLen(2),
+    );
+}
+
+#[test]
+fn test_max_links() {
+    let data = format!(r#"[{}, {{"a": {}}}]"#, CID, CID);
+    assert!(decode(data.as_bytes(), Limits::unlimited().max_links(2)).is_ok());
+    assert_exceeded(
+        decode(data.as_bytes(), Limits::unlimited().max_links(1)),
+        Limit::Links(1),
+    );
+}
+
+#[test]
+fn test_max_fuel() {
+    // A list and three integers.
+    let data = b"[1, 2, 3]";
+    assert!(decode(data, Limits::unlimited().max_fuel(4)).is_ok());
+    assert_exceeded(
+        decode(data, Limits::unlimited().max_fuel(3)),
+        Limit::Fuel(3),
+    );
+
+    // Long content consumes additional fuel.
+    let data = format!(r#"["{}"]"#, "a".repeat(2 * FUEL_BYTES_PER_UNIT));
+    assert!(decode(data.as_bytes(), Limits::unlimited().max_fuel(4)).is_ok());
+    assert_exceeded(
+        decode(data.as_bytes(), Limits::unlimited().max_fuel(3)),
+        Limit::Fuel(3),
+    );
+}
+
+#[test]
+fn test_display() {
+    let error = DecodeError::LimitExceeded(Limit::Depth(64));
+    assert_eq!(error.to_string(), "depth limit of 64 exceeded");
+}
+
+#[test]
+fn test_link_policy() {
+    // A CIDv1 with the raw codec and SHA2-256.
+    let data = format!("[{}]", CID);
+    let decode = |policy: LinkPolicy| -> Result<Ipld, DecodeError> {
+        let options = DecodeOptions::new().link_policy(policy).cid_cache(4);
+        de::from_slice_with_options(data.as_bytes(), &options)
+    };
+    assert!(decode(LinkPolicy::new()).is_ok());
+    assert!(decode(LinkPolicy::new().allow_codecs([0x55, 0x0129])).is_ok());
+    assert!(decode(LinkPolicy::new().deny_hashes([0x00])).is_ok());
+
+    for (policy, expected) in [
+        (
+            LinkPolicy::new().allow_codecs([0x0129]),
+            RejectedLink::Codec(0x55),
+        ),
+        (
+            LinkPolicy::new().deny_codecs([0x55]),
+            RejectedLink::Codec(0x55),
+        ),
+        (
+            LinkPolicy::new().allow_hashes([0x1e]),
+            RejectedLink::Hash(0x12),
+        ),
+        (
+            LinkPolicy::new().deny_hashes([0x12]),
+            RejectedLink::Hash(0x12),
+        ),
+    ] {
+        match decode(policy) {
+            Err(DecodeError::LinkRejected(rejected)) => assert_eq!(rejected, expected),
+            other => panic!("expected {:?}, got {:?}", expected, other),
+        }
+    }
+
+    let error = DecodeError::LinkRejected(RejectedLink::Codec(0x55));
+    assert_eq!(error.to_string(), "link with codec 0x55 rejected");
+}
@@ -0,0 +1,70 @@
+use serde::{Deserialize, Serialize};
+use serde_ipld_dagjson::{from_slice, maybe::Maybe, to_vec};
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+struct Patch {
+    #[serde(default, skip_serializing_if = "Maybe::is_absent")]
+    name: Maybe<String>,
+    #[serde(default, skip_serializing_if = "Maybe::is_absent")]
+    age: Maybe<u8>,
+}
+
+#[test]
+fn test_maybe_roundtrip() {
+    let cases = [
+        (
+            Patch {
+                name: Maybe::Value("a".into()),
+                age: Maybe::Value(1),
+            },
+            &br#"{"name":"a","age":1}"#[..],
+        ),
+        (
+            Patch {
+                name: Maybe::Null,
+                age: Maybe::Absent,
+            },
+            br#"{"name":null}"#,
+        ),
+        (
+            Patch {
+                name: Maybe::Absent,
+                age: Maybe::Absent,
+            },
+            br#"{}"#,
+        ),
+    ];
+    for (patch, expected) in cases {
+        let encoded = to_vec(&patch).unwrap();
+        assert_eq!(encoded, expected);
+        assert_eq!(from_slice::<Patch>(&encoded).unwrap(), patch);
+    }
+}
+
+#[test]
+fn test_maybe_apply() {
+    let patch: Patch = from_slice(br#"{"name":null,"age":3}"#).unwrap();
+    let mut name = Some("a".to_string());
+    let mut age = None;
+    patch.name.apply(&mut name);
+    patch.age.apply(&mut age);
+    assert_eq!(name, None);
+    assert_eq!(age, Some(3));
+
+    let mut unchanged = Some(1);
+    Maybe::Absent.apply(&mut unchanged);
+    assert_eq!(unchanged, Some(1));
+}
+
+#[test]
+fn test_maybe_absent_not_skipped() {
+    #[derive(Serialize)]
+    struct NotSkipped {
+        value: Maybe<u8>,
+    }
+
+    assert!(to_vec(&NotSkipped {
+        value: Maybe::Absent
+    })
+    .is_err());
+}
@@ -0,0 +1,58 @@
+use ipld_core::{
+    cid::{multihash::Multihash, Cid},
+    ipld,
+    ipld::Ipld,
+};
+use serde_ipld_dagjson::multicodec::{self, Codec, DispatchError};
+
+fn cid(codec: u64, data: &[u8]) -> Cid {
+    Cid::new_v1(codec, Multihash::wrap(0x00, data).unwrap())
+}
+
+#[test]
+fn test_codec() {
+    assert_eq!(Codec::from_code(0x0129), Some(Codec::DagJson));
+    assert_eq!(Codec::from_code(0x71), Some(Codec::DagCbor));
+    assert_eq!(Codec::from_code(0x55), None);
+    assert_eq!(Codec::DagCbor.code(), multicodec::DAG_CBOR);
+
+    for json in [&b" {}"[..], b"[1]", b"\"a\"", b"-1", b"true", b"null"] {
+        assert_eq!(Codec::sniff(json), Codec::DagJson);
+    }
+    // A CBOR map with one entry, and invalid UTF-8.
+    assert_eq!(Codec::sniff(&[0xa1, 0x61, 0x61, 0x01]), Codec::DagCbor);
+    assert_eq!(Codec::sniff(&[b'[', 0xff]), Codec::DagCbor);
+}
+
+#[test]
+fn test_decode_dag_json() {
+    let data = br#"{"a":[1,2]}"#;
+    let ipld: Ipld = multicodec::decode(&cid(0x0129, data), data).unwrap();
+    assert_eq!(ipld, ipld!({"a": [1, 2]}));
+    let ipld: Ipld = multicodec::decode_sniffed(data).unwrap();
+    assert_eq!(ipld, ipld!({"a": [1, 2]}));
+
+    let result: Result<Ipld, _> = multicodec::decode(&cid(0x55, data), data);
+    assert!(matches!(result, Err(DispatchError::UnsupportedCodec(0x55))));
+    let result: Result<Ipld, _> = multicodec::decode(&cid(0x0129, b"{"), b"{");
+    assert!(matches!(result, Err(DispatchError::DagJson(_))));
+}
+
+#[cfg(feature = "dag-cbor")]
+#[test]
+fn test_decode_dag_cbor() {
+    // `{"a": [1, 2]}` as DAG-CBOR.
+    let data = [0xa1, 0x61, 0x61, 0x82, 0x01, 0x02];
+    let ipld: Ipld = multicodec::decode(&cid(0x71, &data), &data).unwrap();
+    assert_eq!(ipld, ipld!({"a": [1, 2]}));
+    let ipld: Ipld = multicodec::decode_sniffed(&data).unwrap();
+    assert_eq!(ipld, ipld!({"a": [1, 2]}));
+}
+
+#[cfg(not(feature = "dag-cbor"))]
+#[test]
+fn test_decode_dag_cbor_unsupported() {
+    let data = [0xa0];
+    let result: Result<Ipld, _> = multicodec::decode(&cid(0x71, &data), &data);
+    assert!(matches!(result, Err(DispatchError::UnsupportedCodec(0x71))));
+}
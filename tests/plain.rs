@@ -0,0 +1,124 @@
+#![cfg(feature = "ipld")]
+use serde_ipld_dagjson::plain::to_plain_json;
+use serde_json::json;
+
+#[cfg(feature = "float")]
+#[test]
+fn test_to_plain_json() {
+    let input = br#"{
+        "a": [null, true, -1, 1.5, "text", 18446744073709551615],
+        "b": {"/": {"bytes": "AAECAw"}},
+        "c": {"nested": {"/": "bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy"}}
+    }"#;
+    assert_eq!(
+        to_plain_json(input).unwrap(),
+        json!({
+            "a": [null, true, -1, 1.5, "text", 18446744073709551615u64],
+            "b": "AAECAw",
+            "c": {"nested": "bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy"}
+        })
+    );
+}
+
+#[test]
+fn test_to_plain_json_invalid() {
+    assert!(to_plain_json(br#"{"/": {"bytes": "!"}}"#).is_err());
+}
+
+#[test]
+fn test_import_plain_json() {
+    use std::{collections::BTreeMap, str::FromStr};
+
+    use ipld_core::{cid::Cid, ipld::Ipld};
+    use serde_ipld_dagjson::plain::{import_plain_json, ImportError, ImportRules};
+
+    let cid = Cid::from_str("bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy").unwrap();
+    let plain = json!({
+        "items": [
+            {"thumbnail": "AAECAw==", "image": cid.to_string()},
+            {"thumbnail": "BAU", "image": cid.to_string()}
+        ],
+        "author": cid.to_string(),
+        "count": 2,
+        "ratio": 0.5
+    });
+    let rules = ImportRules::new()
+        .bytes("items/*/thumbnail")
+        .link("items/*/image");
+    let item = |bytes: Vec<u8>| {
+        Ipld::Map(BTreeMap::from([
+            ("thumbnail".to_string(), Ipld::Bytes(bytes)),
+            ("image".to_string(), Ipld::Link(cid)),
+        ]))
+    };
+    let expected = |author: Ipld| {
+        Ipld::Map(BTreeMap::from([
+            (
+                "items".to_string(),
+                Ipld::List(vec![item(vec![0, 1, 2, 3]), item(vec![4, 5])]),
+            ),
+            ("author".to_string(), author),
+            ("count".to_string(), Ipld::Integer(2)),
+            ("ratio".to_string(), Ipld::Float(0.5)),
+        ]))
+    };
+    assert_eq!(
+        import_plain_json(plain.clone(), &rules).unwrap(),
+        expected(Ipld::String(cid.to_string()))
+    );
+    assert_eq!(
+        import_plain_json(plain, &rules.clone().detect_links(true)).unwrap(),
+        expected(Ipld::Link(cid))
+    );
+
+    assert_eq!(
+        import_plain_json(json!({"items": [{"image": "nope"}]}), &rules),
+        Err(ImportError::InvalidLink {
+            path: "items/0/image".to_string()
+        })
+    );
+    assert_eq!(
+        import_plain_json(json!({"items": [{"thumbnail": "!"}]}), &rules),
+        Err(ImportError::InvalidBytes {
+            path: "items/0/thumbnail".to_string()
+        })
+    );
+    assert_eq!(
+        import_plain_json(json!({"a": {"/": "x"}}), &rules),
+        Err(ImportError::ReservedKey {
+            path: "a".to_string()
+        })
+    );
+}
+
+#[test]
+fn test_import_plain_json_escaped_paths() {
+    use std::str::FromStr;
+
+    use ipld_core::{cid::Cid, ipld::Ipld};
+    use serde_ipld_dagjson::plain::{import_plain_json, ImportError, ImportRules};
+
+    let cid = "bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy";
+    let plain = json!({"a/b": cid, "a": {"b": cid}});
+    let rules = ImportRules::new().link("a~1b");
+    let ipld = import_plain_json(plain, &rules).unwrap();
+    assert_eq!(
+        ipld.get("a/b").unwrap(),
+        Some(&Ipld::Link(Cid::from_str(cid).unwrap()))
+    );
+    let nested = ipld.get("a").unwrap().unwrap();
+    assert_eq!(
+        nested.get("b").unwrap(),
+        Some(&Ipld::String(cid.to_string()))
+    );
+
+    assert_eq!(
+        import_plain_json(
+            json!({"~": {"x/y": "nope"}}),
+            &ImportRules::new().link("~0/*")
+        ),
+        Err(ImportError::InvalidLink {
+            path: "~0/x~1y".to_string()
+        })
+    );
+}
@@ -0,0 +1,67 @@
+use std::str::FromStr;
+
+use ipld_core::{
+    cid::{multibase::Base, Cid},
+    ipld::Ipld,
+};
+use serde_ipld_dagjson::{de, ser, DecodeOptions, EncodeOptions, Profile};
+
+#[test]
+fn test_profiles() {
+    let cid = Cid::from_str("bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy").unwrap();
+    let v0 = Cid::from_str("QmdfTbBqBPQ7VNxZEYEj14VmRuZBkqFbiwReogJgS1zR1n").unwrap();
+    let decode = |input: &str, profile: Profile| {
+        de::from_slice_with_options::<Ipld>(input.as_bytes(), &profile.decode_options())
+    };
+
+    let canonical = format!(r#"{{"a":1,"b":{{"/":"{}"}}}}"#, cid);
+    let whitespace = format!(r#"{{"a": 1, "b": {{"/": "{}"}}}}"#, cid);
+    let base58 = format!(
+        r#"{{"/":"{}"}}"#,
+        cid.to_string_of_base(Base::Base58Btc).unwrap()
+    );
+    let cid_v0 = format!(r#"{{"/":"{}"}}"#, v0);
+
+    for profile in [Profile::Canonical, Profile::JsCompat, Profile::Legacy] {
+        assert!(decode(&canonical, profile).is_ok(), "{:?}", profile);
+    }
+
+    assert!(decode(&whitespace, Profile::Canonical).is_err());
+    assert!(decode(&whitespace, Profile::JsCompat).is_ok());
+    assert!(decode(&whitespace, Profile::Legacy).is_ok());
+
+    assert!(decode(&base58, Profile::Canonical).is_err());
+    assert!(decode(&base58, Profile::JsCompat).is_err());
+    assert_eq!(decode(&base58, Profile::Legacy).unwrap(), Ipld::Link(cid));
+
+    assert_eq!(decode(&cid_v0, Profile::Canonical).unwrap(), Ipld::Link(v0));
+    assert_eq!(decode(&cid_v0, Profile::JsCompat).unwrap(), Ipld::Link(v0));
+    assert_eq!(
+        decode(&cid_v0, Profile::Legacy).unwrap(),
+        Ipld::Link(v0.into_v1().unwrap())
+    );
+}
+
+#[test]
+fn test_profile_into_options() {
+    let options: DecodeOptions = Profile::Canonical.into();
+    assert!(de::from_slice_with_options::<Ipld>(b"[1, 2]", &options).is_err());
+
+    let options: EncodeOptions = Profile::Canonical.into();
+    let encoded = ser::to_vec_with_options(&Ipld::List(vec![Ipld::Integer(1)]), &options).unwrap();
+    assert_eq!(encoded, b"[1]");
+}
+
+#[test]
+fn test_canonical_profile_sorts_keys() {
+    use std::collections::HashMap;
+
+    let map: HashMap<String, u32> = (0..20).map(|i| (format!("{:02}", 19 - i), i)).collect();
+    let options = Profile::Canonical.encode_options();
+    let encoded = ser::to_vec_with_options(&map, &options).unwrap();
+    assert!(encoded.starts_with(br#"{"00":19,"01":18,"02":17"#));
+    // The canonical output is accepted by the canonical decoder.
+    let decoded: HashMap<String, u32> =
+        de::from_slice_with_options(&encoded, &Profile::Canonical.decode_options()).unwrap();
+    assert_eq!(decoded, map);
+}
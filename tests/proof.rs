@@ -0,0 +1,132 @@
+#![cfg(feature = "codec")]
+use std::{
+    collections::HashMap,
+    hash::{DefaultHasher, Hash, Hasher},
+};
+
+use ipld_core::{
+    cid::{multihash::Multihash, Cid},
+    ipld::Ipld,
+};
+use serde_ipld_dagjson::proof::{self, ProofError};
+
+/// A toy hash function.
+fn hash(data: &[u8]) -> Multihash<64> {
+    let mut hasher = DefaultHasher::new();
+    data.hash(&mut hasher);
+    Multihash::wrap(0x300000, &hasher.finish().to_be_bytes()).unwrap()
+}
+
+fn put(blocks: &mut HashMap<Cid, Vec<u8>>, data: &str) -> Cid {
+    let cid = Cid::new_v1(0x0129, hash(data.as_bytes()));
+    blocks.insert(cid, data.as_bytes().to_vec());
+    cid
+}
+
+/// A root linking to a list of accounts, which link to their details.
+fn dag() -> (HashMap<Cid, Vec<u8>>, Cid) {
+    let mut blocks = HashMap::new();
+    let alice = put(&mut blocks, r#"{"balance":5,"name":"alice"}"#);
+    let bob = put(&mut blocks, r#"{"balance":7,"name":"bob"}"#);
+    let accounts = put(
+        &mut blocks,
+        &format!(r#"[{{"/":"{}"}},{{"/":"{}"}}]"#, alice, bob),
+    );
+    let root = put(
+        &mut blocks,
+        &format!(r#"{{"accounts":{{"/":"{}"}},"version":1}}"#, accounts),
+    );
+    (blocks, root)
+}
+
+#[test]
+fn test_prove_and_verify() {
+    let (blocks, root) = dag();
+    let proof = proof::prove(root, "accounts/1/balance", &blocks).unwrap();
+    assert_eq!(proof.len(), 3);
+    assert_eq!(proof[0].0, root);
+    assert_eq!(proof[2].1, br#"{"balance":7,"name":"bob"}"#);
+    let value = proof::verify(root, "accounts/1/balance", &proof, &hash).unwrap();
+    assert_eq!(value, Ipld::Integer(7));
+
+    // Only the root block is needed for values within it.
+    let proof = proof::prove(root, "version", &blocks).unwrap();
+    assert_eq!(proof.len(), 1);
+    // The value may be a link, the linked block isn't needed.
+    let proof = proof::prove(root, "accounts/0", &blocks).unwrap();
+    assert_eq!(proof.len(), 2);
+    let value = proof::verify(root, "accounts/0", &proof, &hash).unwrap();
+    assert!(matches!(value, Ipld::Link(_)));
+}
+
+#[test]
+fn test_escaped_path() {
+    let mut blocks = HashMap::new();
+    let leaf = put(&mut blocks, r#"{"a/b":1,"a":{"b":2}}"#);
+    let root = put(&mut blocks, &format!(r#"{{"~":{{"/":"{}"}}}}"#, leaf));
+    let proof = proof::prove(root, "~0/a~1b", &blocks).unwrap();
+    assert_eq!(
+        proof::verify(root, "~0/a~1b", &proof, &hash).unwrap(),
+        Ipld::Integer(1)
+    );
+    let proof = proof::prove(root, "~0/a/b", &blocks).unwrap();
+    assert_eq!(
+        proof::verify(root, "~0/a/b", &proof, &hash).unwrap(),
+        Ipld::Integer(2)
+    );
+}
+
+#[test]
+fn test_not_found() {
+    let (blocks, root) = dag();
+    for (path, missing) in [
+        ("accounts/2", "accounts/2"),
+        ("accounts/x/balance", "accounts/x"),
+        ("version/1", "version/1"),
+        // Indexes are only accepted in their canonical form.
+        ("accounts/+1/balance", "accounts/+1"),
+        ("accounts/01/balance", "accounts/01"),
+    ] {
+        match proof::prove(root, path, &blocks) {
+            Err(ProofError::NotFound { path }) => assert_eq!(path, missing),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
+
+#[test]
+fn test_verify_tampered() {
+    let (blocks, root) = dag();
+    let mut proof = proof::prove(root, "accounts/0/balance", &blocks).unwrap();
+    proof[2].1 = br#"{"balance":500,"name":"alice"}"#.to_vec();
+    assert!(matches!(
+        proof::verify(root, "accounts/0/balance", &proof, &hash),
+        Err(ProofError::HashMismatch { cid }) if cid == proof[2].0
+    ));
+
+    // A proof for another path is incomplete.
+    let proof = proof::prove(root, "accounts/0/balance", &blocks).unwrap();
+    assert!(matches!(
+        proof::verify(root, "accounts/1/balance", &proof, &hash),
+        Err(ProofError::Missing { path, .. }) if path == "accounts/1"
+    ));
+}
+
+#[test]
+fn test_unsupported_codec() {
+    let mut blocks = HashMap::new();
+    let data = br#"{"balance":5}"#.to_vec();
+    let raw = Cid::new_v1(0x55, hash(&data));
+    blocks.insert(raw, data);
+    let root = put(&mut blocks, &format!(r#"{{"alice":{{"/":"{}"}}}}"#, raw));
+    assert!(matches!(
+        proof::prove(root, "alice/balance", &blocks),
+        Err(ProofError::UnsupportedCodec { cid }) if cid == raw
+    ));
+
+    let proof = vec![(raw, blocks[&raw].clone())];
+    assert!(matches!(
+        proof::verify(raw, "balance", &proof, &hash),
+        Err(ProofError::UnsupportedCodec { cid }) if cid == raw
+    ));
+}
@@ -0,0 +1,210 @@
+use serde_ipld_dagjson::schema::{codegen, Schema};
+#[cfg(feature = "ipld")]
+use serde_ipld_dagjson::schema::{Kind, SchemaInference, TypeKind, TypeRef};
+
+#[cfg(feature = "ipld")]
+#[test]
+fn test_infer_schema() {
+    let mut inference = SchemaInference::new();
+    inference
+        .add_block(br#"{"name": "a", "size": 1, "created_at": {"seconds": 1}, "value": 1, "link": {"/": "bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy"}}"#)
+        .unwrap();
+    inference
+        .add_block(br#"{"name": "b", "size": null, "created_at": {"seconds": 2}, "value": "x", "data": {"/": {"bytes": "dm14"}}}"#)
+        .unwrap();
+    let schema = inference.schema("Entry");
+    assert_eq!(
+        schema.to_string(),
+        r#"type Entry struct {
+  created_at EntryCreatedAt
+  data optional Bytes
+  link optional &Any
+  name String
+  size nullable Int
+  value EntryValue
+}
+
+type EntryCreatedAt struct {
+  seconds Int
+}
+
+type EntryValue union {
+  | Int int
+  | String string
+} representation kinded
+"#
+    );
+}
+
+#[cfg(feature = "ipld")]
+#[test]
+fn test_infer_schema_maps_and_lists() {
+    let mut inference = SchemaInference::new();
+    inference
+        .add_block(br#"[{"with space": 1, "other key": null}, {"with space": 2}]"#)
+        .unwrap();
+    let schema = inference.schema("Root");
+    assert_eq!(schema.types.len(), 1);
+    assert_eq!(
+        schema.get("Root").unwrap().kind,
+        TypeKind::Basic(TypeRef::List {
+            value: Box::new(TypeRef::Map {
+                value: Box::new(TypeRef::Int),
+                nullable: true
+            }),
+            nullable: false,
+        })
+    );
+    assert_eq!(schema.to_string(), "type Root [{String:nullable Int}]\n");
+}
+
+#[cfg(feature = "ipld")]
+#[test]
+fn test_infer_schema_union_of_containers() {
+    let mut inference = SchemaInference::new();
+    inference.add_block(br#"{"a": [1]}"#).unwrap();
+    inference.add_block(br#"{"a": {"b": true}}"#).unwrap();
+    let schema = inference.schema("Root");
+    let TypeKind::KindedUnion(members) = &schema.get("RootA").unwrap().kind else {
+        panic!("expected a union");
+    };
+    assert_eq!(members.len(), 2);
+    assert_eq!(members[0].kind, Kind::List);
+    assert_eq!(
+        schema.get(&members[0].ty).unwrap().kind,
+        TypeKind::Basic(TypeRef::List {
+            value: Box::new(TypeRef::Int),
+            nullable: false
+        })
+    );
+    assert_eq!(members[1].kind, Kind::Map);
+    assert!(matches!(
+        schema.get(&members[1].ty).unwrap().kind,
+        TypeKind::Struct(_)
+    ));
+
+    assert!(inference.add_block(b"[1").is_err());
+}
+
+#[allow(dead_code)]
+mod generated {
+    include!("fixtures/codegen/expected.rs");
+}
+
+#[test]
+fn test_codegen() {
+    let schema: Schema = include_str!("fixtures/codegen/schema.ipldsch")
+        .parse()
+        .unwrap();
+    assert_eq!(
+        codegen::generate(&schema),
+        include_str!("fixtures/codegen/expected.rs")
+    );
+}
+
+#[test]
+fn test_codegen_roundtrip() {
+    use generated::{Entry, EntryType};
+
+    let input = br#"{"data":{"/":{"bytes":"dm14"}},"meta":{"a":null,"b":[1]},"name":"a","note":null,"size":null,"tags":["x"],"target":{"/":"bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy"},"type":"file"}"#;
+    let entry: Entry = serde_ipld_dagjson::from_slice(input).unwrap();
+    assert_eq!(entry.r#type, EntryType::String("file".to_string()));
+    assert!(entry.note.is_null());
+    assert_eq!(entry.created_at, None);
+    assert!(entry.target.is_some());
+    assert_eq!(serde_ipld_dagjson::to_vec(&entry).unwrap(), input);
+
+    let entry: Entry = serde_ipld_dagjson::from_slice(
+        br#"{"createdAt":1,"meta":{},"name":"b","size":2,"tags":[],"type":{"/":{"bytes":"dm14"}}}"#,
+    )
+    .unwrap();
+    assert_eq!(entry.created_at, Some(1));
+    assert!(entry.note.is_absent());
+    assert_eq!(
+        entry.r#type,
+        EntryType::Bytes(serde_bytes::ByteBuf::from(b"vmx".to_vec()))
+    );
+}
+
+#[test]
+fn test_parse_schema() {
+    let input = include_str!("fixtures/codegen/schema.ipldsch");
+    let schema: Schema = input.parse().unwrap();
+    // Typed links are links to any data.
+    let reparsed: Schema = schema.to_string().parse().unwrap();
+    assert_eq!(reparsed, schema);
+
+    let error = "type Foo struct {\n  a Int\n"
+        .parse::<Schema>()
+        .unwrap_err();
+    assert_eq!(error.message, "unexpected end of input");
+    let error = "type Foo union {\n  | Int int\n} representation keyed"
+        .parse::<Schema>()
+        .unwrap_err();
+    assert_eq!(error.line, 3);
+}
+
+#[test]
+fn test_schema_for() {
+    use std::collections::BTreeMap;
+
+    use ipld_core::{cid::Cid, ipld::Ipld};
+    use serde::Deserialize;
+    use serde_bytes::ByteBuf;
+    use serde_ipld_dagjson::schema::schema_for;
+
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    struct Node {
+        data: ByteBuf,
+        meta: BTreeMap<String, Option<Ipld>>,
+        next: Option<Box<Node>>,
+        links: Vec<Cid>,
+        position: (f64, f64),
+        tags: Vec<String>,
+        size: u64,
+    }
+
+    let schema = schema_for::<Node>().unwrap();
+    assert_eq!(
+        schema.to_string(),
+        "\
+type Node struct {
+  data Bytes
+  meta {String:nullable Any}
+  next nullable Node
+  links [&Any]
+  position [Float]
+  tags [String]
+  size Int
+}
+"
+    );
+    // The generated schema can be parsed again.
+    assert_eq!(schema.to_string().parse::<Schema>().unwrap(), schema);
+}
+
+#[test]
+fn test_schema_for_unsupported() {
+    use serde::Deserialize;
+    use serde_ipld_dagjson::schema::schema_for;
+
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    enum Color {
+        Red,
+        Green,
+    }
+
+    #[derive(Deserialize)]
+    #[allow(dead_code)]
+    struct Pixel {
+        color: Color,
+    }
+
+    assert_eq!(
+        schema_for::<Pixel>().unwrap_err().message,
+        "enums aren't supported, found `Color`"
+    );
+    assert!(schema_for::<Vec<u8>>().is_err());
+}
@@ -1,3 +1,6 @@
+// The tests predate the associated constants of the primitive types.
+#![allow(clippy::legacy_numeric_constants)]
+
 use std::{collections::BTreeMap, str::FromStr};
 
 use ipld_core::cid::Cid;
@@ -31,12 +34,14 @@ fn test_object() {
     assert_eq!(json, br#"{"a":"A","b":"B","c":"C","d":"D","e":"E"}"#);
 }
 
+#[cfg(feature = "float")]
 #[test]
 fn test_float() {
     let json = to_vec(&12.3f64).unwrap();
     assert_eq!(json, b"12.3");
 }
 
+#[cfg(feature = "float")]
 #[test]
 fn test_f32() {
     let json = to_vec(&4000.5f32).unwrap();
@@ -174,3 +179,595 @@ fn test_nested_bytes() {
     let json = to_vec(&nested).unwrap();
     assert_eq!(json, br#"{"some":{"/":{"bytes":"dm14"}}}"#);
 }
+
+#[cfg(not(feature = "float"))]
+#[test]
+fn test_float_unsupported() {
+    assert!(to_vec(&1.5f64).is_err());
+    assert!(serde_ipld_dagjson::from_slice::<f64>(b"1.5").is_err());
+    #[cfg(feature = "ipld")]
+    assert!(serde_ipld_dagjson::ipld::from_slice(b"[1.5]").is_err());
+    // Integers are still fine.
+    assert_eq!(to_vec(&1u8).unwrap(), b"1");
+}
+
+#[test]
+fn test_link_policy() {
+    use ipld_core::cid::multihash::Multihash;
+    use serde_ipld_dagjson::{
+        limits::{LinkPolicy, RejectedLink},
+        ser::{to_vec_with_options, EncodeOptions},
+        EncodeError,
+    };
+
+    #[derive(Serialize)]
+    enum Entry {
+        Dir { children: Vec<Cid> },
+    }
+
+    #[derive(Serialize)]
+    struct Root {
+        name: String,
+        entries: BTreeMap<String, Entry>,
+    }
+
+    let identity = |len| Cid::new_v1(0x0129, Multihash::wrap(0x00, &vec![1; len]).unwrap());
+    let root = |cid| Root {
+        name: "root".to_string(),
+        entries: BTreeMap::from([(
+            "docs".to_string(),
+            Entry::Dir {
+                children: vec![identity(4), cid],
+            },
+        )]),
+    };
+    let options = EncodeOptions::new()
+        .link_policy(LinkPolicy::new().allow_codecs([0x0129]).max_identity_len(8));
+
+    let accepted = to_vec_with_options(&root(identity(8)), &options).unwrap();
+    assert_eq!(accepted, to_vec(&root(identity(8))).unwrap());
+
+    let result = to_vec_with_options(&root(identity(9)), &options);
+    assert!(matches!(
+        result,
+        Err(EncodeError::LinkRejected { path, reason: RejectedLink::IdentityTooLarge(9) })
+            if path == "entries/docs/Dir/children/1"
+    ));
+
+    let raw = Cid::new_v1(0x55, Multihash::wrap(0x00, b"vmx").unwrap());
+    let error = to_vec_with_options(&BTreeMap::from([("a", vec![raw])]), &options).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "link with codec 0x55 rejected at path `a/0`"
+    );
+
+    // Without options, every link is encoded.
+    assert!(to_vec(&root(identity(9))).is_ok());
+}
+
+#[test]
+fn test_to_json_value() {
+    use serde_ipld_dagjson::ser::to_json_value;
+    use serde_json::json;
+
+    #[derive(Serialize)]
+    struct Attachment<'a> {
+        name: &'a str,
+        #[serde(with = "serde_bytes")]
+        data: &'a [u8],
+        source: Cid,
+        size: Option<u64>,
+    }
+
+    let cid = Cid::from_str("bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy").unwrap();
+    let attachment = Attachment {
+        name: "hello.txt",
+        data: b"hello",
+        source: cid,
+        size: None,
+    };
+    let value = to_json_value(&attachment).unwrap();
+    assert_eq!(
+        value,
+        json!({
+            "name": "hello.txt",
+            "data": {"/": {"bytes": "aGVsbG8"}},
+            "source": {"/": cid.to_string()},
+            "size": null
+        })
+    );
+
+    assert_eq!(
+        serde_json::to_string(&value).unwrap(),
+        format!(
+            r#"{{"data":{{"/":{{"bytes":"aGVsbG8"}}}},"name":"hello.txt","size":null,"source":{{"/":"{}"}}}}"#,
+            cid
+        )
+    );
+
+    // Merged into a larger payload, it's still valid DAG-JSON.
+    let payload = json!({"attachments": [value], "version": 1});
+    let decoded: ipld_core::ipld::Ipld =
+        serde_ipld_dagjson::from_slice(&serde_json::to_vec(&payload).unwrap()).unwrap();
+    assert_eq!(
+        to_vec(&decoded).unwrap(),
+        serde_json::to_vec(&payload).unwrap()
+    );
+
+    assert!(to_json_value(&f64::NAN).is_err());
+    assert!(to_json_value(&BTreeMap::from([(1, 2)])).is_ok());
+}
+
+#[cfg(feature = "codec")]
+#[test]
+fn test_extract_links() {
+    use ipld_core::cid::multihash::Multihash;
+    use serde_ipld_dagjson::{codec::DagJsonCodec, ser::extract_links};
+
+    #[derive(Serialize)]
+    struct Node {
+        name: String,
+        children: Vec<Cid>,
+        parent: Option<Cid>,
+        links: BTreeMap<String, Cid>,
+    }
+
+    let cid = |data: &[u8]| Cid::new_v1(0x55, Multihash::wrap(0x00, data).unwrap());
+    let node = Node {
+        name: "root".to_string(),
+        children: vec![cid(b"a"), cid(b"b")],
+        parent: Some(cid(b"c")),
+        links: BTreeMap::from([("d".to_string(), cid(b"d"))]),
+    };
+    let links = extract_links(&node).unwrap();
+    assert_eq!(links, [cid(b"a"), cid(b"b"), cid(b"c"), cid(b"d")]);
+
+    // The same as for the encoded value.
+    let encoded = to_vec(&node).unwrap();
+    let mut encoded_links = Vec::new();
+    DagJsonCodec::for_each_link(&encoded, |cid| encoded_links.push(cid)).unwrap();
+    assert_eq!(links, encoded_links);
+
+    assert!(extract_links(&"no links").unwrap().is_empty());
+
+    // It fails where encoding fails.
+    assert!(extract_links(&vec![f64::NAN]).is_err());
+    assert!(extract_links(&BTreeMap::from([(vec![1u8], cid(b"a"))])).is_err());
+}
+
+#[test]
+fn test_check_encodable() {
+    #[cfg(feature = "float")]
+    use ipld_core::ipld::Ipld;
+    use serde_ipld_dagjson::ser::check_encodable;
+
+    /// A link, which isn't a valid CID.
+    struct InvalidLink;
+
+    impl Serialize for InvalidLink {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_newtype_struct(
+                ipld_core::cid::serde::CID_SERDE_PRIVATE_IDENTIFIER,
+                Bytes::new(b"garbage"),
+            )
+        }
+    }
+
+    #[cfg(feature = "float")]
+    assert!(check_encodable(&Ipld::List(vec![Ipld::Float(1.5), Ipld::Null])).is_ok());
+    assert!(check_encodable(&BTreeMap::from([("a", vec![1, 2])])).is_ok());
+
+    let error = check_encodable(&BTreeMap::from([("a", BTreeMap::from([(1, true)]))])).unwrap_err();
+    assert_eq!(error.to_string(), "map key at path `a/1` isn't a string");
+    assert!(to_vec(&BTreeMap::from([(1, true)])).is_ok());
+
+    assert!(check_encodable(&vec![f64::INFINITY]).is_err());
+    assert!(check_encodable(&BTreeMap::from([(vec![1], 1)])).is_err());
+    assert!(check_encodable(&vec![InvalidLink]).is_err());
+}
+
+#[test]
+fn test_serialize_ref() {
+    use serde_ipld_dagjson::ser::SerializeRef;
+
+    /// An envelope layer, which embeds its payload with the DAG-JSON semantics.
+    #[derive(Serialize)]
+    struct Envelope<'a> {
+        key_id: u32,
+        payload: SerializeRef<'a, Vec<ByteBuf>>,
+    }
+
+    let payload = vec![ByteBuf::from(vec![1, 2])];
+    let envelope = Envelope {
+        key_id: 7,
+        payload: SerializeRef::new(&payload),
+    };
+    // The adapter applies even though the outer serializer knows nothing about DAG-JSON.
+    assert_eq!(
+        serde_json::to_string(&envelope).unwrap(),
+        r#"{"key_id":7,"payload":[{"/":{"bytes":"AQI"}}]}"#
+    );
+    assert_eq!(serde_json::to_string(&payload).unwrap(), r#"[[1,2]]"#);
+}
+
+#[cfg(feature = "io")]
+#[test]
+fn test_file() {
+    use std::fs;
+
+    use ipld_core::ipld::Ipld;
+    use serde_ipld_dagjson::{from_file, to_file, to_file_atomic};
+
+    let dir = std::env::temp_dir().join(format!("serde_ipld_dagjson_file_{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("state.json");
+    let value = BTreeMap::from([("height".to_string(), Ipld::Integer(42))]);
+
+    to_file(&path, &value).unwrap();
+    assert_eq!(fs::read(&path).unwrap(), br#"{"height":42}"#);
+    let decoded: BTreeMap<String, Ipld> = from_file(&path).unwrap();
+    assert_eq!(decoded, value);
+
+    to_file_atomic(&path, &vec![ByteBuf::from(vec![1, 2])]).unwrap();
+    assert_eq!(fs::read(&path).unwrap(), br#"[{"/":{"bytes":"AQI"}}]"#);
+
+    // A failed write leaves the previous file and no temporary file behind.
+    assert!(to_file_atomic(&path, &vec![f64::NAN]).is_err());
+    assert_eq!(fs::read(&path).unwrap(), br#"[{"/":{"bytes":"AQI"}}]"#);
+    assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+
+    // A directory can't be replaced.
+    assert!(to_file_atomic(&dir, &1).is_err());
+    assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+    assert!(from_file::<Ipld, _>(dir.join("missing.json")).is_err());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[cfg(feature = "io")]
+#[test]
+fn test_to_writer_seq() {
+    use std::io;
+
+    use ipld_core::ipld::Ipld;
+    use serde_ipld_dagjson::ser::to_writer_seq;
+
+    let mut output = Vec::new();
+    to_writer_seq(&mut output, Vec::<u8>::new()).unwrap();
+    assert_eq!(output, b"[]");
+
+    let mut output = Vec::new();
+    let elements = [ByteBuf::from(vec![1, 2]), ByteBuf::from(vec![3])];
+    to_writer_seq(&mut output, &elements).unwrap();
+    assert_eq!(output, to_vec(&elements).unwrap());
+
+    /// A writer, which counts how often it's flushed.
+    #[derive(Default)]
+    struct Counting {
+        written: Vec<u8>,
+        flushes: usize,
+    }
+
+    impl io::Write for Counting {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.written.write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.flushes += 1;
+            Ok(())
+        }
+    }
+
+    let mut writer = Counting::default();
+    to_writer_seq(&mut writer, (0..3000).map(Ipld::Integer)).unwrap();
+    assert_eq!(writer.flushes, 3);
+    let decoded: Vec<i64> = serde_ipld_dagjson::from_slice(&writer.written).unwrap();
+    assert_eq!(decoded, (0..3000).collect::<Vec<_>>());
+
+    assert!(to_writer_seq(&mut Vec::new(), [1.0, f64::NAN]).is_err());
+}
+
+#[cfg(feature = "bytes")]
+#[test]
+fn test_bytes_crate() {
+    use bytes::BytesMut;
+    use serde_ipld_dagjson::{encode_into, from_bytes, to_bytes};
+
+    let value = vec![ByteBuf::from(vec![1, 2])];
+    let encoded = to_bytes(&value).unwrap();
+    assert_eq!(encoded, to_vec(&value).unwrap());
+    assert_eq!(from_bytes::<Vec<ByteBuf>>(encoded).unwrap(), value);
+
+    let mut buf = BytesMut::new();
+    encode_into(&mut buf, &value).unwrap();
+    encode_into(&mut buf, &1).unwrap();
+    assert_eq!(&buf[..], br#"[{"/":{"bytes":"AQI"}}]1"#);
+
+    // A failed encode leaves the buffer as it was.
+    assert!(encode_into(&mut buf, &vec![1.0, f64::NAN]).is_err());
+    assert_eq!(&buf[..], br#"[{"/":{"bytes":"AQI"}}]1"#);
+}
+
+#[test]
+fn test_to_string() {
+    use serde_ipld_dagjson::{ser::to_string_pretty, to_string};
+
+    let cid = Cid::from_str("bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy").unwrap();
+    let mut value = BTreeMap::new();
+    value.insert("bytes", ipld_core::ipld::Ipld::Bytes(vec![1, 2]));
+    value.insert("link", ipld_core::ipld::Ipld::Link(cid));
+    assert_eq!(
+        to_string(&value).unwrap().as_bytes(),
+        to_vec(&value).unwrap()
+    );
+    assert_eq!(
+        to_string_pretty(&value).unwrap(),
+        r#"{
+  "bytes": {
+    "/": {
+      "bytes": "AQI"
+    }
+  },
+  "link": {
+    "/": "bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy"
+  }
+}"#
+    );
+    assert!(to_string(&f64::NAN).is_err());
+}
+
+#[cfg(feature = "io")]
+#[test]
+fn test_to_writer_pretty() {
+    use serde_ipld_dagjson::ser::{to_string_pretty, to_writer_pretty};
+
+    let cid = Cid::from_str("bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy").unwrap();
+    let value = vec![ipld_core::ipld::Ipld::Link(cid)];
+    let mut output = Vec::new();
+    to_writer_pretty(&mut output, &value, "    ").unwrap();
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        r#"[
+    {
+        "/": "bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy"
+    }
+]"#
+    );
+
+    let mut output = Vec::new();
+    to_writer_pretty(&mut output, &value, "  ").unwrap();
+    assert_eq!(output, to_string_pretty(&value).unwrap().as_bytes());
+
+    // Pretty output decodes to the same data.
+    let decoded: Vec<Cid> = serde_ipld_dagjson::from_slice(&output).unwrap();
+    assert_eq!(decoded, [cid]);
+}
+
+#[cfg(all(feature = "ipld", feature = "float"))]
+#[test]
+fn test_sort_keys() {
+    use std::collections::HashMap;
+
+    use ipld_core::ipld::Ipld;
+    use serde_ipld_dagjson::{
+        limits::LinkPolicy,
+        ser::{to_vec_with_options, EncodeOptions},
+        EncodeError,
+    };
+
+    #[derive(Serialize)]
+    struct Entry {
+        z: HashMap<String, u32>,
+        a: Cid,
+    }
+
+    let cid = Cid::from_str("bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy").unwrap();
+    let mut map = HashMap::new();
+    for i in 0..20 {
+        let inner = (0..20).map(|j| (format!("{}", j), j)).collect();
+        map.insert(format!("entry{:02}", i), Entry { z: inner, a: cid });
+    }
+    let options = EncodeOptions::new().sort_keys(true);
+    let encoded = to_vec_with_options(&map, &options).unwrap();
+    // The result is the canonical form, nested structs are sorted as well.
+    let ipld: Ipld = serde_ipld_dagjson::from_slice(&encoded).unwrap();
+    assert_eq!(encoded, serde_ipld_dagjson::ipld::to_vec(&ipld).unwrap());
+    assert!(encoded.starts_with(br#"{"entry00":{"a":{"/":"bafkrei"#));
+
+    // Without the option, the struct keeps its field order.
+    let unsorted = to_vec_with_options(&map, &EncodeOptions::new()).unwrap();
+    assert_ne!(unsorted, encoded);
+
+    // Integer keys are sorted as strings.
+    let numbers: HashMap<u32, bool> = [(10, true), (9, false), (100, true)].into();
+    assert_eq!(
+        to_vec_with_options(&numbers, &options).unwrap(),
+        br#"{"10":true,"100":true,"9":false}"#
+    );
+
+    // Values are encoded like without the option.
+    let floats: HashMap<&str, f32> = [("b", 1.1), ("a", 2.5)].into();
+    assert_eq!(
+        to_vec_with_options(&floats, &options).unwrap(),
+        br#"{"a":2.5,"b":1.1}"#
+    );
+
+    // Top-level structs are sorted too.
+    let entry = Entry {
+        z: HashMap::new(),
+        a: cid,
+    };
+    assert_eq!(
+        to_vec_with_options(&entry, &options).unwrap(),
+        to_vec(&ipld_core::ipld!({"a": cid, "z": {}})).unwrap()
+    );
+
+    // Options still apply to the buffered values.
+    let options = options.link_policy(LinkPolicy::new().allow_codecs([0x0129]));
+    match to_vec_with_options(&map, &options) {
+        Err(EncodeError::LinkRejected { path, .. }) => {
+            assert!(
+                path.starts_with("entry") && path.ends_with("/a"),
+                "{}",
+                path
+            )
+        }
+        other => panic!("expected a rejected link, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_sort_keys_map_key_rules() {
+    use std::collections::HashMap;
+
+    use serde_ipld_dagjson::ser::{to_vec_with_options, EncodeOptions};
+
+    /// A key that fails to serialize.
+    #[derive(PartialEq, Eq, Hash, PartialOrd, Ord)]
+    struct Broken;
+
+    impl Serialize for Broken {
+        fn serialize<S: serde::Serializer>(&self, _serializer: S) -> Result<S::Ok, S::Error> {
+            Err(serde::ser::Error::custom("broken"))
+        }
+    }
+
+    #[derive(PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+    enum Variant {
+        A,
+    }
+
+    fn both<T: Serialize>(value: &T) -> [Result<Vec<u8>, String>; 2] {
+        [EncodeOptions::new(), EncodeOptions::new().sort_keys(true)]
+            .map(|options| to_vec_with_options(value, &options).map_err(|error| error.to_string()))
+    }
+
+    // Keys, which are no strings, are rejected whether the keys are sorted or not.
+    for result in [
+        both(&BTreeMap::from([(vec![1u8, 2], 1)])),
+        both(&HashMap::from([((), 1)])),
+        both(&BTreeMap::from([(true, 1)])),
+        both(&BTreeMap::from([(Some("a"), 1)])),
+        both(&BTreeMap::from([(Broken, 1)])),
+    ] {
+        assert!(
+            result.iter().all(Result::is_err),
+            "unexpected result: {:?}",
+            result
+        );
+    }
+
+    // Strings, chars, integers and unit variants are accepted.
+    for (result, expected) in [
+        (both(&BTreeMap::from([('a', 1)])), br#"{"a":1}"#.as_slice()),
+        (both(&BTreeMap::from([(-1i128, 1)])), br#"{"-1":1}"#),
+        (both(&BTreeMap::from([(Variant::A, 1)])), br#"{"A":1}"#),
+    ] {
+        for encoded in result {
+            assert_eq!(encoded.unwrap(), expected);
+        }
+    }
+}
+
+#[test]
+fn test_canonical() {
+    use std::collections::HashMap;
+
+    use serde::Deserialize;
+    use serde_ipld_dagjson::ser::{
+        to_string_pretty, to_vec_with_options, Canonical, EncodeOptions,
+    };
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    struct Signed {
+        signature: ByteBuf,
+        payload: Canonical<HashMap<String, Vec<Cid>>>,
+    }
+
+    let cid = Cid::from_str("bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy").unwrap();
+    let payload: HashMap<_, _> = (0..10).map(|i| (format!("k{}", i), vec![cid])).collect();
+    let signed = Signed {
+        signature: ByteBuf::from(vec![1, 2]),
+        payload: Canonical(payload),
+    };
+    let canonical = String::from_utf8(
+        to_vec_with_options(&signed.payload.0, &EncodeOptions::new().sort_keys(true)).unwrap(),
+    )
+    .unwrap();
+
+    assert_eq!(
+        to_vec(&signed).unwrap(),
+        format!(
+            r#"{{"signature":{{"/":{{"bytes":"AQI"}}}},"payload":{}}}"#,
+            canonical
+        )
+        .as_bytes()
+    );
+    // The payload stays compact within pretty output.
+    let pretty = to_string_pretty(&signed).unwrap();
+    assert!(pretty.ends_with(&format!("\"payload\": {}\n}}", canonical)));
+
+    let decoded: Signed = serde_ipld_dagjson::from_slice(pretty.as_bytes()).unwrap();
+    assert_eq!(decoded, signed);
+
+    assert!(to_vec(&Canonical(vec![f64::NAN])).is_err());
+}
+
+#[test]
+fn test_canonical_with_options() {
+    use std::collections::HashMap;
+
+    use serde_ipld_dagjson::ser::{to_vec_with_options, Canonical, EncodeOptions};
+
+    let payload = Canonical(HashMap::from([("é", 1), ("b", 2)]));
+    let mut map = HashMap::new();
+    map.insert("z", payload.clone());
+    map.insert("a", payload);
+
+    let options = EncodeOptions::new().sort_keys(true);
+    assert_eq!(
+        to_vec_with_options(&map, &options).unwrap(),
+        r#"{"a":{"b":2,"é":1},"z":{"b":2,"é":1}}"#.as_bytes()
+    );
+
+    let options = options.ascii_only(true);
+    assert_eq!(
+        to_vec_with_options(&map, &options).unwrap(),
+        br#"{"a":{"b":2,"\u00e9":1},"z":{"b":2,"\u00e9":1}}"#
+    );
+}
+
+#[test]
+fn test_ascii_only() {
+    use ipld_core::ipld::Ipld;
+    use serde_ipld_dagjson::ser::{to_vec_with_options, EncodeOptions};
+
+    let cid = Cid::from_str("bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy").unwrap();
+    let mut map = BTreeMap::new();
+    map.insert(
+        "ключ".to_string(),
+        Ipld::String("日本語 and 😀\n".to_string()),
+    );
+    map.insert("link".to_string(), Ipld::Link(cid));
+    map.insert("bytes".to_string(), Ipld::Bytes(vec![0xff]));
+
+    let options = EncodeOptions::new().ascii_only(true);
+    let encoded = to_vec_with_options(&map, &options).unwrap();
+    assert!(encoded.is_ascii());
+    assert_eq!(
+        String::from_utf8(encoded.clone()).unwrap(),
+        r#"{"bytes":{"/":{"bytes":"/w"}},"link":{"/":"bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy"},"\u043a\u043b\u044e\u0447":"\u65e5\u672c\u8a9e and \ud83d\ude00\n"}"#
+    );
+    let decoded: BTreeMap<String, Ipld> = serde_ipld_dagjson::from_slice(&encoded).unwrap();
+    assert_eq!(decoded, map);
+
+    // Other options still apply.
+    let options = options.sort_keys(true);
+    let encoded = to_vec_with_options(&map, &options).unwrap();
+    assert!(encoded.is_ascii());
+
+    // Raw values are escaped as well.
+    let raw = serde_json::value::RawValue::from_string(r#"{"k":"é"}"#.to_string()).unwrap();
+    let encoded = to_vec_with_options(&vec![raw], &EncodeOptions::new().ascii_only(true)).unwrap();
+    assert_eq!(encoded, br#"[{"k":"\u00e9"}]"#);
+}
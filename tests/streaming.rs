@@ -0,0 +1,130 @@
+#![cfg(feature = "io")]
+use std::fmt;
+
+use serde::de::{self, DeserializeSeed};
+use serde_ipld_dagjson::{streaming::BytesSink, DecodeError, Deserializer};
+
+/// A document with a name and a payload, which is streamed into a writer.
+struct Document<'a>(&'a mut Vec<u8>);
+
+impl<'de> DeserializeSeed<'de> for Document<'_> {
+    type Value = (String, u64);
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> de::Visitor<'de> for Document<'_> {
+    type Value = (String, u64);
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a document")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::MapAccess<'de>,
+    {
+        let mut name = None;
+        let mut written = None;
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "name" => name = Some(map.next_value()?),
+                "payload" => written = Some(map.next_value_seed(BytesSink::new(&mut *self.0))?),
+                _ => return Err(de::Error::unknown_field(&key, &["name", "payload"])),
+            }
+        }
+        Ok((
+            name.ok_or_else(|| de::Error::missing_field("name"))?,
+            written.ok_or_else(|| de::Error::missing_field("payload"))?,
+        ))
+    }
+}
+
+fn decode(input: &[u8], sink: &mut Vec<u8>) -> Result<(String, u64), DecodeError> {
+    let mut json = serde_json::Deserializer::from_slice(input);
+    Ok(Document(sink).deserialize(Deserializer::new(&mut json))?)
+}
+
+#[test]
+fn test_bytes_sink() {
+    let payload = (0..100_000).map(|byte| byte as u8).collect::<Vec<_>>();
+    let encoded =
+        serde_ipld_dagjson::to_vec(&ipld_core::ipld::Ipld::Bytes(payload.clone())).unwrap();
+    let input = format!(
+        r#"{{"name": "blob", "payload": {}}}"#,
+        String::from_utf8(encoded).unwrap()
+    );
+
+    let mut sink = Vec::new();
+    assert_eq!(
+        decode(input.as_bytes(), &mut sink).unwrap(),
+        ("blob".to_string(), 100_000)
+    );
+    assert_eq!(sink, payload);
+
+    // From a reader.
+    let mut sink = Vec::new();
+    let mut json = serde_json::Deserializer::from_reader(input.as_bytes());
+    Document(&mut sink)
+        .deserialize(Deserializer::new(&mut json))
+        .unwrap();
+    assert_eq!(sink, payload);
+}
+
+#[test]
+fn test_bytes_sink_invalid() {
+    for input in [
+        r#"{"name": "blob", "payload": "AAEC"}"#,
+        r#"{"name": "blob", "payload": {"/": "bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy"}}"#,
+        r#"{"name": "blob", "payload": {"/": {"bytes": "!!"}}}"#,
+        r#"{"name": "blob", "payload": {"/": {"bytes": "AAEC", "more": 1}}}"#,
+    ] {
+        assert!(
+            decode(input.as_bytes(), &mut Vec::new()).is_err(),
+            "{}",
+            input
+        );
+    }
+}
+
+#[test]
+fn test_bytes_reader() {
+    use std::io;
+
+    use ipld_core::ipld::Ipld;
+    use serde_ipld_dagjson::streaming::BytesReader;
+
+    for len in [0, 1, 2, 3, 12_288, 100_000] {
+        let payload = (0..len).map(|byte| byte as u8).collect::<Vec<_>>();
+        let expected = serde_ipld_dagjson::to_vec(&Ipld::Bytes(payload.clone())).unwrap();
+        let encoded = serde_ipld_dagjson::to_vec(&BytesReader::new(&payload[..])).unwrap();
+        assert_eq!(encoded, expected, "length {}", len);
+
+        let mut written = Vec::new();
+        serde_ipld_dagjson::to_writer(&mut written, &BytesReader::new(&payload[..])).unwrap();
+        assert_eq!(written, expected, "length {}", len);
+    }
+
+    /// A reader that fails after some data.
+    struct Failing(usize);
+
+    impl io::Read for Failing {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.0 == 0 {
+                return Err(io::Error::other("broken pipe"));
+            }
+            let len = buf.len().min(self.0);
+            buf[..len].fill(1);
+            self.0 -= len;
+            Ok(len)
+        }
+    }
+
+    let error = serde_ipld_dagjson::to_vec(&BytesReader::new(Failing(50_000))).unwrap_err();
+    assert!(error.to_string().contains("broken pipe"), "{}", error);
+}
@@ -0,0 +1,195 @@
+use std::collections::BTreeMap;
+
+#[cfg(feature = "float")]
+use ipld_core::cid::Cid;
+use ipld_core::ipld::Ipld;
+use serde::Deserialize;
+use serde_ipld_dagjson::{strict, DecodeError, DecodeOptions};
+#[cfg(feature = "float")]
+use serde_ipld_dagjson::{strict::StrictDeserializer, Deserializer};
+
+#[cfg(feature = "float")]
+const LINK: &str = "bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy";
+
+fn syntax_error<T>(input: &[u8]) -> (usize, String)
+where
+    T: for<'a> Deserialize<'a> + std::fmt::Debug,
+{
+    match strict::from_slice::<T>(input) {
+        Err(DecodeError::Syntax { offset, message }) => (offset, message),
+        other => panic!("expected a syntax error, got {:?}", other),
+    }
+}
+
+#[cfg(feature = "float")]
+#[test]
+fn test_strict_same_as_default() {
+    let input = format!(
+        r#"{{"a": [1, -2, 3.5, true, false, null], "b": {{"/": "{}"}}, "c": {{"/": {{"bytes": "AQI"}}}}, "d": "é😀\n"}}"#,
+        LINK
+    );
+    let strict: Ipld = strict::from_slice(input.as_bytes()).unwrap();
+    let default: Ipld = serde_ipld_dagjson::from_slice(input.as_bytes()).unwrap();
+    assert_eq!(strict, default);
+
+    let cid: Cid = strict::from_slice(format!(r#"{{"/": "{}"}}"#, LINK).as_bytes()).unwrap();
+    assert_eq!(cid.to_string(), LINK);
+}
+
+#[test]
+fn test_strict_structs_and_enums() {
+    #[derive(Debug, Deserialize, PartialEq)]
+    enum Kind {
+        File,
+        Dir { entries: u32 },
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Entry<'a> {
+        name: &'a str,
+        size: Option<u64>,
+        kinds: Vec<Kind>,
+    }
+
+    let input = br#"{"kinds": ["File", {"Dir": {"entries": 2}}], "name": "a", "size": null}"#;
+    let entry: Entry = strict::from_slice(input).unwrap();
+    assert_eq!(
+        entry,
+        Entry {
+            name: "a",
+            size: None,
+            kinds: vec![Kind::File, Kind::Dir { entries: 2 }],
+        }
+    );
+}
+
+#[test]
+fn test_strict_rejects() {
+    assert_eq!(
+        syntax_error::<Ipld>(br#"{"a": 1, "a": 2}"#),
+        (9, "duplicate map key".to_string())
+    );
+    assert_eq!(
+        syntax_error::<Ipld>(b"[01]"),
+        (1, "leading zero".to_string())
+    );
+    assert_eq!(syntax_error::<Ipld>(b"[1,]").1, "trailing comma");
+    assert_eq!(syntax_error::<Ipld>(br#"{"a":1,}"#).1, "trailing comma");
+    assert_eq!(syntax_error::<Ipld>(b"1.").1, "expected digit");
+    assert_eq!(syntax_error::<Ipld>(b"1e999").1, "float out of range");
+    assert_eq!(
+        syntax_error::<Ipld>(b"1234567890123456789012345678901234567890").1,
+        "integer out of range"
+    );
+    assert_eq!(syntax_error::<Ipld>(br#""\ud83d""#).1, "unpaired surrogate");
+    assert_eq!(
+        syntax_error::<Ipld>(b"\"a\x01\"").1,
+        "control character in string"
+    );
+    assert_eq!(syntax_error::<Ipld>(b"\"\xff\"").1, "invalid UTF-8");
+    assert_eq!(syntax_error::<Ipld>(b"[1 2]").1, "expected `,`");
+    assert_eq!(
+        syntax_error::<Ipld>(&[b'['; 200]).1,
+        "recursion limit exceeded"
+    );
+    assert!(matches!(
+        strict::from_slice::<Ipld>(b"1 2"),
+        Err(DecodeError::TrailingData)
+    ));
+
+    // Map keys are strings, even if the map is decoded into one with integer keys.
+    let error = strict::from_slice::<BTreeMap<u8, u8>>(br#"{"1": 2}"#).unwrap_err();
+    assert!(
+        error.to_string().starts_with("invalid type: string"),
+        "{}",
+        error
+    );
+}
+
+#[test]
+fn test_strict_large_integers() {
+    let ipld: Ipld = strict::from_slice(b"[18446744073709551616, -9223372036854775809]").unwrap();
+    assert_eq!(
+        ipld,
+        Ipld::List(vec![
+            Ipld::Integer(18446744073709551616),
+            Ipld::Integer(-9223372036854775809)
+        ])
+    );
+}
+
+#[cfg(feature = "float")]
+#[test]
+fn test_strict_canonical() {
+    let decode = |input: &[u8]| {
+        let mut parser = StrictDeserializer::from_slice(input).canonical(true);
+        Ipld::deserialize(Deserializer::new(&mut parser))?;
+        parser.end()
+    };
+    decode(br#"{"a":[1,-1],"b":{"/":{"bytes":"AQI"}}}"#).unwrap();
+    assert!(matches!(
+        decode(br#"{"a": 1}"#),
+        Err(DecodeError::Syntax { offset: 5, .. })
+    ));
+    assert!(decode(br#"{"b":1,"a":2}"#).is_err());
+    assert!(decode(b"-0").is_err());
+    decode(b"[1.0,1.5,1e100,-0.0]").unwrap();
+    for float in [&b"1.50"[..], b"1E2", b"1e+100", b"10e99"] {
+        assert!(decode(float).is_err(), "{}", String::from_utf8_lossy(float));
+    }
+    // Not canonical, but still valid.
+    let ipld: Ipld = strict::from_slice(b"[ -0 ]").unwrap();
+    assert_eq!(ipld, Ipld::List(vec![Ipld::Integer(0)]));
+}
+
+#[cfg(feature = "float")]
+#[test]
+fn test_strict_float_spelling() {
+    // The spelling of floats is defined by this crate, whatever `serde_json` version is used. The
+    // encoder writes exponents without a plus sign, only that spelling is canonical.
+    let floats = [1e100, 1e16, 1.5e-7, 0.1, 1.0];
+    let encoded = serde_ipld_dagjson::to_vec(&floats).unwrap();
+    assert_eq!(encoded, b"[1e100,1e16,1.5e-7,0.1,1.0]");
+    let options = DecodeOptions::new().canonical(true);
+    let decoded: Vec<f64> = strict::from_slice_with_options(&encoded, &options).unwrap();
+    assert_eq!(decoded, floats);
+    for float in [&b"1e+100"[..], b"1e+16", b"1.5e-07", b"1E100"] {
+        assert!(
+            strict::from_slice_with_options::<f64>(float, &options).is_err(),
+            "{}",
+            String::from_utf8_lossy(float)
+        );
+        let lenient: f64 = strict::from_slice(float).unwrap();
+        assert_eq!(
+            lenient,
+            serde_ipld_dagjson::from_slice::<f64>(float).unwrap()
+        );
+    }
+}
+
+#[cfg(feature = "float")]
+#[test]
+fn test_strict_numbers() {
+    let decode = |input: &[u8]| {
+        let mut parser = StrictDeserializer::from_slice(input).strict_numbers(true);
+        Ipld::deserialize(Deserializer::new(&mut parser))?;
+        parser.end()
+    };
+    // Unlike canonical mode, whitespace and key order don't matter.
+    decode(br#"{ "b": [1.5, -1, 1e100], "a": -0.0 }"#).unwrap();
+    for number in [&b"-0"[..], b"1.50", b"1E2", b"1e+100", b"10e99"] {
+        assert!(
+            matches!(decode(number), Err(DecodeError::Syntax { offset: 0, .. })),
+            "{}",
+            String::from_utf8_lossy(number)
+        );
+    }
+}
+
+#[test]
+fn test_strict_with_options() {
+    let options = DecodeOptions::new().memory_budget(64);
+    let result: Result<Vec<String>, _> =
+        strict::from_slice_with_options(br#"["a long string that exceeds the budget"]"#, &options);
+    assert!(matches!(result, Err(DecodeError::MemoryBudgetExceeded(64))));
+}
@@ -0,0 +1,366 @@
+#![cfg(feature = "testing")]
+use ipld_core::ipld::Ipld;
+use proptest::prelude::*;
+use serde_ipld_dagjson::testing::{arb_cid, arb_ipld};
+
+proptest! {
+    #[test]
+    fn test_arb_ipld_roundtrip(ipld in arb_ipld()) {
+        let encoded = serde_ipld_dagjson::to_vec(&ipld).unwrap();
+        let decoded: Ipld = serde_ipld_dagjson::from_slice(&encoded).unwrap();
+        prop_assert_eq!(decoded, ipld);
+    }
+
+    #[test]
+    fn test_arb_cid_roundtrip(cid in arb_cid()) {
+        let encoded = serde_ipld_dagjson::to_vec(&cid).unwrap();
+        let decoded: ipld_core::cid::Cid = serde_ipld_dagjson::from_slice(&encoded).unwrap();
+        prop_assert_eq!(decoded, cid);
+    }
+}
+
+#[test]
+fn test_assert_ser_tokens() {
+    use std::str::FromStr;
+
+    use ipld_core::cid::Cid;
+    use serde::Serialize;
+    use serde_ipld_dagjson::testing::{assert_ser_tokens, tokenize, Token};
+
+    #[derive(Serialize)]
+    struct Node {
+        links: Vec<Cid>,
+        #[serde(with = "serde_bytes")]
+        data: Vec<u8>,
+        size: Option<f64>,
+        index: i64,
+    }
+
+    let cid = Cid::from_str("bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy").unwrap();
+    let node = Node {
+        links: vec![cid],
+        data: b"vmx".to_vec(),
+        size: None,
+        index: -1,
+    };
+    assert_ser_tokens(
+        &node,
+        &[
+            Token::Map { len: 4 },
+            Token::String("links".into()),
+            Token::List { len: 1 },
+            Token::Link(cid),
+            Token::ListEnd,
+            Token::String("data".into()),
+            Token::Bytes(b"vmx".to_vec()),
+            Token::String("size".into()),
+            Token::Null,
+            Token::String("index".into()),
+            Token::Integer(-1),
+            Token::MapEnd,
+        ],
+    );
+
+    assert_eq!(
+        tokenize(b"[true, 1.5]").unwrap(),
+        vec![
+            Token::List { len: 2 },
+            Token::Bool(true),
+            Token::Float(1.5),
+            Token::ListEnd
+        ]
+    );
+    assert!(tokenize(b"[1] 2").is_err());
+}
+
+#[test]
+#[should_panic(expected = "tokens don't match")]
+fn test_assert_ser_tokens_mismatch() {
+    serde_ipld_dagjson::testing::assert_ser_tokens(
+        &1u8,
+        &[serde_ipld_dagjson::testing::Token::Integer(2)],
+    );
+}
+
+#[test]
+fn test_run_fixture() {
+    use serde_ipld_dagjson::testing::run_fixture;
+
+    let fixtures = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/codec");
+    assert_eq!(run_fixture(fixtures).unwrap(), 2);
+
+    // A fixture whose name doesn't match its content.
+    let dir = std::env::temp_dir().join("serde_ipld_dagjson_run_fixture");
+    std::fs::create_dir_all(&dir).unwrap();
+    let wrong = dir.join("baguqeeraknweany2zl64ctfmpny5n3modc3p4f2l4be2tyt7w6x66kqfhe7a.dag-json");
+    std::fs::write(&wrong, b"[1]").unwrap();
+    let error = run_fixture(&wrong).unwrap_err();
+    assert_eq!(error.path, wrong);
+    assert!(error.message.starts_with("CID differs"));
+
+    // Data that isn't canonically encoded.
+    std::fs::write(&wrong, b"[1, 2]").unwrap();
+    let error = run_fixture(&wrong).unwrap_err();
+    assert!(error.message.starts_with("encoded data differs"));
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+/// Runs the full ipld/codec-fixtures corpus, the path to its `fixtures` directory needs to be set
+/// as `CODEC_FIXTURES_DIR` environment variable.
+#[test]
+#[ignore]
+fn test_codec_fixtures() {
+    let root = std::env::var("CODEC_FIXTURES_DIR").expect("CODEC_FIXTURES_DIR is not set");
+    let mut count = 0;
+    for entry in std::fs::read_dir(root).unwrap() {
+        count += serde_ipld_dagjson::testing::run_fixture(entry.unwrap().path()).unwrap();
+    }
+    assert!(count > 0, "no fixtures found");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_check_differential() {
+    use ipld_core::ipld;
+    use serde_ipld_dagjson::testing::{check_differential, DifferentialError, Reference};
+
+    let value = ipld!({"a": [1, 2.5, null], "b": "c"});
+    // An implementation that keeps the data as is always agrees.
+    check_differential(&Reference::new("cat"), &value).unwrap();
+
+    let diverging = Reference::new("sh")
+        .arg("-c")
+        .arg("cat > /dev/null; printf '[]'");
+    match check_differential(&diverging, &value) {
+        Err(DifferentialError::Mismatch { reference, .. }) => assert_eq!(reference, b"[]"),
+        other => panic!("expected a mismatch, got {:?}", other),
+    }
+
+    let failing = Reference::new("sh")
+        .arg("-c")
+        .arg("echo broken >&2; exit 1");
+    match check_differential(&failing, &value) {
+        Err(DifferentialError::Reference(stderr)) => assert_eq!(stderr, "broken\n"),
+        other => panic!("expected a failure, got {:?}", other),
+    }
+
+    assert!(matches!(
+        check_differential(&Reference::new("/nonexistent/program"), &value),
+        Err(DifferentialError::Io(_))
+    ));
+}
+
+/// Compares random documents with another implementation, its command needs to be set as
+/// `DAGJSON_REFERENCE` environment variable, see `Reference` for what it needs to do.
+#[test]
+#[ignore]
+fn test_differential_reference() {
+    use serde_ipld_dagjson::testing::{check_differential, Reference};
+
+    let command = std::env::var("DAGJSON_REFERENCE").expect("DAGJSON_REFERENCE is not set");
+    let reference = Reference::new("sh").arg("-c").arg(command);
+    proptest!(|(ipld in arb_ipld())| {
+        check_differential(&reference, &ipld).unwrap();
+    });
+}
+
+#[derive(serde::Serialize)]
+struct SnapshotEntry {
+    name: &'static str,
+    #[serde(with = "serde_bytes")]
+    data: Vec<u8>,
+}
+
+#[test]
+fn test_assert_matches_fixture() {
+    use serde_ipld_dagjson::testing::assert_matches_fixture;
+
+    // The fields are not sorted, the snapshot is.
+    let entry = SnapshotEntry {
+        name: "a",
+        data: vec![1, 2],
+    };
+    assert_matches_fixture(&entry, "tests/fixtures/snapshot/entry.dagjson");
+}
+
+#[test]
+#[should_panic(expected = "encoding doesn't match")]
+fn test_assert_matches_fixture_mismatch() {
+    use serde_ipld_dagjson::testing::assert_matches_fixture;
+
+    let entry = SnapshotEntry {
+        name: "b",
+        data: vec![1, 2],
+    };
+    assert_matches_fixture(&entry, "tests/fixtures/snapshot/entry.dagjson");
+}
+
+#[test]
+fn test_assert_roundtrip() {
+    use std::collections::BTreeMap;
+
+    use serde_ipld_dagjson::testing::{assert_roundtrip, check_roundtrip};
+
+    assert_roundtrip(&BTreeMap::from([
+        ("a".to_string(), Some(1u8)),
+        ("b".to_string(), None),
+    ]));
+    assert_eq!(check_roundtrip(&vec![1.0f64]).unwrap(), b"[1.0]");
+}
+
+#[test]
+fn test_check_roundtrip_failures() {
+    use serde::{Deserialize, Deserializer, Serialize};
+    use serde_ipld_dagjson::testing::{check_roundtrip, RoundtripError};
+
+    // Loses the sign when decoding.
+    #[derive(Debug, PartialEq, Serialize)]
+    struct Lossy(i64);
+
+    impl<'de> Deserialize<'de> for Lossy {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            i64::deserialize(deserializer).map(|value| Lossy(value.abs()))
+        }
+    }
+
+    assert!(check_roundtrip(&Lossy(1)).is_ok());
+    let error = check_roundtrip(&Lossy(-1)).unwrap_err();
+    assert!(matches!(error, RoundtripError::Value { .. }));
+    assert!(error.to_string().contains("original: Lossy(-1)"));
+
+    assert!(matches!(
+        check_roundtrip(&f64::NAN),
+        Err(RoundtripError::Encode(_))
+    ));
+}
+
+#[test]
+fn test_check_canonical() {
+    use std::collections::{BTreeMap, HashMap};
+
+    use serde::Serialize;
+    use serde_ipld_dagjson::testing::{
+        assert_canonical, check_canonical, CanonicalError, Violation,
+    };
+
+    #[derive(Serialize)]
+    struct Sorted {
+        hash: String,
+        name: String,
+    }
+
+    #[derive(Serialize)]
+    struct Unsorted {
+        name: String,
+        hash: String,
+        sizes: Vec<f64>,
+        extra: HashMap<u32, bool>,
+    }
+
+    assert_canonical(&Sorted {
+        hash: "a".into(),
+        name: "b".into(),
+    });
+    assert_canonical(&BTreeMap::from([("b", 1), ("a", 2)]));
+
+    let unsorted = Unsorted {
+        name: "b".into(),
+        hash: "a".into(),
+        sizes: vec![1.0, 1.5],
+        extra: HashMap::from([(7, true)]),
+    };
+    let error = check_canonical(&unsorted).unwrap_err();
+    let CanonicalError::Violations { violations, .. } = &error else {
+        panic!("unexpected error: {}", error);
+    };
+    assert_eq!(
+        violations,
+        &[
+            Violation::NonStringKey {
+                path: "extra/7".into()
+            },
+            Violation::UnsortedKey {
+                path: "hash".into(),
+                previous: "name".into()
+            },
+            Violation::Float {
+                path: "sizes/0".into(),
+                value: 1.0
+            },
+            Violation::Float {
+                path: "sizes/1".into(),
+                value: 1.5
+            },
+            Violation::UnsortedKey {
+                path: "extra".into(),
+                previous: "sizes".into()
+            },
+        ]
+    );
+    assert!(error
+        .to_string()
+        .contains("key at path `hash` is encoded after the key `name`"));
+}
+
+#[test]
+fn test_check_stable() {
+    use std::collections::BTreeMap;
+
+    use serde::{Deserialize, Deserializer, Serialize};
+    use serde_ipld_dagjson::testing::{assert_stable, check_stable, RoundtripError};
+
+    // Rounds floats when decoding, rounded values are considered equal.
+    #[derive(Debug, Serialize)]
+    struct Sizes(BTreeMap<String, Vec<f64>>);
+
+    impl PartialEq for Sizes {
+        fn eq(&self, other: &Self) -> bool {
+            let rounded = |sizes: &Self| {
+                sizes
+                    .0
+                    .iter()
+                    .map(|(key, values)| {
+                        (
+                            key.clone(),
+                            values.iter().map(|value| value.round()).collect::<Vec<_>>(),
+                        )
+                    })
+                    .collect::<Vec<_>>()
+            };
+            rounded(self) == rounded(other)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Sizes {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let mut sizes = BTreeMap::<String, Vec<f64>>::deserialize(deserializer)?;
+            for value in sizes.values_mut().flatten() {
+                *value = value.round();
+            }
+            Ok(Sizes(sizes))
+        }
+    }
+
+    assert_stable(&Sizes(BTreeMap::from([("a".into(), vec![1.0])])));
+    let unstable = Sizes(BTreeMap::from([("a".into(), vec![1.0, 2.5])]));
+    let error = check_stable(&unstable).unwrap_err();
+    assert!(matches!(error, RoundtripError::Unstable { .. }));
+    assert!(
+        error
+            .to_string()
+            .contains("at path `a/1`: Float(2.5) became Float(3.0)"),
+        "{}",
+        error
+    );
+
+    // The decoded value is compared as well, not only its encoding.
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    struct Cached {
+        value: u64,
+        #[serde(skip)]
+        hits: u64,
+    }
+
+    let error = check_stable(&Cached { value: 1, hits: 2 }).unwrap_err();
+    assert!(matches!(error, RoundtripError::Value { .. }));
+}
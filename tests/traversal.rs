@@ -0,0 +1,489 @@
+#![cfg(feature = "ipld")]
+use std::{
+    collections::HashMap,
+    hash::{DefaultHasher, Hash, Hasher},
+};
+
+use ipld_core::{
+    cid::{multihash::Multihash, Cid},
+    ipld::Ipld,
+};
+use serde_ipld_dagjson::{
+    de::DecodeOptions,
+    limits::Limits,
+    traversal::{expand, reachable_cids, Order, Traversal, TraversalError, TraversalLimits},
+};
+
+/// A toy hash function.
+fn hash(data: &[u8]) -> Multihash<64> {
+    let mut hasher = DefaultHasher::new();
+    data.hash(&mut hasher);
+    Multihash::wrap(0x300000, &hasher.finish().to_be_bytes()).unwrap()
+}
+
+/// Stores a block.
+fn put(blocks: &mut HashMap<Cid, Vec<u8>>, data: &str) -> Cid {
+    let cid = Cid::new_v1(0x0129, hash(data.as_bytes()));
+    blocks.insert(cid, data.as_bytes().to_vec());
+    cid
+}
+
+/// Builds a DAG where the root links to `a` and `b`, which both link to `c`.
+fn dag() -> (HashMap<Cid, Vec<u8>>, Cid) {
+    let mut blocks = HashMap::new();
+    let c = put(&mut blocks, r#""c""#);
+    let a = put(
+        &mut blocks,
+        &format!(r#"{{"c":{{"/":"{}"}},"name":"a"}}"#, c),
+    );
+    let b = put(&mut blocks, &format!(r#"[{{"/":"{}"}}]"#, c));
+    let root = put(
+        &mut blocks,
+        &format!(r#"{{"a":{{"/":"{}"}},"b":{{"/":"{}"}}}}"#, a, b),
+    );
+    (blocks, root)
+}
+
+fn paths<L: serde_ipld_dagjson::traversal::BlockLoader>(
+    traversal: Traversal<L>,
+    root: Cid,
+) -> Vec<String> {
+    traversal
+        .walk(root)
+        .map(|visit| visit.unwrap().path)
+        .collect()
+}
+
+#[test]
+fn test_breadth_first() {
+    let (blocks, root) = dag();
+    let visits = Traversal::new(&blocks)
+        .walk(root)
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    let paths = visits
+        .iter()
+        .map(|visit| visit.path.as_str())
+        .collect::<Vec<_>>();
+    // Every block is only visited once.
+    assert_eq!(paths, ["", "a", "b", "a/c"]);
+    let depths = visits.iter().map(|visit| visit.depth).collect::<Vec<_>>();
+    assert_eq!(depths, [0, 1, 1, 2]);
+    assert_eq!(visits[0].cid, root);
+    assert_eq!(visits[3].block, Ipld::String("c".to_string()));
+}
+
+#[test]
+fn test_depth_first() {
+    let (blocks, root) = dag();
+    let traversal = Traversal::new(&blocks).order(Order::DepthFirst);
+    assert_eq!(paths(traversal, root), ["", "a", "a/c", "b"]);
+}
+
+#[test]
+fn test_max_depth() {
+    let (blocks, root) = dag();
+    let limits = TraversalLimits::new().max_depth(1);
+    let traversal = Traversal::new(&blocks).limits(limits);
+    assert_eq!(paths(traversal, root), ["", "a", "b"]);
+}
+
+#[test]
+fn test_max_blocks() {
+    let (blocks, root) = dag();
+    let limits = TraversalLimits::new().max_blocks(2);
+    let results = Traversal::new(&blocks)
+        .limits(limits)
+        .walk(root)
+        .collect::<Vec<_>>();
+    assert_eq!(results.len(), 3);
+    assert!(matches!(results[2], Err(TraversalError::TooManyBlocks(2))));
+}
+
+#[test]
+fn test_missing_block() {
+    let (mut blocks, root) = dag();
+    let c = put(&mut blocks, r#""c""#);
+    blocks.remove(&c);
+    let error = Traversal::new(&blocks)
+        .walk(root)
+        .find_map(Result::err)
+        .unwrap();
+    match error {
+        TraversalError::Missing { path, cid } => {
+            assert_eq!(path, "a/c");
+            assert_eq!(cid, c);
+        }
+        other => panic!("unexpected error: {}", other),
+    }
+}
+
+#[test]
+fn test_decode_options() {
+    let (blocks, root) = dag();
+    let options = DecodeOptions::new().limits(Limits::unlimited().max_size(8));
+    let error = Traversal::new(&blocks)
+        .decode_options(options)
+        .walk(root)
+        .find_map(Result::err)
+        .unwrap();
+    assert!(matches!(error, TraversalError::Decode { cid, .. } if cid == root));
+}
+
+#[test]
+fn test_walk_value() {
+    let (blocks, root) = dag();
+    let value = Ipld::List(vec![Ipld::Integer(1), Ipld::Link(root)]);
+    let paths = Traversal::new(&blocks)
+        .walk_value(&value)
+        .map(|visit| visit.unwrap().path)
+        .collect::<Vec<_>>();
+    assert_eq!(paths, ["1", "1/a", "1/b", "1/a/c"]);
+}
+
+#[test]
+fn test_closure_loader() {
+    let (blocks, root) = dag();
+    let loader = |cid: &Cid| blocks.get(cid).cloned();
+    assert_eq!(paths(Traversal::new(loader), root).len(), 4);
+}
+
+#[test]
+fn test_reachable_cids() {
+    let (blocks, root) = dag();
+    let reachable = reachable_cids(root, &blocks, TraversalLimits::new()).unwrap();
+    assert_eq!(reachable, blocks.keys().copied().collect());
+
+    let limited = reachable_cids(root, &blocks, TraversalLimits::new().max_depth(1)).unwrap();
+    assert_eq!(limited.len(), 3);
+    let c = put(&mut HashMap::new(), r#""c""#);
+    assert!(!limited.contains(&c));
+
+    let mut missing = blocks.clone();
+    missing.remove(&c);
+    assert!(matches!(
+        reachable_cids(root, &missing, TraversalLimits::new()),
+        Err(TraversalError::Missing { .. })
+    ));
+}
+
+#[test]
+fn test_other_codecs() {
+    let mut blocks = HashMap::new();
+    // A raw block, which happens to be valid DAG-JSON.
+    let raw = Cid::new_v1(0x55, hash(b"[1]"));
+    blocks.insert(raw, b"[1]".to_vec());
+    let root = put(&mut blocks, &format!(r#"{{"raw":{{"/":"{}"}}}}"#, raw));
+
+    assert_eq!(paths(Traversal::new(&blocks), root), [""]);
+    assert!(paths(Traversal::new(&blocks), raw).is_empty());
+    let reachable = reachable_cids(root, &blocks, TraversalLimits::new()).unwrap();
+    assert_eq!(reachable, [root, raw].into_iter().collect());
+}
+
+#[cfg(feature = "hashes")]
+#[test]
+fn test_verify() {
+    use serde_ipld_dagjson::block::HasherRegistry;
+
+    let (mut blocks, root) = dag();
+    let hashers = HasherRegistry::empty().register(0x300000, hash).unwrap();
+    let verified = Traversal::new(&blocks).verify(hashers.clone());
+    assert_eq!(paths(verified, root).len(), 4);
+
+    let error = Traversal::new(&blocks)
+        .verify(HasherRegistry::empty())
+        .walk(root)
+        .find_map(Result::err)
+        .unwrap();
+    assert!(matches!(error, TraversalError::UnsupportedHash { cid } if cid == root));
+
+    let c = put(&mut HashMap::new(), r#""c""#);
+    blocks.insert(c, br#""tampered""#.to_vec());
+    // Without verification the tampered block is visited.
+    assert_eq!(paths(Traversal::new(&blocks), root).len(), 4);
+    let error = Traversal::new(&blocks)
+        .verify(hashers)
+        .walk(root)
+        .find_map(Result::err)
+        .unwrap();
+    assert!(matches!(error, TraversalError::HashMismatch { cid } if cid == c));
+}
+
+#[test]
+fn test_expand() {
+    let (blocks, root) = dag();
+    let value = Ipld::List(vec![Ipld::Link(root)]);
+    assert_eq!(
+        expand(&value, &blocks, TraversalLimits::new().max_depth(0)).unwrap(),
+        value
+    );
+
+    let expanded = expand(&value, &blocks, TraversalLimits::new().max_depth(2)).unwrap();
+    let encoded = String::from_utf8(serde_ipld_dagjson::ipld::to_vec(&expanded).unwrap()).unwrap();
+    let c = put(&mut HashMap::new(), r#""c""#);
+    assert_eq!(
+        encoded,
+        format!(
+            r#"[{{"a":{{"c":{{"/":"{}"}},"name":"a"}},"b":[{{"/":"{}"}}]}}]"#,
+            c, c
+        )
+    );
+
+    let expanded = expand(&value, &blocks, TraversalLimits::new().max_depth(3)).unwrap();
+    let encoded = serde_ipld_dagjson::ipld::to_vec(&expanded).unwrap();
+    assert_eq!(encoded, br#"[{"a":{"c":"c","name":"a"},"b":["c"]}]"#);
+}
+
+#[test]
+fn test_expand_cycle() {
+    // A loader that claims a block links to itself.
+    let cid = put(&mut HashMap::new(), "self");
+    let data = format!(r#"{{"self":{{"/":"{}"}}}}"#, cid).into_bytes();
+    let loader = |_: &Cid| Some(data.clone());
+    let expanded = expand(
+        &Ipld::Link(cid),
+        loader,
+        TraversalLimits::new().max_depth(10),
+    )
+    .unwrap();
+    let expected = Ipld::Map([("self".to_string(), Ipld::Link(cid))].into());
+    assert_eq!(expanded, expected);
+}
+
+#[test]
+fn test_expand_limits() {
+    // Every block links twice to the next one, the expansion doubles with every level.
+    let mut blocks = HashMap::new();
+    let mut cid = put(&mut blocks, "1");
+    for _ in 0..16 {
+        cid = put(
+            &mut blocks,
+            &format!(r#"[{{"/":"{0}"}},{{"/":"{0}"}}]"#, cid),
+        );
+    }
+    let value = Ipld::Link(cid);
+    let limits = TraversalLimits::new();
+    assert!(matches!(
+        expand(&value, &blocks, limits.clone().max_nodes(1000)),
+        Err(TraversalError::TooManyNodes(1000))
+    ));
+    assert!(matches!(
+        expand(&value, &blocks, limits.clone().max_blocks(1000)),
+        Err(TraversalError::TooManyBlocks(1000))
+    ));
+    assert!(matches!(
+        expand(&value, &blocks, limits.clone().max_bytes(10_000)),
+        Err(TraversalError::TooManyBytes(10_000))
+    ));
+    let expanded = expand(&value, &blocks, limits.max_depth(2).max_nodes(7)).unwrap();
+    assert!(matches!(expanded, Ipld::List(ref list) if list.len() == 2));
+
+    // The blocks are decoded with the options of the traversal.
+    let options = DecodeOptions::new().limits(Limits::unlimited().max_size(8));
+    let error = Traversal::new(&blocks)
+        .decode_options(options)
+        .expand(&value)
+        .unwrap_err();
+    assert!(matches!(error, TraversalError::Decode { cid: failed, .. } if failed == cid));
+
+    // Links to other codecs aren't expanded.
+    let raw = Ipld::Link(Cid::new_v1(0x55, hash(b"1")));
+    assert_eq!(expand(&raw, &blocks, TraversalLimits::new()).unwrap(), raw);
+}
+
+#[test]
+fn test_expand_missing() {
+    let (mut blocks, root) = dag();
+    let c = put(&mut blocks, r#""c""#);
+    blocks.remove(&c);
+    let error = expand(
+        &Ipld::Link(root),
+        &blocks,
+        TraversalLimits::new().max_depth(3),
+    )
+    .unwrap_err();
+    assert!(matches!(error, TraversalError::Missing { path, .. } if path == "a/c"));
+}
+
+#[cfg(feature = "codec")]
+#[test]
+fn test_split_escaped_paths() {
+    use ipld_core::ipld;
+    use serde_ipld_dagjson::block::{redact, split, SplitPolicy};
+
+    let value = ipld!({"a/b": [1], "a": {"b": [2]}, "c": [1], "d": {"e": [1]}});
+    // Equal subtrees are only returned once.
+    let policy = SplitPolicy::new().min_size(1);
+    let (_, blocks) = split(&value, &policy, &hash).unwrap();
+    let mut cids = blocks.iter().map(|(cid, _)| *cid).collect::<Vec<_>>();
+    cids.dedup();
+    assert_eq!(cids.len(), blocks.len());
+    assert_eq!(blocks.iter().filter(|(_, data)| data == b"[1]").count(), 1);
+
+    let policy = SplitPolicy::new().path("a~1b");
+    let (root, blocks) = split(&value, &policy, &hash).unwrap();
+    assert_eq!(blocks.len(), 1);
+    assert_eq!(blocks[0].1, b"[1]");
+    assert!(matches!(root, Ipld::Map(ref map) if matches!(map["a/b"], Ipld::Link(_))));
+
+    let redacted = redact(&value, ["a/b"], b"salt", &hash).unwrap();
+    assert_eq!(redacted.get("a/b").unwrap(), value.get("a/b").unwrap());
+    assert!(
+        matches!(redacted.get("a").unwrap(), Some(Ipld::Map(map)) if matches!(map["b"], Ipld::Link(_)))
+    );
+
+    let (root, blocks) = split(&value, &SplitPolicy::new().path("a~1b"), &hash).unwrap();
+    let store = blocks.into_iter().collect::<HashMap<_, _>>();
+    let paths = Traversal::new(&store)
+        .walk_value(&root)
+        .map(|visit| visit.unwrap().path)
+        .collect::<Vec<_>>();
+    assert_eq!(paths, ["a~1b"]);
+}
+
+#[cfg(feature = "codec")]
+#[test]
+fn test_split_and_expand() {
+    use ipld_core::ipld;
+    use serde_ipld_dagjson::block::{split, SplitPolicy};
+
+    let value = ipld!({
+        "title": "a rather long title",
+        "sections": [{"text": "some long text"}, {"text": "x"}],
+        "meta": {"tags": ["a"]},
+    });
+
+    let policy = SplitPolicy::new().min_size(20);
+    let (root, blocks) = split(&value, &policy, &hash).unwrap();
+    // The first section is cut out first, then the sections, which link to it.
+    let encoded = blocks
+        .iter()
+        .map(|(_, data)| String::from_utf8(data.clone()).unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(encoded[0], r#"{"text":"some long text"}"#);
+    assert!(encoded[1].starts_with(r#"[{"/":"#));
+    assert_eq!(encoded.len(), 2);
+    assert!(matches!(root, Ipld::Map(ref map) if matches!(map["sections"], Ipld::Link(_))));
+
+    let store = blocks.into_iter().collect::<HashMap<_, _>>();
+    assert_eq!(
+        expand(&root, &store, TraversalLimits::new().max_depth(2)).unwrap(),
+        value
+    );
+
+    let policy = SplitPolicy::new().path("meta/tags").path("title");
+    let (root, blocks) = split(&value, &policy, &hash).unwrap();
+    assert_eq!(blocks.len(), 2);
+    assert_eq!(blocks[0].1, br#"["a"]"#);
+    assert_eq!(blocks[1].1, br#""a rather long title""#);
+    let store = blocks.into_iter().collect::<HashMap<_, _>>();
+    assert_eq!(
+        expand(&root, &store, TraversalLimits::new().max_depth(1)).unwrap(),
+        value
+    );
+
+    let (root, blocks) = split(&value, &SplitPolicy::new(), &hash).unwrap();
+    assert_eq!(root, value);
+    assert!(blocks.is_empty());
+}
+
+#[cfg(feature = "codec")]
+#[test]
+fn test_redact() {
+    use ipld_core::ipld;
+    use serde_ipld_dagjson::block::redact;
+
+    let value = ipld!({
+        "name": "alice",
+        "private": {"salary": 100, "notes": ["a", "b"]},
+        "friends": ["bob", "carol"],
+    });
+    let salt = b"salt";
+    let redacted = redact(
+        &value,
+        ["friends/1", "private/notes", "private"],
+        salt,
+        &hash,
+    )
+    .unwrap();
+    // The parent is redacted as a whole, its original content is the block behind the link.
+    let private = put(
+        &mut HashMap::new(),
+        r#"{"salt":{"/":{"bytes":"c2FsdA"}},"value":{"notes":["a","b"],"salary":100}}"#,
+    );
+    let carol = put(
+        &mut HashMap::new(),
+        r#"{"salt":{"/":{"bytes":"c2FsdA"}},"value":"carol"}"#,
+    );
+    let expected = ipld!({
+        "name": "alice",
+        "private": Ipld::Link(private),
+        "friends": ["bob", Ipld::Link(carol)],
+    });
+    assert_eq!(redacted, expected);
+
+    // Another salt results in other links.
+    let other = redact(&value, ["friends/1"], b"pepper", &hash).unwrap();
+    assert_ne!(
+        other.get("friends").unwrap(),
+        redacted.get("friends").unwrap()
+    );
+
+    assert!(redact(&value, ["private/missing"], salt, &hash).is_err());
+    assert!(redact(&value, ["friends/2"], salt, &hash).is_err());
+    assert_eq!(
+        redact(&value, Vec::<String>::new(), salt, &hash).unwrap(),
+        value
+    );
+}
+
+#[test]
+fn test_cancellation() {
+    use serde_ipld_dagjson::cancel::CancellationToken;
+
+    let (blocks, root) = dag();
+    let token = CancellationToken::new();
+    let mut walk = Traversal::new(&blocks)
+        .cancellation(token.clone())
+        .walk(root);
+    assert!(walk.next().unwrap().is_ok());
+    token.cancel();
+    assert!(matches!(walk.next(), Some(Err(TraversalError::Cancelled))));
+    assert!(walk.next().is_none());
+}
+
+#[test]
+fn test_to_mermaid() {
+    use serde_ipld_dagjson::traversal::to_mermaid;
+
+    let (blocks, root) = dag();
+    let cid = |data: &str| put(&mut HashMap::new(), data);
+    let c = cid(r#""c""#);
+    let a = cid(&format!(r#"{{"c":{{"/":"{}"}},"name":"a"}}"#, c));
+    let b = cid(&format!(r#"[{{"/":"{}"}}]"#, c));
+
+    let mermaid = to_mermaid(root, &blocks, TraversalLimits::new()).unwrap();
+    let expected = format!(
+        "\
+flowchart TD
+  n0[\"{root}\"]
+  n1[\"{a}\"]
+  n2[\"{b}\"]
+  n3[\"{c}\"]
+  n0 -- \"a\" --> n1
+  n0 -- \"b\" --> n2
+  n1 -- \"c\" --> n3
+  n2 -- \"0\" --> n3
+"
+    );
+    assert_eq!(mermaid, expected);
+
+    let limited = to_mermaid(root, &blocks, TraversalLimits::new().max_depth(1)).unwrap();
+    assert!(limited.contains(&a.to_string()));
+    assert!(!limited.contains(&c.to_string()));
+    assert_eq!(
+        limited.lines().filter(|line| line.contains("-->")).count(),
+        2
+    );
+}
@@ -0,0 +1,103 @@
+use std::{collections::BTreeMap, str::FromStr};
+
+use ipld_core::cid::Cid;
+use serde::{Deserialize, Serialize};
+use serde_ipld_dagjson::{from_slice, to_vec};
+
+const CID: &str = "bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy";
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+enum Keyed {
+    Foo(u8),
+    Bar { a: String },
+    Link(Cid),
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "tag", content = "content")]
+enum Envelope {
+    Foo(u8),
+    Link(Cid),
+    Bytes(#[serde(with = "serde_bytes")] Vec<u8>),
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(tag = "tag")]
+enum Inline {
+    Bar { a: String, link: Cid },
+}
+
+#[derive(Debug, PartialEq, Deserialize, Serialize)]
+#[serde(untagged)]
+enum Kinded {
+    Int(i64),
+    #[serde(with = "serde_ipld_dagjson::union::string")]
+    String(String),
+    Bytes(#[serde(with = "serde_bytes")] Vec<u8>),
+    Link(Cid),
+    List(Vec<Kinded>),
+    Map(BTreeMap<String, Kinded>),
+}
+
+fn roundtrip<T>(value: T, expected: &str)
+where
+    T: std::fmt::Debug + PartialEq + Serialize + for<'de> Deserialize<'de>,
+{
+    let encoded = to_vec(&value).unwrap();
+    assert_eq!(String::from_utf8(encoded.clone()).unwrap(), expected);
+    assert_eq!(from_slice::<T>(&encoded).unwrap(), value);
+}
+
+#[test]
+fn test_keyed_union() {
+    let cid = Cid::from_str(CID).unwrap();
+    roundtrip(Keyed::Foo(1), r#"{"Foo":1}"#);
+    roundtrip(Keyed::Bar { a: "x".into() }, r#"{"Bar":{"a":"x"}}"#);
+    roundtrip(
+        Keyed::Link(cid),
+        &format!(r#"{{"Link":{{"/":"{}"}}}}"#, CID),
+    );
+}
+
+#[test]
+fn test_envelope_union() {
+    let cid = Cid::from_str(CID).unwrap();
+    roundtrip(Envelope::Foo(1), r#"{"tag":"Foo","content":1}"#);
+    roundtrip(
+        Envelope::Link(cid),
+        &format!(r#"{{"tag":"Link","content":{{"/":"{}"}}}}"#, CID),
+    );
+    roundtrip(
+        Envelope::Bytes(vec![1]),
+        r#"{"tag":"Bytes","content":{"/":{"bytes":"AQ"}}}"#,
+    );
+}
+
+#[test]
+fn test_inline_union() {
+    let cid = Cid::from_str(CID).unwrap();
+    roundtrip(
+        Inline::Bar {
+            a: "x".into(),
+            link: cid,
+        },
+        &format!(r#"{{"tag":"Bar","a":"x","link":{{"/":"{}"}}}}"#, CID),
+    );
+}
+
+#[test]
+fn test_kinded_union() {
+    let cid = Cid::from_str(CID).unwrap();
+    roundtrip(Kinded::Int(1), "1");
+    roundtrip(Kinded::String("s".into()), r#""s""#);
+    roundtrip(Kinded::Bytes(vec![1, 2]), r#"{"/":{"bytes":"AQI"}}"#);
+    roundtrip(Kinded::Link(cid), &format!(r#"{{"/":"{}"}}"#, CID));
+    roundtrip(
+        Kinded::List(vec![Kinded::Int(1), Kinded::Link(cid)]),
+        &format!(r#"[1,{{"/":"{}"}}]"#, CID),
+    );
+    roundtrip(
+        Kinded::Map(BTreeMap::from([("a".into(), Kinded::String("s".into()))])),
+        r#"{"a":"s"}"#,
+    );
+}
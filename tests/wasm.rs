@@ -0,0 +1,50 @@
+#![cfg(all(feature = "wasm", target_arch = "wasm32"))]
+use ipld_core::ipld::Ipld;
+use js_sys::{Array, Reflect, Uint8Array};
+use serde_ipld_dagjson::wasm;
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_test::wasm_bindgen_test;
+
+const INPUT: &[u8] = br#"{"big":9007199254740993,"bytes":{"/":{"bytes":"AQI"}},"link":{"/":"bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy"},"list":[1,1.5,null,true]}"#;
+
+#[wasm_bindgen_test]
+fn test_from_slice() {
+    let value = wasm::from_slice(INPUT).unwrap();
+    let get = |key: &str| Reflect::get(&value, &JsValue::from_str(key)).unwrap();
+
+    assert!(get("big").is_bigint());
+    let bytes = get("bytes").dyn_into::<Uint8Array>().unwrap();
+    assert_eq!(bytes.to_vec(), vec![1, 2]);
+    let link = Reflect::get(&get("link"), &JsValue::from_str("/")).unwrap();
+    assert_eq!(
+        link.as_string().unwrap(),
+        "bafkreibme22gw2h7y2h7tg2fhqotaqjucnbc24deqo72b6mkl2egezxhvy"
+    );
+    let list = Array::from(&get("list"));
+    assert_eq!(list.get(0).as_f64(), Some(1.0));
+    assert!(list.get(2).is_null());
+}
+
+#[wasm_bindgen_test]
+fn test_roundtrip() {
+    let value = wasm::from_slice(INPUT).unwrap();
+    assert_eq!(wasm::to_vec(&value).unwrap(), INPUT);
+}
+
+#[wasm_bindgen_test]
+fn test_to_vec_errors() {
+    assert!(wasm::to_vec(&JsValue::UNDEFINED).is_err());
+    assert!(wasm::to_vec(&JsValue::from_f64(f64::NAN)).is_err());
+}
+
+#[wasm_bindgen_test]
+fn test_js_to_ipld_numbers() {
+    let convert = |number: f64| wasm::js_to_ipld(&JsValue::from_f64(number)).unwrap();
+    assert_eq!(
+        convert(-9007199254740991.0),
+        Ipld::Integer(-9007199254740991)
+    );
+    // Integers outside of the safe range may not be exact, they stay floats.
+    assert_eq!(convert(9007199254740992.0), Ipld::Float(9007199254740992.0));
+    assert_eq!(convert(1e300), Ipld::Float(1e300));
+}